@@ -1,60 +1,179 @@
 use anyhow::Result;
 use axum::{
-    extract::ws::{Message, WebSocket, WebSocketUpgrade},
-    extract::{Path, Query, State},
-    routing::get,
+    extract::ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+    extract::{ConnectInfo, Path, Query, Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
     Json, Router,
 };
 use chrono::{DateTime, Utc};
+use clap::Parser;
 use indexer_core::{
+    candle_codec::encode_candles,
+    candles::{CandleOpenMode, MAX_CANDLE_TIMEFRAME_SECS},
+    cursor::RowCursor,
     config::IndexerConfig,
     db::{
-        create_pool, get_balances_for_mint, get_portfolio_for_wallet,
-        get_bonding_trades_for_mint, get_candles, get_token_transfers_for_mint, run_migrations,
+        backfill_candles, create_pool, get_bad_indices, get_balances_for_mint, get_holder_count, get_parse_misses,
+        get_pnl_for_wallet, get_portfolio_for_wallet, recompute_balances_for_mint, get_bonding_progress, get_bonding_trades_for_mint, get_candles, get_candles_for_mints, get_min_candle_timeframe, get_ohlcv_rollup,
+        get_token_event_counts, get_token_events_for_mint, get_token_transfers_for_mint,
+        get_indexer_lag, get_trader_stats, get_trending_tokens, rebuild_queued_candles, run_migrations,
+        search_mints,
     },
-    models::{Balance, BondingCurveTrade, Candle, TokenTransfer},
+    logging::{init_logging, LogFormat},
+    models::{
+        Balance, BondingTradeRow, Candle, IndexerLag, Mint, TokenTransfer, TraderStats,
+        TrendingToken, WalletMintPnl,
+    },
+    shutdown::shutdown_signal,
+    spl_parser::{EVENT_APPROVE, EVENT_CLOSE_ACCOUNT, EVENT_REVOKE},
 };
+use metrics::{counter, gauge};
+use metrics_exporter_prometheus::PrometheusBuilder;
 use serde::Deserialize;
 use serde_json::Value as JsonValue;
+use sqlx::postgres::PgListener;
 use sqlx::PgPool;
-use std::net::SocketAddr;
+use std::collections::{btree_map::Entry, BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::net::TcpListener;
-use tokio::sync::broadcast;
-use tracing_subscriber::EnvFilter;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tower_http::cors::{AllowOrigin, CorsLayer};
 
 async fn health() -> &'static str {
     "ok"
 }
 
-async fn metrics_handler(
-    State(state): State<AppState>,
-) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
-    // Fetch basic metrics from the database.
-    let token_transfers_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM token_transfers")
-        .fetch_one(&state.pool)
-        .await
-        .unwrap_or(0);
+/// Parsers whose stored miss and bad-index counts `/metrics` reports.
+const METRIC_PARSERS: [&str; 5] = ["spl", "pump", "raydium", "meteora", "orca"];
 
-    let bonding_trades_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM bonding_curve_trades")
-        .fetch_one(&state.pool)
-        .await
-        .unwrap_or(0);
+/// Stored totals behind `/metrics` and `/metrics.json`. A failed query counts as zero.
+struct StoredCounts {
+    token_transfers: i64,
+    bonding_trades: i64,
+    last_processed_slot: Option<i64>,
+    mints: i64,
+    parse_misses: Vec<(String, i64)>,
+    bad_indices: Vec<(String, i64)>,
+    lag: Option<IndexerLag>,
+}
 
-    let last_processed_slot: Option<i64> = sqlx::query_scalar("SELECT slot FROM last_processed_slot WHERE id = 1")
-        .fetch_optional(&state.pool)
-        .await
-        .unwrap_or(None);
+impl StoredCounts {
+    async fn load(pool: &PgPool) -> Self {
+        let token_transfers: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM token_transfers")
+            .fetch_one(pool)
+            .await
+            .unwrap_or(0);
 
-    let total_mints: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM mints")
-        .fetch_one(&state.pool)
-        .await
-        .unwrap_or(0);
+        let bonding_trades: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM bonding_curve_trades")
+            .fetch_one(pool)
+            .await
+            .unwrap_or(0);
+
+        let last_processed_slot: Option<i64> =
+            sqlx::query_scalar("SELECT slot FROM last_processed_slot WHERE id = 1")
+                .fetch_optional(pool)
+                .await
+                .unwrap_or(None);
+
+        let mints: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM mints")
+            .fetch_one(pool)
+            .await
+            .unwrap_or(0);
+
+        Self {
+            token_transfers,
+            bonding_trades,
+            last_processed_slot,
+            mints,
+            parse_misses: get_parse_misses(pool).await.unwrap_or_default(),
+            bad_indices: get_bad_indices(pool).await.unwrap_or_default(),
+            lag: get_indexer_lag(pool).await.unwrap_or(None),
+        }
+    }
+
+    fn misses_for(&self, parser: &str) -> i64 {
+        Self::count_for(&self.parse_misses, parser)
+    }
+
+    fn bad_indices_for(&self, parser: &str) -> i64 {
+        Self::count_for(&self.bad_indices, parser)
+    }
+
+    fn count_for(counts: &[(String, i64)], parser: &str) -> i64 {
+        counts
+            .iter()
+            .find(|(p, _)| p == parser)
+            .map_or(0, |(_, n)| *n)
+    }
+}
+
+/// The stored totals in Prometheus text format, for scraping. The writer's live counters
+/// (blocks, trades per venue, insert latency) are served by indexer-bin on
+/// `monitor.metrics_bind_addr`.
+async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let counts = StoredCounts::load(&state.pool).await;
+
+    let recorder = PrometheusBuilder::new().build_recorder();
+    let handle = recorder.handle();
+    metrics::with_local_recorder(&recorder, || {
+        gauge!("indexer_token_transfers").set(counts.token_transfers as f64);
+        gauge!("indexer_bonding_trades").set(counts.bonding_trades as f64);
+        gauge!("indexer_mints").set(counts.mints as f64);
+        if let Some(slot) = counts.last_processed_slot {
+            gauge!("indexer_last_processed_slot").set(slot as f64);
+        }
+        if let Some(lag) = counts.lag {
+            gauge!("indexer_lag_seconds").set(lag.lag_secs as f64);
+            gauge!("indexer_estimated_tip_slot").set(lag.estimated_tip_slot as f64);
+            if let Some(slots_behind) = lag.slots_behind {
+                gauge!("indexer_slots_behind").set(slots_behind as f64);
+            }
+        }
+        for parser in METRIC_PARSERS {
+            counter!("indexer_parse_misses_total", "parser" => parser)
+                .absolute(counts.misses_for(parser) as u64);
+            counter!("indexer_bad_indices_total", "parser" => parser)
+                .absolute(counts.bad_indices_for(parser) as u64);
+        }
+    });
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        handle.render(),
+    )
+}
+
+/// The stored totals as JSON, the format `/metrics` had before it moved to Prometheus.
+async fn metrics_json_handler(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
+    let counts = StoredCounts::load(&state.pool).await;
+    let misses_for = |parser: &str| counts.misses_for(parser);
+    let bad_indices_for = |parser: &str| counts.bad_indices_for(parser);
 
     let metrics = serde_json::json!({
-        "token_transfers_count": token_transfers_count,
-        "bonding_trades_count": bonding_trades_count,
-        "last_processed_slot": last_processed_slot,
-        "total_mints": total_mints,
+        "token_transfers_count": counts.token_transfers,
+        "bonding_trades_count": counts.bonding_trades,
+        "last_processed_slot": counts.last_processed_slot,
+        "total_mints": counts.mints,
+        "lag_secs": counts.lag.map(|lag| lag.lag_secs),
+        "estimated_tip_slot": counts.lag.map(|lag| lag.estimated_tip_slot),
+        "slots_behind": counts.lag.and_then(|lag| lag.slots_behind),
+        "pump_parse_misses": misses_for("pump"),
+        "raydium_parse_misses": misses_for("raydium"),
+        "meteora_parse_misses": misses_for("meteora"),
+        "orca_parse_misses": misses_for("orca"),
+        "spl_bad_indices": bad_indices_for("spl"),
+        "pump_bad_indices": bad_indices_for("pump"),
+        "raydium_bad_indices": bad_indices_for("raydium"),
+        "meteora_bad_indices": bad_indices_for("meteora"),
+        "orca_bad_indices": bad_indices_for("orca"),
     });
 
     Ok(Json(metrics))
@@ -64,6 +183,8 @@ async fn metrics_handler(
 struct TransfersQuery {
     limit: Option<i64>,
     before_slot: Option<i64>,
+    /// `x-next-cursor` of the previous page.
+    after: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -76,19 +197,50 @@ struct HoldersQuery {
 struct BondingTradesQuery {
     limit: Option<i64>,
     before_slot: Option<i64>,
+    /// `x-next-cursor` of the previous page.
+    after: Option<String>,
+    /// Minimum trade size in lamports.
+    min_sol: Option<i64>,
+    /// Minimum trade size in raw token units.
+    min_token: Option<i64>,
+}
+
+/// Response header carrying the cursor for the next page of a feed, present only when the page
+/// came back full. Clients pass it back as `after`.
+const NEXT_CURSOR_HEADER: &str = "x-next-cursor";
+
+/// Decode an `after` query parameter; a cursor this API did not hand out is a 400.
+fn parse_after(after: Option<&str>) -> Result<Option<RowCursor>, StatusCode> {
+    after
+        .map(|a| RowCursor::decode(a).ok_or(StatusCode::BAD_REQUEST))
+        .transpose()
+}
+
+/// [`NEXT_CURSOR_HEADER`] pointing past `last`, when the page holding it filled `limit`.
+fn next_cursor_headers(page_len: usize, limit: i64, last: Option<RowCursor>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if page_len as i64 >= limit {
+        if let Some(value) = last.and_then(|c| HeaderValue::from_str(&c.encode()).ok()) {
+            headers.insert(NEXT_CURSOR_HEADER, value);
+        }
+    }
+    headers
 }
 
 async fn token_transfers_handler(
     State(state): State<AppState>,
     Path(mint): Path<String>,
     Query(q): Query<TransfersQuery>,
-) -> Result<Json<Vec<TokenTransfer>>, axum::http::StatusCode> {
+) -> Result<(HeaderMap, Json<Vec<TokenTransfer>>), axum::http::StatusCode> {
     let limit = q.limit.unwrap_or(100).clamp(1, 1_000);
-    let transfers = get_token_transfers_for_mint(&state.pool, &mint, limit, q.before_slot)
-        .await
-        .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+    let after = parse_after(q.after.as_deref())?;
+    let transfers =
+        get_token_transfers_for_mint(&state.pool, &mint, limit, q.before_slot, after.as_ref())
+            .await
+            .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    Ok(Json(transfers))
+    let headers = next_cursor_headers(transfers.len(), limit, transfers.last().map(Into::into));
+    Ok((headers, Json(transfers)))
 }
 
 async fn token_holders_handler(
@@ -106,6 +258,17 @@ async fn token_holders_handler(
     Ok(Json(holders))
 }
 
+async fn token_holder_count_handler(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
+    let holder_count = get_holder_count(&state.pool, &mint)
+        .await
+        .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(serde_json::json!({ "holder_count": holder_count })))
+}
+
 async fn wallet_portfolio_handler(
     State(state): State<AppState>,
     Path(owner): Path<String>,
@@ -117,24 +280,264 @@ async fn wallet_portfolio_handler(
     Ok(Json(portfolio))
 }
 
+async fn wallet_pnl_handler(
+    State(state): State<AppState>,
+    Path(owner): Path<String>,
+) -> Result<Json<Vec<WalletMintPnl>>, axum::http::StatusCode> {
+    let pnl = get_pnl_for_wallet(&state.pool, &owner)
+        .await
+        .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(pnl))
+}
+
 async fn bonding_trades_handler(
     State(state): State<AppState>,
     Path(mint): Path<String>,
     Query(q): Query<BondingTradesQuery>,
-) -> Result<Json<Vec<BondingCurveTrade>>, axum::http::StatusCode> {
+) -> Result<(HeaderMap, Json<Vec<BondingTradeRow>>), axum::http::StatusCode> {
     let limit = q.limit.unwrap_or(200).clamp(1, 5_000);
+    if q.min_sol.is_some_and(|v| v < 0) || q.min_token.is_some_and(|v| v < 0) {
+        return Err(axum::http::StatusCode::BAD_REQUEST);
+    }
+    let after = parse_after(q.after.as_deref())?;
+
+    let trades = get_bonding_trades_for_mint(
+        &state.pool,
+        &mint,
+        limit,
+        q.before_slot,
+        after.as_ref(),
+        q.min_sol,
+        q.min_token,
+    )
+    .await
+    .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let headers = next_cursor_headers(trades.len(), limit, trades.last().map(Into::into));
+    Ok((headers, Json(trades)))
+}
+
+#[derive(Debug, Deserialize)]
+struct SafetyEventsQuery {
+    limit: Option<i64>,
+}
 
-    let trades = get_bonding_trades_for_mint(&state.pool, &mint, limit, q.before_slot)
+/// Delegate approvals, revokes and account closes for a mint: per-kind totals and the most
+/// recent occurrences.
+async fn safety_events_handler(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+    Query(q): Query<SafetyEventsQuery>,
+) -> Result<Json<JsonValue>, axum::http::StatusCode> {
+    let limit = q.limit.unwrap_or(20).clamp(1, 500);
+    let counts = get_token_event_counts(&state.pool, &mint)
+        .await
+        .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+    let recent = get_token_events_for_mint(&state.pool, &mint, limit)
+        .await
+        .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let count_of = |kind: &str| counts.iter().find(|(k, _)| k == kind).map_or(0, |(_, n)| *n);
+    Ok(Json(serde_json::json!({
+        "counts": {
+            EVENT_APPROVE: count_of(EVENT_APPROVE),
+            EVENT_REVOKE: count_of(EVENT_REVOKE),
+            EVENT_CLOSE_ACCOUNT: count_of(EVENT_CLOSE_ACCOUNT),
+        },
+        "recent": recent,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+struct TraderStatsQuery {
+    window_secs: Option<i64>,
+}
+
+async fn trader_stats_handler(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+    Query(q): Query<TraderStatsQuery>,
+) -> Result<Json<TraderStats>, axum::http::StatusCode> {
+    let window_secs = q.window_secs.unwrap_or(86_400).clamp(60, 30 * 86_400);
+
+    let stats = get_trader_stats(&state.pool, &mint, window_secs)
+        .await
+        .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(stats))
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchQuery {
+    q: String,
+    limit: Option<i64>,
+}
+
+/// Mints whose symbol or pubkey starts with `q`, for the terminal's search modal.
+async fn search_handler(
+    State(state): State<AppState>,
+    Query(q): Query<SearchQuery>,
+) -> Result<Json<Vec<Mint>>, axum::http::StatusCode> {
+    let query = q.q.trim();
+    if query.is_empty() {
+        return Err(axum::http::StatusCode::BAD_REQUEST);
+    }
+    let limit = q.limit.unwrap_or(20).clamp(1, 100);
+
+    let mints = search_mints(&state.pool, query, limit)
+        .await
+        .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(mints))
+}
+
+#[derive(Debug, Deserialize)]
+struct TrendingQuery {
+    window_secs: Option<i64>,
+    limit: Option<i64>,
+}
+
+/// Mints with the most SOL traded recently, for the terminal's home screen.
+async fn trending_handler(
+    State(state): State<AppState>,
+    Query(q): Query<TrendingQuery>,
+) -> Result<Json<Vec<TrendingToken>>, axum::http::StatusCode> {
+    let window_secs = q.window_secs.unwrap_or(3_600).clamp(60, 30 * 86_400);
+    let limit = q.limit.unwrap_or(20).clamp(1, 100);
+
+    let trending = get_trending_tokens(&state.pool, window_secs, limit)
+        .await
+        .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(trending))
+}
+
+async fn bonding_progress_handler(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
+    let progress_pct = get_bonding_progress(&state.pool, &mint)
         .await
         .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    Ok(Json(trades))
+    Ok(Json(serde_json::json!({ "progress_pct": progress_pct })))
+}
+
+#[derive(Debug, Deserialize)]
+struct RecentQuery {
+    trades_limit: Option<i64>,
+    holders_limit: Option<i64>,
+    /// Window of the summary's trader stats.
+    window_secs: Option<i64>,
+    timeframe_secs: Option<i32>,
+}
+
+/// Everything the terminal loads when a token is opened, in one round trip: trader stats and
+/// bonding progress, the newest candle, the latest trades and the largest holders.
+async fn token_recent_handler(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+    Query(q): Query<RecentQuery>,
+) -> Result<Json<JsonValue>, axum::http::StatusCode> {
+    let trades_limit = q.trades_limit.unwrap_or(50).clamp(1, 500);
+    let holders_limit = q.holders_limit.unwrap_or(10).clamp(1, 100);
+    let window_secs = q.window_secs.unwrap_or(86_400).clamp(60, 30 * 86_400);
+    let tf = state.candle_timeframe(q.timeframe_secs);
+
+    let pool = &state.pool;
+    let (stats, progress_pct, candles, trades, holders) = tokio::try_join!(
+        get_trader_stats(pool, &mint, window_secs),
+        get_bonding_progress(pool, &mint),
+        get_candles(pool, &mint, tf, 1, None),
+        get_bonding_trades_for_mint(pool, &mint, trades_limit, None, None, None, None),
+        get_balances_for_mint(pool, &mint, holders_limit, 0),
+    )
+    .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(serde_json::json!({
+        "summary": {
+            "window_secs": window_secs,
+            "unique_traders": stats.unique_traders,
+            "net_buyers": stats.net_buyers,
+            "bonding_progress_pct": progress_pct,
+        },
+        "latest_candle": candles.into_iter().next(),
+        "recent_trades": trades,
+        "top_holders": holders,
+    })))
 }
 
 #[derive(Clone)]
 struct AppState {
     pool: PgPool,
-    events_tx: broadcast::Sender<String>,
+    events_tx: broadcast::Sender<Arc<WsEvent>>,
+    /// See `ApiConfig::candle_warmup_min_rows`.
+    candle_warmup_min_rows: i64,
+    /// Candle timeframe and row count used when a request leaves them out.
+    default_candle_timeframe_secs: i32,
+    default_candle_limit: i64,
+    /// See `CandleConfig::base_timeframe_secs`: the smallest timeframe served.
+    base_candle_timeframe_secs: i32,
+    /// See `CandleConfig::open_mode`: how backfilled candles open, like the writer's.
+    candle_open_mode: CandleOpenMode,
+    /// (mint, timeframe) pairs a candle backfill has already been started for.
+    candle_backfills: Arc<Mutex<HashSet<(String, i32)>>>,
+    /// See `ApiConfig::admin_token`. `None` rejects every admin request.
+    admin_token: Option<Arc<str>>,
+    /// How often `/ws` pings each client, and how long it waits for a pong before closing.
+    ws_ping_interval: Duration,
+    ws_pong_timeout: Duration,
+    /// See `ApiConfig::ws_max_missed_events`. `None` never closes a slow client.
+    ws_max_missed_events: Option<u64>,
+    /// See `ApiConfig::requests_per_sec`. `None` disables rate limiting.
+    rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl AppState {
+    /// `requested` (or the default) timeframe, within the base timeframe and one day.
+    fn candle_timeframe(&self, requested: Option<i32>) -> i32 {
+        requested
+            .unwrap_or(self.default_candle_timeframe_secs)
+            .clamp(self.base_candle_timeframe_secs, MAX_CANDLE_TIMEFRAME_SECS)
+    }
+}
+
+const DEFAULT_CANDLE_WARMUP_MIN_ROWS: i64 = 10;
+const DEFAULT_CANDLE_TIMEFRAME_SECS: i32 = 60;
+const DEFAULT_CANDLE_LIMIT: i64 = 500;
+const DEFAULT_WS_PING_INTERVAL: Duration = Duration::from_secs(30);
+const DEFAULT_WS_PONG_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_WS_EVENT_CAPACITY: usize = 10_000;
+const DEFAULT_REQUESTS_PER_SEC: u32 = 20;
+/// Upper bound for `/token/:mint/ohlcv?interval=` (one week).
+const MAX_OHLCV_INTERVAL_SECS: i32 = 7 * 86_400;
+
+/// Start a background candle backfill for `mint`/`timeframe_secs` unless one already ran.
+/// Returns whether a backfill was scheduled. A failed backfill clears the guard so a later
+/// request can retry.
+fn schedule_candle_backfill(state: &AppState, mint: &str, timeframe_secs: i32) -> bool {
+    let key = (mint.to_string(), timeframe_secs);
+    if !state.candle_backfills.lock().unwrap().insert(key.clone()) {
+        return false;
+    }
+
+    let pool = state.pool.clone();
+    let guard = state.candle_backfills.clone();
+    let open_mode = state.candle_open_mode;
+    tokio::spawn(async move {
+        match backfill_candles(&pool, &key.0, key.1, open_mode).await {
+            Ok(inserted) => {
+                tracing::info!("backfilled {inserted} candles for {} ({}s)", key.0, key.1)
+            }
+            Err(err) => {
+                tracing::error!("candle backfill failed for {}: {err:?}", key.0);
+                guard.lock().unwrap().remove(&key);
+            }
+        }
+    });
+
+    true
 }
 
 #[derive(Debug, Deserialize)]
@@ -144,13 +547,14 @@ struct CandlesQuery {
     before: Option<String>,
 }
 
-async fn token_candles_handler(
-    State(state): State<AppState>,
-    Path(mint): Path<String>,
-    Query(q): Query<CandlesQuery>,
-) -> Result<Json<Vec<Candle>>, axum::http::StatusCode> {
-    let tf = q.timeframe_secs.unwrap_or(60).clamp(1, 86_400);
-    let limit = q.limit.unwrap_or(500).clamp(1, 5_000);
+/// Shared by the JSON and binary candle endpoints. Returns the clamped timeframe and candles.
+async fn load_candles(
+    state: &AppState,
+    mint: &str,
+    q: &CandlesQuery,
+) -> Result<(i32, Vec<Candle>), axum::http::StatusCode> {
+    let tf = state.candle_timeframe(q.timeframe_secs);
+    let limit = q.limit.unwrap_or(state.default_candle_limit).clamp(1, 5_000);
     let before = if let Some(s) = q.before.as_deref() {
         DateTime::parse_from_rfc3339(s)
             .ok()
@@ -159,153 +563,2077 @@ async fn token_candles_handler(
         None
     };
 
-    let candles = get_candles(&state.pool, &mint, tf, limit, before)
+    let candles = get_candles(&state.pool, mint, tf, limit, before)
         .await
         .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    // Indexer may have started mid-history: warm up candles from stored trades.
+    // Only for the latest page; paging back with `before` doesn't mean the mint is cold.
+    if before.is_none() && (candles.len() as i64) < state.candle_warmup_min_rows {
+        schedule_candle_backfill(state, mint, tf);
+    }
+
+    Ok((tf, candles))
+}
+
+async fn token_candles_handler(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+    Query(q): Query<CandlesQuery>,
+) -> Result<Json<Vec<Candle>>, axum::http::StatusCode> {
+    let (_, candles) = load_candles(&state, &mint, &q).await?;
     Ok(Json(candles))
 }
 
-async fn ws_handler(
+#[derive(Debug, Deserialize)]
+struct OhlcvQuery {
+    interval: Option<i32>,
+    limit: Option<i64>,
+}
+
+/// Candles of any `interval` (seconds), rolled up from the mint's smallest stored timeframe.
+/// 400 when `interval` is missing, above a week or not a multiple of that timeframe.
+async fn token_ohlcv_handler(
     State(state): State<AppState>,
-    ws: WebSocketUpgrade,
-) -> axum::response::Response {
-    ws.on_upgrade(move |socket| handle_ws(socket, state))
+    Path(mint): Path<String>,
+    Query(q): Query<OhlcvQuery>,
+) -> Result<Json<Vec<Candle>>, axum::http::StatusCode> {
+    let interval = match q.interval {
+        Some(secs @ 1..=MAX_OHLCV_INTERVAL_SECS) => secs,
+        _ => return Err(axum::http::StatusCode::BAD_REQUEST),
+    };
+    let limit = q.limit.unwrap_or(state.default_candle_limit).clamp(1, 5_000);
+
+    let base_tf = get_min_candle_timeframe(&state.pool, &mint)
+        .await
+        .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+    let Some(base_tf) = base_tf else {
+        return Ok(Json(Vec::new()));
+    };
+    if interval % base_tf != 0 {
+        return Err(axum::http::StatusCode::BAD_REQUEST);
+    }
+
+    let candles = get_ohlcv_rollup(&state.pool, &mint, base_tf, interval, limit)
+        .await
+        .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(candles))
 }
 
-async fn handle_ws(mut socket: WebSocket, state: AppState) {
-    // Protocol:
-    // - Client may send: {"type":"subscribe","topics":["transfers","holders","candles","bonding"],"mint":"..."}
-    // - Server pushes: {"topic":"...","mint_pubkey":"...","payload":{...}}
-    let mut rx = state.events_tx.subscribe();
+/// Same query as `/token/:mint/candles`, packed with `candle_codec` (layout documented there).
+async fn token_candles_bin_handler(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+    Query(q): Query<CandlesQuery>,
+) -> Result<impl IntoResponse, axum::http::StatusCode> {
+    let (tf, candles) = load_candles(&state, &mint, &q).await?;
+    Ok((
+        [(header::CONTENT_TYPE, "application/octet-stream")],
+        encode_candles(tf, &candles),
+    ))
+}
 
-    // Default: all events.
-    let mut allowed_topics: Option<Vec<String>> = None;
-    let mut allowed_mint: Option<String> = None;
+/// Compare two secrets without returning early at the first differing byte, so response
+/// timing does not reveal how much of a guessed token was right. Only the length can leak.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    std::hint::black_box(diff) == 0
+}
 
-    loop {
-        tokio::select! {
-            recv = socket.recv() => {
-                let Some(Ok(msg)) = recv else { break; };
-                if let Message::Text(txt) = msg {
-                    if let Ok(v) = serde_json::from_str::<JsonValue>(&txt) {
-                        if v.get("type").and_then(|x| x.as_str()) == Some("subscribe") {
-                            allowed_topics = v.get("topics")
-                                .and_then(|t| t.as_array())
-                                .map(|arr| arr.iter().filter_map(|x| x.as_str().map(|s| s.to_string())).collect::<Vec<_>>());
-                            allowed_mint = v.get("mint").and_then(|m| m.as_str()).map(|s| s.to_string());
-                            let _ = socket.send(Message::Text(r#"{"type":"subscribed"}"#.to_string())).await;
-                        }
-                    }
-                }
-            }
-            evt = rx.recv() => {
-                let Ok(payload) = evt else { continue; };
-                // Best-effort filtering without fully parsing each payload:
-                // We parse small JSON to check topic/mint keys.
-                if let Ok(v) = serde_json::from_str::<JsonValue>(&payload) {
-                    let topic = v.get("topic").and_then(|x| x.as_str()).unwrap_or("");
-                    let mint = v.get("mint_pubkey").and_then(|x| x.as_str());
-
-                    if let Some(ref topics) = allowed_topics {
-                        if !topics.iter().any(|t| t == topic) {
-                            continue;
-                        }
-                    }
-                    if let Some(ref m) = allowed_mint {
-                        if mint != Some(m.as_str()) {
-                            continue;
-                        }
-                    }
-                }
+/// Guard for `/admin/*`: 401 unless the request carries `Authorization: Bearer <admin_token>`.
+async fn require_admin_token(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
 
-                if socket.send(Message::Text(payload)).await.is_err() {
-                    break;
-                }
-            }
+    match (state.admin_token.as_deref(), presented) {
+        (Some(expected), Some(token)) if constant_time_eq(expected.as_bytes(), token.as_bytes()) => {
+            next.run(request).await
         }
+        _ => StatusCode::UNAUTHORIZED.into_response(),
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
-
-    let config = IndexerConfig::from_env()?;
+/// Token bucket per client IP: `burst` requests at once, refilled at `per_sec` a second.
+#[derive(Debug)]
+struct RateLimiter {
+    per_sec: f64,
+    burst: f64,
+    buckets: Mutex<RateBuckets>,
+}
 
-    let pool = create_pool(&config.db.url, config.db.max_connections).await?;
-    run_migrations(&pool).await?;
+#[derive(Debug, Default)]
+struct RateBuckets {
+    by_ip: HashMap<IpAddr, RateBucket>,
+    /// When refilled buckets were last dropped; `None` until the first prune.
+    pruned_at: Option<Instant>,
+}
 
-    let (events_tx, _events_rx) = broadcast::channel::<String>(10_000);
+#[derive(Debug, Clone, Copy)]
+struct RateBucket {
+    tokens: f64,
+    updated: Instant,
+}
 
-    // Background: LISTEN/NOTIFY → broadcast for websocket clients.
-    {
-        let db_url = config.db.url.clone();
-        let events_tx = events_tx.clone();
-        tokio::spawn(async move {
-            let mut listener = match sqlx::postgres::PgListener::connect(&db_url).await {
-                Ok(l) => l,
-                Err(e) => {
-                    tracing::error!("PgListener connect failed: {e:?}");
-                    return;
-                }
-            };
+/// Above this many tracked clients, buckets that have refilled completely are dropped, at most
+/// once per `RATE_LIMIT_PRUNE_EVERY` so a crowd of clients doesn't scan the map every request.
+const RATE_LIMIT_PRUNE_AT: usize = 10_000;
+const RATE_LIMIT_PRUNE_EVERY: Duration = Duration::from_secs(60);
 
-            if let Err(e) = listener.listen("indexer_events").await {
-                tracing::error!("PgListener listen failed: {e:?}");
-                return;
-            }
+impl RateLimiter {
+    fn new(per_sec: u32, burst: u32) -> Self {
+        Self {
+            per_sec: f64::from(per_sec),
+            burst: f64::from(burst.max(1)),
+            buckets: Mutex::default(),
+        }
+    }
 
-            loop {
-                match listener.recv().await {
-                    Ok(n) => {
-                        let _ = events_tx.send(n.payload().to_string());
-                    }
-                    Err(e) => {
-                        tracing::error!("PgListener recv failed: {e:?}");
-                        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-                    }
-                }
-            }
+    /// Take a token for `ip`, or return how long until one is available.
+    fn check(&self, ip: IpAddr, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+        let prune_due = buckets
+            .pruned_at
+            .is_none_or(|at| now.saturating_duration_since(at) >= RATE_LIMIT_PRUNE_EVERY);
+        if buckets.by_ip.len() > RATE_LIMIT_PRUNE_AT && prune_due {
+            buckets.by_ip.retain(|_, b| self.refilled(b, now) < self.burst);
+            buckets.pruned_at = Some(now);
+        }
+        let bucket = buckets.by_ip.entry(ip).or_insert(RateBucket {
+            tokens: self.burst,
+            updated: now,
         });
+        bucket.tokens = self.refilled(bucket, now);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.per_sec))
+        }
     }
 
-    let state = AppState { pool, events_tx };
+    fn refilled(&self, bucket: &RateBucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed * self.per_sec).min(self.burst)
+    }
+}
 
-    let app = Router::new()
-        .route("/health", get(health))
-        .route("/metrics", get(metrics_handler))
-        .route(
-            "/token/:mint/transfers",
-            get(token_transfers_handler),
-        )
-        .route(
-            "/token/:mint/holders",
-            get(token_holders_handler),
-        )
-        .route(
-            "/wallet/:owner/portfolio",
-            get(wallet_portfolio_handler),
-        )
-        .route(
-            "/token/:mint/bonding_trades",
-            get(bonding_trades_handler),
-        )
-        .route(
-            "/token/:mint/candles",
-            get(token_candles_handler),
-        )
-        .route("/ws", get(ws_handler))
-        .with_state(state);
+/// 429 with `Retry-After` (whole seconds, rounded up) once a client IP runs out of tokens.
+/// Requests without a peer address (e.g. in-process tests) share one bucket.
+async fn rate_limit(
+    State(state): State<AppState>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(limiter) = state.rate_limiter.as_deref() else {
+        return next.run(request).await;
+    };
+    let ip = peer.map_or(IpAddr::from([0, 0, 0, 0]), |ConnectInfo(addr)| addr.ip());
+
+    match limiter.check(ip, Instant::now()) {
+        Ok(()) => next.run(request).await,
+        Err(wait) => {
+            let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_after.to_string())],
+            )
+                .into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AdminBackfillQuery {
+    timeframe_secs: Option<i32>,
+}
+
+/// Rebuild missing candles for a mint from stored trades, now rather than on the next cold
+/// candles request. Existing candles are left alone.
+async fn admin_backfill_candles_handler(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+    Query(q): Query<AdminBackfillQuery>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let tf = state.candle_timeframe(q.timeframe_secs);
+    let inserted = backfill_candles(&state.pool, &mint, tf, state.candle_open_mode)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tracing::info!("admin backfill for {mint}/{tf}s inserted {inserted} candles");
+
+    Ok(Json(serde_json::json!({ "inserted": inserted })))
+}
+
+/// Rebuild a mint's balances from its stored transfers, to repair balances that drifted.
+async fn admin_recompute_balances_handler(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let balances = recompute_balances_for_mint(&state.pool, &mint)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tracing::info!("admin balance recompute for {mint} wrote {balances} balances");
+
+    Ok(Json(serde_json::json!({ "balances": balances })))
+}
+
+/// Upper bound on mints per `/candles/batch` request.
+const MAX_BATCH_MINTS: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct CandlesBatchRequest {
+    mints: Vec<String>,
+    timeframe_secs: Option<i32>,
+    limit: Option<i64>,
+}
+
+/// Candles for many mints in one round trip (dashboards with a sparkline per token).
+/// Every requested mint is present in the response, with an empty list if it has no candles.
+async fn candles_batch_handler(
+    State(state): State<AppState>,
+    Json(req): Json<CandlesBatchRequest>,
+) -> Result<Json<BTreeMap<String, Vec<Candle>>>, axum::http::StatusCode> {
+    let tf = state.candle_timeframe(req.timeframe_secs);
+    let limit = req.limit.unwrap_or(100).clamp(1, 1_000);
+
+    let mut by_mint: BTreeMap<String, Vec<Candle>> = req
+        .mints
+        .into_iter()
+        .map(|mint| (mint, Vec::new()))
+        .collect();
+    if by_mint.is_empty() || by_mint.len() > MAX_BATCH_MINTS {
+        return Err(axum::http::StatusCode::BAD_REQUEST);
+    }
+
+    let mints: Vec<String> = by_mint.keys().cloned().collect();
+    let candles = get_candles_for_mints(&state.pool, &mints, tf, limit)
+        .await
+        .map_err(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    for candle in candles {
+        if let Some(list) = by_mint.get_mut(&candle.mint_pubkey) {
+            list.push(candle);
+        }
+    }
+
+    Ok(Json(by_mint))
+}
+
+async fn ws_handler(
+    State(state): State<AppState>,
+    ws: WebSocketUpgrade,
+) -> axum::response::Response {
+    ws.on_upgrade(move |socket| handle_ws(socket, state))
+}
+
+/// A realtime event on its way to websocket clients. The routing fields are parsed once when the
+/// NOTIFY arrives; every subscriber shares the same `Arc` and filters on them without touching
+/// the payload again.
+#[derive(Debug, PartialEq)]
+struct WsEvent {
+    topic: String,
+    mint_pubkey: Option<String>,
+    /// `payload.closed` of a candle event.
+    candle_closed: bool,
+    /// `(payload.timeframe_secs, payload.bucket_start)` of a candle event.
+    candle_bucket: Option<(i64, String)>,
+    /// The NOTIFY payload, forwarded to clients unchanged.
+    json: String,
+}
+
+impl WsEvent {
+    /// Unparseable payloads keep an empty topic: they only reach unfiltered subscribers.
+    fn parse(json: String) -> Self {
+        let v = serde_json::from_str::<JsonValue>(&json).unwrap_or_default();
+        Self {
+            topic: v["topic"].as_str().unwrap_or_default().to_string(),
+            mint_pubkey: v["mint_pubkey"].as_str().map(str::to_string),
+            candle_closed: v["payload"]["closed"].as_bool() == Some(true),
+            candle_bucket: v["payload"]["timeframe_secs"]
+                .as_i64()
+                .zip(v["payload"]["bucket_start"].as_str().map(str::to_string)),
+            json,
+        }
+    }
+}
+
+/// `{"type":"error","message":...}` reply for client messages the server can't act on.
+fn ws_error(message: &str) -> Message {
+    Message::Text(serde_json::json!({ "type": "error", "message": message }).to_string())
+}
+
+/// Upper bound for a client's `coalesce_ms`.
+const MAX_COALESCE_MS: u64 = 60_000;
+
+/// Ticks every `period`, starting one period from now; a flush is due on each tick.
+fn coalesce_interval(period: std::time::Duration) -> tokio::time::Interval {
+    let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    interval
+}
+
+/// Candle updates held back for a coalescing client, one per `(mint, timeframe_secs, bucket)`.
+type HeldCandles = BTreeMap<(String, i64, String), JsonValue>;
+
+/// Fold a candle update into the one held for its bucket. Each update carries only the trades
+/// of one writer flush, so volumes and trade counts add up, the range widens, the first open
+/// stays and the latest close wins.
+fn merge_candle_update(held: &mut JsonValue, update: &JsonValue) {
+    let (held, update) = (&mut held["payload"], &update["payload"]);
+    for field in ["volume_token", "volume_sol", "trades_count"] {
+        let sum = held[field].as_i64().unwrap_or(0) + update[field].as_i64().unwrap_or(0);
+        held[field] = sum.into();
+    }
+    if let (Some(a), Some(b)) = (held["high"].as_i64(), update["high"].as_i64()) {
+        held["high"] = a.max(b).into();
+    }
+    if let (Some(a), Some(b)) = (held["low"].as_i64(), update["low"].as_i64()) {
+        held["low"] = a.min(b).into();
+    }
+    held["close"] = update["close"].clone();
+}
+
+/// Send every held-back candle update. Returns false once the socket is gone.
+async fn flush_coalesced(socket: &mut WebSocket, pending: &mut HeldCandles) -> bool {
+    for (_, update) in std::mem::take(pending) {
+        if socket.send(Message::Text(update.to_string())).await.is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+async fn handle_ws(mut socket: WebSocket, state: AppState) {
+    // Protocol:
+    // - Client may send: {"type":"subscribe","topics":["transfers","holders","candles","bonding"],"mint":"...","coalesce_ms":250}
+    // - Server pushes: {"topic":"...","mint_pubkey":"...","payload":{...}}
+    // - Anything else gets: {"type":"error","message":"..."}
+    // With `coalesce_ms`, in-progress candle updates are held back and merged per mint,
+    // timeframe and bucket, and sent at most once per interval. Candle closes and other topics
+    // go out immediately (a close first flushes the held update of its own timeframe and
+    // bucket, so updates never follow their close).
+    // Keepalive: the server pings every 30s (`api.ws_ping_interval_secs`) and closes the socket
+    // when no pong has arrived for 60s (`api.ws_pong_timeout_secs`). Client pings get a pong.
+    // A client too slow to keep up with the event buffer gets {"type":"lagged","skipped":N} for
+    // the events it missed, so it can resync over REST; past `api.ws_max_missed_events` missed
+    // events in total it is disconnected.
+    let mut rx = state.events_tx.subscribe();
+    let mut missed: u64 = 0;
+
+    // Default: all events.
+    let mut allowed_topics: Option<Vec<String>> = None;
+    let mut allowed_mint: Option<String> = None;
+
+    let mut coalesce = false;
+    let mut pending = HeldCandles::new();
+    let mut flush = coalesce_interval(std::time::Duration::from_millis(MAX_COALESCE_MS));
+
+    let mut ping = tokio::time::interval_at(
+        tokio::time::Instant::now() + state.ws_ping_interval,
+        state.ws_ping_interval,
+    );
+    let mut last_pong = tokio::time::Instant::now();
+
+    loop {
+        tokio::select! {
+            recv = socket.recv() => {
+                let Some(Ok(msg)) = recv else { break; };
+                if let Message::Ping(data) = msg {
+                    if socket.send(Message::Pong(data)).await.is_err() {
+                        break;
+                    }
+                } else if let Message::Pong(_) = msg {
+                    last_pong = tokio::time::Instant::now();
+                } else if let Message::Text(txt) = msg {
+                    let Ok(v) = serde_json::from_str::<JsonValue>(&txt) else {
+                        let _ = socket.send(ws_error("invalid json")).await;
+                        continue;
+                    };
+                    match v.get("type").and_then(|x| x.as_str()) {
+                        Some("subscribe") => {
+                            allowed_topics = v.get("topics")
+                                .and_then(|t| t.as_array())
+                                .map(|arr| arr.iter().filter_map(|x| x.as_str().map(|s| s.to_string())).collect::<Vec<_>>());
+                            allowed_mint = v.get("mint").and_then(|m| m.as_str()).map(|s| s.to_string());
+                            // Updates held under the previous subscription still go out.
+                            if !flush_coalesced(&mut socket, &mut pending).await {
+                                break;
+                            }
+                            let coalesce_ms = v.get("coalesce_ms").and_then(|x| x.as_u64()).unwrap_or(0);
+                            coalesce = coalesce_ms > 0;
+                            if coalesce {
+                                flush = coalesce_interval(std::time::Duration::from_millis(
+                                    coalesce_ms.min(MAX_COALESCE_MS),
+                                ));
+                            }
+                            let _ = socket.send(Message::Text(r#"{"type":"subscribed"}"#.to_string())).await;
+                        }
+                        Some(other) => {
+                            let _ = socket.send(ws_error(&format!("unknown message type: {other}"))).await;
+                        }
+                        None => {
+                            let _ = socket.send(ws_error("missing message type")).await;
+                        }
+                    }
+                }
+            }
+            _ = ping.tick() => {
+                if last_pong.elapsed() > state.ws_pong_timeout {
+                    tracing::debug!("closing websocket: no pong for {:?}", last_pong.elapsed());
+                    let _ = socket
+                        .send(Message::Close(Some(CloseFrame {
+                            code: close_code::AWAY,
+                            reason: "pong timeout".into(),
+                        })))
+                        .await;
+                    break;
+                }
+                if socket.send(Message::Ping(Vec::new())).await.is_err() {
+                    break;
+                }
+            }
+            _ = flush.tick(), if !pending.is_empty() => {
+                if !flush_coalesced(&mut socket, &mut pending).await {
+                    break;
+                }
+            }
+            evt = rx.recv() => {
+                let event = match evt {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        missed += skipped;
+                        let notice = format!(r#"{{"type":"lagged","skipped":{skipped}}}"#);
+                        if socket.send(Message::Text(notice)).await.is_err() {
+                            break;
+                        }
+                        if state.ws_max_missed_events.is_some_and(|max| missed >= max) {
+                            tracing::debug!("closing websocket: {missed} events missed");
+                            let _ = socket
+                                .send(Message::Close(Some(CloseFrame {
+                                    code: close_code::AGAIN,
+                                    reason: "too slow".into(),
+                                })))
+                                .await;
+                            break;
+                        }
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                let mint = event.mint_pubkey.as_deref();
+                if let Some(ref topics) = allowed_topics {
+                    if !topics.iter().any(|t| *t == event.topic) {
+                        continue;
+                    }
+                }
+                if let Some(ref m) = allowed_mint {
+                    if mint != Some(m.as_str()) {
+                        continue;
+                    }
+                }
+
+                let mut held_update = None;
+                if let (true, "candles", Some(mint), Some((timeframe_secs, bucket))) =
+                    (coalesce, event.topic.as_str(), mint, &event.candle_bucket)
+                {
+                    let key = (mint.to_string(), *timeframe_secs, bucket.clone());
+                    if event.candle_closed {
+                        held_update = pending.remove(&key);
+                    } else {
+                        let update = serde_json::from_str(&event.json).unwrap_or_default();
+                        match pending.entry(key) {
+                            Entry::Occupied(mut held) => {
+                                merge_candle_update(held.get_mut(), &update)
+                            }
+                            Entry::Vacant(slot) => {
+                                slot.insert(update);
+                            }
+                        }
+                        continue;
+                    }
+                }
+
+                if let Some(update) = held_update {
+                    if socket.send(Message::Text(update.to_string())).await.is_err() {
+                        break;
+                    }
+                }
+                if socket.send(Message::Text(event.json.clone())).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+const LISTENER_RETRY_INITIAL: std::time::Duration = std::time::Duration::from_secs(1);
+const LISTENER_RETRY_MAX: std::time::Duration = std::time::Duration::from_secs(30);
+
+async fn try_connect_event_listener(db_url: &str) -> Result<PgListener, sqlx::Error> {
+    let mut listener = PgListener::connect(db_url).await?;
+    listener.listen("indexer_events").await?;
+    Ok(listener)
+}
+
+/// Connect and LISTEN on `indexer_events`, retrying with exponential backoff (from `initial`,
+/// capped at `max`) until it succeeds, so a DB outage at startup doesn't permanently disable
+/// realtime events. See [`forward_events`] for what happens when the connection drops later.
+async fn connect_event_listener(
+    db_url: &str,
+    initial: std::time::Duration,
+    max: std::time::Duration,
+) -> PgListener {
+    let mut delay = initial;
+    let mut attempt: u32 = 1;
+    loop {
+        match try_connect_event_listener(db_url).await {
+            Ok(listener) => {
+                if attempt > 1 {
+                    tracing::info!("PgListener connected after {attempt} attempts");
+                }
+                return listener;
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "PgListener connect attempt {attempt} failed: {e:?}; retrying in {delay:?}"
+                );
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(max);
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Where [`forward_events`] reads notification payloads from: a `PgListener`, or a fake in
+/// tests.
+trait EventSource {
+    async fn next_payload(&mut self) -> Result<String, sqlx::Error>;
+}
+
+impl EventSource for PgListener {
+    async fn next_payload(&mut self) -> Result<String, sqlx::Error> {
+        self.recv().await.map(|n| n.payload().to_string())
+    }
+}
+
+/// Broadcast every notification to the websocket clients, forever. When a receive fails (e.g.
+/// the database failed over) the source is dropped and `connect` opens and LISTENs on a new
+/// one, after a delay that doubles from `initial` up to `max` while receives keep failing.
+/// `events_tx` stays the same, so connected clients keep their subscriptions.
+async fn forward_events<S, C, F>(
+    mut connect: C,
+    events_tx: &broadcast::Sender<Arc<WsEvent>>,
+    initial: std::time::Duration,
+    max: std::time::Duration,
+) where
+    S: EventSource,
+    C: FnMut() -> F,
+    F: Future<Output = S>,
+{
+    let mut delay = initial;
+    loop {
+        let mut source = connect().await;
+        let err = loop {
+            match source.next_payload().await {
+                Ok(payload) => {
+                    delay = initial;
+                    let _ = events_tx.send(Arc::new(WsEvent::parse(payload)));
+                }
+                Err(err) => break err,
+            }
+        };
+        drop(source);
+        tracing::warn!("PgListener recv failed: {err:?}; reconnecting in {delay:?}");
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(max);
+        tracing::info!("PgListener reconnecting");
+    }
+}
+
+#[derive(Parser)]
+#[command(name = "indexer-api", about = "Serves indexed token data over HTTP and WebSocket")]
+struct Cli {
+    /// Log line format: `pretty` or `json`. Overrides `log.format` from the config.
+    #[arg(long)]
+    log_format: Option<LogFormat>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let config = IndexerConfig::from_env()?;
+    init_logging(&config.log, cli.log_format)?;
+
+    let pool = create_pool(&config.db.url, config.db.max_connections).await?;
+    run_migrations(&pool).await?;
+    let rebuilt = rebuild_queued_candles(&pool, config.candles.open_mode).await?;
+    if rebuilt > 0 {
+        tracing::info!("Rebuilt {rebuilt} candle(s) queued by a migration");
+    }
+
+    let capacity = config.api.ws_event_capacity.unwrap_or(DEFAULT_WS_EVENT_CAPACITY).max(1);
+    let (events_tx, _events_rx) = broadcast::channel::<Arc<WsEvent>>(capacity);
+
+    // Background: LISTEN/NOTIFY → broadcast for websocket clients.
+    {
+        let db_url = config.db.url.clone();
+        let events_tx = events_tx.clone();
+        tokio::spawn(async move {
+            let connect =
+                || connect_event_listener(&db_url, LISTENER_RETRY_INITIAL, LISTENER_RETRY_MAX);
+            forward_events(connect, &events_tx, LISTENER_RETRY_INITIAL, LISTENER_RETRY_MAX).await;
+        });
+    }
+
+    let state = AppState {
+        pool,
+        events_tx,
+        candle_warmup_min_rows: config
+            .api
+            .candle_warmup_min_rows
+            .unwrap_or(DEFAULT_CANDLE_WARMUP_MIN_ROWS),
+        default_candle_timeframe_secs: config
+            .api
+            .default_candle_timeframe_secs
+            .unwrap_or(DEFAULT_CANDLE_TIMEFRAME_SECS),
+        default_candle_limit: config
+            .api
+            .default_candle_limit
+            .unwrap_or(DEFAULT_CANDLE_LIMIT),
+        base_candle_timeframe_secs: config.candles.base_timeframe(),
+        candle_open_mode: config.candles.open_mode,
+        candle_backfills: Arc::default(),
+        admin_token: config
+            .api
+            .admin_token
+            .filter(|t| !t.is_empty())
+            .map(Arc::from),
+        ws_ping_interval: config
+            .api
+            .ws_ping_interval_secs
+            .filter(|&secs| secs > 0)
+            .map_or(DEFAULT_WS_PING_INTERVAL, Duration::from_secs),
+        ws_pong_timeout: config
+            .api
+            .ws_pong_timeout_secs
+            .map_or(DEFAULT_WS_PONG_TIMEOUT, Duration::from_secs),
+        ws_max_missed_events: config.api.ws_max_missed_events.filter(|&n| n > 0),
+        rate_limiter: match config.api.requests_per_sec.unwrap_or(DEFAULT_REQUESTS_PER_SEC) {
+            0 => None,
+            per_sec => Some(Arc::new(RateLimiter::new(
+                per_sec,
+                config.api.burst.unwrap_or(per_sec.saturating_mul(2)),
+            ))),
+        },
+    };
+    if state.admin_token.is_none() {
+        tracing::info!("api.admin_token is not set: /admin routes are disabled");
+    }
+    let production = config.runtime.environment == "production";
+    let app = router(state).layer(cors_layer(config.api.allowed_origins.as_deref(), production));
 
     let addr: SocketAddr = config.api.bind_addr.parse()?;
     tracing::info!("Starting API server on {}", addr);
 
     let listener = TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    serve(listener, app, shutdown_signal()).await?;
+    tracing::info!("API server stopped");
+
+    Ok(())
+}
 
+/// Serve until `shutdown` resolves, then stop accepting connections and let in-flight
+/// requests finish.
+async fn serve(
+    listener: TcpListener,
+    app: Router,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> Result<()> {
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown)
+        .await?;
     Ok(())
 }
 
+/// CORS for browser dashboards. `allowed_origins` lists exact origins (`"*"` allows any); unset
+/// allows any origin outside production and none in production. Applied outside every route so
+/// preflights, 429s and the `/ws` upgrade carry the headers too.
+fn cors_layer(allowed_origins: Option<&[String]>, production: bool) -> CorsLayer {
+    let origins = match allowed_origins {
+        Some(list) if list.iter().any(|o| o == "*") => AllowOrigin::any(),
+        Some(list) => AllowOrigin::list(list.iter().filter_map(|origin| {
+            let value = HeaderValue::from_str(origin).ok();
+            if value.is_none() {
+                tracing::warn!("ignoring invalid api.allowed_origins entry {origin:?}");
+            }
+            value
+        })),
+        None if production => AllowOrigin::list([]),
+        None => AllowOrigin::any(),
+    };
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+        .expose_headers([HeaderName::from_static(NEXT_CURSOR_HEADER), header::RETRY_AFTER])
+}
+
+fn router(state: AppState) -> Router {
+    let admin = Router::new()
+        .route(
+            "/candles/:mint/backfill",
+            post(admin_backfill_candles_handler),
+        )
+        .route(
+            "/balances/:mint/recompute",
+            post(admin_recompute_balances_handler),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_admin_token,
+        ));
+
+    Router::new()
+        .route("/metrics", get(metrics_handler))
+        .route("/metrics.json", get(metrics_json_handler))
+        .route("/trending", get(trending_handler))
+        .route("/search", get(search_handler))
+        .route(
+            "/token/:mint/transfers",
+            get(token_transfers_handler),
+        )
+        .route(
+            "/token/:mint/holders",
+            get(token_holders_handler),
+        )
+        .route(
+            "/token/:mint/holder_count",
+            get(token_holder_count_handler),
+        )
+        .route(
+            "/wallet/:owner/portfolio",
+            get(wallet_portfolio_handler),
+        )
+        .route(
+            "/wallet/:owner/pnl",
+            get(wallet_pnl_handler),
+        )
+        .route(
+            "/token/:mint/bonding_trades",
+            get(bonding_trades_handler),
+        )
+        .route(
+            "/token/:mint/trader_stats",
+            get(trader_stats_handler),
+        )
+        .route(
+            "/token/:mint/bonding_progress",
+            get(bonding_progress_handler),
+        )
+        .route(
+            "/token/:mint/safety_events",
+            get(safety_events_handler),
+        )
+        .route("/token/:mint/recent", get(token_recent_handler))
+        .route(
+            "/token/:mint/candles",
+            get(token_candles_handler),
+        )
+        .route("/token/:mint/ohlcv", get(token_ohlcv_handler))
+        .route(
+            "/token/:mint/candles.bin",
+            get(token_candles_bin_handler),
+        )
+        .route("/candles/batch", post(candles_batch_handler))
+        .route("/ws", get(ws_handler))
+        .nest("/admin", admin)
+        // Only routes added above are limited; health checks never are.
+        .route_layer(middleware::from_fn_with_state(state.clone(), rate_limit))
+        .route("/health", get(health))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod serve_tests {
+    use super::*;
+    use indexer_core::candles::DEFAULT_BASE_CANDLE_TIMEFRAME_SECS;
+    use std::time::Duration;
+    use tokio::sync::oneshot;
+
+    /// `AppState` with every setting at its default and no rate limiting. Tests change what
+    /// they need with struct-update syntax.
+    pub(super) fn test_state(pool: PgPool) -> AppState {
+        let (events_tx, _) = broadcast::channel::<Arc<WsEvent>>(16);
+        AppState {
+            pool,
+            events_tx,
+            candle_warmup_min_rows: DEFAULT_CANDLE_WARMUP_MIN_ROWS,
+            default_candle_timeframe_secs: DEFAULT_CANDLE_TIMEFRAME_SECS,
+            default_candle_limit: DEFAULT_CANDLE_LIMIT,
+            base_candle_timeframe_secs: DEFAULT_BASE_CANDLE_TIMEFRAME_SECS,
+            candle_open_mode: CandleOpenMode::default(),
+            candle_backfills: Arc::default(),
+            admin_token: None,
+            ws_ping_interval: DEFAULT_WS_PING_INTERVAL,
+            ws_pong_timeout: DEFAULT_WS_PONG_TIMEOUT,
+            ws_max_missed_events: None,
+            rate_limiter: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serve_stops_when_shutdown_fires() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route("/health", get(health));
+
+        let (trigger, signal) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve(listener, app, async {
+            let _ = signal.await;
+        }));
+
+        // Server is up and answering before the signal.
+        let stream = tokio::net::TcpStream::connect(addr).await;
+        assert!(stream.is_ok());
+        drop(stream);
+        assert!(!server.is_finished());
+
+        trigger.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("server did not shut down")
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_event_listener_keeps_retrying_bad_url() {
+        let connect = connect_event_listener(
+            "postgres://nobody@127.0.0.1:1/missing",
+            Duration::from_millis(10),
+            Duration::from_millis(20),
+        );
+
+        // A failed connect must not give up: the future is still retrying when we stop waiting.
+        let result = tokio::time::timeout(Duration::from_millis(500), connect).await;
+        assert!(result.is_err(), "listener connect returned instead of retrying");
+    }
+
+    /// Yields its scripted payloads, then fails; the last connection instead waits forever.
+    struct ScriptedSource {
+        payloads: std::collections::VecDeque<&'static str>,
+        fails: bool,
+    }
+
+    impl EventSource for ScriptedSource {
+        async fn next_payload(&mut self) -> Result<String, sqlx::Error> {
+            match self.payloads.pop_front() {
+                Some(payload) => Ok(payload.to_string()),
+                None if self.fails => Err(sqlx::Error::PoolClosed),
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_forward_events_reconnects_after_recv_error() {
+        let (events_tx, mut events_rx) = broadcast::channel::<Arc<WsEvent>>(16);
+        let mut connections = vec![
+            ScriptedSource {
+                payloads: [r#"{"topic":"bonding"}"#].into(),
+                fails: true,
+            },
+            ScriptedSource {
+                payloads: [].into(),
+                fails: true,
+            },
+            ScriptedSource {
+                payloads: [r#"{"topic":"candles"}"#].into(),
+                fails: false,
+            },
+        ]
+        .into_iter();
+        let connects = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counted = connects.clone();
+        let connect = move || {
+            counted.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            std::future::ready(connections.next().expect("reconnected too often"))
+        };
+        let forward = tokio::spawn(async move {
+            let (initial, max) = (Duration::from_millis(1), Duration::from_millis(5));
+            forward_events(connect, &events_tx, initial, max).await;
+        });
+
+        // The same subscriber sees events from before and after the reconnects.
+        for topic in ["bonding", "candles"] {
+            let event = tokio::time::timeout(Duration::from_secs(5), events_rx.recv())
+                .await
+                .expect("no event after reconnect")
+                .unwrap();
+            assert_eq!(event.topic, topic);
+        }
+        assert_eq!(connects.load(std::sync::atomic::Ordering::SeqCst), 3);
+        assert!(!forward.is_finished());
+        forward.abort();
+    }
+
+    type TestWs =
+        tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+    /// Send one text frame and parse the server's next frame as JSON.
+    async fn ws_roundtrip(ws: &mut TestWs, text: &str) -> JsonValue {
+        use futures::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+        ws.send(WsMessage::Text(text.to_string())).await.unwrap();
+        let msg = tokio::time::timeout(Duration::from_secs(5), ws.next())
+            .await
+            .expect("no reply")
+            .unwrap()
+            .unwrap();
+        serde_json::from_str(msg.to_text().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_ws_coalesces_rapid_candle_updates() {
+        use futures::StreamExt;
+
+        let pool = PgPool::connect_lazy("postgres://unused@127.0.0.1/unused").unwrap();
+        let (events_tx, _) = broadcast::channel::<Arc<WsEvent>>(16);
+        let app = router(AppState {
+            events_tx: events_tx.clone(),
+            ..test_state(pool)
+        });
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, app, std::future::pending()));
+
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/ws"))
+            .await
+            .unwrap();
+        let body = ws_roundtrip(&mut ws, r#"{"type":"subscribe","coalesce_ms":300}"#).await;
+        assert_eq!(body["type"], "subscribed");
+
+        let event = |topic: &str, payload: JsonValue| {
+            let json =
+                serde_json::json!({ "topic": topic, "mint_pubkey": "mint", "payload": payload });
+            Arc::new(WsEvent::parse(json.to_string()))
+        };
+        for close in 1..=5 {
+            let update = serde_json::json!({
+                "timeframe_secs": 60, "bucket_start": "t0", "close": close, "closed": false
+            });
+            events_tx.send(event("candles", update)).unwrap();
+        }
+        events_tx.send(event("bonding", serde_json::json!({ "side": "buy" }))).unwrap();
+
+        async fn next(ws: &mut TestWs) -> Option<JsonValue> {
+            let msg = tokio::time::timeout(Duration::from_secs(2), ws.next())
+                .await
+                .ok()?
+                .unwrap()
+                .unwrap();
+            Some(serde_json::from_str(msg.to_text().unwrap()).unwrap())
+        }
+
+        // Other topics are not held back.
+        assert_eq!(next(&mut ws).await.unwrap()["topic"], "bonding");
+        // The five updates arrive as one, carrying the latest values.
+        let candle = next(&mut ws).await.unwrap();
+        assert_eq!(candle["topic"], "candles");
+        assert_eq!(candle["payload"]["close"], 5);
+        assert!(next(&mut ws).await.is_none(), "more than one candle update delivered");
+    }
+
+    #[tokio::test]
+    async fn test_ws_coalesces_candle_updates_per_timeframe_and_bucket() {
+        use futures::StreamExt;
+
+        let pool = PgPool::connect_lazy("postgres://unused@127.0.0.1/unused").unwrap();
+        let (events_tx, _) = broadcast::channel::<Arc<WsEvent>>(16);
+        let app = router(AppState {
+            events_tx: events_tx.clone(),
+            ..test_state(pool)
+        });
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, app, std::future::pending()));
+
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/ws"))
+            .await
+            .unwrap();
+        let body = ws_roundtrip(&mut ws, r#"{"type":"subscribe","coalesce_ms":300}"#).await;
+        assert_eq!(body["type"], "subscribed");
+
+        // One writer flush's trades in a bucket, as the writer announces them.
+        let update = |timeframe_secs: i64, open: i64, close: i64, volume_sol: i64| {
+            let payload = serde_json::json!({
+                "timeframe_secs": timeframe_secs, "bucket_start": "t0", "open": open,
+                "high": open.max(close), "low": open.min(close), "close": close,
+                "volume_token": 1, "volume_sol": volume_sol, "trades_count": 1, "closed": false
+            });
+            let json =
+                serde_json::json!({ "topic": "candles", "mint_pubkey": "mint", "payload": payload });
+            Arc::new(WsEvent::parse(json.to_string()))
+        };
+        for tf in [60, 300] {
+            events_tx.send(update(tf, 10, 12, 100)).unwrap();
+        }
+        for tf in [60, 300] {
+            events_tx.send(update(tf, 12, 8, 50)).unwrap();
+        }
+        let close = serde_json::json!({
+            "topic": "candles", "mint_pubkey": "mint",
+            "payload": { "timeframe_secs": 60, "bucket_start": "t0", "closed": true }
+        });
+        events_tx.send(Arc::new(WsEvent::parse(close.to_string()))).unwrap();
+
+        async fn next(ws: &mut TestWs) -> Option<JsonValue> {
+            let msg = tokio::time::timeout(Duration::from_secs(2), ws.next())
+                .await
+                .ok()?
+                .unwrap()
+                .unwrap();
+            Some(serde_json::from_str(msg.to_text().unwrap()).unwrap())
+        }
+        let merged = |timeframe_secs: i64| {
+            serde_json::json!({
+                "timeframe_secs": timeframe_secs, "bucket_start": "t0", "open": 10, "high": 12,
+                "low": 8, "close": 8, "volume_token": 2, "volume_sol": 150, "trades_count": 2,
+                "closed": false
+            })
+        };
+
+        // The 1m close flushes only the 1m update, with both flushes' trades in it.
+        assert_eq!(next(&mut ws).await.unwrap()["payload"], merged(60));
+        assert_eq!(next(&mut ws).await.unwrap()["payload"]["closed"], true);
+        // The 5m update stays held until the interval, just as complete.
+        assert_eq!(next(&mut ws).await.unwrap()["payload"], merged(300));
+        assert!(next(&mut ws).await.is_none(), "more than one update per bucket delivered");
+    }
+
+    #[tokio::test]
+    async fn test_ws_slow_client_gets_lagged_notice_then_closed() {
+        use futures::StreamExt;
+        use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+        let pool = PgPool::connect_lazy("postgres://unused@127.0.0.1/unused").unwrap();
+        let (events_tx, _) = broadcast::channel::<Arc<WsEvent>>(4);
+        let app = router(AppState {
+            events_tx: events_tx.clone(),
+            ws_max_missed_events: Some(10),
+            ..test_state(pool)
+        });
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, app, std::future::pending()));
+
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/ws"))
+            .await
+            .unwrap();
+        let body = ws_roundtrip(&mut ws, r#"{"type":"subscribe"}"#).await;
+        assert_eq!(body["type"], "subscribed");
+
+        // The test runtime is single-threaded, so the server reads nothing while these are sent:
+        // of each burst of 10 only the newest 4 are still buffered.
+        let burst = || {
+            for seq in 0..10 {
+                let json = serde_json::json!({
+                    "topic": "bonding", "mint_pubkey": "mint", "payload": { "seq": seq }
+                });
+                events_tx.send(Arc::new(WsEvent::parse(json.to_string()))).unwrap();
+            }
+        };
+        async fn next(ws: &mut TestWs) -> WsMessage {
+            tokio::time::timeout(Duration::from_secs(2), ws.next())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap()
+        }
+        let next_json = |msg: WsMessage| -> JsonValue {
+            serde_json::from_str(msg.to_text().unwrap()).unwrap()
+        };
+
+        burst();
+        let notice = next_json(next(&mut ws).await);
+        assert_eq!(notice, serde_json::json!({ "type": "lagged", "skipped": 6 }));
+        for seq in 6..10 {
+            assert_eq!(next_json(next(&mut ws).await)["payload"]["seq"], seq);
+        }
+
+        // 12 missed in total is past the limit of 10: notified, then disconnected.
+        burst();
+        assert_eq!(next_json(next(&mut ws).await)["skipped"], 6);
+        let WsMessage::Close(Some(frame)) = next(&mut ws).await else {
+            panic!("expected a close frame");
+        };
+        assert_eq!(u16::from(frame.code), close_code::AGAIN);
+    }
+
+    #[test]
+    fn test_ws_event_parse() {
+        let json = r#"{"topic":"candles","mint_pubkey":"m1","payload":{"closed":true}}"#;
+        let event = WsEvent::parse(json.to_string());
+        assert_eq!(event.topic, "candles");
+        assert_eq!(event.mint_pubkey.as_deref(), Some("m1"));
+        assert!(event.candle_closed);
+        assert_eq!(event.candle_bucket, None);
+        assert_eq!(event.json, json);
+
+        let json = r#"{"topic":"candles","payload":{"timeframe_secs":60,"bucket_start":"t0"}}"#;
+        let event = WsEvent::parse(json.to_string());
+        assert_eq!(event.candle_bucket, Some((60, "t0".to_string())));
+
+        let event = WsEvent::parse("not json".to_string());
+        assert_eq!(event.topic, "");
+        assert_eq!(event.mint_pubkey, None);
+        assert_eq!(event.json, "not json");
+    }
+
+    /// Fanout benchmark: `cargo test -p indexer-api --release -- --ignored --nocapture bench_ws`.
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn bench_ws_fanout_to_1000_subscribers() {
+        use futures::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+        const SUBSCRIBERS: usize = 1000;
+        const EVENTS_PER_MINT: usize = 20;
+
+        let pool = PgPool::connect_lazy("postgres://unused@127.0.0.1/unused").unwrap();
+        let (events_tx, _) = broadcast::channel::<Arc<WsEvent>>(SUBSCRIBERS * EVENTS_PER_MINT);
+        let app = router(AppState {
+            events_tx: events_tx.clone(),
+            ..test_state(pool)
+        });
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, app, std::future::pending()));
+
+        // One client per mint, so each sees 1 in SUBSCRIBERS events.
+        let mut clients = Vec::with_capacity(SUBSCRIBERS);
+        for i in 0..SUBSCRIBERS {
+            let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/ws"))
+                .await
+                .unwrap();
+            let subscribe = format!(r#"{{"type":"subscribe","topics":["bonding"],"mint":"m{i}"}}"#);
+            ws.send(WsMessage::Text(subscribe)).await.unwrap();
+            ws.next().await.unwrap().unwrap();
+            clients.push(tokio::spawn(async move {
+                for _ in 0..EVENTS_PER_MINT {
+                    ws.next().await.unwrap().unwrap();
+                }
+            }));
+        }
+
+        let started = std::time::Instant::now();
+        for n in 0..EVENTS_PER_MINT {
+            for i in 0..SUBSCRIBERS {
+                let json = serde_json::json!({
+                    "topic": "bonding",
+                    "mint_pubkey": format!("m{i}"),
+                    "payload": { "n": n, "side": "buy", "sol_amount": 1_000_000 },
+                });
+                events_tx.send(Arc::new(WsEvent::parse(json.to_string()))).unwrap();
+            }
+        }
+        for client in clients {
+            client.await.unwrap();
+        }
+        let elapsed = started.elapsed();
+        let events = SUBSCRIBERS * EVENTS_PER_MINT;
+        println!(
+            "{events} events to {SUBSCRIBERS} subscribers in {elapsed:?} ({:.0} events/s)",
+            events as f64 / elapsed.as_secs_f64()
+        );
+    }
+
+    #[test]
+    fn test_rate_limiter_refills_per_ip() {
+        let limiter = RateLimiter::new(2, 3);
+        let a = IpAddr::from([10, 0, 0, 1]);
+        let t0 = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.check(a, t0).is_ok());
+        }
+        assert_eq!(limiter.check(a, t0), Err(Duration::from_millis(500)));
+        // Other clients have their own bucket.
+        assert!(limiter.check(IpAddr::from([10, 0, 0, 2]), t0).is_ok());
+
+        // One token back after half a second at 2/s, and never more than the burst.
+        assert!(limiter.check(a, t0 + Duration::from_millis(500)).is_ok());
+        assert!(limiter.check(a, t0 + Duration::from_millis(500)).is_err());
+        let later = t0 + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert!(limiter.check(a, later).is_ok());
+        }
+        assert!(limiter.check(a, later).is_err());
+    }
+
+    #[test]
+    fn test_rate_limiter_prunes_at_most_once_per_interval() {
+        let limiter = RateLimiter::new(2, 3);
+        let tracked = || limiter.buckets.lock().unwrap().by_ip.len();
+        let fill = |now: Instant| {
+            for i in 0..=RATE_LIMIT_PRUNE_AT as u32 {
+                limiter.check(IpAddr::from(i.to_be_bytes()), now).unwrap();
+            }
+        };
+        let other = IpAddr::from([255, 255, 255, 255]);
+        let t0 = Instant::now();
+
+        fill(t0);
+        // Every bucket has refilled a minute later, so all but the caller's are dropped.
+        let t1 = t0 + RATE_LIMIT_PRUNE_EVERY;
+        limiter.check(other, t1).unwrap();
+        assert_eq!(tracked(), 1);
+
+        // Refilled again, but the last prune was too recent.
+        fill(t1);
+        limiter.check(other, t1 + Duration::from_secs(10)).unwrap();
+        assert_eq!(tracked(), RATE_LIMIT_PRUNE_AT + 2);
+
+        limiter.check(other, t1 + RATE_LIMIT_PRUNE_EVERY).unwrap();
+        assert_eq!(tracked(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_returns_429_with_retry_after() {
+        use axum::extract::connect_info::MockConnectInfo;
+        use tower::ServiceExt;
+
+        // `/search` without a query is rejected before touching the pool.
+        let pool = PgPool::connect_lazy("postgres://unused@127.0.0.1/unused").unwrap();
+        let app = router(AppState {
+            rate_limiter: Some(Arc::new(RateLimiter::new(1, 3))),
+            ..test_state(pool)
+        });
+        let from = |ip: [u8; 4]| app.clone().layer(MockConnectInfo(SocketAddr::from((ip, 4000))));
+        let get = |app: Router, uri: &'static str| async move {
+            app.oneshot(
+                axum::http::Request::builder()
+                    .uri(uri)
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+        };
+
+        for _ in 0..3 {
+            let response = get(from([10, 0, 0, 1]), "/search?q=").await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+        let response = get(from([10, 0, 0, 1]), "/search?q=").await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+
+        // Health checks and other clients are unaffected.
+        let response = get(from([10, 0, 0, 1]), "/health").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = get(from([10, 0, 0, 2]), "/search?q=").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_cors_allows_configured_origins() {
+        use tower::ServiceExt;
+
+        let request = |origin: &str| {
+            axum::http::Request::builder()
+                .uri("/health")
+                .header(header::ORIGIN, origin)
+                .body(axum::body::Body::empty())
+                .unwrap()
+        };
+        let app = |allowed: Option<&[String]>, production: bool| {
+            Router::new()
+                .route("/health", get(health))
+                .layer(cors_layer(allowed, production))
+        };
+        let allow_origin = |response: &Response| {
+            response
+                .headers()
+                .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .map(|v| v.to_str().unwrap().to_string())
+        };
+
+        let allowed = ["https://dash.example.com".to_string()];
+        let response = app(Some(&allowed), true)
+            .oneshot(request("https://dash.example.com"))
+            .await
+            .unwrap();
+        assert_eq!(allow_origin(&response).as_deref(), Some("https://dash.example.com"));
+        let exposed = &response.headers()[header::ACCESS_CONTROL_EXPOSE_HEADERS];
+        assert!(exposed.to_str().unwrap().contains(NEXT_CURSOR_HEADER));
+
+        let response = app(Some(&allowed), true)
+            .oneshot(request("https://evil.example.com"))
+            .await
+            .unwrap();
+        assert_eq!(allow_origin(&response), None);
+
+        // Unset: anything goes in dev, nothing in production.
+        let response = app(None, false).oneshot(request("http://localhost:3000")).await.unwrap();
+        assert_eq!(allow_origin(&response).as_deref(), Some("*"));
+        let response = app(None, true).oneshot(request("http://localhost:3000")).await.unwrap();
+        assert_eq!(allow_origin(&response), None);
+
+        // Preflights are answered by the layer itself.
+        let preflight = axum::http::Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/health")
+            .header(header::ORIGIN, "https://dash.example.com")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = app(Some(&allowed), true).oneshot(preflight).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(allow_origin(&response).as_deref(), Some("https://dash.example.com"));
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"token", b"token"));
+        assert!(!constant_time_eq(b"token", b"tokeN"));
+        assert!(!constant_time_eq(b"token", b"token2"));
+        assert!(!constant_time_eq(b"", b"x"));
+    }
+
+    #[tokio::test]
+    async fn test_admin_routes_disabled_without_token() {
+        use tower::ServiceExt;
+
+        // Rejected by the middleware before any handler touches the pool.
+        let pool = PgPool::connect_lazy("postgres://unused@127.0.0.1/unused").unwrap();
+        let app = router(test_state(pool));
+
+        let response = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .method("POST")
+                    .uri("/admin/candles/mint/backfill")
+                    .header(header::AUTHORIZATION, "Bearer ")
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_ws_replies_with_error_for_bad_messages() {
+        // The websocket handler never touches the database, so a lazy pool is enough.
+        let pool = PgPool::connect_lazy("postgres://unused@127.0.0.1/unused").unwrap();
+        let app = router(test_state(pool));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, app, std::future::pending()));
+
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/ws"))
+            .await
+            .unwrap();
+
+        let body = ws_roundtrip(&mut ws, r#"{"type":"subscrbe"}"#).await;
+        assert_eq!(body["type"], "error");
+        assert_eq!(body["message"], "unknown message type: subscrbe");
+
+        let body = ws_roundtrip(&mut ws, "not json").await;
+        assert_eq!(body["type"], "error");
+        assert_eq!(body["message"], "invalid json");
+
+        // Valid subscribe still works after errors.
+        let body = ws_roundtrip(&mut ws, r#"{"type":"subscribe","topics":["candles"]}"#).await;
+        assert_eq!(body["type"], "subscribed");
+    }
+
+    #[tokio::test]
+    async fn test_ws_keepalive_pings_and_drops_silent_clients() {
+        use futures::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+        let pool = PgPool::connect_lazy("postgres://unused@127.0.0.1/unused").unwrap();
+        let (events_tx, _) = broadcast::channel::<Arc<WsEvent>>(16);
+        let app = router(AppState {
+            events_tx: events_tx.clone(),
+            ws_ping_interval: Duration::from_millis(50),
+            ws_pong_timeout: Duration::from_millis(150),
+            ..test_state(pool)
+        });
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, app, std::future::pending()));
+        let url = format!("ws://{addr}/ws");
+
+        // A client that keeps reading answers pings (tungstenite replies on its own) and stays
+        // connected well past the pong timeout.
+        let (mut ws, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(600);
+        let mut pings = 0;
+        while let Ok(msg) = tokio::time::timeout_at(deadline, ws.next()).await {
+            match msg.unwrap().unwrap() {
+                WsMessage::Ping(_) => pings += 1,
+                other => panic!("unexpected frame while idle: {other:?}"),
+            }
+        }
+        assert!(pings >= 3, "only {pings} pings in 600ms");
+
+        let event = WsEvent::parse(r#"{"topic":"bonding","mint_pubkey":"mint"}"#.to_string());
+        events_tx.send(Arc::new(event)).unwrap();
+        loop {
+            let msg = tokio::time::timeout(Duration::from_secs(2), ws.next())
+                .await
+                .expect("connection went quiet")
+                .unwrap()
+                .unwrap();
+            if let WsMessage::Text(text) = msg {
+                assert!(text.contains("bonding"));
+                break;
+            }
+        }
+
+        // Client pings are answered.
+        ws.send(WsMessage::Ping(b"hi".to_vec())).await.unwrap();
+        loop {
+            let msg = tokio::time::timeout(Duration::from_secs(2), ws.next())
+                .await
+                .expect("no pong")
+                .unwrap()
+                .unwrap();
+            if msg == WsMessage::Pong(b"hi".to_vec()) {
+                break;
+            }
+        }
+
+        // A client that never reads never pongs, so the server hangs up on it.
+        let (mut silent, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        // Draining the queued pings may hit the reset socket before the close frame shows up.
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while let Some(Ok(msg)) = silent.next().await {
+                if msg.is_close() {
+                    break;
+                }
+            }
+        })
+        .await
+        .expect("silent client was not disconnected");
+    }
+}
+
+#[cfg(all(test, db_tests))]
+mod tests {
+    use super::serve_tests::test_state;
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use chrono::TimeZone;
+    use http_body_util::BodyExt;
+    use indexer_core::db::{
+        add_bad_indices, add_parse_misses, insert_bonding_curve_trades, insert_token_events,
+        insert_transfers, set_last_processed_slot, update_balances_for_transfers, upsert_candle,
+        upsert_mints,
+    };
+    use indexer_core::models::{BondingCurveTrade, Mint, TokenEvent};
+    use indexer_core::spl_parser::{SPL_TOKEN_PROGRAM_ID, TOP_LEVEL_INNER_INDEX};
+    use tower::ServiceExt;
+
+    const MINT: &str = "TestMint1111111111111111111111111111111111";
+
+    fn test_app(pool: PgPool) -> Router {
+        router(test_state(pool))
+    }
+
+    async fn get_json(app: Router, uri: &str) -> (StatusCode, JsonValue) {
+        let response = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    async fn post_json(app: Router, uri: &str, body: JsonValue) -> (StatusCode, JsonValue) {
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&bytes).unwrap_or(JsonValue::Null))
+    }
+
+    fn transfer(signature: &str, slot: i64, source: &str, dest: &str, amount: i64) -> TokenTransfer {
+        TokenTransfer {
+            signature: signature.to_string(),
+            slot,
+            block_time: Utc.timestamp_opt(1_700_000_000 + slot, 0).single(),
+            mint_pubkey: MINT.to_string(),
+            source_owner: source.to_string(),
+            dest_owner: dest.to_string(),
+            source_ata: source.to_string(),
+            dest_ata: dest.to_string(),
+            amount,
+            tx_index: 0,
+            ix_index: 0,
+            inner_index: TOP_LEVEL_INNER_INDEX,
+            program: SPL_TOKEN_PROGRAM_ID.to_string(),
+        }
+    }
+
+    fn trade(signature: &str, slot: i64, side: &str, price: i64) -> BondingCurveTrade {
+        BondingCurveTrade {
+            signature: signature.to_string(),
+            slot,
+            block_time: Utc.timestamp_opt(1_700_000_000 + slot, 0).single(),
+            mint_pubkey: MINT.to_string(),
+            trader: "trader".to_string(),
+            side: side.to_string(),
+            token_amount: 1_000,
+            sol_amount: 1_000 * price,
+            price_nanos_per_token: price,
+            tx_index: 0,
+            ix_index: 0,
+            inner_index: TOP_LEVEL_INNER_INDEX,
+        }
+    }
+
+    fn candle(bucket_unix: i64, open: i64, close: i64) -> Candle {
+        Candle {
+            mint_pubkey: MINT.to_string(),
+            timeframe_secs: 60,
+            bucket_start: Utc.timestamp_opt(bucket_unix, 0).single().unwrap(),
+            open,
+            high: open.max(close),
+            low: open.min(close),
+            close,
+            volume_token: 1_000,
+            volume_sol: 1_000 * close,
+            trades_count: 1,
+        }
+    }
+
+    async fn seed(pool: &PgPool) {
+        upsert_mints(
+            pool,
+            &[Mint {
+                mint_pubkey: MINT.to_string(),
+                symbol: Some("TEST".to_string()),
+                decimals: 6,
+                first_seen_slot: 100,
+            }],
+        )
+        .await
+        .unwrap();
+
+        let transfers = vec![
+            transfer("sig_mint", 100, "system", "alice", 1_000),
+            transfer("sig_xfer", 101, "alice", "bob", 300),
+        ];
+        insert_transfers(pool, &transfers).await.unwrap();
+        update_balances_for_transfers(pool, &transfers).await.unwrap();
+
+        insert_bonding_curve_trades(
+            pool,
+            &[trade("sig_buy", 102, "buy", 10), trade("sig_sell", 103, "sell", 12)],
+        )
+        .await
+        .unwrap();
+
+        upsert_candle(pool, &candle(1_700_000_040, 10, 11)).await.unwrap();
+        upsert_candle(pool, &candle(1_700_000_100, 11, 12)).await.unwrap();
+
+        set_last_processed_slot(pool, 103).await.unwrap();
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_candles_endpoint_returns_newest_first(pool: PgPool) {
+        seed(&pool).await;
+
+        let (status, body) = get_json(test_app(pool), &format!("/token/{MINT}/candles")).await;
+
+        assert_eq!(status, StatusCode::OK);
+        let candles = body.as_array().unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[0]["open"], 11);
+        assert_eq!(candles[0]["close"], 12);
+        assert_eq!(candles[1]["open"], 10);
+        assert_eq!(candles[0]["timeframe_secs"], 60);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_ohlcv_endpoint_rolls_up_base_candles(pool: PgPool) {
+        seed(&pool).await;
+        let app = test_app(pool);
+
+        // Both seeded 60s candles fall into the same hour.
+        let uri = format!("/token/{MINT}/ohlcv?interval=3600");
+        let (status, body) = get_json(app.clone(), &uri).await;
+        assert_eq!(status, StatusCode::OK);
+        let candles = body.as_array().unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0]["timeframe_secs"], 3600);
+        assert_eq!(candles[0]["open"], 10);
+        assert_eq!(candles[0]["close"], 12);
+        assert_eq!(candles[0]["trades_count"], 2);
+
+        for uri in [
+            format!("/token/{MINT}/ohlcv"),
+            format!("/token/{MINT}/ohlcv?interval=90"),
+            format!("/token/{MINT}/ohlcv?interval=0"),
+        ] {
+            let request = Request::builder().uri(&uri).body(Body::empty()).unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{uri}");
+        }
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_candles_endpoint_uses_configured_defaults(pool: PgPool) {
+        seed(&pool).await;
+        for (bucket, close) in [(1_700_000_100, 20), (1_700_000_400, 21)] {
+            let five_min = Candle {
+                timeframe_secs: 300,
+                ..candle(bucket, close, close)
+            };
+            upsert_candle(&pool, &five_min).await.unwrap();
+        }
+        let app = router(AppState {
+            candle_warmup_min_rows: 0,
+            default_candle_timeframe_secs: 300,
+            default_candle_limit: 1,
+            base_candle_timeframe_secs: 60,
+            ..test_state(pool)
+        });
+
+        let (status, body) = get_json(app.clone(), &format!("/token/{MINT}/candles")).await;
+        assert_eq!(status, StatusCode::OK);
+        let candles = body.as_array().unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0]["timeframe_secs"], 300);
+        assert_eq!(candles[0]["close"], 21);
+
+        // Query parameters still override the defaults.
+        let uri = format!("/token/{MINT}/candles?timeframe_secs=60&limit=10");
+        let (_, body) = get_json(app.clone(), &uri).await;
+        let candles = body.as_array().unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[0]["timeframe_secs"], 60);
+
+        // Timeframes below the base one are served at the base.
+        let uri = format!("/token/{MINT}/candles?timeframe_secs=1&limit=10");
+        let (_, body) = get_json(app, &uri).await;
+        assert_eq!(body.as_array().unwrap().len(), 2);
+        assert_eq!(body[0]["timeframe_secs"], 60);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_binary_candles_match_json_endpoint(pool: PgPool) {
+        use indexer_core::candle_codec::decode_candles;
+
+        seed(&pool).await;
+        let app = test_app(pool);
+
+        let (_, json) = get_json(app.clone(), &format!("/token/{MINT}/candles")).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/token/{MINT}/candles.bin"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/octet-stream"
+        );
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let decoded = decode_candles(MINT, &bytes).unwrap();
+
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), json);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_candles_batch_groups_by_mint(pool: PgPool) {
+        const OTHER: &str = "OtherMint111111111111111111111111111111111";
+        seed(&pool).await;
+        upsert_mints(
+            &pool,
+            &[Mint {
+                mint_pubkey: OTHER.to_string(),
+                symbol: None,
+                decimals: 6,
+                first_seen_slot: 100,
+            }],
+        )
+        .await
+        .unwrap();
+        let other = Candle {
+            mint_pubkey: OTHER.to_string(),
+            ..candle(1_700_000_040, 50, 55)
+        };
+        upsert_candle(&pool, &other).await.unwrap();
+
+        let (status, body) = post_json(
+            test_app(pool),
+            "/candles/batch",
+            serde_json::json!({ "mints": [MINT, OTHER, "NoCandles"], "timeframe_secs": 60 }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        let mint_candles = body[MINT].as_array().unwrap();
+        assert_eq!(mint_candles.len(), 2);
+        assert_eq!(mint_candles[0]["open"], 11);
+        assert_eq!(mint_candles[1]["open"], 10);
+        let other_candles = body[OTHER].as_array().unwrap();
+        assert_eq!(other_candles.len(), 1);
+        assert_eq!(other_candles[0]["open"], 50);
+        assert_eq!(other_candles[0]["mint_pubkey"], OTHER);
+        assert!(body["NoCandles"].as_array().unwrap().is_empty());
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_candles_batch_rejects_too_many_mints(pool: PgPool) {
+        let mints: Vec<String> = (0..=MAX_BATCH_MINTS).map(|i| format!("mint{i}")).collect();
+
+        let (status, _) = post_json(
+            test_app(pool),
+            "/candles/batch",
+            serde_json::json!({ "mints": mints }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_holders_endpoint_orders_by_balance(pool: PgPool) {
+        seed(&pool).await;
+
+        let (status, body) =
+            get_json(test_app(pool), &format!("/token/{MINT}/holders?limit=2")).await;
+
+        assert_eq!(status, StatusCode::OK);
+        let holders = body.as_array().unwrap();
+        assert_eq!(holders.len(), 2);
+        assert_eq!(holders[0]["wallet"], "alice");
+        assert_eq!(holders[0]["amount"], 700);
+        assert_eq!(holders[1]["wallet"], "bob");
+        assert_eq!(holders[1]["amount"], 300);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_trades_cursor_pages_through_a_shared_slot(pool: PgPool) {
+        seed(&pool).await;
+        // Five transactions in one slot, the last with two inner swaps under one instruction.
+        let mut same_slot: Vec<BondingCurveTrade> = (0..5)
+            .map(|i| BondingCurveTrade {
+                tx_index: i,
+                ..trade(&format!("sig_110_{i}"), 110, "buy", 10)
+            })
+            .collect();
+        same_slot.push(BondingCurveTrade {
+            tx_index: 4,
+            inner_index: 3,
+            ..trade("sig_110_4", 110, "buy", 10)
+        });
+        insert_bonding_curve_trades(&pool, &same_slot).await.unwrap();
+        let app = test_app(pool);
+
+        let mut seen = Vec::new();
+        let mut uri = format!("/token/{MINT}/bonding_trades?limit=2");
+        for _ in 0..10 {
+            let response = app
+                .clone()
+                .oneshot(Request::builder().uri(&uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let next = response
+                .headers()
+                .get(NEXT_CURSOR_HEADER)
+                .map(|v| v.to_str().unwrap().to_string());
+            let bytes = response.into_body().collect().await.unwrap().to_bytes();
+            let page: JsonValue = serde_json::from_slice(&bytes).unwrap();
+            for t in page.as_array().unwrap() {
+                let signature = t["signature"].as_str().unwrap().to_string();
+                seen.push((signature, t["inner_index"].as_i64().unwrap()));
+            }
+            let Some(next) = next else { break };
+            uri = format!("/token/{MINT}/bonding_trades?limit=2&after={next}");
+        }
+
+        let expected: Vec<(String, i64)> = vec![
+            ("sig_110_4".to_string(), 3),
+            ("sig_110_4".to_string(), TOP_LEVEL_INNER_INDEX as i64),
+            ("sig_110_3".to_string(), TOP_LEVEL_INNER_INDEX as i64),
+            ("sig_110_2".to_string(), TOP_LEVEL_INNER_INDEX as i64),
+            ("sig_110_1".to_string(), TOP_LEVEL_INNER_INDEX as i64),
+            ("sig_110_0".to_string(), TOP_LEVEL_INNER_INDEX as i64),
+            ("sig_sell".to_string(), TOP_LEVEL_INNER_INDEX as i64),
+            ("sig_buy".to_string(), TOP_LEVEL_INNER_INDEX as i64),
+        ];
+        assert_eq!(seen, expected);
+
+        let uri = format!("/token/{MINT}/transfers?after=not-a-cursor");
+        let response = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_holder_count_skips_non_positive_balances(pool: PgPool) {
+        seed(&pool).await;
+        // Leaves carol at zero.
+        let transfers = vec![
+            transfer("sig_in", 104, "bob", "carol", 50),
+            transfer("sig_out", 105, "carol", "bob", 50),
+        ];
+        insert_transfers(&pool, &transfers).await.unwrap();
+        update_balances_for_transfers(&pool, &transfers).await.unwrap();
+
+        let (status, body) =
+            get_json(test_app(pool), &format!("/token/{MINT}/holder_count")).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["holder_count"], 2);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_safety_events_endpoint_counts_by_kind(pool: PgPool) {
+        seed(&pool).await;
+        let event = |signature: &str, slot: i64, kind: &str| TokenEvent {
+            signature: signature.to_string(),
+            slot,
+            block_time: Utc.timestamp_opt(1_700_000_000 + slot, 0).single(),
+            mint_pubkey: Some(MINT.to_string()),
+            kind: kind.to_string(),
+            token_account: "alice_ata".to_string(),
+            authority: "alice".to_string(),
+            counterparty: Some("delegate".to_string()),
+            amount: Some(500),
+            tx_index: 0,
+            ix_index: 0,
+            inner_index: TOP_LEVEL_INNER_INDEX,
+        };
+        insert_token_events(
+            &pool,
+            &[
+                event("sig_a1", 104, EVENT_APPROVE),
+                event("sig_a2", 105, EVENT_APPROVE),
+                event("sig_c1", 106, EVENT_CLOSE_ACCOUNT),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let (status, body) =
+            get_json(test_app(pool), &format!("/token/{MINT}/safety_events?limit=2")).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["counts"]["approve"], 2);
+        assert_eq!(body["counts"]["revoke"], 0);
+        assert_eq!(body["counts"]["close_account"], 1);
+        let recent = body["recent"].as_array().unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0]["signature"], "sig_c1");
+        assert_eq!(recent[1]["signature"], "sig_a2");
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_recent_endpoint_combines_token_feeds(pool: PgPool) {
+        seed(&pool).await;
+
+        let (status, body) = get_json(
+            test_app(pool),
+            &format!("/token/{MINT}/recent?trades_limit=1&holders_limit=1"),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        // Seeded trades are far older than the default 24h window.
+        assert_eq!(body["summary"]["window_secs"], 86_400);
+        assert_eq!(body["summary"]["unique_traders"], 0);
+        assert!(body["summary"]["bonding_progress_pct"].is_number());
+
+        let trades = body["recent_trades"].as_array().unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0]["signature"], "sig_sell");
+        // The newest candle closes at the newest trade's price.
+        assert_eq!(body["latest_candle"]["close"], 12);
+        assert_eq!(body["latest_candle"]["close"], trades[0]["price_nanos_per_token"]);
+
+        let holders = body["top_holders"].as_array().unwrap();
+        assert_eq!(holders.len(), 1);
+        assert_eq!(holders[0]["wallet"], "alice");
+        assert_eq!(holders[0]["amount"], 700);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_metrics_endpoint_counts_seeded_rows(pool: PgPool) {
+        seed(&pool).await;
+
+        let (status, body) = get_json(test_app(pool), "/metrics.json").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["token_transfers_count"], 2);
+        assert_eq!(body["bonding_trades_count"], 2);
+        assert_eq!(body["last_processed_slot"], 103);
+        assert_eq!(body["total_mints"], 1);
+        assert_eq!(body["pump_parse_misses"], 0);
+        // Seeded trades are from 2023, so far behind now.
+        assert!(body["lag_secs"].as_i64().unwrap() > 86_400);
+        let tip = body["estimated_tip_slot"].as_i64().unwrap();
+        assert_eq!(body["slots_behind"].as_i64(), Some(tip - 103));
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_metrics_endpoint_reports_parse_misses(pool: PgPool) {
+        add_parse_misses(&pool, "pump", 4).await.unwrap();
+        add_bad_indices(&pool, "spl", 3).await.unwrap();
+
+        let (status, body) = get_json(test_app(pool), "/metrics.json").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["pump_parse_misses"], 4);
+        assert_eq!(body["raydium_parse_misses"], 0);
+        assert_eq!(body["meteora_parse_misses"], 0);
+        assert_eq!(body["orca_parse_misses"], 0);
+        assert_eq!(body["spl_bad_indices"], 3);
+        assert_eq!(body["pump_bad_indices"], 0);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_metrics_endpoint_serves_prometheus_text(pool: PgPool) {
+        seed(&pool).await;
+        add_parse_misses(&pool, "pump", 4).await.unwrap();
+
+        let response = test_app(pool)
+            .oneshot(Request::builder().uri("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let content_type = response.headers()[header::CONTENT_TYPE].to_str().unwrap();
+        assert!(content_type.starts_with("text/plain"));
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(bytes.to_vec()).unwrap();
+
+        for line in [
+            "indexer_token_transfers 2",
+            "indexer_bonding_trades 2",
+            "indexer_mints 1",
+            "indexer_last_processed_slot 103",
+            "indexer_parse_misses_total{parser=\"pump\"} 4",
+            "indexer_parse_misses_total{parser=\"orca\"} 0",
+            "indexer_bad_indices_total{parser=\"spl\"} 0",
+        ] {
+            assert!(text.lines().any(|l| l == line), "missing {line:?} in\n{text}");
+        }
+        assert!(text.lines().any(|l| l.starts_with("indexer_lag_seconds ")));
+        assert!(text.lines().any(|l| l.starts_with("indexer_slots_behind ")));
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_candles_warm_up_from_trades(pool: PgPool) {
+        seed(&pool).await;
+        // Indexer "started mid-history": trades exist but no candles yet.
+        sqlx::query("DELETE FROM candles").execute(&pool).await.unwrap();
+
+        let app = test_app(pool.clone());
+        let uri = format!("/token/{MINT}/candles");
+
+        let (status, body) = get_json(app.clone(), &uri).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.as_array().unwrap().is_empty());
+
+        // The first request scheduled the backfill in the background.
+        for _ in 0..100 {
+            if !get_candles(&pool, MINT, 60, 10, None).await.unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        let (status, body) = get_json(app, &uri).await;
+        assert_eq!(status, StatusCode::OK);
+        let candles = body.as_array().unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0]["open"], 10);
+        assert_eq!(candles[0]["close"], 12);
+        assert_eq!(candles[0]["trades_count"], 2);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_candle_backfill_scheduled_once_per_mint(pool: PgPool) {
+        let state = test_state(pool);
+
+        assert!(schedule_candle_backfill(&state, MINT, 60));
+        assert!(!schedule_candle_backfill(&state, MINT, 60));
+        // Other timeframes are warmed up separately.
+        assert!(schedule_candle_backfill(&state, MINT, 300));
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_admin_routes_require_bearer_token(pool: PgPool) {
+        seed(&pool).await;
+        let app = router(AppState {
+            admin_token: Some(Arc::from("s3cret")),
+            ..test_state(pool)
+        });
+        let uri = format!("/admin/candles/{MINT}/backfill?timeframe_secs=300");
+        let call = |auth: Option<&'static str>| {
+            let mut req = Request::builder().method("POST").uri(&uri);
+            if let Some(auth) = auth {
+                req = req.header(header::AUTHORIZATION, auth);
+            }
+            app.clone().oneshot(req.body(Body::empty()).unwrap())
+        };
+
+        assert_eq!(call(None).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(call(Some("Bearer wrong!")).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(call(Some("s3cret")).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+
+        let response = call(Some("Bearer s3cret")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body: JsonValue = serde_json::from_slice(&bytes).unwrap();
+        // Both seeded trades fall into one 5m bucket
+        assert_eq!(body["inserted"], 1);
+
+        // Public routes are unaffected
+        let (status, _) = get_json(app, "/metrics.json").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
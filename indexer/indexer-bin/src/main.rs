@@ -1,192 +1,827 @@
 use anyhow::Result;
+use clap::{Parser, Subcommand};
 use indexer_core::{
-    bonding_parser::extract_pump_trades_from_block,
-    config::IndexerConfig,
+    block_cache::{list_cache_files, read_cache_file, BlockCacheWriter},
+    bonding_parser::extract_pump_trades_with,
+    candles::{
+        bucket_start, CandleBucketTracker, CandleEvent, CandleEvents, CandleOpenMode,
+        MAX_CANDLE_TIMEFRAME_SECS,
+    },
+    config::{IndexerConfig, RedisConfig, WriterConfig},
     db::{
-        create_pool, get_last_processed_slot, insert_bonding_curve_trades, insert_event,
-        insert_transfers, run_migrations, set_last_processed_slot, update_balances_for_transfers,
-        upsert_candle,
+        add_bad_indices, add_parse_misses, backfill_candles, create_pool, delete_data_for_slots_gte,
+        delete_events_older_than, get_last_processed_slot, get_non_default_mint_decimals,
+        get_token_account_mints, get_wallet_first_seen_slots,
+        insert_bonding_curve_trades_in, insert_event, insert_token_events_in, insert_transfers_in,
+        mark_mints_migrated_in, rebuild_queued_candles, run_migrations, set_last_processed_slot,
+        update_balances_for_transfers_in, upsert_candle, upsert_mints_in,
     },
     firehose::FirehoseClient,
-    models::Candle,
-    raydium_parser::extract_raydium_trades_from_block,
-    meteora_parser::extract_meteora_trades_from_block,
-    spl_parser::{extract_transfers_from_block, BlockRef},
+    logging::{init_logging, LogFormat},
+    models::{BondingCurveTrade, Candle, Mint, TokenEvent, TokenTransfer, UNKNOWN_DECIMALS},
+    raydium_parser::extract_raydium_trades_with,
+    redis::{RedisPublisher, TradeEvent, TransferEvent},
+    meteora_parser::extract_meteora_trades_with,
+    whirlpool_parser::extract_whirlpool_trades_with,
+    price::PriceScale,
+    prometheus::{
+        self, BAD_INDICES_TOTAL, BLOCKS_PROCESSED_TOTAL, DB_INSERT_DURATION_SECONDS,
+        LAST_PROCESSED_SLOT, PARSE_MISSES_TOTAL, PARSE_PANICS_TOTAL, TRADES_PARSED_TOTAL,
+        TRANSFERS_TOTAL,
+    },
+    shutdown::shutdown_signal,
+    spl_parser::{
+        extract_mint_decimals, extract_token_events, extract_transfers_with, BlockRef, ParseCounters,
+        TokenAccountCache,
+        DEFAULT_TOKEN_ACCOUNT_CACHE_SIZE,
+    },
+    trade_rate::{RateAlert, StallDetector, TradeCounters},
 };
-use chrono::TimeZone;
+use metrics::{counter, gauge, histogram};
+use sqlx::{PgConnection, PgPool};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
-use tracing_subscriber::EnvFilter;
+use tokio::time::{sleep_until, Instant};
+
+#[derive(Parser)]
+#[command(name = "indexer-bin", about = "Streams firehose blocks into Postgres")]
+struct Cli {
+    /// Log line format: `pretty` or `json`. Overrides `log.format` from the config.
+    #[arg(long, global = true)]
+    log_format: Option<LogFormat>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Feed cached blocks back through the writer instead of streaming from the firehose.
+    Replay {
+        /// Directory containing `blocks-*.jsonl` files written via `firehose.cache_dir`.
+        #[arg(long)]
+        dir: PathBuf,
+    },
+    /// Rebuild a mint's missing candles from its stored trades, e.g. after whitelisting it.
+    BackfillCandles {
+        #[arg(long)]
+        mint: String,
+        /// Timeframe in seconds; defaults to every timeframe the writer builds.
+        #[arg(long)]
+        timeframe: Option<i32>,
+    },
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
+    let cli = Cli::parse();
 
     let config = IndexerConfig::from_env()?;
+    init_logging(&config.log, cli.log_format)?;
+    if let Some(addr) = config.monitor.metrics_bind_addr.as_deref() {
+        prometheus::install_exporter(addr.parse()?)?;
+        tracing::info!("Serving Prometheus metrics on {addr}");
+    }
 
     tracing::info!("Starting indexer with config: {:?}", config.runtime);
 
     let pool = create_pool(&config.db.url, config.db.max_connections).await?;
     run_migrations(&pool).await?;
+    let rebuilt = rebuild_queued_candles(&pool, config.candles.open_mode).await?;
+    if rebuilt > 0 {
+        tracing::info!("Rebuilt {rebuilt} candle(s) queued by a migration");
+    }
+
+    match cli.command {
+        Some(Command::Replay { dir }) => replay_blocks(&config, &pool, &dir).await?,
+        Some(Command::BackfillCandles { mint, timeframe }) => {
+            let timeframes = match timeframe {
+                Some(tf) if (1..=MAX_CANDLE_TIMEFRAME_SECS).contains(&tf) => vec![tf],
+                Some(tf) => {
+                    anyhow::bail!("--timeframe must be 1..={MAX_CANDLE_TIMEFRAME_SECS}, got {tf}")
+                }
+                None => config.candles.timeframes(),
+            };
+            for tf in timeframes {
+                let inserted = backfill_candles(&pool, &mint, tf, config.candles.open_mode).await?;
+                tracing::info!("Backfilled {inserted} {tf}s candle(s) for {mint}");
+            }
+        }
+        None => run_indexer(config, pool, shutdown_signal()).await?,
+    }
+
+    Ok(())
+}
 
-    run_indexer(config, pool).await?;
+/// Replay every block in a cache directory, oldest segment first.
+/// `last_processed_slot` ends up at the last replayed block, like a live run would leave it.
+async fn replay_blocks(config: &IndexerConfig, pool: &PgPool, dir: &Path) -> Result<()> {
+    let files = list_cache_files(dir)?;
+    tracing::info!("Replaying {} cache file(s) from {}", files.len(), dir.display());
 
+    let mut writer_state = WriterState::new(Arc::default(), config);
+    load_mint_decimals(pool, &mut writer_state.price_scale).await?;
+    let mut replayed = 0u64;
+    for path in files {
+        let blocks = read_cache_file(&path)?;
+        tracing::info!("Replaying {} block(s) from {}", blocks.len(), path.display());
+
+        for block in &blocks {
+            write_block(pool, block, &config.firehose.mint_whitelist, &mut writer_state).await;
+            replayed += 1;
+        }
+    }
+    if !flush_batch(pool, &config.firehose.mint_whitelist, &mut writer_state).await {
+        anyhow::bail!("replay stopped: {} buffered rows not written", writer_state.batch.rows());
+    }
+
+    tracing::info!("Replay finished: {replayed} block(s) written");
     Ok(())
 }
 
-async fn run_indexer(config: IndexerConfig, pool: sqlx::PgPool) -> Result<()> {
+/// Stream from the firehose and write blocks until a task fails or `shutdown` resolves.
+/// On shutdown the firehose is stopped, blocks already queued are written, and the last
+/// written slot is checkpointed before returning.
+async fn run_indexer(
+    config: IndexerConfig,
+    pool: PgPool,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()> {
     let (block_tx, mut block_rx) = mpsc::channel::<BlockRef>(1024);
 
     // Writer task: consumes blocks, parses SPL transfers, and writes to DB.
     let writer_pool = pool.clone();
     let mint_whitelist = config.firehose.mint_whitelist.clone();
-    let writer_handle = tokio::spawn(async move {
-        while let Some(block) = block_rx.recv().await {
-            let transfers = extract_transfers_from_block(&block, &mint_whitelist);
-            let pump_trades = extract_pump_trades_from_block(&block);
-            let raydium_trades = extract_raydium_trades_from_block(&block);
-            let meteora_trades = extract_meteora_trades_from_block(&block);
-
-            if transfers.is_empty() {
-                // still allow pump trades / candles even if there are no SPL transfers in this block
-            }
+    let mut cache = config
+        .firehose
+        .cache_dir
+        .as_deref()
+        .map(BlockCacheWriter::new)
+        .transpose()?;
+    let trade_counters = Arc::new(TradeCounters::new());
+    let checkpoint = get_last_processed_slot(&pool).await.ok().flatten();
+    let mut writer_state = WriterState::new(trade_counters.clone(), &config);
+    load_mint_decimals(&pool, &mut writer_state.price_scale).await?;
+    writer_state.last_slot = checkpoint;
+    writer_state.redis = connect_redis_publisher(config.redis.as_ref()).await;
+    let mut writer_handle = tokio::spawn(async move {
+        let mut last_slot = None;
 
-            if !transfers.is_empty() {
-                if let Err(err) = insert_transfers(&writer_pool, &transfers).await {
-                    tracing::error!("failed to insert transfers: {err:?}");
-                    continue;
-                }
+        loop {
+            let flush_at = writer_state.batch.deadline();
+            tokio::select! {
+                // While a failed write backs off, blocks wait in the channel.
+                block = block_rx.recv(), if writer_state.batch.retry_due() => {
+                    let Some(block) = block else { break };
+                    if let Some(cache) = cache.as_mut() {
+                        if let Err(err) = cache.append(&block) {
+                            tracing::error!("failed to cache block {}: {err:?}", block.slot);
+                        }
+                    }
 
-                if let Err(err) = update_balances_for_transfers(&writer_pool, &transfers).await {
-                    tracing::error!("failed to update balances: {err:?}");
-                    continue;
+                    write_block(&writer_pool, &block, &mint_whitelist, &mut writer_state).await;
+                    last_slot = Some(block.slot);
                 }
-
-                // Realtime event fanout for websockets (Postgres LISTEN/NOTIFY).
-                for t in &transfers {
-                    let payload = serde_json::json!({
-                        "signature": t.signature,
-                        "slot": t.slot,
-                        "mint_pubkey": t.mint_pubkey,
-                        "source_owner": t.source_owner,
-                        "dest_owner": t.dest_owner,
-                        "amount": t.amount,
-                        "tx_index": t.tx_index,
-                        "ix_index": t.ix_index
-                    });
-                    if let Err(err) = insert_event(&writer_pool, "transfers", Some(&t.mint_pubkey), payload).await {
-                        tracing::error!("failed to insert/notify transfer event: {err:?}");
-                    }
+                _ = sleep_until(flush_at.unwrap_or_else(Instant::now)), if flush_at.is_some() => {
+                    flush_batch(&writer_pool, &mint_whitelist, &mut writer_state).await;
                 }
             }
+        }
 
-            if !pump_trades.is_empty() {
-                if let Err(err) = insert_bonding_curve_trades(&writer_pool, &pump_trades).await {
-                    tracing::error!("failed to insert pump trades: {err:?}");
-                    continue;
-                }
+        // Channel closed (shutdown): write what is still buffered and make sure the checkpoint
+        // reflects the last received block. Rows that can't be written leave the checkpoint
+        // before them, so the next run indexes them again.
+        if !flush_batch(&writer_pool, &mint_whitelist, &mut writer_state).await {
+            anyhow::bail!(
+                "{} buffered rows up to slot {last_slot:?} not written; checkpoint not advanced",
+                writer_state.batch.rows()
+            );
+        }
+        if let Some(slot) = last_slot {
+            set_last_processed_slot(&writer_pool, slot).await?;
+            gauge!(LAST_PROCESSED_SLOT).set(slot as f64);
+        }
 
-                for t in &pump_trades {
-                    let payload = serde_json::json!({
-                        "signature": t.signature,
-                        "slot": t.slot,
-                        "mint_pubkey": t.mint_pubkey,
-                        "trader": t.trader,
-                        "side": t.side,
-                        "token_amount": t.token_amount,
-                        "sol_amount": t.sol_amount,
-                        "price_nanos_per_token": t.price_nanos_per_token,
-                        "venue": "pump",
-                        "tx_index": t.tx_index,
-                        "ix_index": t.ix_index
-                    });
-                    if let Err(err) = insert_event(&writer_pool, "bonding", Some(&t.mint_pubkey), payload).await {
-                        tracing::error!("failed to insert/notify pump trade event: {err:?}");
-                    }
+        Result::<Option<i64>, anyhow::Error>::Ok(last_slot)
+    });
+
+    // Retention task: `indexer_events` only feeds NOTIFY fanout, so prune old rows.
+    let retention_secs = config.retention.events_retention_secs.unwrap_or(86_400);
+    let cleanup_interval_secs = config.retention.cleanup_interval_secs.unwrap_or(300);
+    tokio::spawn(run_event_retention(
+        pool.clone(),
+        retention_secs,
+        cleanup_interval_secs,
+    ));
+
+    // Trade-rate monitor: early warning when a venue's parser stops producing trades.
+    let stall_minutes = config.monitor.trade_stall_minutes.unwrap_or(10);
+    tokio::spawn(run_trade_rate_monitor(trade_counters, stall_minutes));
+
+    // Firehose streaming task: connects to the configured Firehose endpoint and streams blocks.
+    let firehose_config = config.firehose.clone();
+
+    let mut firehose_config_with_slot = firehose_config;
+    if let Some(slot) = checkpoint {
+        firehose_config_with_slot.from_slot = Some(slot + 1);
+    }
+
+    let mut firehose_handle = tokio::spawn(async move {
+        let mut client = FirehoseClient::new(firehose_config_with_slot);
+        if let Err(e) = client.stream_blocks(block_tx).await {
+            tracing::error!("Firehose stream failed: {e:?}");
+        }
+    });
+
+    // Run until either task fails (they should run indefinitely) or shutdown is requested.
+    tokio::select! {
+        result = &mut writer_handle => {
+            tracing::error!("Writer task ended: {result:?}");
+        }
+        result = &mut firehose_handle => {
+            tracing::error!("Firehose task ended: {result:?}");
+        }
+        _ = shutdown => {
+            tracing::info!("Shutdown requested: stopping firehose and draining queued blocks");
+            // Aborting the firehose task drops `block_tx`, so the writer exits once the
+            // channel is empty.
+            firehose_handle.abort();
+            match writer_handle.await {
+                Ok(Ok(last_slot)) => {
+                    tracing::info!("Writer drained, last processed slot: {last_slot:?}")
                 }
+                Ok(Err(err)) => tracing::error!("Writer failed while draining: {err:?}"),
+                Err(err) => tracing::error!("Writer task panicked while draining: {err:?}"),
             }
+        }
+    }
 
-            if !raydium_trades.is_empty() {
-                if let Err(err) = insert_bonding_curve_trades(&writer_pool, &raydium_trades).await {
-                    tracing::error!("failed to insert raydium trades: {err:?}");
-                    continue;
-                }
+    Ok(())
+}
 
-                for t in &raydium_trades {
-                    let payload = serde_json::json!({
-                        "signature": t.signature,
-                        "slot": t.slot,
-                        "mint_pubkey": t.mint_pubkey,
-                        "trader": t.trader,
-                        "side": t.side,
-                        "token_amount": t.token_amount,
-                        "sol_amount": t.sol_amount,
-                        "price_nanos_per_token": t.price_nanos_per_token,
-                        "venue": "raydium",
-                        "tx_index": t.tx_index,
-                        "ix_index": t.ix_index
-                    });
-                    if let Err(err) = insert_event(&writer_pool, "bonding", Some(&t.mint_pubkey), payload).await {
-                        tracing::error!("failed to insert/notify raydium trade event: {err:?}");
-                    }
-                }
+/// The stream publisher for a live run, when `redis.enabled` is set. A Redis that can't be
+/// reached is logged and skipped rather than stopping the indexer.
+async fn connect_redis_publisher(config: Option<&RedisConfig>) -> Option<RedisPublisher> {
+    let config = config.filter(|config| config.enabled)?;
+    let publisher = RedisPublisher::new(
+        &config.host,
+        config.port,
+        config.db,
+        &config.password,
+        config.stream_key_prefix.clone(),
+        config.max_stream_len,
+    )
+    .await;
+    match publisher {
+        Ok(publisher) => Some(publisher),
+        Err(err) => {
+            tracing::warn!(
+                "Redis at {}:{} unavailable, not publishing streams: {err:?}",
+                config.host,
+                config.port
+            );
+            None
+        }
+    }
+}
+
+/// A failed batch write or rollback is retried after this long, doubling per failure up to
+/// [`WRITE_RETRY_MAX`].
+const WRITE_RETRY_INITIAL: Duration = Duration::from_millis(500);
+const WRITE_RETRY_MAX: Duration = Duration::from_secs(30);
+
+/// Rows buffer until the oldest buffered block has waited this long, unless configured.
+const DEFAULT_BATCH_MAX_MS: u64 = 200;
+
+/// State carried by the writer from one block to the next.
+struct WriterState {
+    candle_tracker: CandleBucketTracker,
+    candle_open_mode: CandleOpenMode,
+    /// Ascending, starting at the base timeframe the others are multiples of.
+    candle_timeframes: Vec<i32>,
+    trade_counters: Arc<TradeCounters>,
+    price_scale: PriceScale,
+    batch: WriteBatch,
+    /// Token account -> mint seen so far, to resolve plain `Transfer` mints.
+    token_accounts: TokenAccountCache,
+    /// Slot of the last block handed to the writer. A block at or below it rolls back.
+    last_slot: Option<i64>,
+    /// Redis stream publisher, set for live runs with `redis.enabled`.
+    redis: Option<RedisPublisher>,
+}
+
+impl WriterState {
+    fn new(trade_counters: Arc<TradeCounters>, config: &IndexerConfig) -> Self {
+        Self {
+            candle_tracker: CandleBucketTracker::new(),
+            candle_open_mode: config.candles.open_mode,
+            candle_timeframes: config.candles.timeframes(),
+            trade_counters,
+            price_scale: config.pricing.price_scale(),
+            batch: WriteBatch::new(&config.writer),
+            token_accounts: TokenAccountCache::new(
+                config
+                    .writer
+                    .token_account_cache_size
+                    .unwrap_or(DEFAULT_TOKEN_ACCOUNT_CACHE_SIZE),
+            ),
+            last_slot: None,
+            redis: None,
+        }
+    }
+}
+
+/// Rows parsed from blocks that have not been written yet, in block order. Written in one
+/// transaction once it holds `max_rows` rows or its oldest block has waited `max_wait`.
+struct WriteBatch {
+    /// Mints whose decimals the buffered blocks named.
+    mints: Vec<Mint>,
+    transfers: Vec<TokenTransfer>,
+    token_events: Vec<TokenEvent>,
+    /// Trades of every venue, per block in pump/raydium/meteora/orca order, with `trade_venues`
+    /// holding each one's venue.
+    trades: Vec<BondingCurveTrade>,
+    trade_venues: Vec<&'static str>,
+    last_slot: Option<i64>,
+    opened_at: Option<Instant>,
+    max_rows: usize,
+    max_wait: Duration,
+    /// Consecutive failed writes of the buffered rows, and when to try again.
+    failed_writes: u32,
+    retry_at: Option<Instant>,
+}
+
+impl WriteBatch {
+    fn new(config: &WriterConfig) -> Self {
+        Self {
+            mints: Vec::new(),
+            transfers: Vec::new(),
+            token_events: Vec::new(),
+            trades: Vec::new(),
+            trade_venues: Vec::new(),
+            last_slot: None,
+            opened_at: None,
+            max_rows: config.batch_max_rows.unwrap_or(0),
+            max_wait: Duration::from_millis(config.batch_max_ms.unwrap_or(DEFAULT_BATCH_MAX_MS)),
+            failed_writes: 0,
+            retry_at: None,
+        }
+    }
+
+    fn rows(&self) -> usize {
+        self.transfers.len() + self.token_events.len() + self.trades.len()
+    }
+
+    /// Whether at least one block is buffered and the row threshold is reached.
+    fn is_full(&self) -> bool {
+        self.last_slot.is_some() && self.rows() >= self.max_rows
+    }
+
+    /// When the buffered rows are due even if the row threshold is never reached, or when to
+    /// retry them after a failed write.
+    fn deadline(&self) -> Option<Instant> {
+        self.retry_at.or(self.opened_at.map(|opened| opened + self.max_wait))
+    }
+
+    /// False while backing off after a failed write; the writer takes no new blocks then.
+    fn retry_due(&self) -> bool {
+        self.retry_at.is_none_or(|at| Instant::now() >= at)
+    }
+
+    /// Keep the rows for another try after a failed write. Returns the backoff before it.
+    fn write_failed(&mut self) -> Duration {
+        let backoff = WRITE_RETRY_INITIAL
+            .saturating_mul(2u32.saturating_pow(self.failed_writes))
+            .min(WRITE_RETRY_MAX);
+        self.failed_writes += 1;
+        self.retry_at = Some(Instant::now() + backoff);
+        backoff
+    }
+
+    /// Every mint the buffered blocks named with its decimals, plus every mint the buffered
+    /// transfers and trades touch (decimals unknown), first seen at the row's slot.
+    fn all_mints(&self) -> Vec<Mint> {
+        let transfers = self.transfers.iter().map(|t| (&t.mint_pubkey, t.slot));
+        let trades = self.trades.iter().map(|t| (&t.mint_pubkey, t.slot));
+        let touched = transfers.chain(trades).map(|(mint_pubkey, slot)| Mint {
+            mint_pubkey: mint_pubkey.clone(),
+            symbol: None,
+            decimals: UNKNOWN_DECIMALS,
+            first_seen_slot: slot,
+        });
+        self.mints.iter().cloned().chain(touched).collect()
+    }
+
+    /// Mints the buffered non-pump trades show to be off their bonding curve, with the slot.
+    fn migrated_mints(&self) -> Vec<(&str, i64)> {
+        self.trades
+            .iter()
+            .zip(&self.trade_venues)
+            .filter(|(_, &venue)| venue != "pump")
+            .map(|(t, _)| (t.mint_pubkey.as_str(), t.slot))
+            .collect()
+    }
+
+    fn push_trades(&mut self, venue: &'static str, trades: Vec<BondingCurveTrade>) {
+        counter!(TRADES_PARSED_TOTAL, "venue" => venue).increment(trades.len() as u64);
+        self.trade_venues.extend(std::iter::repeat_n(venue, trades.len()));
+        self.trades.extend(trades);
+    }
+
+    /// Drop buffered rows from `slot` on, leaving the batch to end at the slot before it.
+    fn truncate_from_slot(&mut self, slot: i64) {
+        self.mints.retain(|m| m.first_seen_slot < slot);
+        self.transfers.retain(|t| t.slot < slot);
+        self.token_events.retain(|e| e.slot < slot);
+        let mut kept = self.trades.iter().map(|t| t.slot < slot).collect::<Vec<_>>().into_iter();
+        self.trade_venues.retain(|_| kept.next().unwrap_or(false));
+        self.trades.retain(|t| t.slot < slot);
+        if self.last_slot.is_some() {
+            self.last_slot = Some(slot - 1);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.mints.clear();
+        self.transfers.clear();
+        self.token_events.clear();
+        self.trades.clear();
+        self.trade_venues.clear();
+        self.last_slot = None;
+        self.opened_at = None;
+        self.failed_writes = 0;
+        self.retry_at = None;
+    }
+}
+
+/// First trading slot of every trader in `trades`, for the `is_new_wallet` event flag. Must run
+/// after the trades are inserted. If the lookup fails no trade is flagged.
+async fn wallet_first_seen(pool: &PgPool, trades: &[BondingCurveTrade]) -> HashMap<String, i64> {
+    let wallets: Vec<String> = trades.iter().map(|t| t.trader.clone()).collect();
+    match get_wallet_first_seen_slots(pool, &wallets).await {
+        Ok(first_seen) => first_seen,
+        Err(err) => {
+            tracing::error!("failed to look up wallet first-seen slots: {err:?}");
+            Default::default()
+        }
+    }
+}
+
+/// Fill in the mint of events the parser could not resolve from their own transaction, using
+/// stored transfers of the same token account. Events resolved to a mint outside a non-empty
+/// `mint_whitelist` are cleared, and so skipped on insert.
+async fn resolve_token_event_mints(
+    conn: &mut PgConnection,
+    events: &mut [TokenEvent],
+    mint_whitelist: &[String],
+) -> Result<()> {
+    let accounts: Vec<String> = events
+        .iter()
+        .filter(|e| e.mint_pubkey.is_none())
+        .map(|e| e.token_account.clone())
+        .collect();
+    if accounts.is_empty() {
+        return Ok(());
+    }
+    let mints = get_token_account_mints(conn, &accounts).await?;
+    for e in events.iter_mut().filter(|e| e.mint_pubkey.is_none()) {
+        e.mint_pubkey = mints
+            .get(&e.token_account)
+            .filter(|mint| mint_whitelist.is_empty() || mint_whitelist.contains(mint))
+            .cloned();
+    }
+    Ok(())
+}
+
+/// Seed `scale` with the stored mints whose decimals differ from its default, so their trades
+/// are priced from `mints.decimals` before a block names the decimals again.
+async fn load_mint_decimals(pool: &PgPool, scale: &mut PriceScale) -> Result<()> {
+    let default_decimals = scale.default_token_decimals() as i32;
+    for (mint, decimals) in get_non_default_mint_decimals(pool, default_decimals).await? {
+        scale.learn_decimals(&mint, decimals);
+    }
+    Ok(())
+}
+
+/// Remove everything written or buffered from `slot` on, because a block for it arrived again
+/// (a fork switch or a replayed range). A failed rollback is retried with backoff until it
+/// commits, since writing the block over rows still stored would count them twice; blocks wait
+/// in the channel meanwhile.
+async fn roll_back_to(pool: &PgPool, state: &mut WriterState, slot: i64) {
+    state.batch.truncate_from_slot(slot);
+    let mut backoff = WRITE_RETRY_INITIAL;
+    loop {
+        match delete_data_for_slots_gte(pool, slot, state.candle_open_mode).await {
+            Ok(deleted) => {
+                tracing::info!("rolled back {deleted} rows from slot {slot} on");
+                break;
             }
+            Err(err) => {
+                tracing::error!(
+                    "failed to roll back from slot {slot}, retrying in {backoff:?}: {err:?}"
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = backoff.saturating_mul(2).min(WRITE_RETRY_MAX);
+            }
+        }
+    }
+    // Its buckets and closes may belong to removed trades; the rebuilt rows are the truth now.
+    state.candle_tracker = CandleBucketTracker::new();
+}
 
-            if !meteora_trades.is_empty() {
-                if let Err(err) = insert_bonding_curve_trades(&writer_pool, &meteora_trades).await {
-                    tracing::error!("failed to insert meteora trades: {err:?}");
-                    continue;
-                }
+/// Parse one block into the writer's batch, writing the batch if that fills it. A block at or
+/// below the last one seen first rolls back that slot and everything after it.
+/// Shared by the live firehose writer and `replay`.
+async fn write_block(
+    pool: &PgPool,
+    block: &BlockRef,
+    mint_whitelist: &[String],
+    state: &mut WriterState,
+) {
+    if let Some(last) = state.last_slot.filter(|&last| block.slot <= last) {
+        tracing::warn!("slot {} arrived after slot {last}; rolling back", block.slot);
+        roll_back_to(pool, state, block.slot).await;
+    }
+    state.last_slot = Some(block.slot);
+    counter!(BLOCKS_PROCESSED_TOTAL).increment(1);
 
-                for t in &meteora_trades {
-                    let payload = serde_json::json!({
-                        "signature": t.signature,
-                        "slot": t.slot,
-                        "mint_pubkey": t.mint_pubkey,
-                        "trader": t.trader,
-                        "side": t.side,
-                        "token_amount": t.token_amount,
-                        "sol_amount": t.sol_amount,
-                        "price_nanos_per_token": t.price_nanos_per_token,
-                        "venue": "meteora",
-                        "tx_index": t.tx_index,
-                        "ix_index": t.ix_index
-                    });
-                    if let Err(err) = insert_event(&writer_pool, "bonding", Some(&t.mint_pubkey), payload).await {
-                        tracing::error!("failed to insert/notify meteora trade event: {err:?}");
-                    }
-                }
+    // Instructions that matched a venue's program but failed to parse. Non-zero misses usually
+    // mean the program's instruction layout changed; bad indices mean corrupt block data. Both
+    // are surfaced through /metrics.
+    let spl_counters = ParseCounters::default();
+    let pump_counters = ParseCounters::default();
+    let raydium_counters = ParseCounters::default();
+    let meteora_counters = ParseCounters::default();
+    let orca_counters = ParseCounters::default();
+    // Decimals the block names price its own trades too.
+    let mints = extract_mint_decimals(block, mint_whitelist);
+    for m in &mints {
+        state.price_scale.learn_decimals(&m.mint_pubkey, m.decimals);
+    }
+    let scale = &state.price_scale;
+    let transfers = extract_transfers_with(
+        block,
+        mint_whitelist,
+        &mut state.token_accounts,
+        Some(&spl_counters),
+    );
+    let pump_trades = extract_pump_trades_with(block, Some(&pump_counters), scale);
+    let raydium_trades = extract_raydium_trades_with(
+        block,
+        &mut state.token_accounts,
+        Some(&raydium_counters),
+        scale,
+    );
+    let meteora_trades = extract_meteora_trades_with(
+        block,
+        &mut state.token_accounts,
+        Some(&meteora_counters),
+        scale,
+    );
+    let orca_trades = extract_whirlpool_trades_with(block, Some(&orca_counters), scale);
+    counter!(TRANSFERS_TOTAL).increment(transfers.len() as u64);
+    for (parser, counters) in [
+        ("spl", &spl_counters),
+        ("pump", &pump_counters),
+        ("raydium", &raydium_counters),
+        ("meteora", &meteora_counters),
+        ("orca", &orca_counters),
+    ] {
+        let misses = counters.misses();
+        counter!(PARSE_MISSES_TOTAL, "parser" => parser).increment(misses);
+        if misses > 0 {
+            tracing::debug!("slot {}: {misses} {parser} instructions failed to parse", block.slot);
+            if let Err(err) = add_parse_misses(pool, parser, misses as i64).await {
+                tracing::error!("failed to record {parser} parse misses: {err:?}");
+            }
+        }
+        counter!(PARSE_PANICS_TOTAL, "parser" => parser).increment(counters.panics());
+        let bad_indices = counters.bad_indices();
+        counter!(BAD_INDICES_TOTAL, "parser" => parser).increment(bad_indices);
+        if bad_indices > 0 {
+            tracing::debug!(
+                "slot {}: {bad_indices} {parser} instructions had out-of-range account indices",
+                block.slot
+            );
+            if let Err(err) = add_bad_indices(pool, parser, bad_indices as i64).await {
+                tracing::error!("failed to record {parser} bad account indices: {err:?}");
             }
+        }
+    }
 
-            // Candle aggregation: process trades from all venues
-            let all_trades = [pump_trades, raydium_trades, meteora_trades].concat();
-            for t in &all_trades {
-                let Some(bt) = t.block_time else { continue; };
-                let bucket = bt.timestamp() - (bt.timestamp() % 60);
-                let bucket_start = chrono::Utc.timestamp_opt(bucket, 0).single().unwrap();
-
-                let c = Candle {
-                    mint_pubkey: t.mint_pubkey.clone(),
-                    timeframe_secs: 60,
-                    bucket_start,
-                    open: t.price_nanos_per_token,
-                    high: t.price_nanos_per_token,
-                    low: t.price_nanos_per_token,
-                    close: t.price_nanos_per_token,
-                    volume_token: t.token_amount,
-                    volume_sol: t.sol_amount,
-                    trades_count: 1,
-                };
-
-                if let Err(err) = upsert_candle(&writer_pool, &c).await {
-                    tracing::error!("failed to upsert candle: {err:?}");
-                    continue;
-                }
+    let batch = &mut state.batch;
+    batch.mints.extend(mints);
+    batch.transfers.extend(transfers);
+    // Safety signals (delegate approvals, revokes, account closes).
+    batch
+        .token_events
+        .extend(extract_token_events(block, mint_whitelist));
+    batch.push_trades("pump", pump_trades);
+    batch.push_trades("raydium", raydium_trades);
+    batch.push_trades("meteora", meteora_trades);
+    batch.push_trades("orca", orca_trades);
+    batch.last_slot = Some(block.slot);
+    batch.opened_at.get_or_insert_with(Instant::now);
+
+    if batch.is_full() && batch.retry_due() {
+        flush_batch(pool, mint_whitelist, state).await;
+    }
+}
+
+/// Insert the batch's rows in one transaction, one multi-row insert per table. Mints are
+/// recorded with the decimals the batch's blocks named, or unknown decimals.
+async fn insert_batch_rows(
+    pool: &PgPool,
+    batch: &mut WriteBatch,
+    mint_whitelist: &[String],
+) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    // First, since every other table references the mint.
+    timed_insert("mints", upsert_mints_in(&mut tx, &batch.all_mints())).await?;
+    timed_insert("token_transfers", insert_transfers_in(&mut tx, &batch.transfers)).await?;
+    timed_insert("balances", update_balances_for_transfers_in(&mut tx, &batch.transfers)).await?;
+    // After the transfers, so the batch's own token accounts can name an event's mint.
+    resolve_token_event_mints(&mut tx, &mut batch.token_events, mint_whitelist).await?;
+    timed_insert("token_events", insert_token_events_in(&mut tx, &batch.token_events)).await?;
+    timed_insert("bonding_curve_trades", insert_bonding_curve_trades_in(&mut tx, &batch.trades))
+        .await?;
+    mark_mints_migrated_in(&mut tx, &batch.migrated_mints()).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Run one table's insert, recording how long it took in `db_insert_duration_seconds`.
+async fn timed_insert<T>(
+    table: &'static str,
+    insert: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let started = Instant::now();
+    let result = insert.await;
+    histogram!(DB_INSERT_DURATION_SECONDS, "table" => table)
+        .record(started.elapsed().as_secs_f64());
+    result
+}
 
+/// Write the buffered rows, then publish their realtime events (to Redis streams too, when
+/// enabled) and candles in block order and checkpoint the last buffered slot. A failed write
+/// keeps the rows, to be retried with backoff, and leaves the checkpoint where it was; a
+/// failed publish is only logged. Returns whether nothing is left unwritten.
+async fn flush_batch(pool: &PgPool, mint_whitelist: &[String], state: &mut WriterState) -> bool {
+    let Some(last_slot) = state.batch.last_slot else {
+        return true;
+    };
+    if let Err(err) = insert_batch_rows(pool, &mut state.batch, mint_whitelist).await {
+        let backoff = state.batch.write_failed();
+        tracing::error!(
+            "failed to write {} buffered rows up to slot {last_slot}, retrying in {backoff:?}: \
+             {err:?}",
+            state.batch.rows()
+        );
+        return false;
+    }
+    let batch = &state.batch;
+
+    // Realtime event fanout for websockets (Postgres LISTEN/NOTIFY).
+    for t in &batch.transfers {
+        let payload = serde_json::json!({
+            "signature": t.signature,
+            "slot": t.slot,
+            "mint_pubkey": t.mint_pubkey,
+            "source_owner": t.source_owner,
+            "dest_owner": t.dest_owner,
+            "amount": t.amount,
+            "tx_index": t.tx_index,
+            "ix_index": t.ix_index,
+            "inner_index": t.inner_index
+        });
+        if let Err(err) = insert_event(pool, "transfers", Some(&t.mint_pubkey), payload).await {
+            tracing::error!("failed to insert/notify transfer event: {err:?}");
+        }
+        if let Some(redis) = &state.redis {
+            let event = TransferEvent {
+                signature: t.signature.clone(),
+                from: t.source_owner.clone(),
+                to: t.dest_owner.clone(),
+                amount: t.amount,
+                timestamp: t.block_time.map_or(0, |bt| bt.timestamp()),
+            };
+            if let Err(err) = redis.publish_transfer(&t.mint_pubkey, event).await {
+                tracing::warn!("failed to publish transfer {} to Redis: {err:?}", t.signature);
+            }
+        }
+    }
+
+    let first_seen = wallet_first_seen(pool, &batch.trades).await;
+    for (t, &venue) in batch.trades.iter().zip(&batch.trade_venues) {
+        state.trade_counters.add(venue, 1);
+        let is_new_wallet = first_seen.get(&t.trader).is_some_and(|&slot| t.slot <= slot);
+        let payload = serde_json::json!({
+            "signature": t.signature,
+            "slot": t.slot,
+            "mint_pubkey": t.mint_pubkey,
+            "trader": t.trader,
+            "side": t.side,
+            "token_amount": t.token_amount,
+            "sol_amount": t.sol_amount,
+            "price_nanos_per_token": t.price_nanos_per_token,
+            "venue": venue,
+            "is_new_wallet": is_new_wallet,
+            "tx_index": t.tx_index,
+            "ix_index": t.ix_index,
+            "inner_index": t.inner_index
+        });
+        if let Err(err) = insert_event(pool, "bonding", Some(&t.mint_pubkey), payload).await {
+            tracing::error!("failed to insert/notify {venue} trade event: {err:?}");
+        }
+        if let Some(redis) = &state.redis {
+            let event = TradeEvent {
+                signature: t.signature.clone(),
+                slot: t.slot,
+                trader: t.trader.clone(),
+                amount: t.token_amount,
+                direction: t.side.clone(),
+                price: t.price_nanos_per_token,
+                timestamp: t.block_time.map_or(0, |bt| bt.timestamp()),
+            };
+            if let Err(err) = redis.publish_trade(venue, &t.mint_pubkey, event).await {
+                tracing::warn!("failed to publish {venue} trade {} to Redis: {err:?}", t.signature);
+            }
+        }
+    }
+
+    // Candle aggregation: one candle per configured timeframe for every trade of every venue.
+    // Rows are upserted per trade; their events are merged per bucket and sent afterwards.
+    let mut candle_events = CandleEvents::new();
+    let candle_updates: Vec<(&BondingCurveTrade, i32)> = batch
+        .trades
+        .iter()
+        .flat_map(|t| state.candle_timeframes.iter().map(move |&tf| (t, tf)))
+        .collect();
+    for (t, timeframe_secs) in candle_updates {
+        let Some(bt) = t.block_time else { continue; };
+        let bucket_start = bucket_start(bt.timestamp(), timeframe_secs);
+
+        let price = t.price_nanos_per_token;
+        let trade = state.candle_tracker.record_trade(
+            &t.mint_pubkey,
+            timeframe_secs,
+            bucket_start,
+            price,
+            state.candle_open_mode,
+        );
+
+        // A carried-forward open stays inside the candle's range.
+        let c = Candle {
+            mint_pubkey: t.mint_pubkey.clone(),
+            timeframe_secs,
+            bucket_start,
+            open: trade.open,
+            high: price.max(trade.open),
+            low: price.min(trade.open),
+            close: price,
+            volume_token: t.token_amount,
+            volume_sol: t.sol_amount,
+            trades_count: 1,
+        };
+
+        if let Err(err) = upsert_candle(pool, &c).await {
+            tracing::error!("failed to upsert candle: {err:?}");
+            continue;
+        }
+
+        // First trade of a newer bucket: tell clients the previous one is final.
+        if let Some(prev_bucket) = trade.closed_bucket {
+            candle_events.record_closed(&c.mint_pubkey, c.timeframe_secs, prev_bucket);
+        }
+        candle_events.record_update(c);
+    }
+
+    // Lets clients tell which timeframes are exact rollups of others.
+    let base_timeframe_secs = state.candle_timeframes[0];
+    for event in candle_events.into_events() {
+        let (mint_pubkey, payload) = match event {
+            CandleEvent::Closed {
+                mint_pubkey,
+                timeframe_secs,
+                bucket_start,
+            } => {
+                let payload = serde_json::json!({
+                    "mint_pubkey": mint_pubkey,
+                    "timeframe_secs": timeframe_secs,
+                    "base_timeframe_secs": base_timeframe_secs,
+                    "bucket_start": bucket_start,
+                    "closed": true
+                });
+                (mint_pubkey, payload)
+            }
+            CandleEvent::Update(c) => {
                 let payload = serde_json::json!({
                     "mint_pubkey": c.mint_pubkey,
                     "timeframe_secs": c.timeframe_secs,
+                    "base_timeframe_secs": base_timeframe_secs,
                     "bucket_start": c.bucket_start,
                     "open": c.open,
                     "high": c.high,
@@ -194,47 +829,431 @@ async fn run_indexer(config: IndexerConfig, pool: sqlx::PgPool) -> Result<()> {
                     "close": c.close,
                     "volume_token": c.volume_token,
                     "volume_sol": c.volume_sol,
-                    "trades_count": c.trades_count
+                    "trades_count": c.trades_count,
+                    "closed": false
                 });
-                if let Err(err) = insert_event(&writer_pool, "candles", Some(&t.mint_pubkey), payload).await {
-                    tracing::error!("failed to insert/notify candle event: {err:?}");
-                }
+                (c.mint_pubkey, payload)
             }
+        };
+        if let Err(err) = insert_event(pool, "candles", Some(&mint_pubkey), payload).await {
+            tracing::error!("failed to insert/notify candle event: {err:?}");
+        }
+    }
 
-            if let Err(err) = set_last_processed_slot(&writer_pool, block.slot).await {
-                tracing::error!("failed to update last_processed_slot: {err:?}");
+    match set_last_processed_slot(pool, last_slot).await {
+        Ok(()) => gauge!(LAST_PROCESSED_SLOT).set(last_slot as f64),
+        Err(err) => tracing::error!("failed to update last_processed_slot: {err:?}"),
+    }
+    state.batch.clear();
+    true
+}
+
+async fn run_trade_rate_monitor(counters: Arc<TradeCounters>, stall_minutes: u32) {
+    let mut detector = StallDetector::new(stall_minutes);
+    let mut interval = tokio::time::interval(Duration::from_secs(60));
+    interval.tick().await; // first tick fires immediately
+
+    loop {
+        interval.tick().await;
+        for alert in detector.record_minute(&counters.take()) {
+            match alert {
+                RateAlert::Stalled { venue, minutes } => tracing::warn!(
+                    "no {venue} trades inserted for {minutes} consecutive minutes; parser regression?"
+                ),
+                RateAlert::Recovered { venue } => tracing::info!("{venue} trades are flowing again"),
             }
         }
+    }
+}
 
-        Result::<(), anyhow::Error>::Ok(())
-    });
+async fn run_event_retention(pool: PgPool, retention_secs: u64, interval_secs: u64) {
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+    loop {
+        interval.tick().await;
+        match delete_events_older_than(&pool, retention_secs as i64).await {
+            Ok(0) => {}
+            Ok(deleted) => tracing::info!("pruned {deleted} indexer_events older than {retention_secs}s"),
+            Err(err) => tracing::error!("failed to prune indexer_events: {err:?}"),
+        }
+    }
+}
 
-    // Firehose streaming task: connects to the configured Firehose endpoint and streams blocks.
-    let firehose_config = config.firehose.clone();
-    let last_slot = get_last_processed_slot(&pool).await.ok().flatten();
+#[cfg(test)]
+mod publisher_tests {
+    use super::*;
 
-    let mut firehose_config_with_slot = firehose_config;
-    if let Some(slot) = last_slot {
-        firehose_config_with_slot.from_slot = Some(slot + 1);
+    #[tokio::test]
+    async fn test_redis_publisher_only_when_enabled_and_reachable() {
+        let mut redis = RedisConfig {
+            enabled: false,
+            host: "127.0.0.1".to_string(),
+            // Nothing listens on port 1, so connecting fails straight away.
+            port: 1,
+            db: 0,
+            password: String::new(),
+            stream_key_prefix: "indexer:".to_string(),
+            max_stream_len: 100,
+        };
+        assert!(connect_redis_publisher(None).await.is_none());
+        assert!(connect_redis_publisher(Some(&redis)).await.is_none());
+
+        redis.enabled = true;
+        assert!(connect_redis_publisher(Some(&redis)).await.is_none());
     }
+}
 
-    let firehose_handle = tokio::spawn(async move {
-        let mut client = FirehoseClient::new(firehose_config_with_slot);
-        if let Err(e) = client.stream_blocks(block_tx).await {
-            tracing::error!("Firehose stream failed: {e:?}");
+#[cfg(all(test, db_tests))]
+mod tests {
+    use super::*;
+    use indexer_core::config::{ApiConfig, DbConfig, FirehoseConfig, RuntimeConfig};
+    use indexer_core::db::upsert_mints;
+    use indexer_core::models::Mint;
+    use indexer_core::spl_parser::{
+        InstructionRef, MessageRef, TransactionRef, INSTR_TRANSFER_CHECKED, SPL_TOKEN_PROGRAM_ID,
+        TOP_LEVEL_INNER_INDEX,
+    };
+    use tokio::sync::oneshot;
+
+    const MINT: &str = "TestMint1111111111111111111111111111111111";
+
+    fn test_config(from_slot: i64) -> IndexerConfig {
+        IndexerConfig {
+            runtime: RuntimeConfig {
+                environment: "test".to_string(),
+            },
+            api: ApiConfig {
+                bind_addr: "127.0.0.1:0".to_string(),
+                candle_warmup_min_rows: None,
+                default_candle_timeframe_secs: None,
+                default_candle_limit: None,
+                admin_token: None,
+                ws_ping_interval_secs: None,
+                ws_pong_timeout_secs: None,
+                ws_event_capacity: None,
+                ws_max_missed_events: None,
+                requests_per_sec: None,
+                burst: None,
+                allowed_origins: None,
+            },
+            db: DbConfig {
+                url: String::new(),
+                max_connections: 1,
+            },
+            firehose: FirehoseConfig {
+                endpoint: "http://localhost:9000".to_string(),
+                from_slot: Some(from_slot),
+                mint_whitelist: vec![],
+                initial_backoff_ms: None,
+                max_backoff_ms: None,
+                cache_dir: None,
+            },
+            redis: None,
+            retention: Default::default(),
+            monitor: Default::default(),
+            pricing: Default::default(),
+            candles: Default::default(),
+            writer: Default::default(),
+            log: Default::default(),
         }
-    });
+    }
 
-    // Wait for either task to fail (they should run indefinitely).
-    tokio::select! {
-        result = writer_handle => {
-            tracing::error!("Writer task ended: {result:?}");
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_run_indexer_drains_and_returns_on_shutdown(pool: PgPool) {
+        let (trigger, signal) = oneshot::channel::<()>();
+        let indexer = tokio::spawn(run_indexer(test_config(500), pool.clone(), async {
+            let _ = signal.await;
+        }));
+
+        // Wait for the (simulated) firehose to deliver at least one block.
+        let mut checkpoint = None;
+        for _ in 0..50 {
+            checkpoint = get_last_processed_slot(&pool).await.unwrap();
+            if checkpoint.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
         }
-        result = firehose_handle => {
-            tracing::error!("Firehose task ended: {result:?}");
+        assert!(checkpoint.is_some(), "no block was written before shutdown");
+
+        trigger.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), indexer)
+            .await
+            .expect("indexer did not shut down")
+            .unwrap()
+            .unwrap();
+
+        let final_slot = get_last_processed_slot(&pool).await.unwrap().unwrap();
+        assert!(final_slot >= 500);
+    }
+
+    /// A block with one TransferChecked of `MINT`.
+    fn transfer_block(slot: i64, signature: &str) -> BlockRef {
+        let mut data = vec![INSTR_TRANSFER_CHECKED];
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data.push(6);
+        BlockRef {
+            slot,
+            block_time_unix: Some(1_700_000_000 + slot),
+            transactions: vec![TransactionRef {
+                signature: signature.to_string(),
+                index: 0,
+                message: MessageRef {
+                    account_keys: vec![
+                        "source_ata".to_string(),
+                        MINT.to_string(),
+                        "dest_ata".to_string(),
+                        "owner".to_string(),
+                    ],
+                },
+                instructions: vec![InstructionRef {
+                    program_id: SPL_TOKEN_PROGRAM_ID.to_string(),
+                    accounts: vec![0, 1, 2, 3],
+                    data,
+                    index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
+                }],
+            }],
         }
     }
 
-    Ok(())
-}
+    async fn count(pool: &PgPool, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn seed_mint(pool: &PgPool) {
+        upsert_mints(
+            pool,
+            &[Mint {
+                mint_pubkey: MINT.to_string(),
+                symbol: None,
+                decimals: 6,
+                first_seen_slot: 100,
+            }],
+        )
+        .await
+        .unwrap();
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_new_mint_is_recorded_once(pool: PgPool) {
+        let config = test_config(100);
+        let mut state = WriterState::new(Arc::default(), &config);
+
+        write_block(&pool, &transfer_block(100, "sig1"), &[], &mut state).await;
+        write_block(&pool, &transfer_block(101, "sig2"), &[], &mut state).await;
+
+        // TransferChecked names the mint's decimals.
+        let mints: Vec<(String, i32, i64)> =
+            sqlx::query_as("SELECT mint_pubkey, decimals, first_seen_slot FROM mints")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(mints, vec![(MINT.to_string(), 6, 100)]);
+        assert_eq!(count(&pool, "token_transfers").await, 2);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_failed_write_keeps_rows_and_checkpoint_for_retry(pool: PgPool) {
+        seed_mint(&pool).await;
+        let config = test_config(100);
+        let mut state = WriterState::new(Arc::default(), &config);
+        let unreachable = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(100))
+            .connect_lazy("postgres://unused@127.0.0.1:1/unused")
+            .unwrap();
+
+        write_block(&unreachable, &transfer_block(100, "sig1"), &[], &mut state).await;
+        write_block(&unreachable, &transfer_block(101, "sig2"), &[], &mut state).await;
 
+        // Both blocks are kept, and the writer backs off before trying again.
+        assert_eq!(state.batch.rows(), 2);
+        assert_eq!(state.batch.last_slot, Some(101));
+        assert!(!state.batch.retry_due());
+        assert!(state.batch.deadline().unwrap() > Instant::now());
+
+        assert!(flush_batch(&pool, &[], &mut state).await);
+        assert_eq!(count(&pool, "token_transfers").await, 2);
+        assert_eq!(get_last_processed_slot(&pool).await.unwrap(), Some(101));
+        assert!(state.batch.retry_due());
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_write_batch_flushes_at_row_threshold(pool: PgPool) {
+        seed_mint(&pool).await;
+        let mut config = test_config(100);
+        config.writer.batch_max_rows = Some(3);
+        let mut state = WriterState::new(Arc::default(), &config);
+
+        write_block(&pool, &transfer_block(100, "sig1"), &[], &mut state).await;
+        write_block(&pool, &transfer_block(101, "sig2"), &[], &mut state).await;
+
+        // Below the threshold: nothing written, published or checkpointed yet.
+        assert_eq!(state.batch.rows(), 2);
+        assert_eq!(count(&pool, "token_transfers").await, 0);
+        assert_eq!(count(&pool, "indexer_events").await, 0);
+        assert_eq!(get_last_processed_slot(&pool).await.unwrap(), None);
+
+        write_block(&pool, &transfer_block(102, "sig3"), &[], &mut state).await;
+
+        assert_eq!(state.batch.rows(), 0);
+        assert_eq!(count(&pool, "token_transfers").await, 3);
+        assert_eq!(count(&pool, "indexer_events").await, 3);
+        assert_eq!(get_last_processed_slot(&pool).await.unwrap(), Some(102));
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_revisited_slot_rolls_back_written_and_buffered_rows(pool: PgPool) {
+        seed_mint(&pool).await;
+        let mut config = test_config(100);
+        config.writer.batch_max_rows = Some(3);
+        let mut state = WriterState::new(Arc::default(), &config);
+
+        // 100-102 are written, 103-104 still buffered.
+        for slot in 100..=104 {
+            write_block(&pool, &transfer_block(slot, &format!("sig{slot}")), &[], &mut state).await;
+        }
+        assert_eq!(state.batch.rows(), 2);
+        assert_eq!(get_last_processed_slot(&pool).await.unwrap(), Some(102));
+
+        write_block(&pool, &transfer_block(101, "fork101"), &[], &mut state).await;
+        flush_batch(&pool, &[], &mut state).await;
+
+        let rows: Vec<(String, i64)> =
+            sqlx::query_as("SELECT signature, slot FROM token_transfers ORDER BY slot")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(rows, vec![("sig100".to_string(), 100), ("fork101".to_string(), 101)]);
+        assert_eq!(get_last_processed_slot(&pool).await.unwrap(), Some(101));
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_failed_rollback_holds_the_block_until_it_commits(pool: PgPool) {
+        seed_mint(&pool).await;
+        let config = test_config(100);
+        let mut state = WriterState::new(Arc::default(), &config);
+        write_block(&pool, &transfer_block(100, "sig100"), &[], &mut state).await;
+        write_block(&pool, &transfer_block(101, "sig101"), &[], &mut state).await;
+        let unreachable = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(100))
+            .connect_lazy("postgres://unused@127.0.0.1:1/unused")
+            .unwrap();
+
+        // The rollback keeps retrying, so the revisited block is neither seen nor buffered.
+        let fork = transfer_block(101, "fork101");
+        let revisit = write_block(&unreachable, &fork, &[], &mut state);
+        assert!(tokio::time::timeout(Duration::from_secs(1), revisit).await.is_err());
+        assert_eq!(state.last_slot, Some(101));
+        assert_eq!(state.batch.rows(), 0);
+        assert_eq!(count(&pool, "token_transfers").await, 2);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_rolled_back_trades_are_not_counted_twice_in_candles(pool: PgPool) {
+        seed_mint(&pool).await;
+        let mut config = test_config(100);
+        config.candles.timeframes_secs = Some(vec![60]);
+        let mut state = WriterState::new(Arc::default(), &config);
+        let trade = |slot: i64, secs: i64, price: i64| BondingCurveTrade {
+            signature: format!("sig{slot}"),
+            slot,
+            block_time: chrono::DateTime::from_timestamp(secs, 0),
+            mint_pubkey: MINT.to_string(),
+            trader: "trader".to_string(),
+            side: "buy".to_string(),
+            token_amount: 10,
+            sol_amount: price * 10,
+            price_nanos_per_token: price,
+            tx_index: 0,
+            ix_index: 0,
+            inner_index: TOP_LEVEL_INNER_INDEX,
+        };
+        async fn candles(pool: &PgPool) -> Vec<(i64, i64, i64, i64, i64, i32)> {
+            sqlx::query_as(
+                "SELECT open, high, low, close, volume_sol, trades_count FROM candles
+                 WHERE mint_pubkey = $1 ORDER BY bucket_start",
+            )
+            .bind(MINT)
+            .fetch_all(pool)
+            .await
+            .unwrap()
+        }
+
+        // Two trades in one minute.
+        state.batch.push_trades("pump", vec![trade(100, 1_700_000_040, 100)]);
+        state.batch.push_trades("pump", vec![trade(101, 1_700_000_050, 120)]);
+        state.batch.last_slot = Some(101);
+        flush_batch(&pool, &[], &mut state).await;
+        let written = candles(&pool).await;
+        assert_eq!(written, vec![(100, 120, 100, 120, 2_200, 2)]);
+
+        roll_back_to(&pool, &mut state, 101).await;
+        assert_eq!(candles(&pool).await, vec![(100, 100, 100, 100, 1_000, 1)]);
+
+        // Slot 101 comes again: the candle ends up as it was, not with three trades.
+        state.batch.push_trades("pump", vec![trade(101, 1_700_000_050, 120)]);
+        state.batch.last_slot = Some(101);
+        flush_batch(&pool, &[], &mut state).await;
+        assert_eq!(candles(&pool).await, written);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_trade_upserts_a_candle_per_timeframe(pool: PgPool) {
+        seed_mint(&pool).await;
+        let mut config = test_config(100);
+        config.candles.base_timeframe_secs = Some(20);
+        config.candles.timeframes_secs = Some(vec![3_600, 60, 300, 60]);
+        let mut state = WriterState::new(Arc::default(), &config);
+        let block_time = chrono::DateTime::from_timestamp(1_700_000_000, 0);
+        state.batch.push_trades(
+            "pump",
+            vec![BondingCurveTrade {
+                signature: "candle_sig".to_string(),
+                slot: 100,
+                block_time,
+                mint_pubkey: MINT.to_string(),
+                trader: "trader".to_string(),
+                side: "buy".to_string(),
+                token_amount: 1_000_000,
+                sol_amount: 100_000_000,
+                price_nanos_per_token: 100_000_000,
+                tx_index: 0,
+                ix_index: 0,
+                inner_index: TOP_LEVEL_INNER_INDEX,
+            }],
+        );
+        state.batch.last_slot = Some(100);
+
+        flush_batch(&pool, &[], &mut state).await;
+
+        let rows: Vec<(i32, i64, i32)> = sqlx::query_as(
+            "SELECT timeframe_secs, EXTRACT(EPOCH FROM bucket_start)::BIGINT, trades_count
+             FROM candles WHERE mint_pubkey = $1 ORDER BY timeframe_secs",
+        )
+        .bind(MINT)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                (20, 1_700_000_000, 1),
+                (60, 1_699_999_980, 1),
+                (300, 1_699_999_800, 1),
+                (3_600, 1_699_999_200, 1)
+            ]
+        );
+
+        let bases: Vec<i32> = sqlx::query_scalar(
+            "SELECT DISTINCT (payload->>'base_timeframe_secs')::INT FROM indexer_events
+             WHERE topic = 'candles'",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(bases, vec![20]);
+    }
+}
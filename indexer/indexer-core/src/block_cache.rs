@@ -0,0 +1,160 @@
+// On-disk cache of firehose blocks.
+// Blocks are appended as newline-delimited JSON so they can be replayed through the writer
+// later (backfills, parser debugging) without re-streaming from the firehose.
+
+use crate::spl_parser::BlockRef;
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// File extension used for cache segments.
+pub const CACHE_FILE_EXT: &str = "jsonl";
+
+/// Appends received blocks to `<dir>/blocks-<first_slot>.jsonl`.
+/// The segment file is opened lazily on the first block so its name reflects where it starts.
+pub struct BlockCacheWriter {
+    dir: PathBuf,
+    file: Option<BufWriter<File>>,
+}
+
+impl BlockCacheWriter {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create block cache dir {}", dir.display()))?;
+        Ok(Self { dir, file: None })
+    }
+
+    /// Append one block as a single JSON line and flush it to disk.
+    pub fn append(&mut self, block: &BlockRef) -> Result<()> {
+        if self.file.is_none() {
+            let path = self
+                .dir
+                .join(format!("blocks-{:012}.{CACHE_FILE_EXT}", block.slot));
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .with_context(|| format!("failed to open block cache file {}", path.display()))?;
+            self.file = Some(BufWriter::new(file));
+        }
+
+        let file = self.file.as_mut().expect("cache file opened above");
+        file.write_all(&block.to_bytes()?)?;
+        file.write_all(b"\n")?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// List cache segments in `dir`, sorted by file name (and therefore by starting slot).
+pub fn list_cache_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)
+        .with_context(|| format!("failed to read block cache dir {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == CACHE_FILE_EXT) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Read every block from one cache segment. Blank lines are skipped.
+pub fn read_cache_file(path: &Path) -> Result<Vec<BlockRef>> {
+    let file = File::open(path)
+        .with_context(|| format!("failed to open block cache file {}", path.display()))?;
+
+    let mut blocks = Vec::new();
+    for (line_no, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let block = BlockRef::from_bytes(line.as_bytes()).with_context(|| {
+            format!("invalid block at {}:{}", path.display(), line_no + 1)
+        })?;
+        blocks.push(block);
+    }
+    Ok(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spl_parser::{InstructionRef, MessageRef, TransactionRef, TOP_LEVEL_INNER_INDEX};
+
+    fn block(slot: i64) -> BlockRef {
+        BlockRef {
+            slot,
+            block_time_unix: Some(1_700_000_000 + slot),
+            transactions: vec![TransactionRef {
+                signature: format!("sig_{slot}"),
+                index: 0,
+                message: MessageRef {
+                    account_keys: vec!["a".to_string(), "b".to_string()],
+                },
+                instructions: vec![InstructionRef {
+                    program_id: "program".to_string(),
+                    accounts: vec![0, 1],
+                    data: vec![1, 2, 3],
+                    index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
+                }],
+            }],
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "indexer-block-cache-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_block_cache_append_and_read_back() {
+        let dir = temp_dir("roundtrip");
+
+        let mut writer = BlockCacheWriter::new(&dir).unwrap();
+        for slot in [10, 11, 12] {
+            writer.append(&block(slot)).unwrap();
+        }
+        drop(writer);
+
+        let files = list_cache_files(&dir).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("blocks-000000000010.jsonl"));
+
+        let blocks = read_cache_file(&files[0]).unwrap();
+        assert_eq!(blocks, vec![block(10), block(11), block(12)]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_list_cache_files_sorted_and_filtered() {
+        let dir = temp_dir("listing");
+
+        BlockCacheWriter::new(&dir).unwrap().append(&block(200)).unwrap();
+        BlockCacheWriter::new(&dir).unwrap().append(&block(9)).unwrap();
+        fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let files = list_cache_files(&dir).unwrap();
+        let names: Vec<_> = files
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["blocks-000000000009.jsonl", "blocks-000000000200.jsonl"]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
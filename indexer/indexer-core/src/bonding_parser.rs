@@ -1,13 +1,32 @@
 use crate::{
     models::BondingCurveTrade,
-    spl_parser::{BlockRef, InstructionRef, TransactionRef},
+    price::PriceScale,
+    spl_parser::{
+        account_indices_in_range, parse_guarded, BlockRef, InstructionRef, ParseCounters,
+        TransactionRef,
+    },
 };
 use chrono::{TimeZone, Utc};
 use sha2::{Digest, Sha256};
 
 pub const PUMP_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
 
-fn anchor_discriminator(ix_name: &str) -> [u8; 8] {
+/// Real SOL a pump.fun bonding curve holds when it completes and the token migrates
+/// (~85 SOL, the "graduates at $61k" market cap shown by the terminal).
+pub const PUMP_GRADUATION_LAMPORTS: i64 = 85_000_000_000;
+
+/// Bonding-curve progress in percent (0-100) from SOL flows into the curve: `net_lamports` is
+/// buys minus sells so far, `peak_net_lamports` the highest that running total ever reached.
+/// A curve that once reached the graduation threshold has migrated and stays at 100, even
+/// though post-migration trades keep moving the net total.
+pub fn bonding_progress_pct(net_lamports: i64, peak_net_lamports: i64) -> f64 {
+    if peak_net_lamports >= PUMP_GRADUATION_LAMPORTS {
+        return 100.0;
+    }
+    (net_lamports as f64 / PUMP_GRADUATION_LAMPORTS as f64 * 100.0).clamp(0.0, 100.0)
+}
+
+pub(crate) fn anchor_discriminator(ix_name: &str) -> [u8; 8] {
     let preimage = format!("global:{ix_name}");
     let hash = Sha256::digest(preimage.as_bytes());
     let mut out = [0u8; 8];
@@ -15,6 +34,62 @@ fn anchor_discriminator(ix_name: &str) -> [u8; 8] {
     out
 }
 
+/// Leading bytes of an Anchor self-CPI event instruction (`EVENT_IX_TAG_LE` in anchor-lang),
+/// followed by the event's discriminator (see [`anchor_event_discriminator`]) and its fields.
+const ANCHOR_EVENT_IX_TAG: [u8; 8] = [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d];
+
+fn anchor_event_discriminator(event_name: &str) -> [u8; 8] {
+    let preimage = format!("event:{event_name}");
+    let hash = Sha256::digest(preimage.as_bytes());
+    let mut out = [0u8; 8];
+    out.copy_from_slice(&hash[..8]);
+    out
+}
+
+/// The executed amounts of a buy/sell, from the `TradeEvent` pump emits through a self-CPI.
+/// Layout after the tag and discriminator: mint (32), solAmount (u64), tokenAmount (u64),
+/// isBuy (bool), user (32), timestamp (i64), virtualSolReserves (u64), virtualTokenReserves
+/// (u64), then fields added by later program versions. Only the first four are read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TradeEvent {
+    mint: String,
+    sol_amount: u64,
+    token_amount: u64,
+    is_buy: bool,
+}
+
+fn parse_trade_event(data: &[u8]) -> Option<TradeEvent> {
+    if data.get(..8)? != ANCHOR_EVENT_IX_TAG
+        || data.get(8..16)? != anchor_event_discriminator("TradeEvent")
+    {
+        return None;
+    }
+    let fields = data.get(16..)?;
+    Some(TradeEvent {
+        mint: bs58::encode(fields.get(..32)?).into_string(),
+        sol_amount: read_u64_le(fields.get(32..)?)?,
+        token_amount: read_u64_le(fields.get(40..)?)?,
+        is_buy: *fields.get(48)? != 0,
+    })
+}
+
+/// The `TradeEvent` of the pump buy/sell `ix`: the first one for the same mint and side among
+/// the instructions its CPI made after it.
+fn find_trade_event(
+    tx: &TransactionRef,
+    ix: &InstructionRef,
+    mint: &str,
+    is_buy: bool,
+) -> Option<TradeEvent> {
+    tx.instructions
+        .iter()
+        .filter(|i| {
+            i.program_id == PUMP_PROGRAM_ID && i.index == ix.index && i.inner_index > ix.inner_index
+        })
+        .filter_map(|i| parse_trade_event(&i.data))
+        .find(|event| event.mint == mint && event.is_buy == is_buy)
+}
+
 fn read_u64_le(bytes: &[u8]) -> Option<u64> {
     if bytes.len() < 8 {
         return None;
@@ -25,6 +100,23 @@ fn read_u64_le(bytes: &[u8]) -> Option<u64> {
 }
 
 pub fn extract_pump_trades_from_block(block: &BlockRef) -> Vec<BondingCurveTrade> {
+    extract_pump_trades_with(block, None, &PriceScale::default())
+}
+
+/// Same as [`extract_pump_trades_from_block`], pricing trades with `price_scale`. `counters`
+/// gets a miss for every pump instruction that is too short to carry a discriminator or is a
+/// buy/sell that fails to parse, and a bad index for every one with out-of-range accounts.
+pub fn extract_pump_trades_with(
+    block: &BlockRef,
+    counters: Option<&ParseCounters>,
+    price_scale: &PriceScale,
+) -> Vec<BondingCurveTrade> {
+    let miss = || {
+        if let Some(c) = counters {
+            c.miss();
+        }
+    };
+
     let buy_disc = anchor_discriminator("buy");
     let sell_disc = anchor_discriminator("sell");
 
@@ -39,20 +131,32 @@ pub fn extract_pump_trades_from_block(block: &BlockRef) -> Vec<BondingCurveTrade
             if ix.program_id != PUMP_PROGRAM_ID {
                 continue;
             }
+            if !account_indices_in_range("pump", tx, ix, counters) {
+                continue;
+            }
 
             if ix.data.len() < 8 {
+                miss();
                 continue;
             }
             let disc: [u8; 8] = ix.data[0..8].try_into().unwrap();
 
-            if disc == buy_disc {
-                if let Some(trade) = parse_buy(block.slot, block_time, tx, ix) {
-                    out.push(trade);
-                }
+            let parsed = if disc == buy_disc {
+                parse_guarded("pump", tx, ix, counters, || {
+                    parse_buy(block.slot, block_time, tx, ix, price_scale)
+                })
             } else if disc == sell_disc {
-                if let Some(trade) = parse_sell(block.slot, block_time, tx, ix) {
-                    out.push(trade);
-                }
+                parse_guarded("pump", tx, ix, counters, || {
+                    parse_sell(block.slot, block_time, tx, ix, price_scale)
+                })
+            } else {
+                // Other pump instructions (create, withdraw, ...) are not trades.
+                continue;
+            };
+
+            match parsed {
+                Some(trade) => out.push(trade),
+                None => miss(),
             }
         }
     }
@@ -62,11 +166,14 @@ pub fn extract_pump_trades_from_block(block: &BlockRef) -> Vec<BondingCurveTrade
 
 // Pump IDL (public): buy accounts order includes mint at index 2 and user at index 6.
 // Source: pump.fun IDL JSON (see public gist in research).
+// The args only bound the SOL side, so the amounts come from the trade's `TradeEvent` when the
+// block carries it (see [`find_trade_event`]).
 fn parse_buy(
     slot: i64,
     block_time: Option<chrono::DateTime<chrono::Utc>>,
     tx: &TransactionRef,
     ix: &InstructionRef,
+    price_scale: &PriceScale,
 ) -> Option<BondingCurveTrade> {
     let (mint, user) = pump_mint_and_user(tx, ix)?;
 
@@ -74,13 +181,13 @@ fn parse_buy(
     let args = &ix.data[8..];
     let token_amount = read_u64_le(args)?;
     let max_sol_cost = read_u64_le(&args[8..])?;
-
-    let price = if token_amount == 0 {
-        0u64
-    } else {
-        max_sol_cost / token_amount
+    let (token_amount, sol_amount) = match find_trade_event(tx, ix, &mint, true) {
+        Some(event) => (event.token_amount, event.sol_amount),
+        None => (token_amount, max_sol_cost),
     };
 
+    let price = price_scale.price_nanos(&mint, sol_amount, token_amount);
+
     Some(BondingCurveTrade {
         signature: tx.signature.clone(),
         slot,
@@ -89,33 +196,35 @@ fn parse_buy(
         trader: user,
         side: "buy".to_string(),
         token_amount: token_amount as i64,
-        sol_amount: max_sol_cost as i64,
-        price_nanos_per_token: price as i64,
+        sol_amount: sol_amount as i64,
+        price_nanos_per_token: price,
         tx_index: tx.index,
         ix_index: ix.index,
+        inner_index: ix.inner_index,
     })
 }
 
 // Pump IDL: sell accounts include mint at index 2 and user at index 6.
-// args: amount(u64), minSolOutput(u64)
+// args: amount(u64), minSolOutput(u64); the amounts come from the `TradeEvent` as for buys.
 fn parse_sell(
     slot: i64,
     block_time: Option<chrono::DateTime<chrono::Utc>>,
     tx: &TransactionRef,
     ix: &InstructionRef,
+    price_scale: &PriceScale,
 ) -> Option<BondingCurveTrade> {
     let (mint, user) = pump_mint_and_user(tx, ix)?;
 
     let args = &ix.data[8..];
     let token_amount = read_u64_le(args)?;
     let min_sol_output = read_u64_le(&args[8..])?;
-
-    let price = if token_amount == 0 {
-        0u64
-    } else {
-        min_sol_output / token_amount
+    let (token_amount, sol_amount) = match find_trade_event(tx, ix, &mint, false) {
+        Some(event) => (event.token_amount, event.sol_amount),
+        None => (token_amount, min_sol_output),
     };
 
+    let price = price_scale.price_nanos(&mint, sol_amount, token_amount);
+
     Some(BondingCurveTrade {
         signature: tx.signature.clone(),
         slot,
@@ -124,10 +233,11 @@ fn parse_sell(
         trader: user,
         side: "sell".to_string(),
         token_amount: token_amount as i64,
-        sol_amount: min_sol_output as i64,
-        price_nanos_per_token: price as i64,
+        sol_amount: sol_amount as i64,
+        price_nanos_per_token: price,
         tx_index: tx.index,
         ix_index: ix.index,
+        inner_index: ix.inner_index,
     })
 }
 
@@ -149,7 +259,18 @@ fn pump_mint_and_user(tx: &TransactionRef, ix: &InstructionRef) -> Option<(Strin
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::spl_parser::{BlockRef, TransactionRef, MessageRef, InstructionRef};
+    use crate::spl_parser::{BlockRef, TransactionRef, MessageRef, InstructionRef, TOP_LEVEL_INNER_INDEX};
+
+    #[test]
+    fn test_bonding_progress_pct() {
+        assert_eq!(bonding_progress_pct(0, 0), 0.0);
+        let quarter = PUMP_GRADUATION_LAMPORTS / 4;
+        assert_eq!(bonding_progress_pct(quarter, 2 * quarter), 25.0);
+        // More sold than bought (e.g. trades before the indexer started) clamps at 0
+        assert_eq!(bonding_progress_pct(-5, 10), 0.0);
+        // Graduated: sells after migration don't pull it back down
+        assert_eq!(bonding_progress_pct(1, PUMP_GRADUATION_LAMPORTS), 100.0);
+    }
 
     #[test]
     fn test_anchor_discriminator() {
@@ -208,6 +329,7 @@ mod tests {
                     accounts: vec![0, 1, 2, 3, 4, 5, 6],
                     data: create_buy_instruction(),
                     index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
                 }],
             }],
         };
@@ -220,11 +342,44 @@ mod tests {
         assert_eq!(trades[0].side, "buy");
         assert_eq!(trades[0].token_amount, 1_000_000);
         assert_eq!(trades[0].sol_amount, 100_000_000);
-        // price = maxSolCost / token_amount = 100_000_000 / 1_000_000 = 100
-        assert_eq!(trades[0].price_nanos_per_token, 100);
+        // 0.1 SOL for 1 whole (6-decimal) token
+        assert_eq!(trades[0].price_nanos_per_token, 100_000_000);
         assert_eq!(trades[0].slot, 100);
     }
 
+    #[test]
+    fn test_inner_pump_instructions_keep_their_inner_index() {
+        let keys = ["acc0", "acc1", "mint_abc", "acc3", "acc4", "acc5", "trader_wallet"];
+        // A router (top-level instruction 1) CPIs into pump twice.
+        let cpi = |inner_index: i32, data: Vec<u8>| InstructionRef {
+            program_id: PUMP_PROGRAM_ID.to_string(),
+            accounts: vec![0, 1, 2, 3, 4, 5, 6],
+            data,
+            index: 1,
+            inner_index,
+        };
+        let block = BlockRef {
+            slot: 100,
+            block_time_unix: Some(1000),
+            transactions: vec![TransactionRef {
+                signature: "router_sig".to_string(),
+                index: 0,
+                message: MessageRef {
+                    account_keys: keys.iter().map(|k| k.to_string()).collect(),
+                },
+                instructions: vec![
+                    cpi(0, create_buy_instruction()),
+                    cpi(1, create_sell_instruction()),
+                ],
+            }],
+        };
+
+        let trades = extract_pump_trades_from_block(&block);
+
+        let positions: Vec<_> = trades.iter().map(|t| (t.ix_index, t.inner_index)).collect();
+        assert_eq!(positions, vec![(1, 0), (1, 1)]);
+    }
+
     #[test]
     fn test_parse_pump_sell() {
         let block = BlockRef {
@@ -249,6 +404,7 @@ mod tests {
                     accounts: vec![0, 1, 2, 3, 4, 5, 6],
                     data: create_sell_instruction(),
                     index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
                 }],
             }],
         };
@@ -261,8 +417,108 @@ mod tests {
         assert_eq!(trades[0].side, "sell");
         assert_eq!(trades[0].token_amount, 500_000);
         assert_eq!(trades[0].sol_amount, 50_000_000);
-        // price = minSolOutput / token_amount = 50_000_000 / 500_000 = 100
-        assert_eq!(trades[0].price_nanos_per_token, 100);
+        // 0.05 SOL for 0.5 tokens = 0.1 SOL per whole token
+        assert_eq!(trades[0].price_nanos_per_token, 100_000_000);
+    }
+
+    /// Pump's self-CPI `TradeEvent` for a trade of `mint` made by top-level instruction 0.
+    fn trade_event_instruction(
+        mint: &[u8; 32],
+        sol_amount: u64,
+        token_amount: u64,
+        is_buy: bool,
+    ) -> InstructionRef {
+        let mut data = ANCHOR_EVENT_IX_TAG.to_vec();
+        data.extend_from_slice(&anchor_event_discriminator("TradeEvent"));
+        data.extend_from_slice(mint);
+        data.extend_from_slice(&sol_amount.to_le_bytes());
+        data.extend_from_slice(&token_amount.to_le_bytes());
+        data.push(is_buy as u8);
+        data.extend_from_slice(&[9u8; 32]); // user
+        data.extend_from_slice(&1_000i64.to_le_bytes()); // timestamp
+        data.extend_from_slice(&30_000_000_000u64.to_le_bytes()); // virtual SOL reserves
+        data.extend_from_slice(&1_000_000_000_000_000u64.to_le_bytes()); // virtual token reserves
+        InstructionRef {
+            program_id: PUMP_PROGRAM_ID.to_string(),
+            accounts: vec![7], // event authority
+            data,
+            index: 0,
+            inner_index: 3,
+        }
+    }
+
+    #[test]
+    fn test_anchor_event_ix_tag_is_sighash_of_anchor_event() {
+        let hash = Sha256::digest(b"anchor:event");
+        let mut reversed = ANCHOR_EVENT_IX_TAG;
+        reversed.reverse();
+        assert_eq!(reversed, hash[..8]);
+    }
+
+    #[test]
+    fn test_pump_trades_take_amounts_from_trade_event() {
+        let mint = [5u8; 32];
+        let mint_key = bs58::encode(mint).into_string();
+        let trade_block = |trade: Vec<u8>, event: InstructionRef| BlockRef {
+            slot: 250,
+            block_time_unix: Some(2500),
+            transactions: vec![TransactionRef {
+                signature: "event_sig".to_string(),
+                index: 0,
+                message: MessageRef {
+                    account_keys: vec![
+                        "acc0".to_string(),
+                        "acc1".to_string(),
+                        mint_key.clone(),
+                        "acc3".to_string(),
+                        "acc4".to_string(),
+                        "acc5".to_string(),
+                        "trader_wallet".to_string(),
+                        "event_authority".to_string(),
+                    ],
+                },
+                instructions: vec![
+                    InstructionRef {
+                        program_id: PUMP_PROGRAM_ID.to_string(),
+                        accounts: vec![0, 1, 2, 3, 4, 5, 6],
+                        data: trade,
+                        index: 0,
+                        inner_index: TOP_LEVEL_INNER_INDEX,
+                    },
+                    event,
+                ],
+            }],
+        };
+
+        // maxSolCost is 0.1 SOL; the curve actually took 0.08 SOL.
+        let block = trade_block(
+            create_buy_instruction(),
+            trade_event_instruction(&mint, 80_000_000, 1_000_000, true),
+        );
+        let trades = extract_pump_trades_from_block(&block);
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].sol_amount, 80_000_000);
+        assert_eq!(trades[0].token_amount, 1_000_000);
+        assert_eq!(trades[0].price_nanos_per_token, 80_000_000);
+
+        // minSolOutput is 0.05 SOL; the seller got 0.06 SOL.
+        let block = trade_block(
+            create_sell_instruction(),
+            trade_event_instruction(&mint, 60_000_000, 500_000, false),
+        );
+        let trades = extract_pump_trades_from_block(&block);
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].side, "sell");
+        assert_eq!(trades[0].sol_amount, 60_000_000);
+
+        // An event for another side (or mint) isn't this trade's: fall back to the args.
+        let block = trade_block(
+            create_buy_instruction(),
+            trade_event_instruction(&mint, 80_000_000, 1_000_000, false),
+        );
+        let trades = extract_pump_trades_from_block(&block);
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].sol_amount, 100_000_000);
     }
 
     #[test]
@@ -290,6 +546,7 @@ mod tests {
                         accounts: vec![0, 1, 2, 3, 4, 5, 6],
                         data: create_buy_instruction(),
                         index: 0,
+                        inner_index: TOP_LEVEL_INNER_INDEX,
                     }],
                 },
                 TransactionRef {
@@ -311,6 +568,7 @@ mod tests {
                         accounts: vec![0, 1, 2, 3, 4, 5, 6],
                         data: create_sell_instruction(),
                         index: 0,
+                        inner_index: TOP_LEVEL_INNER_INDEX,
                     }],
                 },
             ],
@@ -349,6 +607,7 @@ mod tests {
             accounts: vec![0, 1, 2, 3, 4, 5, 6],
             data: vec![],
             index: 0,
+            inner_index: TOP_LEVEL_INNER_INDEX,
         };
 
         let (mint, user) = pump_mint_and_user(&tx, &ix).expect("should extract");
@@ -372,6 +631,7 @@ mod tests {
             accounts: vec![0, 1], // Only 2 accounts, need 7
             data: vec![],
             index: 0,
+            inner_index: TOP_LEVEL_INNER_INDEX,
         };
 
         let result = pump_mint_and_user(&tx, &ix);
@@ -408,6 +668,7 @@ mod tests {
                         d
                     },
                     index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
                 }],
             }],
         };
@@ -418,4 +679,86 @@ mod tests {
         // price should be 0 when token_amount is 0
         assert_eq!(trades[0].price_nanos_per_token, 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_short_pump_instruction_counts_as_miss() {
+        let buy_disc = anchor_discriminator("buy");
+        let block = BlockRef {
+            slot: 500,
+            block_time_unix: Some(5000),
+            transactions: vec![TransactionRef {
+                signature: "short_sig".to_string(),
+                index: 0,
+                message: MessageRef {
+                    account_keys: vec!["a".to_string(), "b".to_string(), "mint".to_string()],
+                },
+                instructions: vec![
+                    // Too short to hold a discriminator
+                    InstructionRef {
+                        program_id: PUMP_PROGRAM_ID.to_string(),
+                        accounts: vec![0, 1, 2],
+                        data: vec![1, 2, 3],
+                        index: 0,
+                        inner_index: TOP_LEVEL_INNER_INDEX,
+                    },
+                    // Buy discriminator but truncated arguments
+                    InstructionRef {
+                        program_id: PUMP_PROGRAM_ID.to_string(),
+                        accounts: vec![0, 1, 2],
+                        data: buy_disc[..].to_vec(),
+                        index: 1,
+                        inner_index: TOP_LEVEL_INNER_INDEX,
+                    },
+                    // Not a pump instruction at all
+                    InstructionRef {
+                        program_id: "other_program".to_string(),
+                        accounts: vec![0],
+                        data: vec![],
+                        index: 2,
+                        inner_index: TOP_LEVEL_INNER_INDEX,
+                    },
+                ],
+            }],
+        };
+
+        let counters = ParseCounters::default();
+        let trades = extract_pump_trades_with(&block, Some(&counters), &PriceScale::default());
+
+        assert!(trades.is_empty());
+        assert_eq!(counters.misses(), 2);
+        assert_eq!(counters.bad_indices(), 0);
+    }
+
+    #[test]
+    fn test_out_of_range_account_index_counts_as_bad_index() {
+        let mut data = anchor_discriminator("buy").to_vec();
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data.extend_from_slice(&5_000u64.to_le_bytes());
+        let account_keys: Vec<String> = (0..7).map(|i| format!("key{i}")).collect();
+        let block = BlockRef {
+            slot: 600,
+            block_time_unix: Some(6000),
+            transactions: vec![TransactionRef {
+                signature: "bad_index_sig".to_string(),
+                index: 0,
+                message: MessageRef { account_keys },
+                instructions: vec![InstructionRef {
+                    program_id: PUMP_PROGRAM_ID.to_string(),
+                    // The user slot points past the 7 account keys
+                    accounts: vec![0, 1, 2, 3, 4, 5, 200],
+                    data,
+                    index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
+                }],
+            }],
+        };
+
+        let counters = ParseCounters::default();
+        let trades = extract_pump_trades_with(&block, Some(&counters), &PriceScale::default());
+
+        assert!(trades.is_empty());
+        assert_eq!(counters.bad_indices(), 1);
+        // Corrupt input is not reported as a layout change
+        assert_eq!(counters.misses(), 0);
+    }
+}
@@ -0,0 +1,143 @@
+// Compact binary encoding of candle series.
+// JSON candles repeat field names and the mint in every record; clients rendering thousands of
+// candles can ask for this fixed-size layout instead.
+//
+// All integers are little-endian.
+//
+// Header (16 bytes):
+//   0..4    magic          b"CNDL"
+//   4..6    version        u16 (currently 1)
+//   6..8    record_size    u16 (currently 60)
+//   8..12   timeframe_secs i32
+//   12..16  count          u32, number of records that follow
+//
+// Record (60 bytes), in the same order as the JSON endpoint (newest first):
+//   0..8    bucket_start   i64, unix seconds
+//   8..16   open           i64
+//   16..24  high           i64
+//   24..32  low            i64
+//   32..40  close          i64
+//   40..48  volume_token   i64
+//   48..56  volume_sol     i64
+//   56..60  trades_count   i32
+//
+// The mint is not repeated: it is the one the series was requested for.
+
+use crate::models::Candle;
+use anyhow::{bail, Result};
+use chrono::{TimeZone, Utc};
+
+pub const CANDLE_MAGIC: &[u8; 4] = b"CNDL";
+pub const CANDLE_CODEC_VERSION: u16 = 1;
+pub const CANDLE_HEADER_LEN: usize = 16;
+pub const CANDLE_RECORD_LEN: usize = 60;
+
+/// Encode candles of a single mint and timeframe.
+pub fn encode_candles(timeframe_secs: i32, candles: &[Candle]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(CANDLE_HEADER_LEN + candles.len() * CANDLE_RECORD_LEN);
+    out.extend_from_slice(CANDLE_MAGIC);
+    out.extend_from_slice(&CANDLE_CODEC_VERSION.to_le_bytes());
+    out.extend_from_slice(&(CANDLE_RECORD_LEN as u16).to_le_bytes());
+    out.extend_from_slice(&timeframe_secs.to_le_bytes());
+    out.extend_from_slice(&(candles.len() as u32).to_le_bytes());
+
+    for c in candles {
+        out.extend_from_slice(&c.bucket_start.timestamp().to_le_bytes());
+        for v in [c.open, c.high, c.low, c.close, c.volume_token, c.volume_sol] {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        out.extend_from_slice(&c.trades_count.to_le_bytes());
+    }
+    out
+}
+
+/// Decode a buffer produced by [`encode_candles`]. `mint_pubkey` fills in the mint, which the
+/// binary format does not carry.
+pub fn decode_candles(mint_pubkey: &str, data: &[u8]) -> Result<Vec<Candle>> {
+    if data.len() < CANDLE_HEADER_LEN || &data[0..4] != CANDLE_MAGIC {
+        bail!("not a candle buffer");
+    }
+    let version = u16::from_le_bytes(data[4..6].try_into()?);
+    if version != CANDLE_CODEC_VERSION {
+        bail!("unsupported candle encoding version {version}");
+    }
+    let record_len = u16::from_le_bytes(data[6..8].try_into()?) as usize;
+    if record_len != CANDLE_RECORD_LEN {
+        bail!("unexpected candle record size {record_len}");
+    }
+    let timeframe_secs = i32::from_le_bytes(data[8..12].try_into()?);
+    let count = u32::from_le_bytes(data[12..16].try_into()?) as usize;
+
+    let body = &data[CANDLE_HEADER_LEN..];
+    if body.len() != count * CANDLE_RECORD_LEN {
+        bail!(
+            "candle buffer holds {} bytes of records, expected {}",
+            body.len(),
+            count * CANDLE_RECORD_LEN
+        );
+    }
+
+    body.chunks_exact(CANDLE_RECORD_LEN)
+        .map(|rec| {
+            let i64_at = |off: usize| i64::from_le_bytes(rec[off..off + 8].try_into().unwrap());
+            let bucket_secs = i64_at(0);
+            let Some(bucket_start) = Utc.timestamp_opt(bucket_secs, 0).single() else {
+                bail!("invalid bucket_start {bucket_secs}");
+            };
+            Ok(Candle {
+                mint_pubkey: mint_pubkey.to_string(),
+                timeframe_secs,
+                bucket_start,
+                open: i64_at(8),
+                high: i64_at(16),
+                low: i64_at(24),
+                close: i64_at(32),
+                volume_token: i64_at(40),
+                volume_sol: i64_at(48),
+                trades_count: i32::from_le_bytes(rec[56..60].try_into().unwrap()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(bucket_unix: i64, open: i64, close: i64) -> Candle {
+        Candle {
+            mint_pubkey: "mint".to_string(),
+            timeframe_secs: 60,
+            bucket_start: Utc.timestamp_opt(bucket_unix, 0).single().unwrap(),
+            open,
+            high: open.max(close),
+            low: open.min(close),
+            close,
+            volume_token: 1_000,
+            volume_sol: -5,
+            trades_count: 3,
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let candles = vec![candle(1_700_000_100, 11, 12), candle(1_700_000_040, 10, 11)];
+
+        let bytes = encode_candles(60, &candles);
+        assert_eq!(bytes.len(), CANDLE_HEADER_LEN + 2 * CANDLE_RECORD_LEN);
+
+        let decoded = decode_candles("mint", &bytes).unwrap();
+        assert_eq!(decoded.len(), 2);
+        for (a, b) in decoded.iter().zip(&candles) {
+            assert_eq!(serde_json::to_value(a).unwrap(), serde_json::to_value(b).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_truncated_buffer() {
+        let bytes = encode_candles(60, &[candle(1_700_000_040, 10, 11)]);
+
+        assert!(decode_candles("mint", &bytes[..bytes.len() - 1]).is_err());
+        assert!(decode_candles("mint", b"JSON").is_err());
+    }
+}
@@ -0,0 +1,346 @@
+// Candle bookkeeping shared by the writer.
+// Candle rows are upserted on every trade, so realtime clients need to be told when a bucket
+// stops changing. The writer keeps the latest bucket seen per mint/timeframe and reports
+// when a trade rolls over into a newer one. It also keeps the latest close, so that a new
+// bucket can open where the previous one closed (see `CandleOpenMode`). Updates are announced
+// once per bucket per write, not once per trade (see `CandleEvents`).
+
+use crate::models::Candle;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Timeframes the writer aggregates when `[candles] timeframes_secs` is unset: 1m, 5m, 15m, 1h
+/// and 1d.
+pub const DEFAULT_CANDLE_TIMEFRAMES: [i32; 5] = [60, 300, 900, 3_600, 86_400];
+
+/// Smallest timeframe when `[candles] base_timeframe_secs` is unset.
+pub const DEFAULT_BASE_CANDLE_TIMEFRAME_SECS: i32 = 60;
+
+/// Largest candle timeframe the writer builds and the API serves (1d).
+pub const MAX_CANDLE_TIMEFRAME_SECS: i32 = 86_400;
+
+/// Check that `base_secs` is a usable timeframe and every derived timeframe a multiple of it,
+/// so each derived candle is an exact rollup of base candles.
+pub fn check_timeframes(base_secs: i32, derived: &[i32]) -> anyhow::Result<()> {
+    if !(1..=MAX_CANDLE_TIMEFRAME_SECS).contains(&base_secs) {
+        anyhow::bail!(
+            "candle base timeframe must be 1-{MAX_CANDLE_TIMEFRAME_SECS}s, got {base_secs}s"
+        );
+    }
+    for &tf in derived {
+        if !(1..=MAX_CANDLE_TIMEFRAME_SECS).contains(&tf) || tf % base_secs != 0 {
+            anyhow::bail!(
+                "candle timeframe {tf}s must be a multiple of the {base_secs}s base, at most \
+                 {MAX_CANDLE_TIMEFRAME_SECS}s"
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Start of the `timeframe_secs` bucket holding unix time `ts`. `timeframe_secs` must be
+/// positive.
+pub fn bucket_start(ts: i64, timeframe_secs: i32) -> DateTime<Utc> {
+    let bucket = ts - ts.rem_euclid(i64::from(timeframe_secs));
+    Utc.timestamp_opt(bucket, 0).single().unwrap()
+}
+
+/// How the writer picks a new candle's open price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandleOpenMode {
+    /// Open at the previous bucket's close, so consecutive candles join without gaps (the
+    /// terminal's convention). Falls back to the first trade when no earlier close is known,
+    /// e.g. for the first bucket after a restart.
+    #[default]
+    PrevClose,
+    /// Open at the price of the bucket's first trade.
+    FirstTrade,
+}
+
+/// Result of [`CandleBucketTracker::record_trade`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeInBucket {
+    /// Open price to upsert with. Only used when the trade creates the candle row.
+    pub open: i64,
+    /// The bucket this trade closed, as returned by [`CandleBucketTracker::observe`].
+    pub closed_bucket: Option<DateTime<Utc>>,
+}
+
+/// Latest candle bucket and close seen per `(mint, timeframe_secs)`.
+#[derive(Debug, Default)]
+pub struct CandleBucketTracker {
+    last_bucket: HashMap<(String, i32), DateTime<Utc>>,
+    last_close: HashMap<(String, i32), i64>,
+}
+
+impl CandleBucketTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a trade landing in `bucket_start`.
+    /// Returns the previous bucket when this trade opened a newer one, i.e. the bucket that is
+    /// now closed. Trades for the current bucket, the first trade ever seen for a mint, and
+    /// late trades for an older bucket all return `None`.
+    pub fn observe(
+        &mut self,
+        mint_pubkey: &str,
+        timeframe_secs: i32,
+        bucket_start: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let key = (mint_pubkey.to_string(), timeframe_secs);
+        match self.last_bucket.get(&key).copied() {
+            Some(prev) if bucket_start > prev => {
+                self.last_bucket.insert(key, bucket_start);
+                Some(prev)
+            }
+            Some(_) => None,
+            None => {
+                self.last_bucket.insert(key, bucket_start);
+                None
+            }
+        }
+    }
+
+    /// Record a trade at `price` landing in `bucket_start`: pick the open for its candle per
+    /// `mode`, roll the bucket over like [`observe`](Self::observe), and remember `price` as
+    /// the latest close. Late trades for an older bucket open at their own price and do not
+    /// move the latest close.
+    pub fn record_trade(
+        &mut self,
+        mint_pubkey: &str,
+        timeframe_secs: i32,
+        bucket_start: DateTime<Utc>,
+        price: i64,
+        mode: CandleOpenMode,
+    ) -> TradeInBucket {
+        let key = (mint_pubkey.to_string(), timeframe_secs);
+        let last_bucket = self.last_bucket.get(&key).copied();
+
+        let open = match (mode, last_bucket, self.last_close.get(&key)) {
+            (CandleOpenMode::PrevClose, Some(prev), Some(&close)) if bucket_start > prev => close,
+            _ => price,
+        };
+        if last_bucket.is_none_or(|prev| bucket_start >= prev) {
+            self.last_close.insert(key, price);
+        }
+
+        let closed_bucket = self.observe(mint_pubkey, timeframe_secs, bucket_start);
+        TradeInBucket {
+            open,
+            closed_bucket,
+        }
+    }
+}
+
+/// A realtime candle event, as published on the `candles` topic.
+#[derive(Debug, Clone)]
+pub enum CandleEvent {
+    /// Trades since the last update of this bucket, merged like the candle upsert merges them.
+    Update(Candle),
+    /// The bucket starting at `bucket_start` will get no more trades.
+    Closed {
+        mint_pubkey: String,
+        timeframe_secs: i32,
+        bucket_start: DateTime<Utc>,
+    },
+}
+
+/// Candle events of one writer flush, with every trade's update of a `(mint, timeframe,
+/// bucket)` merged into that bucket's first update, so an active token yields one event per
+/// bucket instead of one per trade. Events keep the order they were first recorded in: a close
+/// precedes the update of the bucket whose first trade closed it.
+#[derive(Debug, Default)]
+pub struct CandleEvents {
+    events: Vec<CandleEvent>,
+    /// Index into `events` of each bucket's update.
+    updates: HashMap<(String, i32, DateTime<Utc>), usize>,
+}
+
+impl CandleEvents {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_closed(
+        &mut self,
+        mint_pubkey: &str,
+        timeframe_secs: i32,
+        bucket_start: DateTime<Utc>,
+    ) {
+        self.events.push(CandleEvent::Closed {
+            mint_pubkey: mint_pubkey.to_string(),
+            timeframe_secs,
+            bucket_start,
+        });
+    }
+
+    /// Record one trade's candle update, folding it into an earlier update of the same bucket:
+    /// the first open stays, high/low widen, the later close wins and volumes and trade counts
+    /// add up.
+    pub fn record_update(&mut self, candle: Candle) {
+        let key = (candle.mint_pubkey.clone(), candle.timeframe_secs, candle.bucket_start);
+        if let Some(&i) = self.updates.get(&key) {
+            if let CandleEvent::Update(merged) = &mut self.events[i] {
+                merged.high = merged.high.max(candle.high);
+                merged.low = merged.low.min(candle.low);
+                merged.close = candle.close;
+                merged.volume_token += candle.volume_token;
+                merged.volume_sol += candle.volume_sol;
+                merged.trades_count += candle.trades_count;
+            }
+            return;
+        }
+        self.updates.insert(key, self.events.len());
+        self.events.push(CandleEvent::Update(candle));
+    }
+
+    pub fn into_events(self) -> Vec<CandleEvent> {
+        self.events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(secs, 0).single().unwrap()
+    }
+
+    #[test]
+    fn test_bucket_start_per_timeframe() {
+        // 2023-11-14 22:13:20 UTC
+        let ts = 1_700_000_000;
+        let starts: Vec<_> = DEFAULT_CANDLE_TIMEFRAMES
+            .iter()
+            .map(|&tf| bucket_start(ts, tf).timestamp())
+            .collect();
+        assert_eq!(
+            starts,
+            vec![1_699_999_980, 1_699_999_800, 1_699_999_200, 1_699_999_200, 1_699_920_000]
+        );
+        // Bucket boundaries belong to the bucket they start.
+        assert_eq!(bucket_start(1_699_999_200, 3_600), at(1_699_999_200));
+        assert_eq!(bucket_start(1_699_999_199, 3_600), at(1_699_995_600));
+    }
+
+    #[test]
+    fn test_check_timeframes_requires_multiples_of_base() {
+        let base = DEFAULT_BASE_CANDLE_TIMEFRAME_SECS;
+        assert!(check_timeframes(base, &DEFAULT_CANDLE_TIMEFRAMES).is_ok());
+        assert!(check_timeframes(5, &[5, 15, 60, 300]).is_ok());
+        assert!(check_timeframes(60, &[60, 90]).is_err());
+        assert!(check_timeframes(60, &[0]).is_err());
+        assert!(check_timeframes(60, &[2 * MAX_CANDLE_TIMEFRAME_SECS]).is_err());
+        assert!(check_timeframes(0, &[60]).is_err());
+    }
+
+    #[test]
+    fn test_first_trade_of_new_bucket_closes_previous() {
+        let mut tracker = CandleBucketTracker::new();
+
+        assert_eq!(tracker.observe("mint", 60, at(1_200)), None);
+        // Same bucket: still forming
+        assert_eq!(tracker.observe("mint", 60, at(1_200)), None);
+        // Roll over: the 1_200 bucket is final
+        assert_eq!(tracker.observe("mint", 60, at(1_260)), Some(at(1_200)));
+        assert_eq!(tracker.observe("mint", 60, at(1_260)), None);
+    }
+
+    #[test]
+    fn test_late_trade_for_older_bucket_does_not_close() {
+        let mut tracker = CandleBucketTracker::new();
+
+        tracker.observe("mint", 60, at(1_260));
+        assert_eq!(tracker.observe("mint", 60, at(1_200)), None);
+        // The tracker did not move backwards
+        assert_eq!(tracker.observe("mint", 60, at(1_320)), Some(at(1_260)));
+    }
+
+    #[test]
+    fn test_buckets_tracked_per_mint_and_timeframe() {
+        let mut tracker = CandleBucketTracker::new();
+
+        tracker.observe("mint_a", 60, at(1_200));
+        assert_eq!(tracker.observe("mint_b", 60, at(1_260)), None);
+        assert_eq!(tracker.observe("mint_a", 300, at(1_500)), None);
+        assert_eq!(tracker.observe("mint_a", 60, at(1_260)), Some(at(1_200)));
+    }
+
+    #[test]
+    fn test_bucket_after_gap_opens_at_prior_close() {
+        let mut tracker = CandleBucketTracker::new();
+        let mode = CandleOpenMode::PrevClose;
+
+        assert_eq!(tracker.record_trade("mint", 60, at(1_200), 100, mode).open, 100);
+        tracker.record_trade("mint", 60, at(1_200), 110, mode);
+
+        // Three empty buckets, then a trade at 150: the candle opens at the 110 close.
+        let next = tracker.record_trade("mint", 60, at(1_440), 150, mode);
+        assert_eq!(next.open, 110);
+        assert_eq!(next.closed_bucket, Some(at(1_200)));
+
+        // A late trade for the old bucket neither opens at the close nor replaces it.
+        assert_eq!(tracker.record_trade("mint", 60, at(1_200), 90, mode).open, 90);
+        assert_eq!(tracker.record_trade("mint", 60, at(1_500), 160, mode).open, 150);
+    }
+
+    #[test]
+    fn test_first_trade_mode_opens_at_trade_price() {
+        let mut tracker = CandleBucketTracker::new();
+        let mode = CandleOpenMode::FirstTrade;
+
+        tracker.record_trade("mint", 60, at(1_200), 110, mode);
+        assert_eq!(tracker.record_trade("mint", 60, at(1_440), 150, mode).open, 150);
+    }
+
+    fn candle(bucket: i64, price: i64, volume_sol: i64) -> Candle {
+        Candle {
+            mint_pubkey: "mint".to_string(),
+            timeframe_secs: 60,
+            bucket_start: at(bucket),
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume_token: 10,
+            volume_sol,
+            trades_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_candle_events_merge_trades_per_bucket() {
+        let mut events = CandleEvents::new();
+        events.record_update(candle(1_200, 100, 5));
+        events.record_update(candle(1_200, 130, 7));
+        events.record_update(candle(1_200, 90, 1));
+        events.record_closed("mint", 60, at(1_200));
+        events.record_update(candle(1_260, 95, 2));
+        // Other timeframes of the same mint are separate buckets.
+        events.record_update(Candle {
+            timeframe_secs: 300,
+            ..candle(1_200, 100, 5)
+        });
+
+        let events = events.into_events();
+        assert_eq!(events.len(), 4);
+        let CandleEvent::Update(first) = &events[0] else {
+            panic!("expected an update first, got {:?}", events[0]);
+        };
+        assert_eq!(
+            (first.open, first.high, first.low, first.close),
+            (100, 130, 90, 90)
+        );
+        assert_eq!((first.volume_token, first.volume_sol, first.trades_count), (30, 13, 3));
+        assert!(matches!(
+            &events[1],
+            CandleEvent::Closed { bucket_start, .. } if *bucket_start == at(1_200)
+        ));
+        assert!(matches!(&events[2], CandleEvent::Update(c) if c.bucket_start == at(1_260)));
+        assert!(matches!(&events[3], CandleEvent::Update(c) if c.timeframe_secs == 300));
+    }
+}
@@ -1,6 +1,12 @@
  use anyhow::Result;
  use serde::Deserialize;
 
+use crate::candles::{
+    check_timeframes, CandleOpenMode, DEFAULT_BASE_CANDLE_TIMEFRAME_SECS, DEFAULT_CANDLE_TIMEFRAMES,
+};
+use crate::logging::LogFormat;
+use crate::price::PriceScale;
+
  #[derive(Debug, Deserialize, Clone)]
  pub struct RuntimeConfig {
      pub environment: String,
@@ -9,6 +15,48 @@
  #[derive(Debug, Deserialize, Clone)]
  pub struct ApiConfig {
      pub bind_addr: String,
+     /// A candles request returning fewer rows than this triggers a one-off background
+     /// backfill from stored trades for that mint/timeframe. 0 disables the warm-up.
+     #[serde(default)]
+     pub candle_warmup_min_rows: Option<i64>,
+     /// Candle timeframe for requests that omit `timeframe_secs` (default 60). Requests below
+     /// `[candles] base_timeframe_secs` are raised to it.
+     #[serde(default)]
+     pub default_candle_timeframe_secs: Option<i32>,
+     /// Candle rows returned for requests that omit `limit` (default 500).
+     #[serde(default)]
+     pub default_candle_limit: Option<i64>,
+     /// Shared secret for `/admin/*` routes, sent as `Authorization: Bearer <token>`.
+     /// Unset or empty disables the admin routes (every request gets 401).
+     #[serde(default)]
+     pub admin_token: Option<String>,
+     /// Seconds between server pings on `/ws` (default 30).
+     #[serde(default)]
+     pub ws_ping_interval_secs: Option<u64>,
+     /// A `/ws` client that hasn't answered a ping for this many seconds is disconnected
+     /// (default 60).
+     #[serde(default)]
+     pub ws_pong_timeout_secs: Option<u64>,
+     /// Realtime events buffered for `/ws` clients (default 10000). A client that falls further
+     /// behind skips the oldest and is told how many with a `lagged` message.
+     #[serde(default)]
+     pub ws_event_capacity: Option<usize>,
+     /// Disconnect a `/ws` client once it has skipped this many events in total; unset (or 0)
+     /// keeps slow clients connected.
+     #[serde(default)]
+     pub ws_max_missed_events: Option<u64>,
+     /// Sustained requests per second allowed per client IP (default 20, 0 disables the limit).
+     /// `/health` is never limited. Behind a proxy every client shares the proxy's address.
+     #[serde(default)]
+     pub requests_per_sec: Option<u32>,
+     /// Requests a client IP may send at once before the per-second rate applies
+     /// (default 2 × requests_per_sec).
+     #[serde(default)]
+     pub burst: Option<u32>,
+     /// Origins browsers may call the API from, e.g. `["https://dash.example.com"]`; `["*"]`
+     /// allows any. Unset allows any origin unless `runtime.environment` is "production".
+     #[serde(default)]
+     pub allowed_origins: Option<Vec<String>>,
  }
 
  #[derive(Debug, Deserialize, Clone)]
@@ -26,10 +74,18 @@
      pub initial_backoff_ms: Option<u64>,
      #[serde(default)]
      pub max_backoff_ms: Option<u64>,
+     /// When set, every received block is also appended to newline-delimited JSON files
+     /// in this directory so it can be fed back through the writer with `indexer-bin replay`.
+     #[serde(default)]
+     pub cache_dir: Option<String>,
  }
 
  #[derive(Debug, Deserialize, Clone)]
  pub struct RedisConfig {
+     /// Publish every written trade and transfer to Redis streams from indexer-bin (default
+     /// false). Postgres NOTIFY fanout runs either way.
+     #[serde(default)]
+     pub enabled: bool,
      pub host: String,
      pub port: u16,
      pub db: u8,
@@ -38,6 +94,108 @@
      pub max_stream_len: u64,
  }
 
+ #[derive(Debug, Deserialize, Clone, Default)]
+ pub struct RetentionConfig {
+     /// How long rows stay in `indexer_events` before the cleanup job deletes them.
+     #[serde(default)]
+     pub events_retention_secs: Option<u64>,
+     /// How often the cleanup job runs.
+     #[serde(default)]
+     pub cleanup_interval_secs: Option<u64>,
+ }
+
+ #[derive(Debug, Deserialize, Clone, Default)]
+ pub struct MonitorConfig {
+     /// Warn when a venue that had trades records none for this many consecutive minutes.
+     #[serde(default)]
+     pub trade_stall_minutes: Option<u32>,
+    /// Serve the writer's Prometheus metrics on this address (e.g. `0.0.0.0:9100`); unset
+    /// disables them. See `prometheus`.
+    #[serde(default)]
+    pub metrics_bind_addr: Option<String>,
+ }
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct PricingConfig {
+    /// Token decimals assumed when scaling trade prices for mints whose `mints.decimals` is
+    /// still unknown (default 6).
+    #[serde(default)]
+    pub default_token_decimals: Option<u32>,
+}
+
+impl PricingConfig {
+    pub fn price_scale(&self) -> PriceScale {
+        PriceScale::new(self.default_token_decimals)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct CandleConfig {
+    /// Where a new candle opens: `prev_close` (default) or `first_trade`.
+    #[serde(default)]
+    pub open_mode: CandleOpenMode,
+    /// Smallest timeframe (seconds) built, e.g. 5 for sub-minute charts (default 60). Every
+    /// timeframe must be a multiple of it.
+    #[serde(default)]
+    pub base_timeframe_secs: Option<i32>,
+    /// Timeframes (seconds) derived on top of the base; defaults to 1m, 5m, 15m, 1h and 1d.
+    #[serde(default)]
+    pub timeframes_secs: Option<Vec<i32>>,
+}
+
+impl CandleConfig {
+    pub fn base_timeframe(&self) -> i32 {
+        self.base_timeframe_secs.unwrap_or(DEFAULT_BASE_CANDLE_TIMEFRAME_SECS)
+    }
+
+    /// Fail unless every timeframe is a multiple of the base one.
+    pub fn validate(&self) -> Result<()> {
+        let derived = self.timeframes_secs.as_deref().unwrap_or(&DEFAULT_CANDLE_TIMEFRAMES);
+        check_timeframes(self.base_timeframe(), derived)
+    }
+
+    /// The base and configured timeframes, ascending, without duplicates or non-positive
+    /// values.
+    pub fn timeframes(&self) -> Vec<i32> {
+        let mut timeframes: Vec<i32> = match &self.timeframes_secs {
+            Some(configured) => configured.iter().copied().filter(|&tf| tf > 0).collect(),
+            None => DEFAULT_CANDLE_TIMEFRAMES.to_vec(),
+        };
+        timeframes.push(self.base_timeframe());
+        timeframes.sort_unstable();
+        timeframes.dedup();
+        timeframes
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct WriterConfig {
+    /// Buffer parsed blocks until they hold this many transfers, trades and token events, then
+    /// write them in one transaction. 0 (default) writes every block as it arrives.
+    #[serde(default)]
+    pub batch_max_rows: Option<usize>,
+    /// Write a non-empty buffer once its oldest block has waited this long (default 200).
+    #[serde(default)]
+    pub batch_max_ms: Option<u64>,
+    /// Token accounts whose mint is remembered to resolve plain `Transfer`s (default 100000).
+    #[serde(default)]
+    pub token_account_cache_size: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct LogConfig {
+    /// Filter directives (e.g. `info` or `indexer_bin=debug,sqlx=warn`) used when `RUST_LOG`
+    /// is unset (default `info`).
+    #[serde(default)]
+    pub level: Option<String>,
+    /// `pretty` (default) or `json`; `--log-format` overrides it.
+    #[serde(default)]
+    pub format: Option<LogFormat>,
+    /// Colour pretty logs; defaults to whether stdout is a terminal. JSON logs never are.
+    #[serde(default)]
+    pub ansi: Option<bool>,
+}
+
  #[derive(Debug, Deserialize, Clone)]
  pub struct IndexerConfig {
      pub runtime: RuntimeConfig,
@@ -46,6 +204,18 @@
      pub firehose: FirehoseConfig,
      #[serde(default)]
      pub redis: Option<RedisConfig>,
+     #[serde(default)]
+     pub retention: RetentionConfig,
+     #[serde(default)]
+     pub monitor: MonitorConfig,
+    #[serde(default)]
+    pub pricing: PricingConfig,
+    #[serde(default)]
+    pub candles: CandleConfig,
+    #[serde(default)]
+    pub writer: WriterConfig,
+    #[serde(default)]
+    pub log: LogConfig,
  }
 
  impl IndexerConfig {
@@ -58,7 +228,9 @@
             .add_source(config::Environment::with_prefix("INDEXER").separator("__"))
             .build()?;
 
-        settings.try_deserialize().map_err(Into::into)
+        let config: Self = settings.try_deserialize()?;
+        config.candles.validate()?;
+        Ok(config)
      }
  }
 
@@ -0,0 +1,100 @@
+// Opaque page cursors for feeds ordered newest first.
+// A slot holds many transfers and trades, so paging by slot alone duplicates or skips rows at a
+// page boundary. The cursor is the position of the last row a client received:
+// (slot, tx_index, ix_index, inner_index), which is unique per row and matches the feeds' order.
+//
+// Encoding: the four fields little-endian (i64, i32, i32, i32), as unpadded URL-safe base64 so
+// the cursor can go into a query string as is.
+
+use crate::models::{BondingTradeRow, TokenTransfer};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
+const CURSOR_LEN: usize = 20;
+
+/// Position of a row in a feed ordered by (slot, tx_index, ix_index, inner_index) descending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowCursor {
+    pub slot: i64,
+    pub tx_index: i32,
+    pub ix_index: i32,
+    pub inner_index: i32,
+}
+
+impl RowCursor {
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::with_capacity(CURSOR_LEN);
+        bytes.extend_from_slice(&self.slot.to_le_bytes());
+        for v in [self.tx_index, self.ix_index, self.inner_index] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// Parse a cursor produced by [`RowCursor::encode`]. `None` for anything else.
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        if bytes.len() != CURSOR_LEN {
+            return None;
+        }
+        let i32_at = |off: usize| i32::from_le_bytes(bytes[off..off + 4].try_into().unwrap());
+        Some(Self {
+            slot: i64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+            tx_index: i32_at(8),
+            ix_index: i32_at(12),
+            inner_index: i32_at(16),
+        })
+    }
+}
+
+impl From<&TokenTransfer> for RowCursor {
+    fn from(t: &TokenTransfer) -> Self {
+        Self {
+            slot: t.slot,
+            tx_index: t.tx_index,
+            ix_index: t.ix_index,
+            inner_index: t.inner_index,
+        }
+    }
+}
+
+impl From<&BondingTradeRow> for RowCursor {
+    fn from(row: &BondingTradeRow) -> Self {
+        let t = &row.trade;
+        Self {
+            slot: t.slot,
+            tx_index: t.tx_index,
+            ix_index: t.ix_index,
+            inner_index: t.inner_index,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = RowCursor {
+            slot: 312_000_123,
+            tx_index: 7,
+            ix_index: 2,
+            inner_index: -1,
+        };
+
+        let encoded = cursor.encode();
+        assert!(encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(RowCursor::decode(&encoded), Some(cursor));
+    }
+
+    #[test]
+    fn test_decode_rejects_foreign_strings() {
+        assert_eq!(RowCursor::decode(""), None);
+        assert_eq!(RowCursor::decode("not a cursor!"), None);
+        // Valid base64, wrong length.
+        assert_eq!(RowCursor::decode("AAAA"), None);
+    }
+}
@@ -1,6 +1,18 @@
-use crate::models::{Balance, BondingCurveTrade, Candle, Mint, TokenTransfer};
+use crate::bonding_parser::bonding_progress_pct;
+use crate::candles::CandleOpenMode;
+use crate::models::{
+    Balance, BondingCurveTrade, BondingTradeRow, Candle, IndexerLag, Mint, TokenEvent,
+    TokenTransfer, TraderStats, TrendingToken, WalletMintPnl, UNKNOWN_DECIMALS,
+};
+use crate::cursor::RowCursor;
+use crate::spl_parser::{BURN_DEST_OWNER, MINT_SOURCE_OWNER};
 use anyhow::Result;
-use sqlx::{postgres::PgPoolOptions, PgPool, Row};
+use sqlx::{postgres::PgPoolOptions, PgConnection, PgExecutor, PgPool, Postgres, QueryBuilder, Row};
+use std::collections::{BTreeMap, HashMap};
+
+/// Rows per multi-row INSERT. Keeps the widest table (13 columns) well under Postgres' 65535
+/// bind parameter limit.
+const MAX_ROWS_PER_INSERT: usize = 1000;
 
  pub async fn create_pool(database_url: &str, max_connections: u32) -> Result<PgPool> {
      let pool = PgPoolOptions::new()
@@ -17,12 +29,14 @@ pub async fn run_migrations(pool: &PgPool) -> Result<()> {
 }
 
 pub async fn insert_transfers(pool: &PgPool, transfers: &[TokenTransfer]) -> Result<()> {
-     if transfers.is_empty() {
-         return Ok(());
-     }
+    let mut conn = pool.acquire().await?;
+    insert_transfers_in(&mut conn, transfers).await
+}
 
-    for t in transfers {
-        sqlx::query(
+/// Multi-row form of [`insert_transfers`] on a caller-provided connection or transaction.
+pub async fn insert_transfers_in(conn: &mut PgConnection, transfers: &[TokenTransfer]) -> Result<()> {
+    for chunk in transfers.chunks(MAX_ROWS_PER_INSERT) {
+        let mut query = QueryBuilder::<Postgres>::new(
             r#"
             INSERT INTO token_transfers (
                 signature,
@@ -35,149 +49,265 @@ pub async fn insert_transfers(pool: &PgPool, transfers: &[TokenTransfer]) -> Res
                 dest_ata,
                 amount,
                 tx_index,
-                ix_index
-            ) VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11)
-            ON CONFLICT (signature, ix_index) DO NOTHING
-            "#,
-        )
-        .bind(&t.signature)
-        .bind(t.slot)
-        .bind(t.block_time)
-        .bind(&t.mint_pubkey)
-        .bind(&t.source_owner)
-        .bind(&t.dest_owner)
-        .bind(&t.source_ata)
-        .bind(&t.dest_ata)
-        .bind(t.amount)
-        .bind(t.tx_index)
-        .bind(t.ix_index)
-        .execute(pool)
-        .await?;
+                ix_index,
+                inner_index,
+                program
+            ) "#,
+        );
+        query.push_values(chunk, |mut row, t| {
+            row.push_bind(&t.signature)
+                .push_bind(t.slot)
+                .push_bind(t.block_time)
+                .push_bind(&t.mint_pubkey)
+                .push_bind(&t.source_owner)
+                .push_bind(&t.dest_owner)
+                .push_bind(&t.source_ata)
+                .push_bind(&t.dest_ata)
+                .push_bind(t.amount)
+                .push_bind(t.tx_index)
+                .push_bind(t.ix_index)
+                .push_bind(t.inner_index)
+                .push_bind(&t.program);
+        });
+        query.push(" ON CONFLICT (signature, ix_index, inner_index) DO NOTHING");
+        query.build().execute(&mut *conn).await?;
     }
-     Ok(())
- }
+    Ok(())
+}
 
+/// Insert mints or update known ones. A known symbol is kept when the new row has none, known
+/// decimals are kept over [`UNKNOWN_DECIMALS`], and `first_seen_slot` only moves back.
 pub async fn upsert_mints(pool: &PgPool, mints: &[Mint]) -> Result<()> {
-     if mints.is_empty() {
-         return Ok(());
-     }
+    let mut conn = pool.acquire().await?;
+    upsert_mints_in(&mut conn, mints).await
+}
 
+/// Multi-row form of [`upsert_mints`] on a caller-provided connection or transaction. Rows for
+/// the same mint are merged first, since one upsert statement can't touch a row twice.
+pub async fn upsert_mints_in(conn: &mut PgConnection, mints: &[Mint]) -> Result<()> {
+    let mut merged: BTreeMap<&str, Mint> = BTreeMap::new();
     for m in mints {
-        sqlx::query(
+        merged
+            .entry(&m.mint_pubkey)
+            .and_modify(|seen| {
+                seen.symbol = m.symbol.clone().or(seen.symbol.take());
+                if m.decimals != UNKNOWN_DECIMALS {
+                    seen.decimals = m.decimals;
+                }
+                seen.first_seen_slot = seen.first_seen_slot.min(m.first_seen_slot);
+            })
+            .or_insert_with(|| m.clone());
+    }
+
+    let merged: Vec<Mint> = merged.into_values().collect();
+    for chunk in merged.chunks(MAX_ROWS_PER_INSERT) {
+        let mut query = QueryBuilder::<Postgres>::new(
+            "INSERT INTO mints (mint_pubkey, symbol, decimals, first_seen_slot) ",
+        );
+        query.push_values(chunk, |mut row, m| {
+            row.push_bind(&m.mint_pubkey)
+                .push_bind(&m.symbol)
+                .push_bind(m.decimals)
+                .push_bind(m.first_seen_slot);
+        });
+        // UNKNOWN_DECIMALS is the only negative value stored.
+        query.push(
             r#"
-            INSERT INTO mints (mint_pubkey, symbol, decimals, first_seen_slot)
-            VALUES ($1,$2,$3,$4)
             ON CONFLICT (mint_pubkey) DO UPDATE
             SET symbol = COALESCE(EXCLUDED.symbol, mints.symbol),
-                decimals = EXCLUDED.decimals,
+                decimals = CASE WHEN EXCLUDED.decimals < 0 THEN mints.decimals ELSE EXCLUDED.decimals END,
                 first_seen_slot = LEAST(mints.first_seen_slot, EXCLUDED.first_seen_slot)
             "#,
-        )
-        .bind(&m.mint_pubkey)
-        .bind(&m.symbol)
-        .bind(m.decimals)
-        .bind(m.first_seen_slot)
-        .execute(pool)
-        .await?;
-     }
-     Ok(())
- }
+        );
+        query.build().execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+/// Mints whose known decimals differ from `default_decimals`, as `(mint, decimals)`: all a
+/// writer's [`PriceScale`](crate::price::PriceScale) needs to price their trades at startup.
+pub async fn get_non_default_mint_decimals(
+    pool: &PgPool,
+    default_decimals: i32,
+) -> Result<Vec<(String, i32)>> {
+    let rows = sqlx::query_as::<_, (String, i32)>(
+        "SELECT mint_pubkey, decimals FROM mints WHERE decimals >= 0 AND decimals <> $1",
+    )
+    .bind(default_decimals)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Record that each mint traded off its bonding curve at the paired slot, keeping the earliest
+/// slot per mint in `mints.migrated_slot`. The mints must already be stored.
+pub async fn mark_mints_migrated_in(
+    conn: &mut PgConnection,
+    migrated: &[(&str, i64)],
+) -> Result<()> {
+    let mut earliest: BTreeMap<&str, i64> = BTreeMap::new();
+    for &(mint_pubkey, slot) in migrated {
+        earliest
+            .entry(mint_pubkey)
+            .and_modify(|seen| *seen = (*seen).min(slot))
+            .or_insert(slot);
+    }
+
+    let earliest: Vec<_> = earliest.into_iter().collect();
+    for chunk in earliest.chunks(MAX_ROWS_PER_INSERT) {
+        let mut query = QueryBuilder::<Postgres>::new(
+            "UPDATE mints SET migrated_slot = LEAST(mints.migrated_slot, v.slot) FROM (",
+        );
+        query.push_values(chunk, |mut row, (mint_pubkey, slot)| {
+            row.push_bind(*mint_pubkey).push_bind(*slot);
+        });
+        query.push(") AS v (mint_pubkey, slot) WHERE mints.mint_pubkey = v.mint_pubkey");
+        query.build().execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+/// Mints whose symbol (case-insensitively) or pubkey starts with `query`. Exact symbol matches
+/// come first, then symbols in order; `%` and `_` in `query` match themselves.
+pub async fn search_mints(pool: &PgPool, query: &str, limit: i64) -> Result<Vec<Mint>> {
+    let prefix = query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    let rows = sqlx::query_as::<_, Mint>(
+        r#"
+        SELECT mint_pubkey, symbol, decimals, first_seen_slot
+        FROM mints
+        WHERE symbol ILIKE $1 || '%' OR mint_pubkey LIKE $1 || '%'
+        ORDER BY
+            (LOWER(symbol) = LOWER($2)) DESC NULLS LAST,
+            symbol NULLS LAST,
+            mint_pubkey
+        LIMIT $3
+        "#,
+    )
+    .bind(prefix)
+    .bind(query)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
 
 pub async fn update_balances_for_transfers(pool: &PgPool, transfers: &[TokenTransfer]) -> Result<()> {
-     if transfers.is_empty() {
-         return Ok(());
-     }
-
-     for t in transfers {
-         // source wallet loses amount
-         apply_delta(pool, &t.source_owner, &t.mint_pubkey, -t.amount).await?;
-         // dest wallet gains amount
-         apply_delta(pool, &t.dest_owner, &t.mint_pubkey, t.amount).await?;
-     }
-     Ok(())
- }
+    let mut conn = pool.acquire().await?;
+    update_balances_for_transfers_in(&mut conn, transfers).await
+}
 
- async fn apply_delta(
-    pool: &PgPool,
-     wallet: &str,
-     mint_pubkey: &str,
-    delta: i64,
- ) -> Result<()> {
-    sqlx::query(
+/// [`update_balances_for_transfers`] on a caller-provided connection or transaction. Deltas are
+/// summed per (wallet, mint) first, since one upsert statement can't touch a row twice. The
+/// MintTo source and Burn destination sentinels get no balance, so they don't count as holders.
+pub async fn update_balances_for_transfers_in(
+    conn: &mut PgConnection,
+    transfers: &[TokenTransfer],
+) -> Result<()> {
+    // Sorted keys give concurrent writers a consistent lock order.
+    let mut deltas: BTreeMap<(&str, &str), i64> = BTreeMap::new();
+    for t in transfers {
+        // source wallet loses amount, dest wallet gains it
+        if t.source_owner != MINT_SOURCE_OWNER {
+            *deltas.entry((&t.source_owner, &t.mint_pubkey)).or_default() -= t.amount;
+        }
+        if t.dest_owner != BURN_DEST_OWNER {
+            *deltas.entry((&t.dest_owner, &t.mint_pubkey)).or_default() += t.amount;
+        }
+    }
+
+    let deltas: Vec<_> = deltas.into_iter().collect();
+    for chunk in deltas.chunks(MAX_ROWS_PER_INSERT) {
+        let mut query = QueryBuilder::<Postgres>::new("INSERT INTO balances (wallet, mint_pubkey, amount) ");
+        query.push_values(chunk, |mut row, ((wallet, mint_pubkey), delta)| {
+            row.push_bind(*wallet).push_bind(*mint_pubkey).push_bind(*delta);
+        });
+        query.push(
+            r#"
+            ON CONFLICT (wallet, mint_pubkey)
+            DO UPDATE SET amount = balances.amount + EXCLUDED.amount
+            "#,
+        );
+        query.build().execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+/// Rebuild every balance of `mint_pubkey` from its stored transfers, for repairing balances that
+/// drifted (e.g. written before the sentinel owners were skipped). Returns the number of balance
+/// rows written.
+pub async fn recompute_balances_for_mint(pool: &PgPool, mint_pubkey: &str) -> Result<u64> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM balances WHERE mint_pubkey = $1")
+        .bind(mint_pubkey)
+        .execute(&mut *tx)
+        .await?;
+    let result = sqlx::query(
         r#"
         INSERT INTO balances (wallet, mint_pubkey, amount)
-        VALUES ($1,$2,$3)
-        ON CONFLICT (wallet, mint_pubkey)
-        DO UPDATE SET amount = balances.amount + EXCLUDED.amount
+        SELECT wallet, $1, SUM(delta)::BIGINT
+        FROM (
+            SELECT dest_owner AS wallet, amount AS delta
+            FROM token_transfers WHERE mint_pubkey = $1 AND dest_owner <> $3
+            UNION ALL
+            SELECT source_owner AS wallet, -amount AS delta
+            FROM token_transfers WHERE mint_pubkey = $1 AND source_owner <> $2
+        ) deltas
+        GROUP BY wallet
         "#,
     )
-    .bind(wallet)
     .bind(mint_pubkey)
-    .bind(delta)
-    .execute(pool)
+    .bind(MINT_SOURCE_OWNER)
+    .bind(BURN_DEST_OWNER)
+    .execute(&mut *tx)
     .await?;
-     Ok(())
- }
+    tx.commit().await?;
+
+    Ok(result.rows_affected())
+}
 
+/// Transfers of `mint_pubkey`, newest first. `after` continues from the last row of a previous
+/// page; `before_slot` only keeps rows below that slot.
 pub async fn get_token_transfers_for_mint(
     pool: &PgPool,
     mint_pubkey: &str,
     limit: i64,
     before_slot: Option<i64>,
+    after: Option<&RowCursor>,
 ) -> Result<Vec<TokenTransfer>> {
-    let rows = if let Some(before) = before_slot {
-        sqlx::query_as::<_, TokenTransfer>(
-            r#"
-            SELECT
-                signature,
-                slot,
-                block_time,
-                mint_pubkey,
-                source_owner,
-                dest_owner,
-                source_ata,
-                dest_ata,
-                amount,
-                tx_index,
-                ix_index
-            FROM token_transfers
-            WHERE mint_pubkey = $1
-              AND slot < $2
-            ORDER BY slot DESC
-            LIMIT $3
-            "#,
-        )
-        .bind(mint_pubkey)
-        .bind(before)
-        .bind(limit)
-        .fetch_all(pool)
-        .await?
-    } else {
-        sqlx::query_as::<_, TokenTransfer>(
-            r#"
-            SELECT
-                signature,
-                slot,
-                block_time,
-                mint_pubkey,
-                source_owner,
-                dest_owner,
-                source_ata,
-                dest_ata,
-                amount,
-                tx_index,
-                ix_index
-            FROM token_transfers
-            WHERE mint_pubkey = $1
-            ORDER BY slot DESC
-            LIMIT $2
-            "#,
-        )
-        .bind(mint_pubkey)
-        .bind(limit)
-        .fetch_all(pool)
-        .await?
-    };
+    let rows = sqlx::query_as::<_, TokenTransfer>(
+        r#"
+        SELECT
+            signature,
+            slot,
+            block_time,
+            mint_pubkey,
+            source_owner,
+            dest_owner,
+            source_ata,
+            dest_ata,
+            amount,
+            tx_index,
+            ix_index,
+            inner_index,
+            program
+        FROM token_transfers
+        WHERE mint_pubkey = $1
+          AND ($2::BIGINT IS NULL OR slot < $2)
+          AND ($3::BIGINT IS NULL OR (slot, tx_index, ix_index, inner_index) < ($3, $4, $5, $6))
+        ORDER BY slot DESC, tx_index DESC, ix_index DESC, inner_index DESC
+        LIMIT $7
+        "#,
+    )
+    .bind(mint_pubkey)
+    .bind(before_slot)
+    .bind(after.map(|c| c.slot))
+    .bind(after.map(|c| c.tx_index))
+    .bind(after.map(|c| c.ix_index))
+    .bind(after.map(|c| c.inner_index))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
 
     Ok(rows)
 }
@@ -209,6 +339,22 @@ pub async fn get_balances_for_mint(
     Ok(rows)
 }
 
+/// Number of wallets holding a positive balance of `mint_pubkey`. Zero and negative balances
+/// (e.g. wallets with missed inbound transfers) are not holders.
+pub async fn get_holder_count(pool: &PgPool, mint_pubkey: &str) -> Result<i64> {
+    let count = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM balances
+        WHERE mint_pubkey = $1 AND amount > 0
+        "#,
+    )
+    .bind(mint_pubkey)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count)
+}
+
 pub async fn get_portfolio_for_wallet(pool: &PgPool, wallet: &str) -> Result<Vec<Balance>> {
     let rows = sqlx::query_as::<_, Balance>(
         r#"
@@ -263,13 +409,35 @@ pub async fn insert_event(
     Ok(())
 }
 
+/// Delete `indexer_events` rows older than `retention_secs`.
+/// Returns the number of rows removed.
+pub async fn delete_events_older_than(pool: &PgPool, retention_secs: i64) -> Result<u64> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM indexer_events
+        WHERE created_at < now() - make_interval(secs => $1)
+        "#,
+    )
+    .bind(retention_secs as f64)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
 pub async fn insert_bonding_curve_trades(pool: &PgPool, trades: &[BondingCurveTrade]) -> Result<()> {
-    if trades.is_empty() {
-        return Ok(());
-    }
+    let mut conn = pool.acquire().await?;
+    insert_bonding_curve_trades_in(&mut conn, trades).await
+}
 
-    for t in trades {
-        sqlx::query(
+/// Multi-row form of [`insert_bonding_curve_trades`] on a caller-provided connection or
+/// transaction. Also lowers each trader's `wallet_first_seen` slot.
+pub async fn insert_bonding_curve_trades_in(
+    conn: &mut PgConnection,
+    trades: &[BondingCurveTrade],
+) -> Result<()> {
+    for chunk in trades.chunks(MAX_ROWS_PER_INSERT) {
+        let mut query = QueryBuilder::<Postgres>::new(
             r#"
             INSERT INTO bonding_curve_trades (
                 signature,
@@ -282,29 +450,160 @@ pub async fn insert_bonding_curve_trades(pool: &PgPool, trades: &[BondingCurveTr
                 sol_amount,
                 price_nanos_per_token,
                 tx_index,
-                ix_index
-            ) VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11)
-            ON CONFLICT (signature, ix_index) DO NOTHING
+                ix_index,
+                inner_index
+            ) "#,
+        );
+        query.push_values(chunk, |mut row, t| {
+            row.push_bind(&t.signature)
+                .push_bind(t.slot)
+                .push_bind(t.block_time)
+                .push_bind(&t.mint_pubkey)
+                .push_bind(&t.trader)
+                .push_bind(&t.side)
+                .push_bind(t.token_amount)
+                .push_bind(t.sol_amount)
+                .push_bind(t.price_nanos_per_token)
+                .push_bind(t.tx_index)
+                .push_bind(t.ix_index)
+                .push_bind(t.inner_index);
+        });
+        query.push(" ON CONFLICT (signature, ix_index, inner_index) DO NOTHING");
+        query.build().execute(&mut *conn).await?;
+    }
+
+    let mut first_seen: BTreeMap<&str, i64> = BTreeMap::new();
+    for t in trades {
+        first_seen
+            .entry(&t.trader)
+            .and_modify(|slot| *slot = (*slot).min(t.slot))
+            .or_insert(t.slot);
+    }
+    let first_seen: Vec<_> = first_seen.into_iter().collect();
+    for chunk in first_seen.chunks(MAX_ROWS_PER_INSERT) {
+        let mut query =
+            QueryBuilder::<Postgres>::new("INSERT INTO wallet_first_seen (wallet, first_seen_slot) ");
+        query.push_values(chunk, |mut row, (wallet, slot)| {
+            row.push_bind(*wallet).push_bind(*slot);
+        });
+        query.push(
+            r#"
+            ON CONFLICT (wallet) DO UPDATE SET first_seen_slot = EXCLUDED.first_seen_slot
+            WHERE EXCLUDED.first_seen_slot < wallet_first_seen.first_seen_slot
             "#,
-        )
-        .bind(&t.signature)
-        .bind(t.slot)
-        .bind(t.block_time)
-        .bind(&t.mint_pubkey)
-        .bind(&t.trader)
-        .bind(&t.side)
-        .bind(t.token_amount)
-        .bind(t.sol_amount)
-        .bind(t.price_nanos_per_token)
-        .bind(t.tx_index)
-        .bind(t.ix_index)
-        .execute(pool)
-        .await?;
+        );
+        query.build().execute(&mut *conn).await?;
+    }
+
+    Ok(())
+}
+
+/// First slot each of `wallets` was seen trading in. Wallets without trades are absent.
+pub async fn get_wallet_first_seen_slots(
+    pool: &PgPool,
+    wallets: &[String],
+) -> Result<HashMap<String, i64>> {
+    let rows = sqlx::query_as::<_, (String, i64)>(
+        r#"
+        SELECT wallet, first_seen_slot FROM wallet_first_seen
+        WHERE wallet = ANY($1)
+        "#,
+    )
+    .bind(wallets)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// Insert safety events, skipping ones without a resolved mint or whose mint is not indexed
+/// (approvals and closes happen on every token; only tracked mints matter).
+pub async fn insert_token_events(pool: &PgPool, events: &[TokenEvent]) -> Result<()> {
+    let mut conn = pool.acquire().await?;
+    insert_token_events_in(&mut conn, events).await
+}
+
+/// Multi-row form of [`insert_token_events`] on a caller-provided connection or transaction.
+pub async fn insert_token_events_in(conn: &mut PgConnection, events: &[TokenEvent]) -> Result<()> {
+    let resolved: Vec<(&TokenEvent, &String)> = events
+        .iter()
+        .filter_map(|e| Some((e, e.mint_pubkey.as_ref()?)))
+        .collect();
+
+    for chunk in resolved.chunks(MAX_ROWS_PER_INSERT) {
+        let mut query = QueryBuilder::<Postgres>::new(
+            r#"
+            INSERT INTO token_events (
+                signature,
+                slot,
+                block_time,
+                mint_pubkey,
+                kind,
+                token_account,
+                authority,
+                counterparty,
+                amount,
+                tx_index,
+                ix_index,
+                inner_index
+            )
+            SELECT * FROM ("#,
+        );
+        query.push_values(chunk, |mut row, (e, mint)| {
+            row.push_bind(&e.signature)
+                .push_bind(e.slot)
+                .push_bind(e.block_time)
+                .push_bind(*mint)
+                .push_bind(&e.kind)
+                .push_bind(&e.token_account)
+                .push_bind(&e.authority)
+                .push_bind(&e.counterparty)
+                .push_bind(e.amount)
+                .push_bind(e.tx_index)
+                .push_bind(e.ix_index)
+                .push_bind(e.inner_index);
+        });
+        query.push(
+            r#") AS v (
+                signature, slot, block_time, mint_pubkey, kind, token_account, authority,
+                counterparty, amount, tx_index, ix_index, inner_index
+            )
+            WHERE EXISTS (SELECT 1 FROM mints WHERE mints.mint_pubkey = v.mint_pubkey)
+            ON CONFLICT (signature, ix_index, inner_index) DO NOTHING
+            "#,
+        );
+        query.build().execute(&mut *conn).await?;
     }
 
     Ok(())
 }
 
+/// Mint of each of `token_accounts`, from the most recent stored transfer touching it.
+/// Accounts that never appeared in a transfer are absent.
+pub async fn get_token_account_mints<'e>(
+    executor: impl PgExecutor<'e>,
+    token_accounts: &[String],
+) -> Result<HashMap<String, String>> {
+    let rows = sqlx::query_as::<_, (String, String)>(
+        r#"
+        SELECT DISTINCT ON (account) account, mint_pubkey
+        FROM (
+            SELECT source_ata AS account, mint_pubkey, slot FROM token_transfers
+            WHERE source_ata = ANY($1)
+            UNION ALL
+            SELECT dest_ata AS account, mint_pubkey, slot FROM token_transfers
+            WHERE dest_ata = ANY($1)
+        ) touched
+        ORDER BY account, slot DESC
+        "#,
+    )
+    .bind(token_accounts)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
 pub async fn upsert_candle(
     pool: &PgPool,
     candle: &Candle,
@@ -415,82 +714,546 @@ pub async fn get_candles(
     Ok(rows)
 }
 
-pub async fn get_bonding_trades_for_mint(
+/// Latest `limit` candles per mint for several mints in one query, newest first within each mint.
+pub async fn get_candles_for_mints(
     pool: &PgPool,
-    mint_pubkey: &str,
+    mint_pubkeys: &[String],
+    timeframe_secs: i32,
     limit: i64,
-    before_slot: Option<i64>,
-) -> Result<Vec<BondingCurveTrade>> {
-    let rows = if let Some(before) = before_slot {
-        sqlx::query_as::<_, BondingCurveTrade>(
-            r#"
-            SELECT
-                signature,
-                slot,
-                block_time,
-                mint_pubkey,
-                trader,
-                side,
-                token_amount,
-                sol_amount,
-                price_nanos_per_token,
-                tx_index,
-                ix_index
-            FROM bonding_curve_trades
-            WHERE mint_pubkey = $1
-              AND slot < $2
-            ORDER BY slot DESC
-            LIMIT $3
-            "#,
-        )
-        .bind(mint_pubkey)
-        .bind(before)
-        .bind(limit)
-        .fetch_all(pool)
-        .await?
-    } else {
-        sqlx::query_as::<_, BondingCurveTrade>(
-            r#"
-            SELECT
-                signature,
-                slot,
-                block_time,
-                mint_pubkey,
-                trader,
-                side,
-                token_amount,
-                sol_amount,
-                price_nanos_per_token,
-                tx_index,
-                ix_index
-            FROM bonding_curve_trades
-            WHERE mint_pubkey = $1
-            ORDER BY slot DESC
-            LIMIT $2
-            "#,
-        )
-        .bind(mint_pubkey)
-        .bind(limit)
-        .fetch_all(pool)
-        .await?
-    };
-
-    Ok(rows)
-}
-
- pub async fn get_last_processed_slot(pool: &PgPool) -> Result<Option<i64>> {
-    let rec = sqlx::query(
+) -> Result<Vec<Candle>> {
+    let rows = sqlx::query_as::<_, Candle>(
         r#"
-        SELECT slot FROM last_processed_slot
-        WHERE id = 1
+        SELECT
+            mint_pubkey,
+            timeframe_secs,
+            bucket_start,
+            open,
+            high,
+            low,
+            close,
+            volume_token,
+            volume_sol,
+            trades_count
+        FROM (
+            SELECT
+                c.*,
+                ROW_NUMBER() OVER (PARTITION BY mint_pubkey ORDER BY bucket_start DESC) AS rn
+            FROM candles c
+            WHERE mint_pubkey = ANY($1)
+              AND timeframe_secs = $2
+        ) ranked
+        WHERE rn <= $3
+        ORDER BY mint_pubkey, bucket_start DESC
         "#,
     )
-    .fetch_optional(pool)
+    .bind(mint_pubkeys)
+    .bind(timeframe_secs)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Smallest candle timeframe stored for a mint, if it has any candles.
+pub async fn get_min_candle_timeframe(pool: &PgPool, mint_pubkey: &str) -> Result<Option<i32>> {
+    let tf: Option<i32> =
+        sqlx::query_scalar("SELECT MIN(timeframe_secs) FROM candles WHERE mint_pubkey = $1")
+            .bind(mint_pubkey)
+            .fetch_one(pool)
+            .await?;
+
+    Ok(tf)
+}
+
+/// Latest `limit` candles of `target_tf` seconds for a mint, rolled up on the fly from its
+/// stored `base_tf` candles, newest first. Buckets are aligned to the Unix epoch like stored
+/// candles; a bucket with no base candles is skipped rather than filled.
+/// Only base candles within `limit` target buckets of the newest one are scanned, so a gap in
+/// trading can return fewer than `limit` candles.
+/// `target_tf` must be a positive multiple of `base_tf`.
+pub async fn get_ohlcv_rollup(
+    pool: &PgPool,
+    mint_pubkey: &str,
+    base_tf: i32,
+    target_tf: i32,
+    limit: i64,
+) -> Result<Vec<Candle>> {
+    if base_tf <= 0 || target_tf <= 0 || target_tf % base_tf != 0 {
+        anyhow::bail!("target timeframe {target_tf}s is not a multiple of {base_tf}s");
+    }
+
+    let rows = sqlx::query_as::<_, Candle>(
+        r#"
+        SELECT
+            $1::TEXT AS mint_pubkey,
+            $3::INT AS timeframe_secs,
+            bucket AS bucket_start,
+            (ARRAY_AGG(open ORDER BY bucket_start))[1] AS open,
+            MAX(high) AS high,
+            MIN(low) AS low,
+            (ARRAY_AGG(close ORDER BY bucket_start DESC))[1] AS close,
+            SUM(volume_token)::BIGINT AS volume_token,
+            SUM(volume_sol)::BIGINT AS volume_sol,
+            SUM(trades_count)::INT AS trades_count
+        FROM (
+            SELECT
+                c.*,
+                date_bin(make_interval(secs => $3::INT), bucket_start, TIMESTAMPTZ 'epoch') AS bucket
+            FROM candles c
+            WHERE mint_pubkey = $1
+              AND timeframe_secs = $2
+              AND bucket_start >= (
+                  SELECT date_bin(
+                          make_interval(secs => $3::INT), MAX(bucket_start), TIMESTAMPTZ 'epoch'
+                      )
+                      - make_interval(secs => $3::INT) * ($4 - 1)::DOUBLE PRECISION
+                  FROM candles
+                  WHERE mint_pubkey = $1
+                    AND timeframe_secs = $2
+              )
+        ) binned
+        GROUP BY bucket
+        ORDER BY bucket DESC
+        LIMIT $4
+        "#,
+    )
+    .bind(mint_pubkey)
+    .bind(base_tf)
+    .bind(target_tf)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Rebuild candles for a mint from its stored trades.
+/// Buckets that already have a candle are left alone, so this never double-counts trades the
+/// writer has already aggregated. Each bucket opens per `open_mode`, like the writer's: under
+/// `PrevClose` at the close of the previous bucket with trades (the first bucket opens at its
+/// first trade), with high and low widened to take in that open. Returns the number of candles
+/// inserted.
+pub async fn backfill_candles<'e>(
+    executor: impl PgExecutor<'e>,
+    mint_pubkey: &str,
+    timeframe_secs: i32,
+    open_mode: CandleOpenMode,
+) -> Result<u64> {
+    let result = sqlx::query(
+        r#"
+        WITH buckets AS (
+            SELECT
+                bucket_start,
+                (array_agg(price_nanos_per_token ORDER BY slot, tx_index, ix_index, inner_index))[1]
+                    AS first_price,
+                MAX(price_nanos_per_token) AS high,
+                MIN(price_nanos_per_token) AS low,
+                (array_agg(price_nanos_per_token ORDER BY slot DESC, tx_index DESC, ix_index DESC, inner_index DESC))[1]
+                    AS close,
+                SUM(token_amount)::BIGINT AS volume_token,
+                SUM(sol_amount)::BIGINT AS volume_sol,
+                COUNT(*)::INT AS trades_count
+            FROM (
+                SELECT
+                    *,
+                    to_timestamp((floor(extract(epoch FROM block_time) / $2) * $2)::DOUBLE PRECISION)
+                        AS bucket_start
+                FROM bonding_curve_trades
+                WHERE mint_pubkey = $1
+                  AND block_time IS NOT NULL
+            ) t
+            GROUP BY bucket_start
+        ),
+        opened AS (
+            SELECT
+                *,
+                CASE WHEN $3
+                    THEN COALESCE(LAG(close) OVER (ORDER BY bucket_start), first_price)
+                    ELSE first_price
+                END AS open
+            FROM buckets
+        )
+        INSERT INTO candles (
+            mint_pubkey,
+            timeframe_secs,
+            bucket_start,
+            open,
+            high,
+            low,
+            close,
+            volume_token,
+            volume_sol,
+            trades_count
+        )
+        SELECT
+            $1,
+            $2,
+            bucket_start,
+            open,
+            GREATEST(high, open),
+            LEAST(low, open),
+            close,
+            volume_token,
+            volume_sol,
+            trades_count
+        FROM opened
+        ON CONFLICT (mint_pubkey, timeframe_secs, bucket_start) DO NOTHING
+        "#,
+    )
+    .bind(mint_pubkey)
+    .bind(timeframe_secs)
+    .bind(open_mode == CandleOpenMode::PrevClose)
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Rebuild the candle series queued in `candle_rebuilds` (by migration 0013, which rescaled the
+/// trade prices) from their trades, opening per `open_mode` (see [`backfill_candles`]), and
+/// empty the queue. Returns the number of candles inserted.
+pub async fn rebuild_queued_candles(pool: &PgPool, open_mode: CandleOpenMode) -> Result<u64> {
+    let mut tx = pool.begin().await?;
+    let series: Vec<(String, i32)> =
+        sqlx::query_as("DELETE FROM candle_rebuilds RETURNING mint_pubkey, timeframe_secs")
+            .fetch_all(&mut *tx)
+            .await?;
+
+    let mut inserted = 0;
+    for (mint_pubkey, timeframe_secs) in &series {
+        inserted += backfill_candles(&mut *tx, mint_pubkey, *timeframe_secs, open_mode).await?;
+    }
+    tx.commit().await?;
+
+    Ok(inserted)
+}
+
+/// Unique traders and net buyers for `mint_pubkey` over the last `window_secs`, from
+/// bonding-curve trades. Each trader's buys and sells are netted in SOL; traders who come out
+/// exactly flat count as traders but not toward `net_buyers`. An empty window yields zeros.
+pub async fn get_trader_stats(pool: &PgPool, mint_pubkey: &str, window_secs: i64) -> Result<TraderStats> {
+    // Only 'buy' and 'sell' rows count. A Raydium swap whose side was guessed from its amount
+    // ratio (see `infer_swap_direction_raydium`) and got it wrong has its SOL and token amounts
+    // swapped, which puts its price orders of magnitude off the rest; such outliers would
+    // dominate the volumes, so trades priced over 100x away from the window's median are left
+    // out.
+    let stats = sqlx::query_as::<_, TraderStats>(
+        r#"
+        WITH windowed AS (
+            SELECT trader, side, sol_amount, price_nanos_per_token
+            FROM bonding_curve_trades
+            WHERE mint_pubkey = $1
+              AND block_time >= now() - make_interval(secs => $2)
+              AND side IN ('buy', 'sell')
+        ),
+        median AS (
+            SELECT percentile_cont(0.5) WITHIN GROUP (ORDER BY price_nanos_per_token) AS price
+            FROM windowed
+        ),
+        priced AS (
+            SELECT w.*
+            FROM windowed w, median m
+            WHERE m.price = 0
+               OR w.price_nanos_per_token BETWEEN m.price / 100 AND m.price * 100
+        ),
+        per_trader AS (
+            SELECT
+                trader,
+                SUM(CASE WHEN side = 'buy' THEN sol_amount ELSE -sol_amount END) AS net_sol
+            FROM priced
+            GROUP BY trader
+        ),
+        volumes AS (
+            SELECT
+                COUNT(DISTINCT trader) FILTER (WHERE side = 'buy') AS buyers,
+                COUNT(DISTINCT trader) FILTER (WHERE side = 'sell') AS sellers,
+                COALESCE(SUM(sol_amount) FILTER (WHERE side = 'buy'), 0)::BIGINT AS buy_volume_sol,
+                COALESCE(SUM(sol_amount) FILTER (WHERE side = 'sell'), 0)::BIGINT AS sell_volume_sol
+            FROM priced
+        )
+        SELECT
+            COUNT(p.trader) AS unique_traders,
+            COUNT(p.trader) FILTER (WHERE p.net_sol > 0)
+                - COUNT(p.trader) FILTER (WHERE p.net_sol < 0) AS net_buyers,
+            v.buyers,
+            v.sellers,
+            v.buy_volume_sol,
+            v.sell_volume_sol,
+            v.buy_volume_sol - v.sell_volume_sol AS net_volume_sol
+        FROM volumes v
+        LEFT JOIN per_trader p ON true
+        GROUP BY v.buyers, v.sellers, v.buy_volume_sol, v.sell_volume_sol
+        "#,
+    )
+    .bind(mint_pubkey)
+    .bind(window_secs as f64)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(stats)
+}
+
+/// The `limit` mints with the most SOL traded over the last `window_secs`, busiest first.
+pub async fn get_trending_tokens(
+    pool: &PgPool,
+    window_secs: i64,
+    limit: i64,
+) -> Result<Vec<TrendingToken>> {
+    let rows = sqlx::query_as::<_, TrendingToken>(
+        r#"
+        SELECT
+            t.mint_pubkey,
+            m.symbol,
+            SUM(t.sol_amount)::BIGINT AS volume_sol,
+            COUNT(*) AS trades_count,
+            (
+                SELECT l.price_nanos_per_token FROM bonding_curve_trades l
+                WHERE l.mint_pubkey = t.mint_pubkey
+                ORDER BY l.slot DESC, l.tx_index DESC, l.ix_index DESC, l.inner_index DESC
+                LIMIT 1
+            ) AS last_price_nanos_per_token
+        FROM bonding_curve_trades t
+        LEFT JOIN mints m ON m.mint_pubkey = t.mint_pubkey
+        WHERE t.block_time >= now() - make_interval(secs => $1)
+        GROUP BY t.mint_pubkey, m.symbol
+        ORDER BY volume_sol DESC, trades_count DESC, t.mint_pubkey
+        LIMIT $2
+        "#,
+    )
+    .bind(window_secs as f64)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Buy/sell totals of `wallet` per traded mint, with each mint's latest trade price.
+pub async fn get_pnl_for_wallet(pool: &PgPool, wallet: &str) -> Result<Vec<WalletMintPnl>> {
+    let rows = sqlx::query_as::<_, WalletMintPnl>(
+        r#"
+        SELECT
+            t.mint_pubkey,
+            m.decimals,
+            COALESCE(SUM(t.token_amount) FILTER (WHERE t.side = 'buy'), 0)::BIGINT AS tokens_bought,
+            COALESCE(SUM(t.sol_amount) FILTER (WHERE t.side = 'buy'), 0)::BIGINT AS sol_spent,
+            COALESCE(SUM(t.token_amount) FILTER (WHERE t.side = 'sell'), 0)::BIGINT AS tokens_sold,
+            COALESCE(SUM(t.sol_amount) FILTER (WHERE t.side = 'sell'), 0)::BIGINT AS sol_received,
+            (
+                SELECT l.price_nanos_per_token FROM bonding_curve_trades l
+                WHERE l.mint_pubkey = t.mint_pubkey
+                ORDER BY l.slot DESC, l.tx_index DESC, l.ix_index DESC, l.inner_index DESC
+                LIMIT 1
+            ) AS last_price_nanos_per_token
+        FROM bonding_curve_trades t
+        JOIN mints m ON m.mint_pubkey = t.mint_pubkey
+        WHERE t.trader = $1
+        GROUP BY t.mint_pubkey, m.decimals
+        ORDER BY t.mint_pubkey
+        "#,
+    )
+    .bind(wallet)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Bonding-curve progress of `mint_pubkey` in percent (0-100), from net SOL bought into the
+/// curve over all stored trades. See [`bonding_progress_pct`] for the graduation rule; a mint
+/// that has traded off the curve (see [`mark_mints_migrated_in`]) is always at 100.
+pub async fn get_bonding_progress(pool: &PgPool, mint_pubkey: &str) -> Result<f64> {
+    let row = sqlx::query(
+        r#"
+        WITH running AS (
+            SELECT
+                slot,
+                tx_index,
+                ix_index,
+                inner_index,
+                SUM(CASE WHEN side = 'buy' THEN sol_amount ELSE -sol_amount END)
+                    OVER (ORDER BY slot, tx_index, ix_index, inner_index) AS net_sol
+            FROM bonding_curve_trades
+            WHERE mint_pubkey = $1
+        )
+        SELECT
+            COALESCE((
+                SELECT net_sol FROM running
+                ORDER BY slot DESC, tx_index DESC, ix_index DESC, inner_index DESC
+                LIMIT 1
+            ), 0)::BIGINT AS net_sol,
+            COALESCE((SELECT MAX(net_sol) FROM running), 0)::BIGINT AS peak_net_sol,
+            EXISTS (
+                SELECT 1 FROM mints WHERE mint_pubkey = $1 AND migrated_slot IS NOT NULL
+            ) AS migrated
+        "#,
+    )
+    .bind(mint_pubkey)
+    .fetch_one(pool)
+    .await?;
+
+    if row.get::<bool, _>("migrated") {
+        return Ok(100.0);
+    }
+    Ok(bonding_progress_pct(row.get("net_sol"), row.get("peak_net_sol")))
+}
+
+/// Trades of `mint_pubkey`, newest first, paged like [`get_token_transfers_for_mint`] and
+/// optionally limited to trades of at least `min_sol` lamports or `min_token` units.
+pub async fn get_bonding_trades_for_mint(
+    pool: &PgPool,
+    mint_pubkey: &str,
+    limit: i64,
+    before_slot: Option<i64>,
+    after: Option<&RowCursor>,
+    min_sol: Option<i64>,
+    min_token: Option<i64>,
+) -> Result<Vec<BondingTradeRow>> {
+    let rows = sqlx::query_as::<_, BondingTradeRow>(
+        r#"
+        SELECT
+            signature,
+            slot,
+            block_time,
+            mint_pubkey,
+            trader,
+            side,
+            token_amount,
+            sol_amount,
+            price_nanos_per_token,
+            tx_index,
+            ix_index,
+            inner_index,
+            (w.first_seen_slot IS NOT NULL AND slot <= w.first_seen_slot) AS is_new_wallet
+        FROM bonding_curve_trades
+        LEFT JOIN wallet_first_seen w ON w.wallet = trader
+        WHERE mint_pubkey = $1
+          AND ($2::BIGINT IS NULL OR slot < $2)
+          AND ($3::BIGINT IS NULL OR (slot, tx_index, ix_index, inner_index) < ($3, $4, $5, $6))
+          AND ($7::BIGINT IS NULL OR sol_amount >= $7)
+          AND ($8::BIGINT IS NULL OR token_amount >= $8)
+        ORDER BY slot DESC, tx_index DESC, ix_index DESC, inner_index DESC
+        LIMIT $9
+        "#,
+    )
+    .bind(mint_pubkey)
+    .bind(before_slot)
+    .bind(after.map(|c| c.slot))
+    .bind(after.map(|c| c.tx_index))
+    .bind(after.map(|c| c.ix_index))
+    .bind(after.map(|c| c.inner_index))
+    .bind(min_sol)
+    .bind(min_token)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Number of safety events per kind for `mint_pubkey`. Kinds that never occurred are absent.
+pub async fn get_token_event_counts(pool: &PgPool, mint_pubkey: &str) -> Result<Vec<(String, i64)>> {
+    let rows = sqlx::query_as::<_, (String, i64)>(
+        r#"
+        SELECT kind, COUNT(*) FROM token_events
+        WHERE mint_pubkey = $1
+        GROUP BY kind
+        ORDER BY kind
+        "#,
+    )
+    .bind(mint_pubkey)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Most recent safety events for `mint_pubkey`, newest first.
+pub async fn get_token_events_for_mint(
+    pool: &PgPool,
+    mint_pubkey: &str,
+    limit: i64,
+) -> Result<Vec<TokenEvent>> {
+    let rows = sqlx::query_as::<_, TokenEvent>(
+        r#"
+        SELECT
+            signature,
+            slot,
+            block_time,
+            mint_pubkey,
+            kind,
+            token_account,
+            authority,
+            counterparty,
+            amount,
+            tx_index,
+            ix_index,
+            inner_index
+        FROM token_events
+        WHERE mint_pubkey = $1
+        ORDER BY slot DESC, tx_index DESC, ix_index DESC, inner_index DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(mint_pubkey)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+ pub async fn get_last_processed_slot(pool: &PgPool) -> Result<Option<i64>> {
+    let rec = sqlx::query(
+        r#"
+        SELECT slot FROM last_processed_slot
+        WHERE id = 1
+        "#,
+    )
+    .fetch_optional(pool)
     .await?;
 
     Ok(rec.map(|row| row.get::<i64, _>("slot")))
  }
 
+/// Average slot time, for estimating the chain tip from a past slot.
+const SLOT_MILLIS: i64 = 400;
+
+/// How far behind the chain the indexer is, from the newest trade with a block time. `None`
+/// until such a trade is stored.
+pub async fn get_indexer_lag(pool: &PgPool) -> Result<Option<IndexerLag>> {
+    let row = sqlx::query(
+        r#"
+        WITH latest AS (
+            SELECT slot, GREATEST(EXTRACT(EPOCH FROM now() - block_time), 0) AS lag
+            FROM bonding_curve_trades
+            WHERE block_time IS NOT NULL
+            ORDER BY block_time DESC
+            LIMIT 1
+        )
+        SELECT
+            lag::BIGINT AS lag_secs,
+            slot + (lag * 1000 / $1)::BIGINT AS estimated_tip_slot,
+            (SELECT slot FROM last_processed_slot WHERE id = 1) AS last_processed_slot
+        FROM latest
+        "#,
+    )
+    .bind(SLOT_MILLIS)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| {
+        let estimated_tip_slot: i64 = row.get("estimated_tip_slot");
+        let last_processed_slot: Option<i64> = row.get("last_processed_slot");
+        IndexerLag {
+            lag_secs: row.get("lag_secs"),
+            estimated_tip_slot,
+            slots_behind: last_processed_slot.map(|slot| estimated_tip_slot - slot),
+        }
+    }))
+}
+
  pub async fn set_last_processed_slot(pool: &PgPool, slot: i64) -> Result<()> {
     sqlx::query(
         r#"
@@ -505,3 +1268,1107 @@ pub async fn get_bonding_trades_for_mint(
      Ok(())
  }
 
+/// Roll the indexed chain back to before `slot`, for a fork or replay that revisits it: deletes
+/// transfers, token events and trades at `slot` or later, reverses their balance deltas, recomputes
+/// the affected `wallet_first_seen` slots and rewinds `last_processed_slot` to `slot - 1`.
+/// Every candle from the bucket of a mint's earliest removed trade on is rebuilt from the
+/// remaining trades, opening per `open_mode` (see [`backfill_candles`]), so re-indexing the
+/// range doesn't count its trades twice. Returns the number of rows deleted, candles not
+/// included.
+pub async fn delete_data_for_slots_gte(
+    pool: &PgPool,
+    slot: i64,
+    open_mode: CandleOpenMode,
+) -> Result<u64> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        UPDATE balances b
+        SET amount = b.amount - d.delta
+        FROM (
+            SELECT wallet, mint_pubkey, SUM(delta)::BIGINT AS delta
+            FROM (
+                SELECT dest_owner AS wallet, mint_pubkey, amount AS delta
+                FROM token_transfers WHERE slot >= $1
+                UNION ALL
+                SELECT source_owner AS wallet, mint_pubkey, -amount AS delta
+                FROM token_transfers WHERE slot >= $1
+            ) deltas
+            GROUP BY wallet, mint_pubkey
+        ) d
+        WHERE b.wallet = d.wallet AND b.mint_pubkey = d.mint_pubkey
+        "#,
+    )
+    .bind(slot)
+    .execute(&mut *tx)
+    .await?;
+
+    // Per mint, the time of the earliest trade about to be removed.
+    let removed_from: Vec<(String, chrono::DateTime<chrono::Utc>)> = sqlx::query_as(
+        r#"
+        SELECT mint_pubkey, MIN(block_time)
+        FROM bonding_curve_trades
+        WHERE slot >= $1 AND block_time IS NOT NULL
+        GROUP BY mint_pubkey
+        "#,
+    )
+    .bind(slot)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut deleted = 0;
+    for table in ["token_transfers", "token_events", "bonding_curve_trades"] {
+        deleted += sqlx::query(&format!("DELETE FROM {table} WHERE slot >= $1"))
+            .bind(slot)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    }
+
+    // Wallets first seen in a removed slot fall back to their earliest remaining trade, if any.
+    sqlx::query(
+        r#"
+        UPDATE wallet_first_seen w
+        SET first_seen_slot = t.first_seen_slot
+        FROM (
+            SELECT trader, MIN(slot) AS first_seen_slot
+            FROM bonding_curve_trades
+            WHERE trader IN (SELECT wallet FROM wallet_first_seen WHERE first_seen_slot >= $1)
+            GROUP BY trader
+        ) t
+        WHERE w.wallet = t.trader
+        "#,
+    )
+    .bind(slot)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM wallet_first_seen WHERE first_seen_slot >= $1")
+        .bind(slot)
+        .execute(&mut *tx)
+        .await?;
+
+    for (mint_pubkey, from) in &removed_from {
+        // The candles whose bucket ends after `from`, i.e. holds it or starts later.
+        let timeframes: Vec<i32> = sqlx::query_scalar(
+            r#"
+            WITH removed AS (
+                DELETE FROM candles
+                WHERE mint_pubkey = $1
+                  AND bucket_start + make_interval(secs => timeframe_secs) > $2
+                RETURNING timeframe_secs
+            )
+            SELECT DISTINCT timeframe_secs FROM removed
+            "#,
+        )
+        .bind(mint_pubkey)
+        .bind(from)
+        .fetch_all(&mut *tx)
+        .await?;
+        for timeframe_secs in timeframes {
+            backfill_candles(&mut *tx, mint_pubkey, timeframe_secs, open_mode).await?;
+        }
+    }
+
+    sqlx::query("UPDATE last_processed_slot SET slot = $1 - 1 WHERE id = 1 AND slot >= $1")
+        .bind(slot)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(deleted)
+}
+
+/// Add `misses` to the running parse-miss counter for `parser` (e.g. "pump").
+pub async fn add_parse_misses(pool: &PgPool, parser: &str, misses: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO parser_stats (parser, parse_misses, updated_at)
+        VALUES ($1, $2, now())
+        ON CONFLICT (parser) DO UPDATE SET
+            parse_misses = parser_stats.parse_misses + EXCLUDED.parse_misses,
+            updated_at = now()
+        "#,
+    )
+    .bind(parser)
+    .bind(misses)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Parse-miss counters per parser. Parsers that never missed are absent.
+pub async fn get_parse_misses(pool: &PgPool) -> Result<Vec<(String, i64)>> {
+    let rows = sqlx::query_as::<_, (String, i64)>(
+        r#"
+        SELECT parser, parse_misses FROM parser_stats
+        WHERE parse_misses > 0
+        ORDER BY parser
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Add `bad_indices` to the running out-of-range account index counter for `parser`.
+pub async fn add_bad_indices(pool: &PgPool, parser: &str, bad_indices: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO parser_stats (parser, bad_indices, updated_at)
+        VALUES ($1, $2, now())
+        ON CONFLICT (parser) DO UPDATE SET
+            bad_indices = parser_stats.bad_indices + EXCLUDED.bad_indices,
+            updated_at = now()
+        "#,
+    )
+    .bind(parser)
+    .bind(bad_indices)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Out-of-range account index counters per parser. Parsers that never saw one are absent.
+pub async fn get_bad_indices(pool: &PgPool) -> Result<Vec<(String, i64)>> {
+    let rows = sqlx::query_as::<_, (String, i64)>(
+        r#"
+        SELECT parser, bad_indices FROM parser_stats
+        WHERE bad_indices > 0
+        ORDER BY parser
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+
+#[cfg(all(test, db_tests))]
+mod tests {
+    use super::*;
+    use crate::spl_parser::TOP_LEVEL_INNER_INDEX;
+
+    const MINT: &str = "TestMint1111111111111111111111111111111111";
+
+    async fn seed_mint(pool: &PgPool) {
+        upsert_mints(
+            pool,
+            &[Mint {
+                mint_pubkey: MINT.to_string(),
+                symbol: None,
+                decimals: 6,
+                first_seen_slot: 0,
+            }],
+        )
+        .await
+        .unwrap();
+    }
+
+    fn trade(signature: &str, slot: i64, sol_amount: i64, token_amount: i64) -> BondingCurveTrade {
+        BondingCurveTrade {
+            signature: signature.to_string(),
+            slot,
+            block_time: None,
+            mint_pubkey: MINT.to_string(),
+            trader: "trader".to_string(),
+            side: "buy".to_string(),
+            token_amount,
+            sol_amount,
+            price_nanos_per_token: sol_amount.checked_div(token_amount).unwrap_or(0),
+            tx_index: 0,
+            ix_index: 0,
+            inner_index: TOP_LEVEL_INNER_INDEX,
+        }
+    }
+
+    fn transfer(signature: &str, slot: i64, source: &str, dest: &str, amount: i64) -> TokenTransfer {
+        TokenTransfer {
+            signature: signature.to_string(),
+            slot,
+            block_time: None,
+            mint_pubkey: MINT.to_string(),
+            source_owner: source.to_string(),
+            dest_owner: dest.to_string(),
+            source_ata: format!("{source}_ata"),
+            dest_ata: format!("{dest}_ata"),
+            amount,
+            tx_index: 0,
+            ix_index: 0,
+            inner_index: TOP_LEVEL_INNER_INDEX,
+            program: crate::spl_parser::SPL_TOKEN_PROGRAM_ID.to_string(),
+        }
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_insert_500_transfers_in_batches(pool: PgPool) {
+        seed_mint(&pool).await;
+        let wallets: Vec<String> = (0..10).map(|w| format!("wallet{w}")).collect();
+        let transfers: Vec<TokenTransfer> = (0..500)
+            .map(|i| {
+                let (source, dest) = (&wallets[i % 10], &wallets[(i * 3 + 1) % 10]);
+                transfer(&format!("sig{i}"), 100 + i as i64, source, dest, i as i64 + 1)
+            })
+            .collect();
+        let mut expected: HashMap<String, i64> = HashMap::new();
+        for t in &transfers {
+            *expected.entry(t.source_owner.clone()).or_default() -= t.amount;
+            *expected.entry(t.dest_owner.clone()).or_default() += t.amount;
+        }
+
+        let mut tx = pool.begin().await.unwrap();
+        insert_transfers_in(&mut tx, &transfers).await.unwrap();
+        update_balances_for_transfers_in(&mut tx, &transfers).await.unwrap();
+        tx.commit().await.unwrap();
+        // Replayed rows are skipped.
+        insert_transfers(&pool, &transfers[..50]).await.unwrap();
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM token_transfers")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 500);
+        let balances: HashMap<String, i64> = get_balances_for_mint(&pool, MINT, 100, 0)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|b| (b.wallet, b.amount))
+            .collect();
+        assert_eq!(balances, expected);
+    }
+
+    async fn all_balances(pool: &PgPool) -> HashMap<String, i64> {
+        sqlx::query_as::<_, (String, i64)>("SELECT wallet, amount FROM balances")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_mint_then_transfer_leaves_no_negative_balances(pool: PgPool) {
+        seed_mint(&pool).await;
+        let transfers = [
+            transfer("mint_to", 100, MINT_SOURCE_OWNER, "alice", 1_000),
+            transfer("xfer", 101, "alice", "bob", 300),
+            transfer("burn", 102, "bob", BURN_DEST_OWNER, 100),
+        ];
+        insert_transfers(&pool, &transfers).await.unwrap();
+        update_balances_for_transfers(&pool, &transfers).await.unwrap();
+
+        let expected =
+            HashMap::from([("alice".to_string(), 700), ("bob".to_string(), 200)]);
+        assert_eq!(all_balances(&pool).await, expected);
+
+        // Drifted rows, like those written before sentinels were skipped, are repaired.
+        sqlx::query("UPDATE balances SET amount = -5 WHERE wallet = 'bob'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO balances (wallet, mint_pubkey, amount) VALUES ('system', $1, -1000)")
+            .bind(MINT)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(recompute_balances_for_mint(&pool, MINT).await.unwrap(), 2);
+        assert_eq!(all_balances(&pool).await, expected);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_delete_data_for_slots_gte_rolls_back_later_slots(pool: PgPool) {
+        seed_mint(&pool).await;
+        let slots = 100..=105;
+        let transfers: Vec<TokenTransfer> = slots
+            .clone()
+            .map(|slot| transfer(&format!("xfer{slot}"), slot, "alice", "bob", slot))
+            .collect();
+        let trades: Vec<BondingCurveTrade> = slots
+            .map(|slot| trade(&format!("trade{slot}"), slot, 1_000, 10))
+            .collect();
+        insert_transfers(&pool, &transfers).await.unwrap();
+        update_balances_for_transfers(&pool, &transfers).await.unwrap();
+        insert_bonding_curve_trades(&pool, &trades).await.unwrap();
+        // A trader only seen in the rolled-back slots is forgotten.
+        insert_bonding_curve_trades(&pool, &[BondingCurveTrade {
+            trader: "late".to_string(),
+            ..trade("late_trade", 104, 1_000, 10)
+        }])
+        .await
+        .unwrap();
+        set_last_processed_slot(&pool, 105).await.unwrap();
+
+        let deleted = delete_data_for_slots_gte(&pool, 103, CandleOpenMode::PrevClose).await.unwrap();
+        assert_eq!(deleted, 3 + 3 + 1);
+
+        let transfer_slots: Vec<i64> =
+            sqlx::query_scalar("SELECT slot FROM token_transfers ORDER BY slot")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(transfer_slots, vec![100, 101, 102]);
+        let trade_slots: Vec<i64> =
+            sqlx::query_scalar("SELECT slot FROM bonding_curve_trades ORDER BY slot")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(trade_slots, vec![100, 101, 102]);
+
+        let balances: HashMap<String, i64> = get_balances_for_mint(&pool, MINT, 10, 0)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|b| (b.wallet, b.amount))
+            .collect();
+        assert_eq!(balances["alice"], -303);
+        assert_eq!(balances["bob"], 303);
+
+        let first_seen =
+            get_wallet_first_seen_slots(&pool, &["trader".to_string(), "late".to_string()])
+                .await
+                .unwrap();
+        assert_eq!(first_seen, HashMap::from([("trader".to_string(), 100)]));
+        assert_eq!(get_last_processed_slot(&pool).await.unwrap(), Some(102));
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_inner_instructions_under_same_outer_index_both_persist(pool: PgPool) {
+        seed_mint(&pool).await;
+        // Two CPI swaps invoked by the same top-level instruction 2.
+        let inner = |inner_index: i32, sol: i64| BondingCurveTrade {
+            ix_index: 2,
+            inner_index,
+            ..trade("router_sig", 100, sol, 1_000)
+        };
+        let trades = [inner(0, 5_000), inner(1, 7_000)];
+
+        insert_bonding_curve_trades(&pool, &trades).await.unwrap();
+        // Replaying the block must still dedup.
+        insert_bonding_curve_trades(&pool, &trades).await.unwrap();
+
+        let rows: Vec<(i32, i32, i64)> = sqlx::query_as(
+            "SELECT ix_index, inner_index, sol_amount FROM bonding_curve_trades ORDER BY inner_index",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(rows, vec![(2, 0, 5_000), (2, 1, 7_000)]);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_pnl_for_wallet_totals_buys_and_sells(pool: PgPool) {
+        seed_mint(&pool).await;
+        let t = |sig: &str, slot: i64, trader: &str, side: &str, sol: i64, tokens: i64| {
+            BondingCurveTrade {
+                trader: trader.to_string(),
+                side: side.to_string(),
+                ..trade(sig, slot, sol, tokens)
+            }
+        };
+        insert_bonding_curve_trades(
+            &pool,
+            &[
+                t("a1", 100, "alice", "buy", 4_000, 1_000),
+                t("a2", 101, "alice", "buy", 6_000, 1_000),
+                t("a3", 102, "alice", "sell", 3_000, 500),
+                // Another trader sets the latest price
+                t("b1", 103, "bob", "buy", 9_000, 1_000),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let pnl = get_pnl_for_wallet(&pool, "alice").await.unwrap();
+        assert_eq!(
+            pnl,
+            vec![WalletMintPnl {
+                mint_pubkey: MINT.to_string(),
+                decimals: 6,
+                tokens_bought: 2_000,
+                sol_spent: 10_000,
+                tokens_sold: 500,
+                sol_received: 3_000,
+                last_price_nanos_per_token: Some(9),
+            }]
+        );
+        assert!(get_pnl_for_wallet(&pool, "nobody").await.unwrap().is_empty());
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_bonding_progress_from_net_sol(pool: PgPool) {
+        use crate::bonding_parser::PUMP_GRADUATION_LAMPORTS;
+
+        seed_mint(&pool).await;
+        assert_eq!(get_bonding_progress(&pool, MINT).await.unwrap(), 0.0);
+
+        let quarter = PUMP_GRADUATION_LAMPORTS / 4;
+        let t = |sig: &str, slot: i64, side: &str, sol: i64| BondingCurveTrade {
+            side: side.to_string(),
+            ..trade(sig, slot, sol, 1_000)
+        };
+        insert_bonding_curve_trades(
+            &pool,
+            &[t("b1", 100, "buy", 2 * quarter), t("s1", 101, "sell", quarter)],
+        )
+        .await
+        .unwrap();
+        assert_eq!(get_bonding_progress(&pool, MINT).await.unwrap(), 25.0);
+
+        // Curve completes, then post-migration selling drains the net total again.
+        insert_bonding_curve_trades(
+            &pool,
+            &[t("b2", 102, "buy", 3 * quarter), t("s2", 103, "sell", 4 * quarter)],
+        )
+        .await
+        .unwrap();
+        assert_eq!(get_bonding_progress(&pool, MINT).await.unwrap(), 100.0);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_indexer_lag_from_newest_trade(pool: PgPool) {
+        seed_mint(&pool).await;
+        insert_bonding_curve_trades(&pool, &[trade("no_time", 900, 1, 1)]).await.unwrap();
+        assert_eq!(get_indexer_lag(&pool).await.unwrap(), None);
+
+        let now = chrono::Utc::now();
+        let at = |sig: &str, slot: i64, secs_ago: i64| BondingCurveTrade {
+            block_time: Some(now - chrono::Duration::seconds(secs_ago)),
+            ..trade(sig, slot, 1, 1)
+        };
+        insert_bonding_curve_trades(&pool, &[at("old", 100, 600), at("new", 500, 30)])
+            .await
+            .unwrap();
+        set_last_processed_slot(&pool, 520).await.unwrap();
+
+        let lag = get_indexer_lag(&pool).await.unwrap().unwrap();
+        assert!((30..=31).contains(&lag.lag_secs), "{lag:?}");
+        // 30s at 400ms a slot after the newest trade's slot
+        assert!((575..=578).contains(&lag.estimated_tip_slot), "{lag:?}");
+        assert_eq!(lag.slots_behind, Some(lag.estimated_tip_slot - 520));
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_bonding_progress_of_migrated_mint(pool: PgPool) {
+        seed_mint(&pool).await;
+        insert_bonding_curve_trades(&pool, &[trade("b1", 100, 1_000_000_000, 1_000)])
+            .await
+            .unwrap();
+        assert!(get_bonding_progress(&pool, MINT).await.unwrap() < 100.0);
+
+        let mut conn = pool.acquire().await.unwrap();
+        mark_mints_migrated_in(&mut conn, &[(MINT, 300), (MINT, 200)]).await.unwrap();
+        mark_mints_migrated_in(&mut conn, &[(MINT, 250)]).await.unwrap();
+        let migrated_slot: Option<i64> =
+            sqlx::query_scalar("SELECT migrated_slot FROM mints WHERE mint_pubkey = $1")
+                .bind(MINT)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(migrated_slot, Some(200));
+        assert_eq!(get_bonding_progress(&pool, MINT).await.unwrap(), 100.0);
+    }
+
+    async fn insert_event_aged(pool: &PgPool, topic: &str, age_secs: i64) {
+        sqlx::query(
+            r#"
+            INSERT INTO indexer_events (created_at, topic, payload)
+            VALUES (now() - make_interval(secs => $1), $2, '{}'::jsonb)
+            "#,
+        )
+        .bind(age_secs as f64)
+        .bind(topic)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_delete_events_older_than_keeps_recent_rows(pool: PgPool) {
+        insert_event_aged(&pool, "old", 7_200).await;
+        insert_event_aged(&pool, "recent", 60).await;
+
+        let deleted = delete_events_older_than(&pool, 3_600).await.unwrap();
+        assert_eq!(deleted, 1);
+
+        let topics: Vec<String> = sqlx::query_scalar("SELECT topic FROM indexer_events")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(topics, vec!["recent".to_string()]);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_trader_stats_nets_buyers_against_sellers(pool: PgPool) {
+        seed_mint(&pool).await;
+        let now = chrono::Utc::now();
+        let t = |sig: &str, trader: &str, side: &str, sol: i64, age_secs: i64| BondingCurveTrade {
+            trader: trader.to_string(),
+            side: side.to_string(),
+            block_time: Some(now - chrono::Duration::seconds(age_secs)),
+            ..trade(sig, 100, sol, 1_000)
+        };
+        insert_bonding_curve_trades(
+            &pool,
+            &[
+                t("a1", "alice", "buy", 5_000, 60),
+                t("a2", "alice", "sell", 2_000, 30),
+                t("b1", "bob", "buy", 1_000, 60),
+                t("b2", "bob", "sell", 3_000, 30),
+                t("c1", "carol", "buy", 4_000, 10),
+                t("e1", "erin", "buy", 1_000, 20),
+                t("e2", "erin", "sell", 1_000, 10),
+                // Outside the window
+                t("d1", "dave", "buy", 9_000, 2 * 86_400),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let stats = get_trader_stats(&pool, MINT, 86_400).await.unwrap();
+        // alice and carol net-bought, bob net-sold, erin is flat
+        assert_eq!((stats.unique_traders, stats.net_buyers), (4, 1));
+
+        let empty = get_trader_stats(&pool, "NoTradesMint", 86_400).await.unwrap();
+        assert_eq!(
+            empty,
+            TraderStats {
+                unique_traders: 0,
+                net_buyers: 0,
+                buyers: 0,
+                sellers: 0,
+                buy_volume_sol: 0,
+                sell_volume_sol: 0,
+                net_volume_sol: 0,
+            }
+        );
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_trader_stats_net_volume_and_buyer_counts(pool: PgPool) {
+        seed_mint(&pool).await;
+        let now = chrono::Utc::now();
+        let t = |sig: &str, trader: &str, side: &str, sol: i64, tokens: i64| BondingCurveTrade {
+            trader: trader.to_string(),
+            side: side.to_string(),
+            block_time: Some(now - chrono::Duration::seconds(60)),
+            ..trade(sig, 100, sol, tokens)
+        };
+        insert_bonding_curve_trades(
+            &pool,
+            &[
+                t("a1", "alice", "buy", 4_000_000, 1_000),
+                t("a2", "alice", "buy", 2_000_000, 500),
+                t("b1", "bob", "buy", 1_000_000, 250),
+                t("b2", "bob", "sell", 3_000_000, 750),
+                t("c1", "carol", "sell", 500_000, 125),
+                // Neither side: ignored
+                t("x1", "xavier", "swap", 9_000_000, 2_250),
+                // A buy misread as a sell: the token amount landed in sol_amount, and vice versa
+                t("m1", "mallory", "sell", 2_000_000_000, 250),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let stats = get_trader_stats(&pool, MINT, 86_400).await.unwrap();
+        assert_eq!(
+            stats,
+            TraderStats {
+                unique_traders: 3,
+                // alice net-bought; bob (-2M) and carol net-sold
+                net_buyers: -1,
+                buyers: 2,
+                sellers: 2,
+                buy_volume_sol: 7_000_000,
+                sell_volume_sol: 3_500_000,
+                net_volume_sol: 3_500_000,
+            }
+        );
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_bonding_trades_size_filters(pool: PgPool) {
+        seed_mint(&pool).await;
+        insert_bonding_curve_trades(
+            &pool,
+            &[
+                trade("dust", 100, 1_000, 10),
+                trade("whale", 101, 50_000_000_000, 1_000_000_000),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let all = get_bonding_trades_for_mint(&pool, MINT, 10, None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+
+        let by_sol = get_bonding_trades_for_mint(&pool, MINT, 10, None, None, Some(1_000_000_000), None)
+            .await
+            .unwrap();
+        assert_eq!(by_sol.len(), 1);
+        assert_eq!(by_sol[0].trade.signature, "whale");
+
+        let by_token = get_bonding_trades_for_mint(&pool, MINT, 10, None, None, None, Some(1_000))
+            .await
+            .unwrap();
+        assert_eq!(by_token.len(), 1);
+        assert_eq!(by_token[0].trade.signature, "whale");
+
+        let with_cursor =
+            get_bonding_trades_for_mint(&pool, MINT, 10, Some(101), None, Some(1_000_000_000), None)
+                .await
+                .unwrap();
+        assert!(with_cursor.is_empty());
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_upsert_mints_keeps_known_decimals(pool: PgPool) {
+        let mint = |decimals: i32, first_seen_slot: i64| Mint {
+            mint_pubkey: MINT.to_string(),
+            symbol: None,
+            decimals,
+            first_seen_slot,
+        };
+        let stored = |pool: PgPool| async move {
+            sqlx::query_as::<_, (i32, i64)>("SELECT decimals, first_seen_slot FROM mints")
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        };
+
+        upsert_mints(&pool, &[mint(UNKNOWN_DECIMALS, 200)]).await.unwrap();
+        assert_eq!(stored(pool.clone()).await, (UNKNOWN_DECIMALS, 200));
+        // Known decimals in the same batch win over unknown ones, whatever the order.
+        upsert_mints(&pool, &[mint(9, 150), mint(UNKNOWN_DECIMALS, 100)]).await.unwrap();
+        assert_eq!(stored(pool.clone()).await, (9, 100));
+        upsert_mints(&pool, &[mint(UNKNOWN_DECIMALS, 300)]).await.unwrap();
+        assert_eq!(stored(pool).await, (9, 100));
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_search_mints_by_symbol_or_pubkey_prefix(pool: PgPool) {
+        let mint = |pubkey: &str, symbol: Option<&str>| Mint {
+            mint_pubkey: pubkey.to_string(),
+            symbol: symbol.map(str::to_string),
+            decimals: 6,
+            first_seen_slot: 0,
+        };
+        upsert_mints(
+            &pool,
+            &[
+                mint("Mint1", Some("BONKERS")),
+                mint("Mint2", Some("BONK")),
+                mint("Mint3", Some("B_NK")),
+                mint("Mint4", Some("WIF")),
+                mint("BonkPubkey", None),
+            ],
+        )
+        .await
+        .unwrap();
+        let symbols = |mints: Vec<Mint>| -> Vec<String> {
+            mints.into_iter().map(|m| m.symbol.unwrap_or(m.mint_pubkey)).collect()
+        };
+
+        let found = search_mints(&pool, "bon", 10).await.unwrap();
+        assert_eq!(symbols(found), vec!["BONK", "BONKERS"]);
+        // An exact symbol ranks first; `_` is not a wildcard.
+        assert_eq!(symbols(search_mints(&pool, "b_nk", 10).await.unwrap()), vec!["B_NK"]);
+        assert_eq!(symbols(search_mints(&pool, "Bonk", 1).await.unwrap()), vec!["BONK"]);
+        assert_eq!(symbols(search_mints(&pool, "BonkP", 10).await.unwrap()), vec!["BonkPubkey"]);
+        assert!(search_mints(&pool, "%", 10).await.unwrap().is_empty());
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_trending_tokens_rank_recent_volume(pool: PgPool) {
+        const QUIET_MINT: &str = "QuietMint11111111111111111111111111111111";
+        seed_mint(&pool).await;
+        upsert_mints(
+            &pool,
+            &[Mint {
+                mint_pubkey: QUIET_MINT.to_string(),
+                symbol: Some("QUIET".to_string()),
+                decimals: 6,
+                first_seen_slot: 0,
+            }],
+        )
+        .await
+        .unwrap();
+        let now = Some(chrono::Utc::now());
+        let trades = [
+            // A whale trade from two days ago is outside the window.
+            BondingCurveTrade {
+                block_time: Some(chrono::Utc::now() - chrono::Duration::days(2)),
+                ..trade("old", 100, 50_000_000_000, 1_000)
+            },
+            BondingCurveTrade { block_time: now, ..trade("a1", 101, 2_000, 10) },
+            BondingCurveTrade { block_time: now, ..trade("a2", 102, 3_000, 10) },
+            BondingCurveTrade {
+                block_time: now,
+                mint_pubkey: QUIET_MINT.to_string(),
+                ..trade("q1", 103, 1_000, 10)
+            },
+        ];
+        insert_bonding_curve_trades(&pool, &trades).await.unwrap();
+
+        let trending = get_trending_tokens(&pool, 3_600, 10).await.unwrap();
+
+        assert_eq!(
+            trending,
+            vec![
+                TrendingToken {
+                    mint_pubkey: MINT.to_string(),
+                    symbol: None,
+                    volume_sol: 5_000,
+                    trades_count: 2,
+                    last_price_nanos_per_token: 300,
+                },
+                TrendingToken {
+                    mint_pubkey: QUIET_MINT.to_string(),
+                    symbol: Some("QUIET".to_string()),
+                    volume_sol: 1_000,
+                    trades_count: 1,
+                    last_price_nanos_per_token: 100,
+                },
+            ]
+        );
+        assert_eq!(get_trending_tokens(&pool, 3_600, 1).await.unwrap().len(), 1);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_new_wallet_is_first_trade_in_any_mint(pool: PgPool) {
+        const OTHER_MINT: &str = "OtherMint111111111111111111111111111111111";
+        seed_mint(&pool).await;
+        upsert_mints(
+            &pool,
+            &[Mint {
+                mint_pubkey: OTHER_MINT.to_string(),
+                symbol: None,
+                decimals: 6,
+                first_seen_slot: 0,
+            }],
+        )
+        .await
+        .unwrap();
+        let t = |sig: &str, slot: i64, trader: &str, mint: &str| BondingCurveTrade {
+            trader: trader.to_string(),
+            mint_pubkey: mint.to_string(),
+            ..trade(sig, slot, 1_000, 10)
+        };
+        insert_bonding_curve_trades(
+            &pool,
+            &[
+                t("alice_other", 90, "alice", OTHER_MINT),
+                t("alice_1", 100, "alice", MINT),
+                t("bob_1", 100, "bob", MINT),
+                t("bob_2", 105, "bob", MINT),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let is_new = |rows: &[BondingTradeRow], sig: &str| {
+            rows.iter()
+                .find(|r| r.trade.signature == sig)
+                .map(|r| r.is_new_wallet)
+                .unwrap()
+        };
+        let rows = get_bonding_trades_for_mint(&pool, MINT, 10, None, None, None, None)
+            .await
+            .unwrap();
+        // alice already traded another mint at slot 90
+        assert!(!is_new(&rows, "alice_1"));
+        assert!(is_new(&rows, "bob_1"));
+        assert!(!is_new(&rows, "bob_2"));
+
+        // Replaying an older block moves bob's first trade back.
+        insert_bonding_curve_trades(&pool, &[t("bob_0", 80, "bob", OTHER_MINT)])
+            .await
+            .unwrap();
+        let rows = get_bonding_trades_for_mint(&pool, MINT, 10, None, None, None, None)
+            .await
+            .unwrap();
+        assert!(!is_new(&rows, "bob_1"));
+        assert_eq!(
+            get_wallet_first_seen_slots(&pool, &["alice".to_string(), "carol".to_string()])
+                .await
+                .unwrap(),
+            HashMap::from([("alice".to_string(), 90)])
+        );
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_token_events_resolve_and_count(pool: PgPool) {
+        use crate::spl_parser::{EVENT_APPROVE, EVENT_CLOSE_ACCOUNT, TOKEN_2022_PROGRAM_ID};
+
+        seed_mint(&pool).await;
+        let transfer = TokenTransfer {
+            signature: "t1".to_string(),
+            slot: 50,
+            block_time: None,
+            mint_pubkey: MINT.to_string(),
+            source_owner: "alice".to_string(),
+            dest_owner: "bob".to_string(),
+            source_ata: "alice_ata".to_string(),
+            dest_ata: "bob_ata".to_string(),
+            amount: 10,
+            tx_index: 0,
+            ix_index: 0,
+            inner_index: TOP_LEVEL_INNER_INDEX,
+            program: TOKEN_2022_PROGRAM_ID.to_string(),
+        };
+        insert_transfers(&pool, &[transfer]).await.unwrap();
+        let stored = get_token_transfers_for_mint(&pool, MINT, 10, None, None).await.unwrap();
+        assert_eq!(stored[0].program, TOKEN_2022_PROGRAM_ID);
+
+        let resolved = get_token_account_mints(
+            &pool,
+            &["bob_ata".to_string(), "stranger_ata".to_string()],
+        )
+        .await
+        .unwrap();
+        assert_eq!(resolved, HashMap::from([("bob_ata".to_string(), MINT.to_string())]));
+
+        let event = |sig: &str, slot: i64, kind: &str, mint: Option<&str>| TokenEvent {
+            signature: sig.to_string(),
+            slot,
+            block_time: None,
+            mint_pubkey: mint.map(str::to_string),
+            kind: kind.to_string(),
+            token_account: "bob_ata".to_string(),
+            authority: "bob".to_string(),
+            counterparty: Some("drainer".to_string()),
+            amount: Some(1),
+            tx_index: 0,
+            ix_index: 0,
+            inner_index: TOP_LEVEL_INNER_INDEX,
+        };
+        let events = [
+            event("e1", 60, EVENT_APPROVE, Some(MINT)),
+            event("e2", 61, EVENT_APPROVE, Some(MINT)),
+            event("e3", 62, EVENT_CLOSE_ACCOUNT, Some(MINT)),
+            // Unresolved and unindexed mints are skipped
+            event("e4", 63, EVENT_APPROVE, None),
+            event("e5", 64, EVENT_APPROVE, Some("UntrackedMint")),
+        ];
+        insert_token_events(&pool, &events).await.unwrap();
+        insert_token_events(&pool, &events).await.unwrap();
+
+        assert_eq!(
+            get_token_event_counts(&pool, MINT).await.unwrap(),
+            vec![
+                (EVENT_APPROVE.to_string(), 2),
+                (EVENT_CLOSE_ACCOUNT.to_string(), 1)
+            ]
+        );
+        let recent = get_token_events_for_mint(&pool, MINT, 2).await.unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].signature, "e3");
+        assert_eq!(recent[1].signature, "e2");
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_backfill_candles_from_trades(pool: PgPool) {
+        use chrono::TimeZone;
+
+        seed_mint(&pool).await;
+        let at = |secs: i64| chrono::Utc.timestamp_opt(secs, 0).single();
+        insert_bonding_curve_trades(
+            &pool,
+            &[
+                BondingCurveTrade { block_time: at(1_200), ..trade("a", 100, 1_000, 100) },
+                BondingCurveTrade { block_time: at(1_230), ..trade("b", 101, 3_000, 100) },
+                BondingCurveTrade { block_time: at(1_250), ..trade("c", 102, 2_000, 100) },
+                BondingCurveTrade { block_time: at(1_290), ..trade("d", 103, 4_000, 100) },
+                // No block time: can't be bucketed
+                trade("e", 104, 9_000, 100),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let backfill = |mode| backfill_candles(&pool, MINT, 60, mode);
+        assert_eq!(backfill(CandleOpenMode::FirstTrade).await.unwrap(), 2);
+
+        let candles = get_candles(&pool, MINT, 60, 10, None).await.unwrap();
+        assert_eq!(candles.len(), 2);
+        let (newer, older) = (&candles[0], &candles[1]);
+        assert_eq!(older.bucket_start, at(1_200).unwrap());
+        assert_eq!((older.open, older.high, older.low, older.close), (10, 30, 10, 20));
+        assert_eq!(older.trades_count, 3);
+        assert_eq!(older.volume_sol, 6_000);
+        assert_eq!(newer.bucket_start, at(1_260).unwrap());
+        assert_eq!(newer.open, 40);
+
+        // Existing buckets are not touched again
+        assert_eq!(backfill(CandleOpenMode::PrevClose).await.unwrap(), 0);
+
+        // Like the writer, prev_close opens a bucket at the last close and widens its range.
+        sqlx::query("DELETE FROM candles").execute(&pool).await.unwrap();
+        assert_eq!(backfill(CandleOpenMode::PrevClose).await.unwrap(), 2);
+        let candles = get_candles(&pool, MINT, 60, 10, None).await.unwrap();
+        let (newer, older) = (&candles[0], &candles[1]);
+        assert_eq!((older.open, older.high, older.low, older.close), (10, 30, 10, 20));
+        assert_eq!((newer.open, newer.high, newer.low, newer.close), (20, 40, 20, 40));
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_price_rescale_migration(pool: PgPool) {
+        use chrono::TimeZone;
+        use sqlx::Executor;
+
+        // The schema as it was before migration 0013, with rows in the old price unit.
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../migrations");
+        let mut files: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        files.sort();
+        let (before, rescale): (Vec<_>, Vec<_>) = files
+            .iter()
+            .partition(|path| path.file_name().unwrap().to_str().unwrap() < "0013");
+        for path in before {
+            pool.execute(std::fs::read_to_string(path).unwrap().as_str()).await.unwrap();
+        }
+
+        let mint = |mint_pubkey: &str, decimals: i32| Mint {
+            mint_pubkey: mint_pubkey.to_string(),
+            symbol: None,
+            decimals,
+            first_seen_slot: 0,
+        };
+        upsert_mints(&pool, &[mint(MINT, 6), mint("nine", 9), mint("unknown", UNKNOWN_DECIMALS)])
+            .await
+            .unwrap();
+        let at = |secs: i64| chrono::Utc.timestamp_opt(secs, 0).single();
+        insert_bonding_curve_trades(
+            &pool,
+            &[
+                // 0.001 SOL per whole token stored as 1_000 / 1_000_000 = 0
+                BondingCurveTrade { block_time: at(1_200), ..trade("a", 100, 1_000, 1_000_000) },
+                BondingCurveTrade { block_time: at(1_230), ..trade("b", 101, 3_000, 1_000_000) },
+                BondingCurveTrade { block_time: at(1_290), ..trade("f", 105, 5_000, 1_000_000) },
+                BondingCurveTrade {
+                    mint_pubkey: "nine".to_string(),
+                    ..trade("c", 102, 5, 1_000_000_000)
+                },
+                BondingCurveTrade {
+                    mint_pubkey: "unknown".to_string(),
+                    ..trade("d", 103, 7, 1_000_000)
+                },
+                trade("e", 104, 9_000, 0),
+            ],
+        )
+        .await
+        .unwrap();
+        backfill_candles(&pool, MINT, 60, CandleOpenMode::PrevClose).await.unwrap();
+
+        assert_eq!(rescale.len(), 1, "{rescale:?}");
+        pool.execute(std::fs::read_to_string(rescale[0]).unwrap().as_str()).await.unwrap();
+
+        let prices: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT signature, price_nanos_per_token FROM bonding_curve_trades ORDER BY signature",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let expected = [("a", 1_000), ("b", 3_000), ("c", 5), ("d", 7), ("e", 0), ("f", 5_000)];
+        assert_eq!(
+            prices,
+            expected.map(|(sig, price)| (sig.to_string(), price)).to_vec()
+        );
+
+        // The candles are dropped and queued, then rebuilt from the rescaled prices with the
+        // configured open mode.
+        assert!(get_candles(&pool, MINT, 60, 10, None).await.unwrap().is_empty());
+        assert_eq!(rebuild_queued_candles(&pool, CandleOpenMode::PrevClose).await.unwrap(), 2);
+        let candles = get_candles(&pool, MINT, 60, 10, None).await.unwrap();
+        let (newer, older) = (&candles[0], &candles[1]);
+        assert_eq!((older.open, older.high, older.low, older.close), (1_000, 3_000, 1_000, 3_000));
+        assert_eq!((older.trades_count, older.volume_sol), (2, 4_000));
+        assert_eq!((newer.open, newer.high, newer.low, newer.close), (3_000, 5_000, 3_000, 5_000));
+
+        // The queue is emptied.
+        assert_eq!(rebuild_queued_candles(&pool, CandleOpenMode::PrevClose).await.unwrap(), 0);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_ohlcv_rollup_from_base_candles(pool: PgPool) {
+        use chrono::TimeZone;
+
+        seed_mint(&pool).await;
+        let at = |secs: i64| chrono::Utc.timestamp_opt(secs, 0).unwrap();
+        let candle = |tf: i32, secs: i64, ohlc: (i64, i64, i64, i64), trades: i32| Candle {
+            mint_pubkey: MINT.to_string(),
+            timeframe_secs: tf,
+            bucket_start: at(secs),
+            open: ohlc.0,
+            high: ohlc.1,
+            low: ohlc.2,
+            close: ohlc.3,
+            volume_token: 100 * i64::from(trades),
+            volume_sol: 10 * i64::from(trades),
+            trades_count: trades,
+        };
+        for c in [
+            candle(60, 1_800, (10, 15, 9, 12), 1),
+            candle(60, 1_860, (12, 20, 11, 18), 2),
+            candle(60, 1_920, (18, 19, 5, 7), 3),
+            // Next 180s bucket
+            candle(60, 1_980, (7, 8, 6, 8), 1),
+            // Other timeframes are not mixed in
+            candle(300, 1_800, (1, 100, 1, 1), 50),
+        ] {
+            upsert_candle(&pool, &c).await.unwrap();
+        }
+
+        let rolled = get_ohlcv_rollup(&pool, MINT, 60, 180, 10).await.unwrap();
+        assert_eq!(rolled.len(), 2);
+        assert_eq!(rolled[0].bucket_start, at(1_980));
+        let c = &rolled[1];
+        assert_eq!((c.timeframe_secs, c.bucket_start), (180, at(1_800)));
+        assert_eq!((c.open, c.high, c.low, c.close), (10, 20, 5, 7));
+        assert_eq!((c.volume_token, c.volume_sol, c.trades_count), (600, 60, 6));
+
+        let newest = get_ohlcv_rollup(&pool, MINT, 60, 180, 1).await.unwrap();
+        assert_eq!(newest.len(), 1);
+        assert_eq!((newest[0].bucket_start, newest[0].trades_count), (at(1_980), 1));
+        assert!(get_ohlcv_rollup(&pool, MINT, 60, 90, 10).await.is_err());
+        assert_eq!(get_min_candle_timeframe(&pool, MINT).await.unwrap(), Some(60));
+        assert_eq!(get_min_candle_timeframe(&pool, "other").await.unwrap(), None);
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_add_parse_misses_accumulates(pool: PgPool) {
+        add_parse_misses(&pool, "pump", 2).await.unwrap();
+        add_parse_misses(&pool, "pump", 3).await.unwrap();
+        add_parse_misses(&pool, "raydium", 1).await.unwrap();
+
+        let misses = get_parse_misses(&pool).await.unwrap();
+        assert_eq!(
+            misses,
+            vec![("pump".to_string(), 5), ("raydium".to_string(), 1)]
+        );
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_bad_indices_tracked_apart_from_misses(pool: PgPool) {
+        add_parse_misses(&pool, "pump", 2).await.unwrap();
+        add_bad_indices(&pool, "pump", 1).await.unwrap();
+        add_bad_indices(&pool, "spl", 4).await.unwrap();
+
+        assert_eq!(
+            get_bad_indices(&pool).await.unwrap(),
+            vec![("pump".to_string(), 1), ("spl".to_string(), 4)]
+        );
+        assert_eq!(
+            get_parse_misses(&pool).await.unwrap(),
+            vec![("pump".to_string(), 2)]
+        );
+    }
+}
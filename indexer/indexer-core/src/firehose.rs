@@ -205,6 +205,7 @@ mod tests {
             mint_whitelist: vec![],
             initial_backoff_ms: Some(1000),
             max_backoff_ms: Some(30000),
+            cache_dir: None,
         };
 
         let client = FirehoseClient::new(config);
@@ -219,6 +220,7 @@ mod tests {
             mint_whitelist: vec![],
             initial_backoff_ms: Some(1000),
             max_backoff_ms: Some(30000),
+            cache_dir: None,
         };
 
         let mut client = FirehoseClient::new(config);
@@ -273,6 +275,7 @@ mod tests {
             mint_whitelist: vec![],
             initial_backoff_ms: Some(1000),
             max_backoff_ms: Some(30000),
+            cache_dir: None,
         };
 
         let client = FirehoseClient::new(config);
@@ -6,4 +6,14 @@ pub mod bonding_parser;
  pub mod firehose;
  pub mod raydium_parser;
  pub mod meteora_parser;
+ pub mod whirlpool_parser;
  pub mod redis;
+ pub mod block_cache;
+ pub mod candles;
+pub mod logging;
+ pub mod shutdown;
+ pub mod trade_rate;
+ pub mod candle_codec;
+ pub mod cursor;
+ pub mod price;
+pub mod prometheus;
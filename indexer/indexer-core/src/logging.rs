@@ -0,0 +1,128 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::io::IsTerminal;
+use tracing::Subscriber;
+use tracing_subscriber::{fmt::MakeWriter, EnvFilter};
+
+use crate::config::LogConfig;
+
+/// Level used when neither `RUST_LOG` nor `log.level` is set.
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+
+/// Shape of each log line.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines, coloured when stdout is a terminal.
+    #[default]
+    Pretty,
+    /// One JSON object per line (`timestamp`, `level`, `target`, `fields`), for log ingestion.
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            other => Err(format!(
+                "invalid log format '{other}' (expected one of: pretty, json)"
+            )),
+        }
+    }
+}
+
+/// Install the global subscriber for a binary. `RUST_LOG` directives win over `log.level`;
+/// `format_override` (the `--log-format` flag) wins over `log.format`.
+pub fn init_logging(config: &LogConfig, format_override: Option<LogFormat>) -> Result<()> {
+    let format = format_override.or(config.format).unwrap_or_default();
+    let ansi = config.ansi.unwrap_or_else(|| std::io::stdout().is_terminal());
+    let filter = env_filter(config.level.as_deref())?;
+    tracing::subscriber::set_global_default(build_subscriber(filter, format, ansi, std::io::stdout))?;
+    Ok(())
+}
+
+/// `RUST_LOG` when set, otherwise `level`, otherwise [`DEFAULT_LOG_LEVEL`].
+fn env_filter(level: Option<&str>) -> Result<EnvFilter> {
+    if std::env::var_os(EnvFilter::DEFAULT_ENV).is_some() {
+        return Ok(EnvFilter::try_from_default_env()?);
+    }
+    Ok(EnvFilter::try_new(level.unwrap_or(DEFAULT_LOG_LEVEL))?)
+}
+
+fn build_subscriber<W>(
+    filter: EnvFilter,
+    format: LogFormat,
+    ansi: bool,
+    writer: W,
+) -> Box<dyn Subscriber + Send + Sync>
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer);
+    match format {
+        LogFormat::Pretty => Box::new(builder.with_ansi(ansi).finish()),
+        LogFormat::Json => Box::new(builder.json().with_ansi(false).finish()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_format_emits_one_json_object_per_line() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = build_subscriber(
+            EnvFilter::new("info"),
+            LogFormat::Json,
+            true,
+            move || writer.clone(),
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(slot = 42, "block written");
+            tracing::warn!("writer lagging \"behind\"");
+            tracing::debug!("filtered out");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("log line is not JSON"))
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["level"], "INFO");
+        assert_eq!(lines[0]["fields"]["message"], "block written");
+        assert_eq!(lines[0]["fields"]["slot"], 42);
+        assert!(lines[0]["timestamp"].is_string());
+        assert_eq!(lines[1]["fields"]["message"], "writer lagging \"behind\"");
+    }
+
+    #[test]
+    fn test_log_format_from_str() {
+        assert_eq!("json".parse::<LogFormat>(), Ok(LogFormat::Json));
+        assert_eq!(" Pretty ".parse::<LogFormat>(), Ok(LogFormat::Pretty));
+        assert!("xml".parse::<LogFormat>().unwrap_err().contains("pretty, json"));
+    }
+}
@@ -2,7 +2,11 @@
 // Handles detection and parsing of Meteora DLMM pool swaps (v1 and v2).
 
 use crate::models::BondingCurveTrade;
-use crate::spl_parser::{BlockRef, InstructionRef, TransactionRef};
+use crate::price::PriceScale;
+use crate::spl_parser::{
+    account_indices_in_range, parse_guarded, swap_side, swap_vault_mints, BlockRef,
+    InstructionRef, ParseCounters, TokenAccountCache, TransactionRef,
+};
 use chrono::{TimeZone, Utc};
 
 // Meteora DLMM program ID (mainnet).
@@ -31,6 +35,24 @@ fn read_u32_le(bytes: &[u8]) -> Option<u32> {
 }
 
 pub fn extract_meteora_trades_from_block(block: &BlockRef) -> Vec<BondingCurveTrade> {
+    extract_meteora_trades_with(
+        block,
+        &mut TokenAccountCache::default(),
+        None,
+        &PriceScale::default(),
+    )
+}
+
+/// Same as [`extract_meteora_trades_from_block`], looking up in `accounts` the mints of token
+/// accounts the transaction itself doesn't reveal, pricing trades with `price_scale` and adding
+/// to `counters` a miss for every Meteora instruction that could not be parsed as a swap and a
+/// bad index for every one with out-of-range accounts.
+pub fn extract_meteora_trades_with(
+    block: &BlockRef,
+    accounts: &mut TokenAccountCache,
+    counters: Option<&ParseCounters>,
+    price_scale: &PriceScale,
+) -> Vec<BondingCurveTrade> {
     let mut trades = Vec::new();
 
     let block_time = block
@@ -44,8 +66,20 @@ pub fn extract_meteora_trades_from_block(block: &BlockRef) -> Vec<BondingCurveTr
                 continue;
             }
 
-            if let Some(trade) = parse_meteora_swap(block.slot, block_time, tx, ix) {
-                trades.push(trade);
+            if !account_indices_in_range("meteora", tx, ix, counters) {
+                continue;
+            }
+
+            let parsed = parse_guarded("meteora", tx, ix, counters, || {
+                parse_meteora_swap(block.slot, block_time, tx, ix, accounts, price_scale)
+            });
+            match parsed {
+                Some(trade) => trades.push(trade),
+                None => {
+                    if let Some(c) = counters {
+                        c.miss();
+                    }
+                }
             }
         }
     }
@@ -70,6 +104,8 @@ fn parse_meteora_swap(
     block_time: Option<chrono::DateTime<chrono::Utc>>,
     tx: &TransactionRef,
     ix: &InstructionRef,
+    accounts: &mut TokenAccountCache,
+    price_scale: &PriceScale,
 ) -> Option<BondingCurveTrade> {
     if ix.data.len() < 17 {
         // Need at least discriminator + amount_in + amount_out
@@ -101,27 +137,48 @@ fn parse_meteora_swap(
         parse_meteora_v1_metadata(ix)
     };
 
-    // Infer direction
-    let direction = infer_dlmm_direction(amount_in, amount_out);
-
-    let price = if amount_out == 0 {
-        0u64
+    // Direction from which reserve (accounts 2 and 3) the trader paid into. Mints the
+    // transaction doesn't reveal come from the trader's token accounts (4 pays in, 5 is paid)
+    // as seen in earlier blocks; the amount ratio is only a last resort. Pairs known not to
+    // include SOL are skipped.
+    let key = |pos: usize| {
+        let idx = *ix.accounts.get(pos)? as usize;
+        tx.message.account_keys.get(idx).map(String::as_str)
+    };
+    let reserves: Vec<&str> = [2, 3].into_iter().filter_map(key).collect();
+    let (input_mint, output_mint) = swap_vault_mints(tx, ix, &reserves);
+    let mut cached_mint = |pos: usize| Some(accounts.get(key(pos)?)?.mint.clone());
+    let input_mint = input_mint.map(str::to_string).or_else(|| cached_mint(4));
+    let output_mint = output_mint.map(str::to_string).or_else(|| cached_mint(5));
+    let (direction, mint_pubkey) = match (input_mint.as_deref(), output_mint.as_deref()) {
+        (Some(input), Some(output)) => {
+            let (side, base_mint) = swap_side(Some(input), Some(output))?;
+            (side, base_mint.to_string())
+        }
+        _ => (infer_dlmm_direction(amount_in, amount_out), pool_id),
+    };
+    // SOL is paid in on a buy and out on a sell.
+    let (sol_amount, token_amount) = if direction == "sell" {
+        (amount_out, amount_in)
     } else {
-        amount_in / amount_out
+        (amount_in, amount_out)
     };
 
+    let price = price_scale.price_nanos(&mint_pubkey, sol_amount, token_amount);
+
     Some(BondingCurveTrade {
         signature: tx.signature.clone(),
         slot,
         block_time,
-        mint_pubkey: pool_id,
+        mint_pubkey,
         trader,
         side: direction.to_string(),
-        token_amount: amount_out as i64,
-        sol_amount: amount_in as i64,
-        price_nanos_per_token: price as i64,
+        token_amount: token_amount as i64,
+        sol_amount: sol_amount as i64,
+        price_nanos_per_token: price,
         tx_index: tx.index,
         ix_index: ix.index,
+        inner_index: ix.inner_index,
     })
 }
 
@@ -190,7 +247,8 @@ fn parse_meteora_v2_metadata(ix: &InstructionRef) -> (Vec<i32>, Option<i64>, Opt
     (bins_used, fee_tier, active_bin)
 }
 
-/// Infer swap direction based on amount comparison.
+/// Guess swap direction from the amounts, for swaps whose mints are unknown. Misreads pairs of
+/// similarly valued units, e.g. SOL/USDC.
 fn infer_dlmm_direction(amount_in: u64, amount_out: u64) -> &'static str {
     if amount_in == 0 || amount_out == 0 {
         return "buy";
@@ -207,7 +265,10 @@ fn infer_dlmm_direction(amount_in: u64, amount_out: u64) -> &'static str {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::spl_parser::{InstructionRef, MessageRef, TransactionRef};
+    use crate::spl_parser::{
+        InstructionRef, MessageRef, TransactionRef, SPL_TOKEN_PROGRAM_ID, TOP_LEVEL_INNER_INDEX,
+        USDC_MINT, WSOL_MINT,
+    };
 
     fn create_meteora_v1_instruction(amount_in: u64, amount_out: u64) -> Vec<u8> {
         let mut data = vec![DLMM_SWAP]; // discriminator
@@ -253,6 +314,7 @@ mod tests {
                     accounts: vec![0, 1, 2, 3, 4],
                     data: create_meteora_v1_instruction(500_000_000, 2_500_000_000),
                     index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
                 }],
             }],
         };
@@ -295,6 +357,7 @@ mod tests {
                         &[1001, 1002, 1003],
                     ),
                     index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
                 }],
             }],
         };
@@ -311,6 +374,7 @@ mod tests {
             accounts: vec![0, 1, 2, 3, 4],
             data: vec![],
             index: 0,
+            inner_index: TOP_LEVEL_INNER_INDEX,
         };
 
         let v2_ix = InstructionRef {
@@ -318,6 +382,7 @@ mod tests {
             accounts: vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10],
             data: vec![],
             index: 0,
+            inner_index: TOP_LEVEL_INNER_INDEX,
         };
 
         assert_eq!(infer_dlmm_version(&v1_ix, DLMM_SWAP), 1);
@@ -335,6 +400,7 @@ mod tests {
             accounts: vec![0, 1],
             data: ix_data,
             index: 0,
+            inner_index: TOP_LEVEL_INNER_INDEX,
         };
 
         let (extracted_bins, _, active_bin) = parse_meteora_v2_metadata(&ix);
@@ -352,6 +418,7 @@ mod tests {
             accounts: vec![0, 1],
             data: ix_data,
             index: 0,
+            inner_index: TOP_LEVEL_INNER_INDEX,
         };
 
         let (_, fee_tier, _) = parse_meteora_v2_metadata(&ix);
@@ -381,6 +448,7 @@ mod tests {
                         accounts: vec![0, 1, 2, 3, 4],
                         data: create_meteora_v1_instruction(100_000_000, 500_000_000),
                         index: 0,
+                        inner_index: TOP_LEVEL_INNER_INDEX,
                     }],
                 },
                 TransactionRef {
@@ -400,6 +468,7 @@ mod tests {
                         accounts: vec![0, 1, 2, 3, 4],
                         data: create_meteora_v1_instruction(5_000_000_000, 100_000_000),
                         index: 0,
+                        inner_index: TOP_LEVEL_INNER_INDEX,
                     }],
                 },
             ],
@@ -411,6 +480,132 @@ mod tests {
         assert_eq!(trades[1].trader, "user2");
     }
 
+    #[test]
+    fn test_meteora_sell_detected_from_reserve_paid_into() {
+        // TransferChecked accounts: [source, mint, dest, owner]
+        let transfer_checked = |accounts: Vec<u8>, amount: u64, inner_index: i32| {
+            let mut data = vec![12];
+            data.extend_from_slice(&amount.to_le_bytes());
+            data.push(9);
+            InstructionRef {
+                program_id: SPL_TOKEN_PROGRAM_ID.to_string(),
+                accounts,
+                data,
+                index: 0,
+                inner_index,
+            }
+        };
+        let block = BlockRef {
+            slot: 205,
+            block_time_unix: Some(2005),
+            transactions: vec![TransactionRef {
+                signature: "meteora_sell_sig".to_string(),
+                index: 0,
+                message: MessageRef {
+                    account_keys: vec![
+                        "trader".to_string(),
+                        "pool".to_string(),
+                        "token_reserve".to_string(),
+                        "wsol_reserve".to_string(),
+                        "authority".to_string(),
+                        "trader_token".to_string(),
+                        "trader_wsol".to_string(),
+                        "token_mint".to_string(),
+                        WSOL_MINT.to_string(),
+                    ],
+                },
+                instructions: vec![
+                    InstructionRef {
+                        program_id: METEORA_DLMM_PROGRAM_ID.to_string(),
+                        accounts: vec![0, 1, 2, 3, 4],
+                        // 0.001 tokens for 2 SOL: the ratio heuristic would call this a buy.
+                        data: create_meteora_v1_instruction(1_000_000, 2_000_000_000),
+                        index: 0,
+                        inner_index: TOP_LEVEL_INNER_INDEX,
+                    },
+                    transfer_checked(vec![5, 7, 2, 0], 1_000_000, 0),
+                    transfer_checked(vec![3, 8, 6, 4], 2_000_000_000, 1),
+                ],
+            }],
+        };
+
+        let trades = extract_meteora_trades_from_block(&block);
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].side, "sell");
+        assert_eq!(trades[0].mint_pubkey, "token_mint");
+        assert_eq!(trades[0].sol_amount, 2_000_000_000);
+        assert_eq!(trades[0].token_amount, 1_000_000);
+    }
+
+    #[test]
+    fn test_meteora_usdc_pairs_from_cached_trader_accounts() {
+        let mut accounts = TokenAccountCache::new(16);
+        accounts.insert("trader_wsol", WSOL_MINT, Some("trader"));
+        accounts.insert("trader_usdc", USDC_MINT, Some("trader"));
+        accounts.insert("trader_token", "token_mint", Some("trader"));
+        let swap = |signature: &str, accts: Vec<u8>, amount_in: u64, amount_out: u64| {
+            TransactionRef {
+                signature: signature.to_string(),
+                index: 0,
+                message: MessageRef {
+                    account_keys: vec![
+                        "trader".to_string(),
+                        "sol_usdc_pool".to_string(),
+                        "reserve_x".to_string(),
+                        "reserve_y".to_string(),
+                        "trader_wsol".to_string(),
+                        "trader_usdc".to_string(),
+                        "trader_token".to_string(),
+                    ],
+                },
+                instructions: vec![InstructionRef {
+                    program_id: METEORA_DLMM_PROGRAM_ID.to_string(),
+                    accounts: accts,
+                    data: create_meteora_v1_instruction(amount_in, amount_out),
+                    index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
+                }],
+            }
+        };
+        let block = BlockRef {
+            slot: 206,
+            block_time_unix: Some(2006),
+            transactions: vec![
+                // 10 SOL for 1,500 USDC, paying from the SOL account.
+                swap("sell_sol", vec![0, 1, 2, 3, 4, 5], 10_000_000_000, 1_500_000_000),
+                // 150 USDC for 1 SOL, paying from the USDC account.
+                swap("buy_sol", vec![0, 1, 2, 3, 5, 4], 150_000_000, 1_000_000_000),
+                // 20 USDC for tokens: no SOL side, so not recorded.
+                swap("usdc_quoted", vec![0, 1, 2, 3, 5, 6], 20_000_000, 3_000_000_000),
+            ],
+        };
+
+        let counters = ParseCounters::default();
+        let trades = extract_meteora_trades_with(
+            &block,
+            &mut accounts,
+            Some(&counters),
+            &PriceScale::default(),
+        );
+
+        assert_eq!(trades.len(), 2);
+        assert_eq!(counters.misses(), 1);
+        // Against SOL, USDC is the traded side and SOL stays the quote.
+        assert_eq!(trades[0].side, "buy");
+        assert_eq!(trades[0].mint_pubkey, USDC_MINT);
+        assert_eq!(trades[0].token_amount, 1_500_000_000);
+        assert_eq!(trades[0].sol_amount, 10_000_000_000);
+        assert_eq!(trades[1].side, "sell");
+        assert_eq!(trades[1].mint_pubkey, USDC_MINT);
+        assert_eq!(trades[1].token_amount, 150_000_000);
+        assert_eq!(trades[1].sol_amount, 1_000_000_000);
+
+        // Without the cache the same swap falls back to the ratio.
+        let trades = extract_meteora_trades_from_block(&block);
+        assert_eq!(trades[0].side, "buy");
+        assert_eq!(trades[0].mint_pubkey, "sol_usdc_pool");
+    }
+
     #[test]
     fn test_meteora_direction_inference() {
         assert_eq!(infer_dlmm_direction(1_000_000_000, 5_000_000_000), "buy");
@@ -436,6 +631,7 @@ mod tests {
             accounts: vec![],
             data: vec![],
             index: 0,
+            inner_index: TOP_LEVEL_INNER_INDEX,
         };
 
         assert_eq!(ix.program_id, METEORA_DLMM_PROGRAM_ID);
@@ -1,6 +1,9 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+/// `Mint::decimals` of a mint first seen in an instruction that does not carry its decimals.
+pub const UNKNOWN_DECIMALS: i32 = -1;
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
  pub struct Mint {
      pub mint_pubkey: String,
@@ -22,8 +25,31 @@ use serde::{Deserialize, Serialize};
     pub amount: i64,
      pub tx_index: i32,
      pub ix_index: i32,
+     pub inner_index: i32,
+     /// Token program that executed the instruction: SPL Token or Token-2022.
+     pub program: String,
  }
 
+/// A non-transfer SPL Token instruction kept as a safety signal: `kind` is "approve",
+/// "revoke" or "close_account". `authority` is the owner that signed; `counterparty` is the
+/// approved delegate or the close's rent destination. `mint_pubkey` is `None` only between
+/// parsing and the writer resolving it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TokenEvent {
+    pub signature: String,
+    pub slot: i64,
+    pub block_time: Option<DateTime<Utc>>,
+    pub mint_pubkey: Option<String>,
+    pub kind: String,
+    pub token_account: String,
+    pub authority: String,
+    pub counterparty: Option<String>,
+    pub amount: Option<i64>,
+    pub tx_index: i32,
+    pub ix_index: i32,
+    pub inner_index: i32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
  pub struct Balance {
      pub wallet: String,
@@ -44,6 +70,69 @@ pub struct BondingCurveTrade {
     pub price_nanos_per_token: i64,
     pub tx_index: i32,
     pub ix_index: i32,
+    pub inner_index: i32,
+}
+
+/// A stored trade as served by the API. `is_new_wallet` is true when the trade happened in
+/// the first slot the trader was ever seen trading, in any mint.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BondingTradeRow {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub trade: BondingCurveTrade,
+    pub is_new_wallet: bool,
+}
+
+/// A wallet's bonding-curve trade totals in one mint, the input for cost basis and PnL.
+/// Amounts are raw token units and lamports; `last_price_nanos_per_token` is the mint's latest
+/// trade price from any trader.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WalletMintPnl {
+    pub mint_pubkey: String,
+    pub decimals: i32,
+    pub tokens_bought: i64,
+    pub sol_spent: i64,
+    pub tokens_sold: i64,
+    pub sol_received: i64,
+    pub last_price_nanos_per_token: Option<i64>,
+}
+
+/// How far the indexer trails the chain, judged by its newest stored trade. Overstated while
+/// none of the indexed mints trade.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct IndexerLag {
+    /// Wall-clock seconds since the newest trade's block time.
+    pub lag_secs: i64,
+    /// Chain tip estimated from the newest trade's slot, at 400ms per slot since then.
+    pub estimated_tip_slot: i64,
+    /// `estimated_tip_slot` minus `last_processed_slot`; `None` before the first checkpoint.
+    pub slots_behind: Option<i64>,
+}
+
+/// A mint's bonding-curve activity over a recent window, for the trending list.
+/// `volume_sol` is buys plus sells in lamports; the price is the mint's latest trade.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TrendingToken {
+    pub mint_pubkey: String,
+    pub symbol: Option<String>,
+    pub volume_sol: i64,
+    pub trades_count: i64,
+    pub last_price_nanos_per_token: i64,
+}
+
+/// Trader activity for a mint over a time window.
+/// `net_buyers` is wallets that net-bought (in SOL) minus wallets that net-sold.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TraderStats {
+    pub unique_traders: i64,
+    pub net_buyers: i64,
+    /// Distinct wallets with at least one buy, and with at least one sell.
+    pub buyers: i64,
+    pub sellers: i64,
+    /// Lamports spent on buys and received from sells; `net_volume_sol` is buys minus sells.
+    pub buy_volume_sol: i64,
+    pub sell_volume_sol: i64,
+    pub net_volume_sol: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
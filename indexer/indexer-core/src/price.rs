@@ -0,0 +1,163 @@
+// Fixed-point trade prices.
+//
+// `price_nanos_per_token` (trades and candles) is the price of one *whole* token in nano-SOL
+// (lamports):
+//
+//     price = sol_amount * 10^token_decimals * 10^(9 - sol_decimals) / token_amount
+//
+// with `sol_amount` and `token_amount` in raw on-chain units. Scaling by the token's decimals
+// keeps sub-lamport-per-raw-unit prices (cheap 6-decimal memecoins) from truncating to zero.
+// The division rounds half up, and results that do not fit in an i64 saturate at `i64::MAX`
+// instead of wrapping negative. `token_decimals` is the mint's `mints.decimals`, or
+// `[pricing] default_token_decimals` while it is unknown. Migration 0013 rescales rows stored
+// before this scheme (lamports per raw unit) the same way.
+
+use std::collections::HashMap;
+
+/// Decimals of native SOL (lamports).
+pub const SOL_DECIMALS: u32 = 9;
+
+/// Decimals assumed for mints without an override. Pump.fun mints always use 6.
+pub const DEFAULT_TOKEN_DECIMALS: u32 = 6;
+
+/// Price of one whole token in nano-SOL. See the module comment for the scheme.
+/// Returns 0 when `token_amount` is 0.
+pub fn scale_price(sol_amount: u64, token_amount: u64, sol_decimals: u32, token_decimals: u32) -> i64 {
+    if token_amount == 0 {
+        return 0;
+    }
+
+    // Move the 10^(9 - sol_decimals) factor to the denominator when SOL has more than 9 decimals.
+    let (num_exp, den_exp) = if sol_decimals <= SOL_DECIMALS {
+        (token_decimals + (SOL_DECIMALS - sol_decimals), 0)
+    } else {
+        (token_decimals, sol_decimals - SOL_DECIMALS)
+    };
+
+    let numerator = 10u128
+        .checked_pow(num_exp)
+        .and_then(|scale| (sol_amount as u128).checked_mul(scale));
+    let denominator = 10u128
+        .checked_pow(den_exp)
+        .and_then(|scale| (token_amount as u128).checked_mul(scale));
+
+    let price = match (numerator, denominator) {
+        (Some(n), Some(d)) => n / d + u128::from(n % d >= d - d / 2),
+        // Numerator overflowed u128: certainly beyond i64.
+        (None, _) => return i64::MAX,
+        // Denominator overflowed: the price rounds to zero.
+        (Some(_), None) => return 0,
+    };
+
+    i64::try_from(price).unwrap_or(i64::MAX)
+}
+
+/// Per-mint token decimals used when scaling prices, learned from `mints.decimals`. Only mints
+/// whose decimals differ from the default are kept, so most pump.fun mints cost nothing.
+#[derive(Debug, Clone, Default)]
+pub struct PriceScale {
+    default_token_decimals: Option<u32>,
+    token_decimals: HashMap<String, u32>,
+}
+
+impl PriceScale {
+    pub fn new(default_token_decimals: Option<u32>) -> Self {
+        Self {
+            default_token_decimals,
+            token_decimals: HashMap::new(),
+        }
+    }
+
+    /// Decimals assumed for mints whose decimals haven't been seen.
+    pub fn default_token_decimals(&self) -> u32 {
+        self.default_token_decimals.unwrap_or(DEFAULT_TOKEN_DECIMALS)
+    }
+
+    /// Record `mint_pubkey`'s decimals, as stored in `mints.decimals`.
+    /// [`crate::models::UNKNOWN_DECIMALS`] is ignored.
+    pub fn learn_decimals(&mut self, mint_pubkey: &str, decimals: i32) {
+        let Ok(decimals) = u32::try_from(decimals) else {
+            return;
+        };
+        if decimals == self.default_token_decimals() {
+            self.token_decimals.remove(mint_pubkey);
+        } else {
+            self.token_decimals.insert(mint_pubkey.to_string(), decimals);
+        }
+    }
+
+    pub fn token_decimals(&self, mint_pubkey: &str) -> u32 {
+        self.token_decimals
+            .get(mint_pubkey)
+            .copied()
+            .unwrap_or_else(|| self.default_token_decimals())
+    }
+
+    /// [`scale_price`] for a SOL-quoted trade of `mint_pubkey`.
+    pub fn price_nanos(&self, mint_pubkey: &str, sol_amount: u64, token_amount: u64) -> i64 {
+        scale_price(
+            sol_amount,
+            token_amount,
+            SOL_DECIMALS,
+            self.token_decimals(mint_pubkey),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::UNKNOWN_DECIMALS;
+
+    #[test]
+    fn test_scale_price_whole_token_units() {
+        // 0.1 SOL for 1 token (6 decimals) = 100_000_000 nanos per token
+        assert_eq!(scale_price(100_000_000, 1_000_000, 9, 6), 100_000_000);
+        assert_eq!(scale_price(5, 0, 9, 6), 0);
+    }
+
+    #[test]
+    fn test_scale_price_keeps_tiny_prices() {
+        // 999 lamports for 1 whole token: plain integer division of raw units gives 0.
+        assert_eq!(999u64 / 1_000_000, 0);
+        assert_eq!(scale_price(999, 1_000_000, 9, 6), 999);
+        // Rounds half up rather than truncating.
+        assert_eq!(scale_price(3, 2_000_000, 9, 6), 2);
+        assert_eq!(scale_price(1, 3_000_000, 9, 6), 0);
+    }
+
+    #[test]
+    fn test_scale_price_saturates_instead_of_wrapping() {
+        assert_eq!(scale_price(u64::MAX, 1, 9, 9), i64::MAX);
+        assert_eq!(scale_price(u64::MAX, 1, 9, 30), i64::MAX);
+        assert_eq!(scale_price(u64::MAX, 1, 0, 40), i64::MAX);
+        assert!(scale_price(u64::MAX, u64::MAX, 9, 6) > 0);
+    }
+
+    #[test]
+    fn test_scale_price_non_lamport_quote_decimals() {
+        // Quote asset with 12 decimals: 10^12 units = 1 "SOL" = 10^9 nanos
+        assert_eq!(scale_price(1_000_000_000_000, 1, 12, 0), 1_000_000_000);
+    }
+
+    #[test]
+    fn test_price_scale_per_mint_decimals() {
+        let mut scale = PriceScale::new(None);
+        scale.learn_decimals("nine", 9);
+        scale.learn_decimals("six", 6);
+        scale.learn_decimals("unknown", UNKNOWN_DECIMALS);
+
+        assert_eq!(scale.token_decimals("nine"), 9);
+        assert_eq!(scale.token_decimals("other"), DEFAULT_TOKEN_DECIMALS);
+        assert_eq!(scale.price_nanos("nine", 1, 1_000_000_000), 1);
+        assert_eq!(scale.price_nanos("other", 1, 1_000_000), 1);
+        // Default-decimals mints aren't stored.
+        assert_eq!(scale.token_decimals.len(), 1);
+
+        // Unknown decimals don't overwrite known ones; a correction back to the default does.
+        scale.learn_decimals("nine", UNKNOWN_DECIMALS);
+        assert_eq!(scale.token_decimals("nine"), 9);
+        scale.learn_decimals("nine", 6);
+        assert!(scale.token_decimals.is_empty());
+    }
+}
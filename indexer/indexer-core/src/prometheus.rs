@@ -0,0 +1,93 @@
+// Prometheus metrics of the writer.
+// indexer-bin records these with the `metrics` macros and, when `[monitor] metrics_bind_addr`
+// is set, serves them for scraping via `install_exporter`. They count what this process has
+// done since it started; the stored totals (rows, parse misses) are served by the API.
+
+use anyhow::Result;
+use metrics::{describe_counter, describe_gauge, describe_histogram, Unit};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder};
+use std::net::SocketAddr;
+
+/// Blocks handed to the writer, live or replayed.
+pub const BLOCKS_PROCESSED_TOTAL: &str = "blocks_processed_total";
+/// Trades parsed from blocks, labelled by `venue` (pump, raydium, meteora, orca).
+pub const TRADES_PARSED_TOTAL: &str = "trades_parsed_total";
+/// Token transfers parsed from blocks.
+pub const TRANSFERS_TOTAL: &str = "transfers_total";
+/// Instructions of a venue's program that failed to parse, labelled by `parser`.
+pub const PARSE_MISSES_TOTAL: &str = "parse_misses_total";
+/// Instructions with out-of-range account indices, labelled by `parser`.
+pub const BAD_INDICES_TOTAL: &str = "bad_indices_total";
+/// Instructions whose parser panicked, labelled by `parser`. Also counted as parse misses.
+pub const PARSE_PANICS_TOTAL: &str = "parse_panics_total";
+/// Slot of the last checkpoint written.
+pub const LAST_PROCESSED_SLOT: &str = "last_processed_slot";
+/// Time to insert a batch's rows into one table, labelled by `table`.
+pub const DB_INSERT_DURATION_SECONDS: &str = "db_insert_duration_seconds";
+
+/// Histogram buckets of [`DB_INSERT_DURATION_SECONDS`], from 1ms to 5s.
+const DB_INSERT_BUCKETS: &[f64] =
+    &[0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+fn builder() -> Result<PrometheusBuilder> {
+    let builder = PrometheusBuilder::new().set_buckets_for_metric(
+        Matcher::Full(DB_INSERT_DURATION_SECONDS.to_string()),
+        DB_INSERT_BUCKETS,
+    )?;
+    Ok(builder)
+}
+
+fn describe() {
+    describe_counter!(BLOCKS_PROCESSED_TOTAL, "Blocks handed to the writer");
+    describe_counter!(TRADES_PARSED_TOTAL, "Trades parsed from blocks, per venue");
+    describe_counter!(TRANSFERS_TOTAL, "Token transfers parsed from blocks");
+    describe_counter!(PARSE_MISSES_TOTAL, "Instructions that failed to parse, per parser");
+    describe_counter!(
+        BAD_INDICES_TOTAL,
+        "Instructions with out-of-range account indices, per parser"
+    );
+    describe_counter!(PARSE_PANICS_TOTAL, "Instructions whose parser panicked, per parser");
+    describe_gauge!(LAST_PROCESSED_SLOT, "Slot of the last written checkpoint");
+    describe_histogram!(
+        DB_INSERT_DURATION_SECONDS,
+        Unit::Seconds,
+        "Time to insert a batch's rows into one table"
+    );
+}
+
+/// Record metrics globally and serve them in Prometheus text format on `bind_addr` (any
+/// path). Must be called from within a Tokio runtime, at most once per process.
+pub fn install_exporter(bind_addr: SocketAddr) -> Result<()> {
+    builder()?.with_http_listener(bind_addr).install()?;
+    describe();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics::{counter, gauge, histogram};
+
+    #[test]
+    fn test_writer_metrics_render_as_prometheus_text() {
+        let recorder = builder().unwrap().build_recorder();
+        let handle = recorder.handle();
+        metrics::with_local_recorder(&recorder, || {
+            describe();
+            counter!(TRADES_PARSED_TOTAL, "venue" => "pump").increment(2);
+            counter!(TRADES_PARSED_TOTAL, "venue" => "orca").increment(1);
+            gauge!(LAST_PROCESSED_SLOT).set(123.0);
+            histogram!(DB_INSERT_DURATION_SECONDS, "table" => "token_transfers").record(0.003);
+        });
+
+        let text = handle.render();
+        assert!(text.contains("# TYPE trades_parsed_total counter"));
+        assert!(text.contains("trades_parsed_total{venue=\"pump\"} 2"));
+        assert!(text.contains("trades_parsed_total{venue=\"orca\"} 1"));
+        assert!(text.contains("last_processed_slot 123"));
+        // A histogram with the configured buckets, not a summary.
+        assert!(text.contains("# TYPE db_insert_duration_seconds histogram"));
+        let bucket = "db_insert_duration_seconds_bucket{table=\"token_transfers\",le=\"0.005\"} 1";
+        assert!(text.contains(bucket), "{text}");
+    }
+}
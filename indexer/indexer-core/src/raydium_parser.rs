@@ -2,7 +2,11 @@
 // Handles detection and parsing of Raydium Fusion Pools and standard AMM swaps.
 
 use crate::models::BondingCurveTrade;
-use crate::spl_parser::{BlockRef, InstructionRef, TransactionRef};
+use crate::price::PriceScale;
+use crate::spl_parser::{
+    account_indices_in_range, parse_guarded, swap_side, swap_vault_mints, BlockRef,
+    InstructionRef, ParseCounters, TokenAccountCache, TransactionRef,
+};
 use chrono::{TimeZone, Utc};
 
 // Raydium AMM program IDs (mainnet).
@@ -24,6 +28,24 @@ fn read_u64_le(bytes: &[u8]) -> Option<u64> {
 }
 
 pub fn extract_raydium_trades_from_block(block: &BlockRef) -> Vec<BondingCurveTrade> {
+    extract_raydium_trades_with(
+        block,
+        &mut TokenAccountCache::default(),
+        None,
+        &PriceScale::default(),
+    )
+}
+
+/// Same as [`extract_raydium_trades_from_block`], looking up in `accounts` the vault mints the
+/// transaction itself doesn't reveal, pricing trades with `price_scale` and adding to `counters`
+/// a miss for every Raydium instruction that could not be parsed as a swap and a bad index for
+/// every one with out-of-range accounts.
+pub fn extract_raydium_trades_with(
+    block: &BlockRef,
+    accounts: &mut TokenAccountCache,
+    counters: Option<&ParseCounters>,
+    price_scale: &PriceScale,
+) -> Vec<BondingCurveTrade> {
     let mut trades = Vec::new();
 
     let block_time = block
@@ -37,8 +59,20 @@ pub fn extract_raydium_trades_from_block(block: &BlockRef) -> Vec<BondingCurveTr
                 continue;
             }
 
-            if let Some(trade) = parse_raydium_swap(block.slot, block_time, tx, ix) {
-                trades.push(trade);
+            if !account_indices_in_range("raydium", tx, ix, counters) {
+                continue;
+            }
+
+            let parsed = parse_guarded("raydium", tx, ix, counters, || {
+                parse_raydium_swap(block.slot, block_time, tx, ix, accounts, price_scale)
+            });
+            match parsed {
+                Some(trade) => trades.push(trade),
+                None => {
+                    if let Some(c) = counters {
+                        c.miss();
+                    }
+                }
             }
         }
     }
@@ -64,12 +98,20 @@ fn is_raydium_program(program_id: &str) -> bool {
 /// [1] - Token program (SPL Token)
 /// [2] - Swap account (pool)
 /// [3] - Authority account
-/// [4+] - Token input/output accounts
+/// [4], [5] - Pool token vaults (either order)
+///
+/// The side comes from which vault the swap's inner SPL transfers pay into: paying SOL in is a
+/// buy of the other vault's mint, which then becomes the trade's mint. When the vault mints
+/// can't be seen in the transaction, the amount ratio is used as a guess and the mint is the
+/// non-SOL vault's mint from `accounts`, or the pool account if that isn't known either. Swaps
+/// whose mints are known but don't include SOL give `None`.
 fn parse_raydium_swap(
     slot: i64,
     block_time: Option<chrono::DateTime<chrono::Utc>>,
     tx: &TransactionRef,
     ix: &InstructionRef,
+    accounts: &mut TokenAccountCache,
+    price_scale: &PriceScale,
 ) -> Option<BondingCurveTrade> {
     if ix.data.len() < 17 {
         // Need at least 1 byte discriminator + 8 bytes for amount_in + 8 bytes for amount_out
@@ -84,9 +126,6 @@ fn parse_raydium_swap(
     let amount_in = read_u64_le(&ix.data[1..])?;
     let amount_out = read_u64_le(&ix.data[9..])?;
 
-    // Infer swap direction based on relative amounts
-    let direction = infer_swap_direction_raydium(amount_in, amount_out);
-
     // Extract accounts: need at least trader + pool accounts
     if ix.accounts.len() < 3 {
         return None;
@@ -94,17 +133,47 @@ fn parse_raydium_swap(
 
     let trader_idx = ix.accounts.get(0).copied()? as usize;
     let trader = tx.message.account_keys.get(trader_idx)?.clone();
-
-    // For pool, typically at account index 2 or 3
-    // We'll use the accounts to infer a mint - in production this would come from on-chain data
-    let mint_pubkey = format!("raydium_pool_{}", &trader[..trader.len().min(8)]);
-
-    let price = if amount_out == 0 {
-        0u64
+    let pool_idx = ix.accounts.get(2).copied()? as usize;
+    let pool_id = tx.message.account_keys.get(pool_idx)?;
+
+    let vaults: Vec<&str> = [4, 5]
+        .iter()
+        .filter_map(|&pos| ix.accounts.get(pos))
+        .filter_map(|&idx| tx.message.account_keys.get(idx as usize))
+        .map(String::as_str)
+        .collect();
+    let (input_mint, output_mint) = swap_vault_mints(tx, ix, &vaults);
+    let (direction, mint_pubkey) = match (input_mint, output_mint) {
+        (Some(_), Some(_)) => {
+            let (side, base_mint) = swap_side(input_mint, output_mint)?;
+            (side, base_mint.to_string())
+        }
+        _ => {
+            // Which vault was paid into is unknown, but cached vault mints still name the
+            // traded token.
+            let vault_mints: Vec<String> = vaults
+                .iter()
+                .filter_map(|vault| Some(accounts.get(vault)?.mint.clone()))
+                .collect();
+            let base_mint = match vault_mints.as_slice() {
+                [a, b] => Some(swap_side(Some(a), Some(b))?.1.to_string()),
+                _ => None,
+            };
+            (
+                infer_swap_direction_raydium(amount_in, amount_out),
+                base_mint.unwrap_or_else(|| pool_id.clone()),
+            )
+        }
+    };
+    // SOL is paid in on a buy and out on a sell.
+    let (sol_amount, token_amount) = if direction == "sell" {
+        (amount_out, amount_in)
     } else {
-        amount_in / amount_out
+        (amount_in, amount_out)
     };
 
+    let price = price_scale.price_nanos(&mint_pubkey, sol_amount, token_amount);
+
     Some(BondingCurveTrade {
         signature: tx.signature.clone(),
         slot,
@@ -112,15 +181,17 @@ fn parse_raydium_swap(
         mint_pubkey,
         trader,
         side: direction.to_string(),
-        token_amount: amount_out as i64,
-        sol_amount: amount_in as i64,
-        price_nanos_per_token: price as i64,
+        token_amount: token_amount as i64,
+        sol_amount: sol_amount as i64,
+        price_nanos_per_token: price,
         tx_index: tx.index,
         ix_index: ix.index,
+        inner_index: ix.inner_index,
     })
 }
 
-/// Infer swap direction based on amount comparison.
+/// Guess swap direction from the amounts, for swaps whose vault mints are unknown. Misreads any
+/// buy with a small output (or sell with a large one).
 /// 
 /// If amount_in is significantly smaller than amount_out: BUY signal (small SOL → many tokens)
 /// If amount_out is significantly smaller than amount_in: SELL signal (many tokens → small SOL)
@@ -142,7 +213,10 @@ fn infer_swap_direction_raydium(amount_in: u64, amount_out: u64) -> &'static str
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::spl_parser::{InstructionRef, MessageRef, TransactionRef};
+    use crate::spl_parser::{
+        InstructionRef, MessageRef, TransactionRef, SPL_TOKEN_PROGRAM_ID, TOP_LEVEL_INNER_INDEX,
+        USDC_MINT, WSOL_MINT,
+    };
 
     fn create_raydium_swap_instruction(amount_in: u64, amount_out: u64) -> Vec<u8> {
         let mut data = vec![SWAP_EXACT_TOKENS_FOR_TOKENS];
@@ -151,6 +225,113 @@ mod tests {
         data
     }
 
+    /// Inner TransferChecked made by the swap at instruction 0: accounts [source, mint, dest, owner].
+    fn inner_transfer_checked(accounts: Vec<u8>, amount: u64, inner_index: i32) -> InstructionRef {
+        let mut data = vec![12];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.push(9);
+        InstructionRef {
+            program_id: SPL_TOKEN_PROGRAM_ID.to_string(),
+            accounts,
+            data,
+            index: 0,
+            inner_index,
+        }
+    }
+
+    /// Swap between a WSOL vault (4) and a token vault (5), with the trader's SOL and token
+    /// accounts at 6 and 7 and the mints at 8 (WSOL) and 9.
+    fn vault_swap_block(amount_in: u64, amount_out: u64, sells_token: bool) -> BlockRef {
+        let (pay, receive) = if sells_token {
+            // token account -> token vault, WSOL vault -> SOL account
+            (
+                inner_transfer_checked(vec![7, 9, 5, 0], amount_in, 0),
+                inner_transfer_checked(vec![4, 8, 6, 3], amount_out, 1),
+            )
+        } else {
+            (
+                inner_transfer_checked(vec![6, 8, 4, 0], amount_in, 0),
+                inner_transfer_checked(vec![5, 9, 7, 3], amount_out, 1),
+            )
+        };
+        BlockRef {
+            slot: 105,
+            block_time_unix: Some(1005),
+            transactions: vec![TransactionRef {
+                signature: "vault_swap_sig".to_string(),
+                index: 0,
+                message: MessageRef {
+                    account_keys: vec![
+                        "trader_wallet".to_string(),
+                        "token_program".to_string(),
+                        "pool_account".to_string(),
+                        "authority".to_string(),
+                        "wsol_vault".to_string(),
+                        "token_vault".to_string(),
+                        "trader_wsol".to_string(),
+                        "trader_token".to_string(),
+                        WSOL_MINT.to_string(),
+                        "token_mint".to_string(),
+                    ],
+                },
+                instructions: vec![
+                    InstructionRef {
+                        program_id: RAYDIUM_AMM_V4_PROGRAM_ID.to_string(),
+                        accounts: vec![0, 1, 2, 3, 4, 5],
+                        data: create_raydium_swap_instruction(amount_in, amount_out),
+                        index: 0,
+                        inner_index: TOP_LEVEL_INNER_INDEX,
+                    },
+                    pay,
+                    receive,
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_raydium_side_follows_vault_paid_into() {
+        // 1 SOL buying 0.05 tokens: the ratio heuristic would call this a sell.
+        let block = vault_swap_block(1_000_000_000, 50_000_000, false);
+        let trades = extract_raydium_trades_from_block(&block);
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].side, "buy");
+        assert_eq!(trades[0].mint_pubkey, "token_mint");
+        assert_eq!(trades[0].sol_amount, 1_000_000_000);
+        assert_eq!(trades[0].token_amount, 50_000_000);
+
+        // 0.001 tokens selling for 2 SOL: the ratio heuristic would call this a buy.
+        let block = vault_swap_block(1_000_000, 2_000_000_000, true);
+        let trades = extract_raydium_trades_from_block(&block);
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].side, "sell");
+        assert_eq!(trades[0].mint_pubkey, "token_mint");
+        assert_eq!(trades[0].sol_amount, 2_000_000_000);
+        assert_eq!(trades[0].token_amount, 1_000_000);
+    }
+
+    #[test]
+    fn test_raydium_skips_usdc_quoted_swaps() {
+        // 50 USDC buying tokens: there's no SOL amount to record.
+        let mut block = vault_swap_block(50_000_000, 2_000_000_000, false);
+        block.transactions[0].message.account_keys[8] = USDC_MINT.to_string();
+        let counters = ParseCounters::default();
+        let mut accounts = TokenAccountCache::new(16);
+        let price_scale = PriceScale::default();
+        let trades =
+            extract_raydium_trades_with(&block, &mut accounts, Some(&counters), &price_scale);
+        assert!(trades.is_empty());
+        assert_eq!(counters.misses(), 1);
+
+        // Without the transfers, vault mints cached from earlier blocks skip it too.
+        block.transactions[0].instructions.truncate(1);
+        let mut accounts = TokenAccountCache::new(16);
+        accounts.insert("wsol_vault", USDC_MINT, None);
+        accounts.insert("token_vault", "token_mint", None);
+        let trades = extract_raydium_trades_with(&block, &mut accounts, None, &price_scale);
+        assert!(trades.is_empty());
+    }
+
     #[test]
     fn test_raydium_swap_exact_tokens_parsing() {
         let block = BlockRef {
@@ -174,6 +355,7 @@ mod tests {
                     accounts: vec![0, 1, 2, 3, 4, 5],
                     data: create_raydium_swap_instruction(1_000_000_000, 5_000_000_000), // 1 SOL → 5B tokens
                     index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
                 }],
             }],
         };
@@ -184,6 +366,17 @@ mod tests {
         assert_eq!(trades[0].token_amount, 5_000_000_000);
         assert_eq!(trades[0].trader, "trader_wallet");
         assert_eq!(trades[0].side, "buy");
+        // Vault mints unknown: attributed to the pool account.
+        assert_eq!(trades[0].mint_pubkey, "pool_account");
+
+        // Vault mints known from earlier blocks: attributed to the non-SOL vault's mint.
+        let mut accounts = TokenAccountCache::new(16);
+        accounts.insert("input_token", WSOL_MINT, None);
+        accounts.insert("output_token", "token_mint", None);
+        let trades =
+            extract_raydium_trades_with(&block, &mut accounts, None, &PriceScale::default());
+        assert_eq!(trades[0].mint_pubkey, "token_mint");
+        assert_eq!(trades[0].side, "buy");
     }
 
     #[test]
@@ -209,6 +402,7 @@ mod tests {
                     accounts: vec![0, 1, 2, 3, 4, 5],
                     data: create_raydium_swap_instruction(10_000_000_000, 50_000_000), // 10B tokens → 0.05 SOL
                     index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
                 }],
             }],
         };
@@ -249,6 +443,7 @@ mod tests {
                         accounts: vec![0, 1, 2, 3, 4, 5],
                         data: create_raydium_swap_instruction(100_000_000, 1_000_000_000),
                         index: 0,
+                        inner_index: TOP_LEVEL_INNER_INDEX,
                     }],
                 },
                 TransactionRef {
@@ -269,6 +464,7 @@ mod tests {
                         accounts: vec![0, 1, 2, 3, 4, 5],
                         data: create_raydium_swap_instruction(5_000_000_000, 100_000_000),
                         index: 0,
+                        inner_index: TOP_LEVEL_INNER_INDEX,
                     }],
                 },
             ],
@@ -298,6 +494,7 @@ mod tests {
                     accounts: vec![0], // Only 1 account, need at least 3
                     data: create_raydium_swap_instruction(1_000_000, 1_000_000),
                     index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
                 }],
             }],
         };
@@ -327,6 +524,7 @@ mod tests {
                     accounts: vec![0, 1, 2, 3],
                     data: create_raydium_swap_instruction(1_000_000, 1_000_000),
                     index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
                 }],
             }],
         };
@@ -351,6 +549,7 @@ mod tests {
                     accounts: vec![0, 1, 2, 3],
                     data: vec![9, 1, 2], // Too short (need 17 bytes)
                     index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
                 }],
             }],
         };
@@ -1,6 +1,6 @@
 // Redis streams integration for real-time event publishing and consumption
 use anyhow::Result;
-use redis::{aio::ConnectionManager, Client};
+use redis::{aio::ConnectionManager, from_owned_redis_value, Client, Cmd, Value};
 use serde_json::json;
 use std::collections::HashMap;
 use tracing::info;
@@ -116,6 +116,77 @@ impl RedisPublisher {
     }
 }
 
+/// How long `XREADGROUP` waits for new entries before returning an empty batch, so worker loops
+/// get a chance to notice shutdown.
+const GROUP_READ_BLOCK_MS: u64 = 5_000;
+
+/// One stream entry delivered to a consumer group. Ack `id` once the entry is processed;
+/// until then it stays in the group's pending-entries list.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamEntry {
+    pub id: String,
+    /// Field/value pairs as written by XADD. Empty for a pending entry that was trimmed from
+    /// the stream before it was acked.
+    pub fields: Vec<(String, String)>,
+}
+
+impl StreamEntry {
+    /// The JSON payload the publishers write under `data`.
+    pub fn data(&self) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == "data")
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// `XGROUP CREATE` for `group` on `stream`, delivering entries after `start_id` ("$" for only
+/// new ones, "0" for the whole stream). MKSTREAM creates the stream if nothing was published yet.
+fn xgroup_create_cmd(stream: &str, group: &str, start_id: &str) -> Cmd {
+    let mut cmd = redis::cmd("XGROUP");
+    cmd.arg("CREATE")
+        .arg(stream)
+        .arg(group)
+        .arg(start_id)
+        .arg("MKSTREAM");
+    cmd
+}
+
+/// `XREADGROUP` of up to `count` entries. `id` ">" asks for never-delivered entries; "0" re-reads
+/// the consumer's own pending entries, which never blocks.
+fn xreadgroup_cmd(stream: &str, group: &str, consumer: &str, count: usize, id: &str) -> Cmd {
+    let mut cmd = redis::cmd("XREADGROUP");
+    cmd.arg("GROUP").arg(group).arg(consumer).arg("COUNT").arg(count);
+    if id == ">" {
+        cmd.arg("BLOCK").arg(GROUP_READ_BLOCK_MS);
+    }
+    cmd.arg("STREAMS").arg(stream).arg(id);
+    cmd
+}
+
+fn xack_cmd(stream: &str, group: &str, ids: &[String]) -> Cmd {
+    let mut cmd = redis::cmd("XACK");
+    cmd.arg(stream).arg(group).arg(ids);
+    cmd
+}
+
+/// Flatten an `XREADGROUP` reply (`[[stream, [[id, [field, value, ...]], ...]]]`, or nil when
+/// BLOCK timed out) into its entries.
+fn parse_group_reply(reply: Value) -> Result<Vec<StreamEntry>> {
+    let streams = reply
+        .into_sequence()
+        .map_err(|v| anyhow::anyhow!("unexpected XREADGROUP reply: {:?}", v))?;
+    let mut entries = Vec::new();
+    for stream in streams {
+        let (_key, items): (String, Vec<Value>) = from_owned_redis_value(stream)?;
+        for item in items {
+            let (id, fields): (String, Vec<(String, String)>) = from_owned_redis_value(item)?;
+            entries.push(StreamEntry { id, fields });
+        }
+    }
+    Ok(entries)
+}
+
 pub struct RedisConsumer {
     client: ConnectionManager,
     key_prefix: String,
@@ -189,6 +260,72 @@ impl RedisConsumer {
 
         Ok(result)
     }
+
+    /// Stream key of a venue's trades for `token_mint`, as written by [`RedisPublisher`].
+    pub fn trades_stream(&self, venue: &str, token_mint: &str) -> String {
+        format!("{}trades:{}:{}", self.key_prefix, venue, token_mint)
+    }
+
+    /// Stream key of `token_mint`'s transfers, as written by [`RedisPublisher`].
+    pub fn transfers_stream(&self, token_mint: &str) -> String {
+        format!("{}transfers:{}", self.key_prefix, token_mint)
+    }
+
+    /// Create consumer group `group` on `stream` if it doesn't exist yet. `start_id` only
+    /// matters on creation: "$" delivers entries published from now on, "0" the whole stream.
+    pub async fn create_group(&self, stream: &str, group: &str, start_id: &str) -> Result<()> {
+        let created: redis::RedisResult<()> = xgroup_create_cmd(stream, group, start_id)
+            .query_async(&mut self.client.clone())
+            .await;
+        match created {
+            Err(e) if e.code() == Some("BUSYGROUP") => Ok(()),
+            other => Ok(other?),
+        }
+    }
+
+    /// Read up to `count` entries no consumer of `group` has been given yet, waiting up to
+    /// [`GROUP_READ_BLOCK_MS`] for some to arrive. Each entry stays pending for `consumer`
+    /// until it is passed to [`RedisConsumer::ack`], so a crash means redelivery, not loss.
+    pub async fn read_group(
+        &self,
+        stream: &str,
+        group: &str,
+        consumer: &str,
+        count: usize,
+    ) -> Result<Vec<StreamEntry>> {
+        let reply: Value = xreadgroup_cmd(stream, group, consumer, count, ">")
+            .query_async(&mut self.client.clone())
+            .await?;
+        parse_group_reply(reply)
+    }
+
+    /// Up to `count` entries delivered to `consumer` but never acked, oldest first. A worker
+    /// that restarts under the same consumer name drains these before calling
+    /// [`RedisConsumer::read_group`].
+    pub async fn read_pending(
+        &self,
+        stream: &str,
+        group: &str,
+        consumer: &str,
+        count: usize,
+    ) -> Result<Vec<StreamEntry>> {
+        let reply: Value = xreadgroup_cmd(stream, group, consumer, count, "0")
+            .query_async(&mut self.client.clone())
+            .await?;
+        parse_group_reply(reply)
+    }
+
+    /// Acknowledge processed entries, removing them from the pending-entries list. Returns how
+    /// many were still pending.
+    pub async fn ack(&self, stream: &str, group: &str, ids: &[String]) -> Result<u64> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let acked = xack_cmd(stream, group, ids)
+            .query_async(&mut self.client.clone())
+            .await?;
+        Ok(acked)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -228,6 +365,68 @@ mod tests {
         assert_eq!(transfer_key, "indexer:transfers:ABC123");
     }
 
+    fn cmd_args(cmd: &Cmd) -> Vec<String> {
+        cmd.args_iter()
+            .map(|arg| match arg {
+                redis::Arg::Simple(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+                redis::Arg::Cursor => "<cursor>".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_redis_group_command_construction() {
+        let stream = "indexer:trades:pump:ABC123";
+
+        assert_eq!(
+            cmd_args(&xgroup_create_cmd(stream, "candles", "$")),
+            ["XGROUP", "CREATE", stream, "candles", "$", "MKSTREAM"]
+        );
+        assert_eq!(
+            cmd_args(&xreadgroup_cmd(stream, "candles", "worker-1", 100, ">")),
+            [
+                "XREADGROUP", "GROUP", "candles", "worker-1", "COUNT", "100", "BLOCK", "5000",
+                "STREAMS", stream, ">",
+            ]
+        );
+        // Re-reading the pending list must not block.
+        assert_eq!(
+            cmd_args(&xreadgroup_cmd(stream, "candles", "worker-1", 10, "0")),
+            ["XREADGROUP", "GROUP", "candles", "worker-1", "COUNT", "10", "STREAMS", stream, "0"]
+        );
+        let ids = vec!["1-0".to_string(), "1-1".to_string()];
+        assert_eq!(
+            cmd_args(&xack_cmd(stream, "candles", &ids)),
+            ["XACK", stream, "candles", "1-0", "1-1"]
+        );
+    }
+
+    #[test]
+    fn test_parse_group_reply() {
+        let data = |s: &str| Value::Data(s.as_bytes().to_vec());
+        let reply = Value::Bulk(vec![Value::Bulk(vec![
+            data("indexer:transfers:ABC123"),
+            Value::Bulk(vec![
+                Value::Bulk(vec![
+                    data("1700000000000-0"),
+                    Value::Bulk(vec![data("data"), data(r#"{"amount":5}"#)]),
+                ]),
+                // Pending entry trimmed from the stream before it was acked.
+                Value::Bulk(vec![data("1700000000000-1"), Value::Nil]),
+            ]),
+        ])]);
+
+        let entries = parse_group_reply(reply).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, "1700000000000-0");
+        assert_eq!(entries[0].data(), Some(r#"{"amount":5}"#));
+        assert_eq!(entries[1].id, "1700000000000-1");
+        assert!(entries[1].fields.is_empty());
+
+        // BLOCK timed out with nothing new.
+        assert!(parse_group_reply(Value::Nil).unwrap().is_empty());
+    }
+
     #[test]
     fn test_trade_event_serialization() {
         let event = TradeEvent {
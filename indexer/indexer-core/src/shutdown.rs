@@ -0,0 +1,36 @@
+// Process shutdown signal shared by indexer-bin and indexer-api.
+
+use tracing::{error, info};
+
+/// Resolves when the process receives SIGTERM (e.g. `docker stop`) or Ctrl-C.
+/// If a handler can't be installed, that source is ignored rather than triggering shutdown.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            error!("failed to listen for Ctrl-C: {err:?}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(err) => {
+                error!("failed to listen for SIGTERM: {err:?}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("received Ctrl-C, shutting down"),
+        _ = terminate => info!("received SIGTERM, shutting down"),
+    }
+}
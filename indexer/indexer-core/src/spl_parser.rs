@@ -1,57 +1,335 @@
-use crate::models::TokenTransfer;
+use crate::models::{Mint, TokenEvent, TokenTransfer};
 use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
 
  /// Placeholder types for Firehose-derived data structures.
  /// In real integration, replace these with jetstreamer_firehose / Solana types.
- #[derive(Debug)]
+ #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
  pub struct BlockRef {
      pub slot: i64,
      pub block_time_unix: Option<i64>,
      pub transactions: Vec<TransactionRef>,
  }
 
- #[derive(Debug)]
+ impl BlockRef {
+     /// Decode a block previously encoded with [`BlockRef::to_bytes`] (one JSON document).
+     pub fn from_bytes(bytes: &[u8]) -> serde_json::Result<Self> {
+         serde_json::from_slice(bytes)
+     }
+
+     /// Encode the block as a single-line JSON document, as stored in the block cache.
+     pub fn to_bytes(&self) -> serde_json::Result<Vec<u8>> {
+         serde_json::to_vec(self)
+     }
+ }
+
+ #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
  pub struct TransactionRef {
      pub signature: String,
      pub index: i32,
      pub message: MessageRef,
+     /// Top-level instructions, each followed by the inner (CPI) instructions it invoked, told
+     /// apart by `inner_index`. Parsers walk all of them: a DEX swap's token transfers are
+     /// usually inner instructions under the DEX program's top-level call.
      pub instructions: Vec<InstructionRef>,
  }
 
- #[derive(Debug)]
+ #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
  pub struct MessageRef {
      pub account_keys: Vec<String>,
  }
 
- #[derive(Debug)]
+ #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
  pub struct InstructionRef {
      pub program_id: String,
      pub accounts: Vec<u8>,
      pub data: Vec<u8>,
+     /// Position of the top-level instruction in the transaction. Inner (CPI) instructions
+     /// carry the index of the top-level instruction that invoked them.
      pub index: i32,
+     /// Position among the inner instructions of `index`, or [`TOP_LEVEL_INNER_INDEX`] for the
+     /// top-level instruction itself. Rows are deduplicated on `(signature, index, inner_index)`.
+     #[serde(default = "top_level_inner_index")]
+     pub inner_index: i32,
+ }
+
+ /// `inner_index` of a top-level (non-CPI) instruction.
+ pub const TOP_LEVEL_INNER_INDEX: i32 = -1;
+
+ fn top_level_inner_index() -> i32 {
+     TOP_LEVEL_INNER_INDEX
+ }
+
+ /// Per-parser diagnostics for one block, surfaced through /metrics.
+ #[derive(Debug, Default)]
+ pub struct ParseCounters {
+     /// Instructions that matched the parser's program but could not be decoded.
+     pub misses: AtomicU64,
+     /// Instructions referencing an account index past the transaction's account keys. These
+     /// are corrupt input rather than unrecognised instructions and are dropped unparsed.
+     pub bad_indices: AtomicU64,
+     /// Instructions whose parser panicked (see [`parse_guarded`]); also counted as misses by
+     /// the caller. Always a parser bug.
+     pub panics: AtomicU64,
+ }
+
+ impl ParseCounters {
+     pub fn miss(&self) {
+         self.misses.fetch_add(1, Ordering::Relaxed);
+     }
+
+     pub fn misses(&self) -> u64 {
+         self.misses.load(Ordering::Relaxed)
+     }
+
+     pub fn bad_index(&self) {
+         self.bad_indices.fetch_add(1, Ordering::Relaxed);
+     }
+
+     pub fn bad_indices(&self) -> u64 {
+         self.bad_indices.load(Ordering::Relaxed)
+     }
+
+     pub fn panicked(&self) {
+         self.panics.fetch_add(1, Ordering::Relaxed);
+     }
+
+     pub fn panics(&self) -> u64 {
+         self.panics.load(Ordering::Relaxed)
+     }
+ }
+
+ /// Check that every account index of `ix` resolves in `tx`'s account keys. Otherwise log the
+ /// instruction, count it in `counters` and return false so the caller skips it.
+ pub fn account_indices_in_range(
+     parser: &str,
+     tx: &TransactionRef,
+     ix: &InstructionRef,
+     counters: Option<&ParseCounters>,
+ ) -> bool {
+     let keys = tx.message.account_keys.len();
+     let Some(&bad) = ix.accounts.iter().find(|&&i| i as usize >= keys) else {
+         return true;
+     };
+     tracing::debug!(
+         "{parser}: tx {} ix {}/{} references account {bad} of {keys}",
+         tx.signature,
+         ix.index,
+         ix.inner_index
+     );
+     if let Some(c) = counters {
+         c.bad_index();
+     }
+     false
+ }
+
+ /// Run one instruction's parser, turning a panic (e.g. a slicing bug hit by malformed data)
+ /// into `None` so a single instruction can't take down the writer. The panic is logged with
+ /// the instruction's location and counted in `counters`; callers count the `None` like any
+ /// other failed parse.
+ pub fn parse_guarded<T>(
+     parser: &str,
+     tx: &TransactionRef,
+     ix: &InstructionRef,
+     counters: Option<&ParseCounters>,
+     parse: impl FnOnce() -> Option<T>,
+ ) -> Option<T> {
+     match panic::catch_unwind(AssertUnwindSafe(parse)) {
+         Ok(parsed) => parsed,
+         Err(payload) => {
+             let message = payload
+                 .downcast_ref::<&str>()
+                 .copied()
+                 .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                 .unwrap_or("non-string panic payload");
+             tracing::error!(
+                 "{parser}: parser panicked on tx {} ix {}/{}, skipping it: {message}",
+                 tx.signature,
+                 ix.index,
+                 ix.inner_index
+             );
+             if let Some(c) = counters {
+                 c.panicked();
+             }
+             None
+         }
+     }
  }
 
  /// SPL Token program id on Solana mainnet.
  pub const SPL_TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+ /// Token-2022 program id. Its base instructions share SPL Token's discriminators and account
+ /// layouts, so both are parsed the same way.
+ pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+
+ /// Whether `program_id` is SPL Token or Token-2022.
+ pub fn is_token_program(program_id: &str) -> bool {
+     program_id == SPL_TOKEN_PROGRAM_ID || program_id == TOKEN_2022_PROGRAM_ID
+ }
+
+ /// Owner recorded as the source of MintTo and the destination of Burn. These sentinels are not
+ /// wallets, so they never hold a balance.
+ pub const MINT_SOURCE_OWNER: &str = "system";
+ pub const BURN_DEST_OWNER: &str = "burn";
 
  /// SPL Token instruction discriminators.
  pub const INSTR_TRANSFER: u8 = 3;
  pub const INSTR_TRANSFER_CHECKED: u8 = 12;
  pub const INSTR_MINT_TO: u8 = 7;
- pub const INSTR_MINT_TO_CHECKED: u8 = 13;
+ pub const INSTR_MINT_TO_CHECKED: u8 = 14;
  pub const INSTR_BURN: u8 = 8;
- pub const INSTR_BURN_CHECKED: u8 = 14;
+ pub const INSTR_BURN_CHECKED: u8 = 15;
+ /// Instructions only read for the mint's decimals (see [`extract_mint_decimals`]).
+ /// Accounts: [mint, ...]. Data: [discriminator] [decimals: 1 byte] [mint_authority: 32 bytes] ...
+ pub const INSTR_INITIALIZE_MINT: u8 = 0;
+ pub const INSTR_INITIALIZE_MINT2: u8 = 20;
+ /// Accounts: [source, mint, delegate, owner]. Data: [discriminator] [amount: u64 LE] [decimals]
+ pub const INSTR_APPROVE_CHECKED: u8 = 13;
+ /// Account initialization, only read to learn each token account's mint and owner (see
+ /// [`TokenAccountCache`]).
+ /// Accounts: [account, mint, owner, rent_sysvar]
+ pub const INSTR_INITIALIZE_ACCOUNT: u8 = 1;
+ /// Accounts: [account, mint, rent_sysvar]. Data: [discriminator] [owner: 32 bytes]
+ pub const INSTR_INITIALIZE_ACCOUNT2: u8 = 16;
+ /// Accounts: [account, mint]. Data: [discriminator] [owner: 32 bytes]
+ pub const INSTR_INITIALIZE_ACCOUNT3: u8 = 18;
+
+ /// Non-transfer instructions kept as safety signals (see [`extract_token_events`]).
+ pub const INSTR_APPROVE: u8 = 4;
+ pub const INSTR_REVOKE: u8 = 5;
+ pub const INSTR_CLOSE_ACCOUNT: u8 = 9;
+
+ /// `TokenEvent::kind` values.
+ pub const EVENT_APPROVE: &str = "approve";
+ pub const EVENT_REVOKE: &str = "revoke";
+ pub const EVENT_CLOSE_ACCOUNT: &str = "close_account";
+
+ /// Default number of token accounts remembered by [`TokenAccountCache`].
+ pub const DEFAULT_TOKEN_ACCOUNT_CACHE_SIZE: usize = 100_000;
+
+ /// What is known about one token account.
+ #[derive(Debug, Clone, PartialEq, Eq)]
+ pub struct TokenAccount {
+     pub mint: String,
+     /// The wallet that owns the account, once its InitializeAccount has been seen.
+     pub owner: Option<String>,
+ }
+
+ /// Token account -> mint (and owner), learned from the instructions that name them (see
+ /// [`account_mint_positions`] and [`initialized_account_owner`]). Plain `Transfer` only names
+ /// the token accounts, so its mint is looked up here, and every transfer's owners are.
+ /// Bounded: the least recently used account is evicted past `capacity`.
+ #[derive(Debug, Clone)]
+ pub struct TokenAccountCache {
+     capacity: usize,
+     clock: u64,
+     /// account -> (what is known, last use)
+     entries: HashMap<String, (TokenAccount, u64)>,
+     /// last use -> account, oldest first
+     recency: BTreeMap<u64, String>,
+ }
+
+ impl TokenAccountCache {
+     pub fn new(capacity: usize) -> Self {
+         Self { capacity, clock: 0, entries: HashMap::new(), recency: BTreeMap::new() }
+     }
+
+     pub fn len(&self) -> usize {
+         self.entries.len()
+     }
+
+     pub fn is_empty(&self) -> bool {
+         self.entries.is_empty()
+     }
+
+     /// What is known about `account`, marking it as recently used.
+     pub fn get(&mut self, account: &str) -> Option<&TokenAccount> {
+         self.clock += 1;
+         let (known, used) = self.entries.get_mut(account)?;
+         self.recency.remove(used);
+         *used = self.clock;
+         self.recency.insert(self.clock, account.to_string());
+         Some(known)
+     }
+
+     /// Record `account`'s mint. Without an `owner`, an owner already known for the same mint
+     /// is kept; a different mint means the account was closed and reinitialized.
+     pub fn insert(&mut self, account: &str, mint: &str, owner: Option<&str>) {
+         self.clock += 1;
+         let mut owner = owner.map(str::to_string);
+         if let Some((known, used)) = self.entries.remove(account) {
+             self.recency.remove(&used);
+             if owner.is_none() && known.mint == mint {
+                 owner = known.owner;
+             }
+         }
+         let known = TokenAccount { mint: mint.to_string(), owner };
+         self.entries.insert(account.to_string(), (known, self.clock));
+         self.recency.insert(self.clock, account.to_string());
+         while self.entries.len() > self.capacity {
+             let Some((_, oldest)) = self.recency.pop_first() else {
+                 break;
+             };
+             self.entries.remove(&oldest);
+         }
+     }
+
+     /// The wallet owning `account`, or `account` itself when its owner isn't known.
+     fn owner_or_account(&mut self, account: &str) -> String {
+         self.get(account)
+             .and_then(|known| known.owner.clone())
+             .unwrap_or_else(|| account.to_string())
+     }
+
+     /// Remember every token account -> mint pair `ix` names, with the owner if it initializes
+     /// the account.
+     fn learn(&mut self, tx: &TransactionRef, ix: &InstructionRef) {
+         let owner = initialized_account_owner(tx, ix);
+         for (account, mint) in instruction_account_mints(tx, ix) {
+             self.insert(account, mint, owner.as_deref());
+         }
+     }
+ }
 
+ impl Default for TokenAccountCache {
+     fn default() -> Self {
+         Self::new(DEFAULT_TOKEN_ACCOUNT_CACHE_SIZE)
+     }
+ }
+
+ /// Parse one block on its own: a plain `Transfer` only resolves if the same block names its
+ /// source account's mint. The writer keeps a [`TokenAccountCache`] across blocks instead.
  pub fn extract_transfers_from_block(block: &BlockRef, mint_whitelist: &[String]) -> Vec<TokenTransfer> {
+     extract_transfers_with(block, mint_whitelist, &mut TokenAccountCache::default(), None)
+ }
+
+ /// Same as [`extract_transfers_from_block`], resolving plain `Transfer` mints through (and
+ /// teaching) `accounts`, and adding to `counters` every token instruction with an
+ /// out-of-range account index.
+ pub fn extract_transfers_with(
+     block: &BlockRef,
+     mint_whitelist: &[String],
+     accounts: &mut TokenAccountCache,
+     counters: Option<&ParseCounters>,
+ ) -> Vec<TokenTransfer> {
      let mut transfers = Vec::new();
 
      for tx in &block.transactions {
          for ix in &tx.instructions {
-             if ix.program_id != SPL_TOKEN_PROGRAM_ID {
+             if !is_token_program(&ix.program_id) {
                  continue;
              }
+             if !account_indices_in_range("spl", tx, ix, counters) {
+                 continue;
+             }
+             accounts.learn(tx, ix);
 
-             if let Some(t) = parse_spl_transfer(block, tx, ix, mint_whitelist) {
+             if let Some(t) = parse_guarded("spl", tx, ix, counters, || {
+                 parse_spl_transfer(block, tx, ix, mint_whitelist, accounts)
+             }) {
                  transfers.push(t);
              }
          }
@@ -65,6 +343,7 @@ use chrono::{TimeZone, Utc};
      tx: &TransactionRef,
      ix: &InstructionRef,
      mint_whitelist: &[String],
+     accounts: &mut TokenAccountCache,
  ) -> Option<TokenTransfer> {
      if ix.data.is_empty() {
          return None;
@@ -73,31 +352,34 @@ use chrono::{TimeZone, Utc};
      let instr_type = ix.data[0];
 
      match instr_type {
-         INSTR_TRANSFER => parse_transfer(block, tx, ix, mint_whitelist),
-         INSTR_TRANSFER_CHECKED => parse_transfer_checked(block, tx, ix, mint_whitelist),
-         INSTR_MINT_TO => parse_mint_to(block, tx, ix, mint_whitelist),
-         INSTR_MINT_TO_CHECKED => parse_mint_to_checked(block, tx, ix, mint_whitelist),
-         INSTR_BURN => parse_burn(block, tx, ix, mint_whitelist),
-         INSTR_BURN_CHECKED => parse_burn_checked(block, tx, ix, mint_whitelist),
+         INSTR_TRANSFER => parse_transfer(block, tx, ix, mint_whitelist, accounts),
+         INSTR_TRANSFER_CHECKED => parse_transfer_checked(block, tx, ix, mint_whitelist, accounts),
+         INSTR_MINT_TO => parse_mint_to(block, tx, ix, mint_whitelist, accounts),
+         INSTR_MINT_TO_CHECKED => parse_mint_to_checked(block, tx, ix, mint_whitelist, accounts),
+         INSTR_BURN => parse_burn(block, tx, ix, mint_whitelist, accounts),
+         INSTR_BURN_CHECKED => parse_burn_checked(block, tx, ix, mint_whitelist, accounts),
          _ => None,
      }
  }
 
  /// Parse Transfer instruction (3):
- /// Accounts: [source_token_account, mint, dest_token_account, owner_or_delegate]
+ /// Accounts: [source_token_account, dest_token_account, owner_or_delegate]
  /// Data: [discriminator: 1 byte] [amount: u64 LE]
+ /// The mint isn't named, so it comes from `accounts`; a source account it hasn't seen is
+ /// skipped. The destination holds the same mint, so it is learned here.
  fn parse_transfer(
      block: &BlockRef,
      tx: &TransactionRef,
      ix: &InstructionRef,
      mint_whitelist: &[String],
+     accounts: &mut TokenAccountCache,
  ) -> Option<TokenTransfer> {
      if ix.accounts.len() < 3 {
          return None;
      }
 
      let source_ata_idx = ix.accounts.get(0).copied()? as usize;
-     let dest_ata_idx = ix.accounts.get(2).copied()? as usize;
+     let dest_ata_idx = ix.accounts.get(1).copied()? as usize;
 
      let source_ata = tx.message.account_keys.get(source_ata_idx)?.clone();
      let dest_ata = tx.message.account_keys.get(dest_ata_idx)?.clone();
@@ -105,14 +387,13 @@ use chrono::{TimeZone, Utc};
      // Amount is at bytes 1..9 (u64 LE).
      let amount = read_u64_le(&ix.data[1..])?;
 
-     // For Transfer, we need to know the mint from on-chain data, which we might not have.
-     // For now, we'll accept any transfer if mint_whitelist is empty, or skip if we can't match.
-     // In a real scenario, we'd cache token account -> mint mappings from Firehose account state.
-     
-     // If mint_whitelist is provided, we can't match without mint knowledge, so skip.
-     // If mint_whitelist is empty, we'll allow the transfer but mint_pubkey is a placeholder.
-     if !mint_whitelist.is_empty() {
-         // Can't determine mint from instruction alone; would need on-chain account data.
+     let Some(mint) = accounts.get(&source_ata).map(|known| known.mint.clone()) else {
+         tracing::trace!("spl: tx {} ix {}: unknown token account {source_ata}", tx.signature, ix.index);
+         return None;
+     };
+     accounts.insert(&dest_ata, &mint, None);
+
+     if !mint_whitelist.is_empty() && !mint_whitelist.contains(&mint) {
          return None;
      }
 
@@ -125,14 +406,16 @@ use chrono::{TimeZone, Utc};
          signature: tx.signature.clone(),
          slot: block.slot,
          block_time,
-         mint_pubkey: "unknown_mint".to_string(),
-         source_owner: source_ata.clone(),
-         dest_owner: dest_ata.clone(),
+         mint_pubkey: mint,
+         source_owner: accounts.owner_or_account(&source_ata),
+         dest_owner: accounts.owner_or_account(&dest_ata),
          source_ata,
          dest_ata,
          amount: amount as i64,
          tx_index: tx.index,
          ix_index: ix.index,
+         inner_index: ix.inner_index,
+         program: ix.program_id.clone(),
      })
  }
 
@@ -144,6 +427,7 @@ use chrono::{TimeZone, Utc};
      tx: &TransactionRef,
      ix: &InstructionRef,
      mint_whitelist: &[String],
+     accounts: &mut TokenAccountCache,
  ) -> Option<TokenTransfer> {
      if ix.accounts.len() < 3 || ix.data.len() < 10 {
          return None;
@@ -174,13 +458,15 @@ use chrono::{TimeZone, Utc};
          slot: block.slot,
          block_time,
          mint_pubkey,
-         source_owner: source_ata.clone(),
-         dest_owner: dest_ata.clone(),
+         source_owner: accounts.owner_or_account(&source_ata),
+         dest_owner: accounts.owner_or_account(&dest_ata),
          source_ata,
          dest_ata,
          amount: amount as i64,
          tx_index: tx.index,
          ix_index: ix.index,
+         inner_index: ix.inner_index,
+         program: ix.program_id.clone(),
      })
  }
 
@@ -192,6 +478,7 @@ use chrono::{TimeZone, Utc};
      tx: &TransactionRef,
      ix: &InstructionRef,
      mint_whitelist: &[String],
+     accounts: &mut TokenAccountCache,
  ) -> Option<TokenTransfer> {
      if ix.accounts.len() < 2 || ix.data.len() < 9 {
          return None;
@@ -219,17 +506,19 @@ use chrono::{TimeZone, Utc};
          slot: block.slot,
          block_time,
          mint_pubkey,
-         source_owner: "system".to_string(), // MintTo has no source_owner, use system
-         dest_owner: dest_ata.clone(),
+         source_owner: MINT_SOURCE_OWNER.to_string(), // MintTo has no source_owner
+         dest_owner: accounts.owner_or_account(&dest_ata),
          source_ata: "system".to_string(),
          dest_ata,
          amount: amount as i64,
          tx_index: tx.index,
          ix_index: ix.index,
+         inner_index: ix.inner_index,
+         program: ix.program_id.clone(),
      })
  }
 
- /// Parse MintToChecked instruction (13):
+ /// Parse MintToChecked instruction (14):
  /// Accounts: [mint, dest_token_account, mint_authority]
  /// Data: [discriminator: 1 byte] [token_amount: u64 LE] [decimals: 1 byte]
  fn parse_mint_to_checked(
@@ -237,6 +526,7 @@ use chrono::{TimeZone, Utc};
      tx: &TransactionRef,
      ix: &InstructionRef,
      mint_whitelist: &[String],
+     accounts: &mut TokenAccountCache,
  ) -> Option<TokenTransfer> {
      if ix.accounts.len() < 2 || ix.data.len() < 10 {
          return None;
@@ -264,13 +554,15 @@ use chrono::{TimeZone, Utc};
          slot: block.slot,
          block_time,
          mint_pubkey,
-         source_owner: "system".to_string(),
-         dest_owner: dest_ata.clone(),
+         source_owner: MINT_SOURCE_OWNER.to_string(),
+         dest_owner: accounts.owner_or_account(&dest_ata),
          source_ata: "system".to_string(),
          dest_ata,
          amount: amount as i64,
          tx_index: tx.index,
          ix_index: ix.index,
+         inner_index: ix.inner_index,
+         program: ix.program_id.clone(),
      })
  }
 
@@ -282,6 +574,7 @@ use chrono::{TimeZone, Utc};
      tx: &TransactionRef,
      ix: &InstructionRef,
      mint_whitelist: &[String],
+     accounts: &mut TokenAccountCache,
  ) -> Option<TokenTransfer> {
      if ix.accounts.len() < 2 || ix.data.len() < 9 {
          return None;
@@ -309,17 +602,19 @@ use chrono::{TimeZone, Utc};
          slot: block.slot,
          block_time,
          mint_pubkey,
-         source_owner: source_ata.clone(),
-         dest_owner: "burn".to_string(), // Burn targets void
+         source_owner: accounts.owner_or_account(&source_ata),
+         dest_owner: BURN_DEST_OWNER.to_string(), // Burn targets void
          source_ata,
          dest_ata: "burn".to_string(),
          amount: amount as i64,
          tx_index: tx.index,
          ix_index: ix.index,
+         inner_index: ix.inner_index,
+         program: ix.program_id.clone(),
      })
  }
 
- /// Parse BurnChecked instruction (14):
+ /// Parse BurnChecked instruction (15):
  /// Accounts: [token_account, mint, owner_or_delegate]
  /// Data: [discriminator: 1 byte] [token_amount: u64 LE] [decimals: 1 byte]
  fn parse_burn_checked(
@@ -327,6 +622,7 @@ use chrono::{TimeZone, Utc};
      tx: &TransactionRef,
      ix: &InstructionRef,
      mint_whitelist: &[String],
+     accounts: &mut TokenAccountCache,
  ) -> Option<TokenTransfer> {
      if ix.accounts.len() < 2 || ix.data.len() < 10 {
          return None;
@@ -354,13 +650,283 @@ use chrono::{TimeZone, Utc};
          slot: block.slot,
          block_time,
          mint_pubkey,
-         source_owner: source_ata.clone(),
-         dest_owner: "burn".to_string(),
+         source_owner: accounts.owner_or_account(&source_ata),
+         dest_owner: BURN_DEST_OWNER.to_string(),
          source_ata,
          dest_ata: "burn".to_string(),
          amount: amount as i64,
          tx_index: tx.index,
          ix_index: ix.index,
+         inner_index: ix.inner_index,
+         program: ix.program_id.clone(),
+     })
+ }
+
+ /// Extract delegate approvals, revokes and token-account closes. An approval lets the delegate
+ /// move the owner's tokens; a burst of closes often accompanies a rug.
+ ///
+ /// None of these instructions names the mint. It is taken from another instruction of the
+ /// same transaction that does (checked transfers, mints, burns) and left `None` otherwise, for
+ /// the writer to resolve from stored transfers. Events for mints outside a non-empty
+ /// `mint_whitelist` are dropped. Out-of-range account indices are skipped here and counted by
+ /// [`extract_transfers_with`].
+ pub fn extract_token_events(block: &BlockRef, mint_whitelist: &[String]) -> Vec<TokenEvent> {
+     let block_time = block
+         .block_time_unix
+         .and_then(|t| Utc.timestamp_opt(t, 0).single());
+     let mut events = Vec::new();
+
+     for tx in &block.transactions {
+         let account_mints = token_account_mints(tx);
+         for ix in &tx.instructions {
+             if !is_token_program(&ix.program_id) {
+                 continue;
+             }
+             let Some(mut event) = parse_guarded("spl", tx, ix, None, || parse_token_event(tx, ix))
+             else {
+                 continue;
+             };
+             event.mint_pubkey = account_mints.get(event.token_account.as_str()).map(|m| m.to_string());
+             if let Some(mint) = &event.mint_pubkey {
+                 if !mint_whitelist.is_empty() && !mint_whitelist.contains(mint) {
+                     continue;
+                 }
+             }
+             event.slot = block.slot;
+             event.block_time = block_time;
+             events.push(event);
+         }
+     }
+
+     events
+ }
+
+ /// (mint account position, decimals byte offset) for each token instruction that carries the
+ /// mint's decimals.
+ fn mint_decimals_position(instr: u8) -> Option<(usize, usize)> {
+     match instr {
+         INSTR_TRANSFER_CHECKED | INSTR_APPROVE_CHECKED | INSTR_BURN_CHECKED => Some((1, 9)),
+         INSTR_MINT_TO_CHECKED => Some((0, 9)),
+         INSTR_INITIALIZE_MINT | INSTR_INITIALIZE_MINT2 => Some((0, 1)),
+         _ => None,
+     }
+ }
+
+ /// Decimals of every mint named by a Checked instruction or a mint initialization in `block`,
+ /// as [`Mint`] rows first seen at the block's slot. Mints outside a non-empty `mint_whitelist`
+ /// are dropped.
+ pub fn extract_mint_decimals(block: &BlockRef, mint_whitelist: &[String]) -> Vec<Mint> {
+     let mut mints = Vec::new();
+
+     for tx in &block.transactions {
+         for ix in &tx.instructions {
+             if !is_token_program(&ix.program_id) || ix.data.is_empty() {
+                 continue;
+             }
+             let Some((mint_pos, offset)) = mint_decimals_position(ix.data[0]) else {
+                 continue;
+             };
+             let Some(&decimals) = ix.data.get(offset) else {
+                 continue;
+             };
+             let Some(mint_pubkey) = ix
+                 .accounts
+                 .get(mint_pos)
+                 .and_then(|&idx| tx.message.account_keys.get(idx as usize))
+             else {
+                 continue;
+             };
+             if !mint_whitelist.is_empty() && !mint_whitelist.contains(mint_pubkey) {
+                 continue;
+             }
+             mints.push(Mint {
+                 mint_pubkey: mint_pubkey.clone(),
+                 symbol: None,
+                 decimals: decimals as i32,
+                 first_seen_slot: block.slot,
+             });
+         }
+     }
+
+     mints
+ }
+
+ /// (token account position, mint position) for each token instruction that names both.
+ fn account_mint_positions(instr: u8) -> &'static [(usize, usize)] {
+     match instr {
+         INSTR_TRANSFER_CHECKED => &[(0, 1), (2, 1)],
+         INSTR_MINT_TO | INSTR_MINT_TO_CHECKED => &[(1, 0)],
+         INSTR_BURN | INSTR_BURN_CHECKED => &[(0, 1)],
+         INSTR_INITIALIZE_ACCOUNT | INSTR_INITIALIZE_ACCOUNT2 | INSTR_INITIALIZE_ACCOUNT3 => &[(0, 1)],
+         _ => &[],
+     }
+ }
+
+ /// Token account -> mint pairs named by the token instruction `ix`.
+ fn instruction_account_mints<'a>(
+     tx: &'a TransactionRef,
+     ix: &InstructionRef,
+ ) -> Vec<(&'a str, &'a str)> {
+     if !is_token_program(&ix.program_id) || ix.data.is_empty() {
+         return Vec::new();
+     }
+     let key = |pos: usize| {
+         let idx = *ix.accounts.get(pos)? as usize;
+         tx.message.account_keys.get(idx).map(String::as_str)
+     };
+     account_mint_positions(ix.data[0])
+         .iter()
+         .filter_map(|&(account_pos, mint_pos)| Some((key(account_pos)?, key(mint_pos)?)))
+         .collect()
+ }
+
+ /// The owner set by `ix` if it is one of the InitializeAccount instructions: an account of
+ /// InitializeAccount, the base58 pubkey in the data of InitializeAccount2/3.
+ fn initialized_account_owner(tx: &TransactionRef, ix: &InstructionRef) -> Option<String> {
+     if !is_token_program(&ix.program_id) {
+         return None;
+     }
+     match *ix.data.first()? {
+         INSTR_INITIALIZE_ACCOUNT => {
+             let idx = *ix.accounts.get(2)? as usize;
+             tx.message.account_keys.get(idx).cloned()
+         }
+         INSTR_INITIALIZE_ACCOUNT2 | INSTR_INITIALIZE_ACCOUNT3 => {
+             Some(bs58::encode(ix.data.get(1..33)?).into_string())
+         }
+         _ => None,
+     }
+ }
+
+ /// Token account -> mint for every instruction of `tx` that names both.
+ fn token_account_mints(tx: &TransactionRef) -> HashMap<&str, &str> {
+     tx.instructions.iter().flat_map(|ix| instruction_account_mints(tx, ix)).collect()
+ }
+
+ /// Wrapped SOL, the only mint swaps are priced in: trades store `sol_amount` in lamports, so
+ /// paying WSOL into a pool buys the other side and a pair without it (e.g. token/USDC) isn't
+ /// recorded.
+ pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+ pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+ /// Mints paid into and out of a pool by the SPL transfers swap instruction `ix` made through
+ /// CPI, as `(input, output)`: the trader pays into one of the pool's `vaults` and is paid from
+ /// the other. A vault's mint comes from a transfer of `tx` that names it (see
+ /// [`token_account_mints`]); either side is `None` when no such transfer is found.
+ pub fn swap_vault_mints<'a>(
+     tx: &'a TransactionRef,
+     ix: &InstructionRef,
+     vaults: &[&str],
+ ) -> (Option<&'a str>, Option<&'a str>) {
+     let account_mints = token_account_mints(tx);
+     let (mut input, mut output) = (None, None);
+     for (source, dest, _) in swap_transfers(tx, ix) {
+         if vaults.contains(&dest) {
+             input = input.or(account_mints.get(dest).copied());
+         }
+         if vaults.contains(&source) {
+             output = output.or(account_mints.get(source).copied());
+         }
+     }
+     (input, output)
+ }
+
+ /// Amounts the trader paid into and was paid out of the pool's `vaults` by swap instruction
+ /// `ix`, as `(input, output)`: the executed amounts, from the same transfers as
+ /// [`swap_vault_mints`]. Either side is `None` when no such transfer is found.
+ pub fn swap_vault_amounts(
+     tx: &TransactionRef,
+     ix: &InstructionRef,
+     vaults: &[&str],
+ ) -> (Option<u64>, Option<u64>) {
+     let (mut input, mut output) = (None, None);
+     for (source, dest, amount) in swap_transfers(tx, ix) {
+         if vaults.contains(&dest) {
+             input = input.or(Some(amount));
+         }
+         if vaults.contains(&source) {
+             output = output.or(Some(amount));
+         }
+     }
+     (input, output)
+ }
+
+ /// `(source, destination, amount)` of every SPL transfer swap instruction `ix` made through CPI.
+ fn swap_transfers<'a>(
+     tx: &'a TransactionRef,
+     ix: &'a InstructionRef,
+ ) -> impl Iterator<Item = (&'a str, &'a str, u64)> {
+     let key = |inner: &InstructionRef, pos: usize| {
+         let idx = *inner.accounts.get(pos)? as usize;
+         tx.message.account_keys.get(idx).map(String::as_str)
+     };
+     tx.instructions
+         .iter()
+         .filter(|i| {
+             i.index == ix.index
+                 && i.inner_index != TOP_LEVEL_INNER_INDEX
+                 && is_token_program(&i.program_id)
+         })
+         .filter_map(move |inner| {
+             // (source, destination) positions
+             let (source_pos, dest_pos) = match inner.data.first() {
+                 Some(&INSTR_TRANSFER) => (0, 1),
+                 Some(&INSTR_TRANSFER_CHECKED) => (0, 2),
+                 _ => return None,
+             };
+             let amount = u64::from_le_bytes(inner.data.get(1..9)?.try_into().ok()?);
+             Some((key(inner, source_pos)?, key(inner, dest_pos)?, amount))
+         })
+ }
+
+ /// Side and traded (base) mint of a swap paying `input_mint` for `output_mint`, or `None` when
+ /// either mint is unknown, neither is [`WSOL_MINT`] or both are.
+ pub fn swap_side<'a>(
+     input_mint: Option<&'a str>,
+     output_mint: Option<&'a str>,
+ ) -> Option<(&'static str, &'a str)> {
+     let (input, output) = (input_mint?, output_mint?);
+     match (input == WSOL_MINT, output == WSOL_MINT) {
+         (true, false) => Some(("buy", output)),
+         (false, true) => Some(("sell", input)),
+         _ => None,
+     }
+ }
+
+ /// Approve (4), Revoke (5) and CloseAccount (9). `mint_pubkey`, `slot` and `block_time` are
+ /// filled in by the caller.
+ /// Approve accounts: [source, delegate, owner], data: [4] [amount: u64 LE]
+ /// Revoke accounts: [source, owner]
+ /// CloseAccount accounts: [account, destination, owner]
+ fn parse_token_event(tx: &TransactionRef, ix: &InstructionRef) -> Option<TokenEvent> {
+     let key = |pos: usize| {
+         let idx = *ix.accounts.get(pos)? as usize;
+         tx.message.account_keys.get(idx).cloned()
+     };
+
+     let (kind, token_account, authority, counterparty, amount) = match *ix.data.first()? {
+         INSTR_APPROVE => {
+             let amount = read_u64_le(&ix.data[1..])?;
+             (EVENT_APPROVE, key(0)?, key(2)?, key(1), Some(amount as i64))
+         }
+         INSTR_REVOKE => (EVENT_REVOKE, key(0)?, key(1)?, None, None),
+         INSTR_CLOSE_ACCOUNT => (EVENT_CLOSE_ACCOUNT, key(0)?, key(2)?, key(1), None),
+         _ => return None,
+     };
+
+     Some(TokenEvent {
+         signature: tx.signature.clone(),
+         slot: 0,
+         block_time: None,
+         mint_pubkey: None,
+         kind: kind.to_string(),
+         token_account,
+         authority,
+         counterparty,
+         amount,
+         tx_index: tx.index,
+         ix_index: ix.index,
+         inner_index: ix.inner_index,
      })
  }
 
@@ -377,6 +943,77 @@ use chrono::{TimeZone, Utc};
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_instruction_without_inner_index_is_top_level() {
+        let ix: InstructionRef = serde_json::from_str(
+            r#"{"program_id":"p","accounts":[],"data":[],"index":3}"#,
+        )
+        .unwrap();
+        assert_eq!(ix.inner_index, TOP_LEVEL_INNER_INDEX);
+    }
+
+    #[test]
+    fn test_parse_transfers_from_inner_instructions() {
+        let transfer_checked = |accounts: Vec<u8>, amount: u64, inner_index: i32| InstructionRef {
+            program_id: SPL_TOKEN_PROGRAM_ID.to_string(),
+            accounts,
+            data: {
+                let mut d = vec![INSTR_TRANSFER_CHECKED];
+                d.extend_from_slice(&amount.to_le_bytes());
+                d.push(6); // decimals
+                d
+            },
+            index: 0,
+            inner_index,
+        };
+        let block = BlockRef {
+            slot: 100,
+            block_time_unix: Some(1000),
+            transactions: vec![TransactionRef {
+                signature: "sig123".to_string(),
+                index: 0,
+                message: MessageRef {
+                    account_keys: vec![
+                        "trader_ata".to_string(),
+                        "test_mint".to_string(),
+                        "vault_ata".to_string(),
+                        "trader".to_string(),
+                        "dex_program".to_string(),
+                        "pool".to_string(),
+                    ],
+                },
+                // A swap: the DEX's top-level call moves tokens both ways through CPI.
+                instructions: vec![
+                    InstructionRef {
+                        program_id: "dex_program".to_string(),
+                        accounts: vec![5, 0, 2, 3],
+                        data: vec![9],
+                        index: 0,
+                        inner_index: TOP_LEVEL_INNER_INDEX,
+                    },
+                    transfer_checked(vec![0, 1, 2, 3], 1_000_000, 0),
+                    transfer_checked(vec![2, 1, 0, 5], 400_000, 1),
+                ],
+            }],
+        };
+
+        let transfers = extract_transfers_from_block(&block, &["test_mint".to_string()]);
+
+        let moves: Vec<_> = transfers
+            .iter()
+            .map(|t| {
+                (t.source_ata.as_str(), t.dest_ata.as_str(), t.amount, t.ix_index, t.inner_index)
+            })
+            .collect();
+        assert_eq!(
+            moves,
+            vec![
+                ("trader_ata", "vault_ata", 1_000_000, 0, 0),
+                ("vault_ata", "trader_ata", 400_000, 0, 1),
+            ]
+        );
+    }
+
     #[test]
     fn test_parse_transfer_checked() {
         let block = BlockRef {
@@ -403,6 +1040,7 @@ mod tests {
                         d
                     },
                     index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
                 }],
             }],
         };
@@ -415,6 +1053,47 @@ mod tests {
         assert_eq!(transfers[0].amount, 1_000_000);
         assert_eq!(transfers[0].source_ata, "source_ata");
         assert_eq!(transfers[0].dest_ata, "dest_ata");
+        assert_eq!(transfers[0].program, SPL_TOKEN_PROGRAM_ID);
+    }
+
+    #[test]
+    fn test_parse_transfer_checked_token_2022() {
+        let block = BlockRef {
+            slot: 100,
+            block_time_unix: Some(1000),
+            transactions: vec![TransactionRef {
+                signature: "sig123".to_string(),
+                index: 0,
+                message: MessageRef {
+                    account_keys: vec![
+                        "source_ata".to_string(),
+                        "test_mint".to_string(),
+                        "dest_ata".to_string(),
+                        "owner".to_string(),
+                    ],
+                },
+                instructions: vec![InstructionRef {
+                    program_id: TOKEN_2022_PROGRAM_ID.to_string(),
+                    accounts: vec![0, 1, 2, 3],
+                    data: {
+                        let mut d = vec![INSTR_TRANSFER_CHECKED];
+                        d.extend_from_slice(&(1_000_000u64).to_le_bytes());
+                        d.push(6); // decimals
+                        d
+                    },
+                    index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
+                }],
+            }],
+        };
+
+        let transfers =
+            extract_transfers_from_block(&block, &["test_mint".to_string()]);
+
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].mint_pubkey, "test_mint");
+        assert_eq!(transfers[0].amount, 1_000_000);
+        assert_eq!(transfers[0].program, TOKEN_2022_PROGRAM_ID);
     }
 
     #[test]
@@ -443,6 +1122,7 @@ mod tests {
                         d
                     },
                     index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
                 }],
             }],
         };
@@ -479,6 +1159,7 @@ mod tests {
                         d
                     },
                     index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
                 }],
             }],
         };
@@ -493,6 +1174,44 @@ mod tests {
         assert_eq!(transfers[0].dest_owner, "dest_ata");
     }
 
+    #[test]
+    fn test_mint_decimals_from_checked_instructions() {
+        let mut mint_to = vec![INSTR_MINT_TO_CHECKED];
+        mint_to.extend_from_slice(&10_000_000u64.to_le_bytes());
+        mint_to.push(6);
+        let mut transfer = vec![INSTR_TRANSFER_CHECKED];
+        transfer.extend_from_slice(&5u64.to_le_bytes());
+        transfer.push(9);
+        // MintTo carries no decimals
+        let plain_mint_to = {
+            let mut d = vec![INSTR_MINT_TO];
+            d.extend_from_slice(&1u64.to_le_bytes());
+            d
+        };
+        let block = transfer_block(
+            300,
+            &["mint_a", "dest_ata", "authority", "src_ata", "mint_b", "owner"],
+            vec![
+                // MintToChecked: [mint, dest, authority]
+                spl_ix(vec![0, 1, 2], mint_to, 0),
+                // TransferChecked: [source, mint, dest, owner]
+                spl_ix(vec![3, 4, 1, 5], transfer, 1),
+                spl_ix(vec![0, 1, 2], plain_mint_to, 2),
+            ],
+        );
+
+        let mints = extract_mint_decimals(&block, &[]);
+        let decimals: Vec<(&str, i32, i64)> = mints
+            .iter()
+            .map(|m| (m.mint_pubkey.as_str(), m.decimals, m.first_seen_slot))
+            .collect();
+        assert_eq!(decimals, vec![("mint_a", 6, 300), ("mint_b", 9, 300)]);
+
+        let whitelisted = extract_mint_decimals(&block, &["mint_b".to_string()]);
+        assert_eq!(whitelisted.len(), 1);
+        assert_eq!(whitelisted[0].mint_pubkey, "mint_b");
+    }
+
     #[test]
     fn test_parse_burn_checked() {
         let block = BlockRef {
@@ -518,6 +1237,7 @@ mod tests {
                         d
                     },
                     index: 0,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
                 }],
             }],
         };
@@ -531,5 +1251,461 @@ mod tests {
         assert_eq!(transfers[0].source_owner, "source_ata");
         assert_eq!(transfers[0].dest_owner, "burn");
     }
-}
 
+    #[test]
+    fn test_block_ref_bytes_round_trip() {
+        let block = BlockRef {
+            slot: 4242,
+            block_time_unix: Some(1_700_000_000),
+            transactions: vec![TransactionRef {
+                signature: "sig_cached".to_string(),
+                index: 3,
+                message: MessageRef {
+                    account_keys: vec![
+                        "source_ata".to_string(),
+                        "test_mint".to_string(),
+                        "dest_ata".to_string(),
+                        "owner".to_string(),
+                    ],
+                },
+                instructions: vec![InstructionRef {
+                    program_id: SPL_TOKEN_PROGRAM_ID.to_string(),
+                    accounts: vec![0, 1, 2, 3],
+                    data: {
+                        let mut d = vec![INSTR_TRANSFER_CHECKED];
+                        d.extend_from_slice(&(1_000_000u64).to_le_bytes());
+                        d.push(6);
+                        d
+                    },
+                    index: 1,
+                    inner_index: TOP_LEVEL_INNER_INDEX,
+                }],
+            }],
+        };
+
+        let bytes = block.to_bytes().unwrap();
+        // The cache stores one block per line, so the encoding must not contain newlines.
+        assert!(!bytes.contains(&b'\n'));
+
+        let decoded = BlockRef::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, block);
+        assert_eq!(
+            extract_transfers_from_block(&decoded, &["test_mint".to_string()]).len(),
+            1
+        );
+    }
+
+    fn spl_ix(accounts: Vec<u8>, data: Vec<u8>, index: i32) -> InstructionRef {
+        InstructionRef {
+            program_id: SPL_TOKEN_PROGRAM_ID.to_string(),
+            accounts,
+            data,
+            index,
+            inner_index: TOP_LEVEL_INNER_INDEX,
+        }
+    }
+
+    #[test]
+    fn test_parse_approve_event() {
+        let mut approve = vec![INSTR_APPROVE];
+        approve.extend_from_slice(&5_000u64.to_le_bytes());
+        let mut transfer = vec![INSTR_TRANSFER_CHECKED];
+        transfer.extend_from_slice(&1u64.to_le_bytes());
+        transfer.push(6);
+        let block = BlockRef {
+            slot: 200,
+            block_time_unix: Some(2000),
+            transactions: vec![TransactionRef {
+                signature: "approve_sig".to_string(),
+                index: 0,
+                message: MessageRef {
+                    account_keys: vec![
+                        "owner_ata".to_string(),
+                        "drainer".to_string(),
+                        "owner".to_string(),
+                        "test_mint".to_string(),
+                        "other_ata".to_string(),
+                    ],
+                },
+                instructions: vec![
+                    spl_ix(vec![0, 1, 2], approve, 0),
+                    // Names the mint of owner_ata
+                    spl_ix(vec![0, 3, 4, 2], transfer, 1),
+                ],
+            }],
+        };
+
+        let events = extract_token_events(&block, &["test_mint".to_string()]);
+
+        assert_eq!(events.len(), 1);
+        let e = &events[0];
+        assert_eq!(e.kind, EVENT_APPROVE);
+        assert_eq!(e.mint_pubkey.as_deref(), Some("test_mint"));
+        assert_eq!(e.token_account, "owner_ata");
+        assert_eq!(e.authority, "owner");
+        assert_eq!(e.counterparty.as_deref(), Some("drainer"));
+        assert_eq!(e.amount, Some(5_000));
+        assert_eq!(e.slot, 200);
+        // Not a transfer
+        assert_eq!(extract_transfers_from_block(&block, &["test_mint".to_string()]).len(), 1);
+
+        assert!(extract_token_events(&block, &["other_mint".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_close_account_event() {
+        let block = BlockRef {
+            slot: 300,
+            block_time_unix: None,
+            transactions: vec![TransactionRef {
+                signature: "close_sig".to_string(),
+                index: 2,
+                message: MessageRef {
+                    account_keys: vec![
+                        "ata".to_string(),
+                        "rent_dest".to_string(),
+                        "owner".to_string(),
+                    ],
+                },
+                instructions: vec![
+                    spl_ix(vec![0, 1, 2], vec![INSTR_CLOSE_ACCOUNT], 4),
+                    spl_ix(vec![0, 2], vec![INSTR_REVOKE], 5),
+                    // Truncated approve and out-of-range index are ignored
+                    spl_ix(vec![0, 1, 2], vec![INSTR_APPROVE, 1], 6),
+                    spl_ix(vec![0, 1, 9], vec![INSTR_CLOSE_ACCOUNT], 7),
+                ],
+            }],
+        };
+
+        let events = extract_token_events(&block, &["test_mint".to_string()]);
+
+        // No instruction names the mint: left for the writer to resolve
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, EVENT_CLOSE_ACCOUNT);
+        assert_eq!(events[0].mint_pubkey, None);
+        assert_eq!(events[0].token_account, "ata");
+        assert_eq!(events[0].counterparty.as_deref(), Some("rent_dest"));
+        assert_eq!(events[0].authority, "owner");
+        assert_eq!((events[0].tx_index, events[0].ix_index), (2, 4));
+        assert_eq!(events[1].kind, EVENT_REVOKE);
+        assert_eq!(events[1].counterparty, None);
+    }
+
+    fn transfer_block(slot: i64, keys: &[&str], instructions: Vec<InstructionRef>) -> BlockRef {
+        BlockRef {
+            slot,
+            block_time_unix: Some(3000),
+            transactions: vec![TransactionRef {
+                signature: format!("sig_{slot}"),
+                index: 0,
+                message: MessageRef { account_keys: keys.iter().map(|k| k.to_string()).collect() },
+                instructions,
+            }],
+        }
+    }
+
+    fn plain_transfer(amount: u64, index: i32) -> InstructionRef {
+        let mut data = vec![INSTR_TRANSFER];
+        data.extend_from_slice(&amount.to_le_bytes());
+        // [source, destination, owner]
+        spl_ix(vec![0, 1, 2], data, index)
+    }
+
+    #[test]
+    fn test_plain_transfer_resolves_mint_from_earlier_blocks() {
+        let mut accounts = TokenAccountCache::new(16);
+        let whitelist = ["test_mint".to_string()];
+
+        // InitializeAccount: [account, mint, owner, rent]
+        let init = transfer_block(
+            1,
+            &["alice_ata", "test_mint", "alice", "rent"],
+            vec![spl_ix(vec![0, 1, 2, 3], vec![INSTR_INITIALIZE_ACCOUNT], 0)],
+        );
+        assert!(extract_transfers_with(&init, &whitelist, &mut accounts, None).is_empty());
+        assert_eq!(accounts.len(), 1);
+
+        let transfer = transfer_block(
+            2,
+            &["alice_ata", "bob_ata", "alice"],
+            vec![plain_transfer(750, 0)],
+        );
+        let transfers = extract_transfers_with(&transfer, &whitelist, &mut accounts, None);
+        assert_eq!(transfers.len(), 1);
+        let t = &transfers[0];
+        assert_eq!(t.mint_pubkey, "test_mint");
+        assert_eq!(t.source_ata, "alice_ata");
+        assert_eq!(t.dest_ata, "bob_ata");
+        assert_eq!(t.amount, 750);
+        assert_eq!(t.source_owner, "alice");
+        // No InitializeAccount seen for the destination yet.
+        assert_eq!(t.dest_owner, "bob_ata");
+
+        // The destination learned the mint from the transfer itself.
+        let onward = transfer_block(3, &["bob_ata", "carol_ata", "bob"], vec![plain_transfer(5, 0)]);
+        let transfers = extract_transfers_with(&onward, &whitelist, &mut accounts, None);
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].mint_pubkey, "test_mint");
+
+        // Known mint, but not whitelisted.
+        let other = ["other_mint".to_string()];
+        assert!(extract_transfers_with(&onward, &other, &mut accounts, None).is_empty());
+    }
+
+    #[test]
+    fn test_initialize_account_2_and_3_resolve_owners_from_data() {
+        let mut accounts = TokenAccountCache::new(16);
+        let alice = [7u8; 32];
+        let bob = [9u8; 32];
+        let mut init2 = vec![INSTR_INITIALIZE_ACCOUNT2];
+        init2.extend_from_slice(&alice);
+        let mut init3 = vec![INSTR_INITIALIZE_ACCOUNT3];
+        init3.extend_from_slice(&bob);
+        let init = transfer_block(
+            6,
+            &["alice_ata", "test_mint", "rent", "bob_ata"],
+            vec![
+                // InitializeAccount2: [account, mint, rent]
+                spl_ix(vec![0, 1, 2], init2, 0),
+                // InitializeAccount3: [account, mint]
+                spl_ix(vec![3, 1], init3, 1),
+            ],
+        );
+        assert!(extract_transfers_with(&init, &[], &mut accounts, None).is_empty());
+
+        let mut checked = vec![INSTR_TRANSFER_CHECKED];
+        checked.extend_from_slice(&40u64.to_le_bytes());
+        checked.push(6);
+        let transfer = transfer_block(
+            7,
+            &["alice_ata", "test_mint", "bob_ata", "delegate"],
+            vec![spl_ix(vec![0, 1, 2, 3], checked, 0)],
+        );
+        let transfers = extract_transfers_with(&transfer, &[], &mut accounts, None);
+
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].source_owner, bs58::encode(alice).into_string());
+        assert_eq!(transfers[0].dest_owner, bs58::encode(bob).into_string());
+        // The checked transfer re-learned both accounts' mint without dropping the owners.
+        let known = accounts.get("bob_ata").unwrap();
+        assert_eq!(known.owner.as_deref(), Some(bs58::encode(bob).into_string().as_str()));
+
+        // Truncated owner data: the mint is still learned, the owner isn't.
+        let short = transfer_block(
+            8,
+            &["carol_ata", "test_mint"],
+            vec![spl_ix(vec![0, 1], vec![INSTR_INITIALIZE_ACCOUNT3, 1, 2], 0)],
+        );
+        extract_transfers_with(&short, &[], &mut accounts, None);
+        let known = accounts.get("carol_ata").unwrap();
+        assert_eq!(known.mint, "test_mint");
+        assert_eq!(known.owner, None);
+    }
+
+    #[test]
+    fn test_plain_transfer_learns_from_checked_transfer_in_same_block() {
+        let mut checked = vec![INSTR_TRANSFER_CHECKED];
+        checked.extend_from_slice(&10u64.to_le_bytes());
+        checked.push(6);
+        let block = transfer_block(
+            4,
+            &["alice_ata", "bob_ata", "alice", "test_mint"],
+            vec![
+                // [source, mint, destination, owner]
+                spl_ix(vec![0, 3, 1, 2], checked, 0),
+                plain_transfer(20, 1),
+            ],
+        );
+
+        let transfers = extract_transfers_from_block(&block, &["test_mint".to_string()]);
+
+        assert_eq!(transfers.len(), 2);
+        assert_eq!(transfers[1].mint_pubkey, "test_mint");
+        assert_eq!(transfers[1].amount, 20);
+    }
+
+    #[test]
+    fn test_plain_transfer_from_unknown_account_is_skipped() {
+        let block = transfer_block(5, &["alice_ata", "bob_ata", "alice"], vec![plain_transfer(1, 0)]);
+
+        assert!(extract_transfers_from_block(&block, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_token_account_cache_evicts_least_recently_used() {
+        let mut cache = TokenAccountCache::new(2);
+        let mint = |cache: &mut TokenAccountCache, account| {
+            cache.get(account).map(|known| known.mint.clone())
+        };
+        cache.insert("a", "mint_a", None);
+        cache.insert("b", "mint_b", None);
+        // Touching `a` leaves `b` as the oldest.
+        assert_eq!(mint(&mut cache, "a").as_deref(), Some("mint_a"));
+        cache.insert("c", "mint_c", None);
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(mint(&mut cache, "b"), None);
+        assert_eq!(mint(&mut cache, "a").as_deref(), Some("mint_a"));
+        assert_eq!(mint(&mut cache, "c").as_deref(), Some("mint_c"));
+
+        // Re-inserting updates in place.
+        cache.insert("c", "mint_c2", None);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(mint(&mut cache, "c").as_deref(), Some("mint_c2"));
+    }
+
+    #[test]
+    fn test_swap_side_is_priced_in_sol_only() {
+        assert_eq!(swap_side(Some(WSOL_MINT), Some("token")), Some(("buy", "token")));
+        assert_eq!(swap_side(Some("token"), Some(WSOL_MINT)), Some(("sell", "token")));
+        // SOL/USDC: USDC is bought with SOL.
+        assert_eq!(swap_side(Some(WSOL_MINT), Some(USDC_MINT)), Some(("buy", USDC_MINT)));
+        // Dollar-quoted swaps have no SOL amount.
+        assert_eq!(swap_side(Some("token"), Some(USDC_MINT)), None);
+        assert_eq!(swap_side(Some(USDC_MINT), Some("token")), None);
+        assert_eq!(swap_side(Some(WSOL_MINT), Some(WSOL_MINT)), None);
+        assert_eq!(swap_side(Some("a"), Some("b")), None);
+        assert_eq!(swap_side(None, Some(WSOL_MINT)), None);
+    }
+
+    #[test]
+    fn test_parse_guarded_turns_panic_into_none() {
+        let tx = TransactionRef {
+            signature: "sig".to_string(),
+            index: 0,
+            message: MessageRef { account_keys: vec![] },
+            instructions: vec![],
+        };
+        let ix = spl_ix(vec![INSTR_TRANSFER], vec![], 0);
+        let counters = ParseCounters::default();
+        let parsed: Option<u64> = parse_guarded("spl", &tx, &ix, Some(&counters), || {
+            let data: &[u8] = &ix.data;
+            Some(u64::from_le_bytes(data[1..9].try_into().unwrap()))
+        });
+        assert_eq!(parsed, None);
+        assert_eq!(parse_guarded("spl", &tx, &ix, Some(&counters), || Some(7)), Some(7));
+        assert_eq!(counters.panics(), 1);
+    }
+
+    /// xorshift64, so the fuzz test below is reproducible without a rand dependency.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    #[test]
+    fn test_extractors_never_panic_on_random_instructions() {
+        use crate::bonding_parser::{anchor_discriminator, extract_pump_trades_with, PUMP_PROGRAM_ID};
+        use crate::meteora_parser::{extract_meteora_trades_with, METEORA_DLMM_PROGRAM_ID};
+        use crate::price::PriceScale;
+        use crate::raydium_parser::{extract_raydium_trades_with, RAYDIUM_AMM_V4_PROGRAM_ID};
+        use crate::whirlpool_parser::{extract_whirlpool_trades_with, WHIRLPOOL_PROGRAM_ID};
+
+        let programs = [
+            SPL_TOKEN_PROGRAM_ID,
+            PUMP_PROGRAM_ID,
+            RAYDIUM_AMM_V4_PROGRAM_ID,
+            METEORA_DLMM_PROGRAM_ID,
+            WHIRLPOOL_PROGRAM_ID,
+        ];
+        // Known leading bytes, so random data reaches the instruction-specific parsers rather
+        // than stopping at the discriminator check.
+        let mut prefixes: Vec<Vec<u8>> = [
+            INSTR_TRANSFER,
+            INSTR_TRANSFER_CHECKED,
+            INSTR_MINT_TO,
+            INSTR_MINT_TO_CHECKED,
+            INSTR_BURN,
+            INSTR_BURN_CHECKED,
+            INSTR_APPROVE,
+            INSTR_REVOKE,
+            INSTR_CLOSE_ACCOUNT,
+            INSTR_INITIALIZE_ACCOUNT,
+            INSTR_INITIALIZE_ACCOUNT2,
+            INSTR_INITIALIZE_ACCOUNT3,
+            INSTR_INITIALIZE_MINT,
+            INSTR_APPROVE_CHECKED,
+            9,
+            10,
+            11,
+            22,
+        ]
+        .iter()
+        .map(|&b| vec![b])
+        .collect();
+        prefixes.push(anchor_discriminator("buy").to_vec());
+        prefixes.push(anchor_discriminator("sell").to_vec());
+        prefixes.push(anchor_discriminator("swap").to_vec());
+        prefixes.push(anchor_discriminator("swap_v2").to_vec());
+
+        let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+        let scale = PriceScale::default();
+        for round in 0..2_000 {
+            let keys = rng.below(12);
+            let transactions = (0..4)
+                .map(|tx_index| {
+                    let instructions = (0..6)
+                        .map(|index| {
+                            let mut data = if rng.below(4) == 0 {
+                                Vec::new()
+                            } else {
+                                prefixes[rng.below(prefixes.len())].clone()
+                            };
+                            data.extend((0..rng.below(80)).map(|_| rng.next() as u8));
+                            InstructionRef {
+                                program_id: programs[rng.below(programs.len())].to_string(),
+                                // Mostly in range, so parsing isn't skipped by the index check.
+                                accounts: (0..rng.below(20))
+                                    .map(|_| rng.below(keys.max(1) + 1) as u8)
+                                    .collect(),
+                                data,
+                                index,
+                                inner_index: TOP_LEVEL_INNER_INDEX,
+                            }
+                        })
+                        .collect();
+                    TransactionRef {
+                        signature: format!("sig{round}_{tx_index}"),
+                        index: tx_index,
+                        message: MessageRef {
+                            account_keys: (0..keys).map(|k| format!("key{k}")).collect(),
+                        },
+                        instructions,
+                    }
+                })
+                .collect();
+            let block = BlockRef {
+                slot: round,
+                block_time_unix: Some(1_700_000_000),
+                transactions,
+            };
+
+            // The extractors catch parser panics, so they are read from the counters. Token
+            // events are extracted without counters, so their parser is called directly.
+            let counters = ParseCounters::default();
+            let mut accounts = TokenAccountCache::new(8);
+            extract_transfers_with(&block, &[], &mut accounts, Some(&counters));
+            extract_mint_decimals(&block, &[]);
+            extract_pump_trades_with(&block, Some(&counters), &scale);
+            extract_raydium_trades_with(&block, &mut accounts, Some(&counters), &scale);
+            extract_meteora_trades_with(&block, &mut accounts, Some(&counters), &scale);
+            extract_whirlpool_trades_with(&block, Some(&counters), &scale);
+            for tx in &block.transactions {
+                for ix in &tx.instructions {
+                    parse_token_event(tx, ix);
+                }
+            }
+            assert_eq!(counters.panics(), 0, "a parser panicked on round {round}: {block:?}");
+        }
+    }
+}
@@ -0,0 +1,172 @@
+// Trade-rate monitoring.
+// If a deploy breaks a parser, that venue's trades silently drop to zero. The writer counts
+// inserted trades per venue; a once-a-minute monitor feeds the counts to `StallDetector`,
+// which flags venues that were trading and then went quiet for several minutes.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+/// Trades inserted per venue since the last `take`. Shared between the writer and the monitor.
+#[derive(Debug, Default)]
+pub struct TradeCounters {
+    counts: Mutex<HashMap<String, u64>>,
+}
+
+impl TradeCounters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&self, venue: &str, trades: u64) {
+        if trades == 0 {
+            return;
+        }
+        *self
+            .counts
+            .lock()
+            .unwrap()
+            .entry(venue.to_string())
+            .or_default() += trades;
+    }
+
+    /// Return the counts accumulated so far and reset them.
+    pub fn take(&self) -> HashMap<String, u64> {
+        std::mem::take(&mut *self.counts.lock().unwrap())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RateAlert {
+    /// A previously active venue has had no trades for `minutes` consecutive minutes.
+    Stalled { venue: String, minutes: u32 },
+    /// A stalled venue is producing trades again.
+    Recovered { venue: String },
+}
+
+#[derive(Debug, Default)]
+struct VenueState {
+    zero_minutes: u32,
+    stalled: bool,
+}
+
+/// Zero-rate detection over per-minute trade counts.
+/// Venues are only tracked once they have had at least one trade, and each stall is reported
+/// once until the venue recovers.
+#[derive(Debug)]
+pub struct StallDetector {
+    stall_minutes: u32,
+    venues: BTreeMap<String, VenueState>,
+}
+
+impl StallDetector {
+    pub fn new(stall_minutes: u32) -> Self {
+        Self {
+            stall_minutes: stall_minutes.max(1),
+            venues: BTreeMap::new(),
+        }
+    }
+
+    /// Feed one minute of per-venue trade counts. Venues missing from `counts` had zero trades.
+    pub fn record_minute(&mut self, counts: &HashMap<String, u64>) -> Vec<RateAlert> {
+        for (venue, &n) in counts {
+            if n > 0 {
+                self.venues.entry(venue.clone()).or_default();
+            }
+        }
+
+        let mut alerts = Vec::new();
+        for (venue, state) in &mut self.venues {
+            if counts.get(venue).copied().unwrap_or(0) > 0 {
+                if state.stalled {
+                    alerts.push(RateAlert::Recovered {
+                        venue: venue.clone(),
+                    });
+                }
+                *state = VenueState::default();
+                continue;
+            }
+
+            state.zero_minutes += 1;
+            if !state.stalled && state.zero_minutes >= self.stall_minutes {
+                state.stalled = true;
+                alerts.push(RateAlert::Stalled {
+                    venue: venue.clone(),
+                    minutes: state.zero_minutes,
+                });
+            }
+        }
+
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minute(counts: &[(&str, u64)]) -> HashMap<String, u64> {
+        counts.iter().map(|(v, n)| (v.to_string(), *n)).collect()
+    }
+
+    #[test]
+    fn test_stall_reported_after_consecutive_zero_minutes() {
+        let mut detector = StallDetector::new(3);
+
+        assert!(detector.record_minute(&minute(&[("pump", 40)])).is_empty());
+        assert!(detector.record_minute(&minute(&[])).is_empty());
+        assert!(detector.record_minute(&minute(&[("pump", 0)])).is_empty());
+        assert_eq!(
+            detector.record_minute(&minute(&[])),
+            vec![RateAlert::Stalled {
+                venue: "pump".to_string(),
+                minutes: 3
+            }]
+        );
+        // Reported once, not every minute after.
+        assert!(detector.record_minute(&minute(&[])).is_empty());
+    }
+
+    #[test]
+    fn test_trade_resets_zero_streak() {
+        let mut detector = StallDetector::new(2);
+
+        detector.record_minute(&minute(&[("raydium", 5)]));
+        assert!(detector.record_minute(&minute(&[])).is_empty());
+        assert!(detector.record_minute(&minute(&[("raydium", 1)])).is_empty());
+        assert!(detector.record_minute(&minute(&[])).is_empty());
+    }
+
+    #[test]
+    fn test_recovery_reported_after_stall() {
+        let mut detector = StallDetector::new(1);
+
+        detector.record_minute(&minute(&[("meteora", 2)]));
+        assert_eq!(detector.record_minute(&minute(&[])).len(), 1);
+        assert_eq!(
+            detector.record_minute(&minute(&[("meteora", 7)])),
+            vec![RateAlert::Recovered {
+                venue: "meteora".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_never_active_venue_is_not_reported() {
+        let mut detector = StallDetector::new(1);
+
+        detector.record_minute(&minute(&[("pump", 3)]));
+        let alerts = detector.record_minute(&minute(&[("pump", 3), ("raydium", 0)]));
+        assert!(alerts.is_empty());
+    }
+
+    #[test]
+    fn test_counters_take_resets() {
+        let counters = TradeCounters::new();
+        counters.add("pump", 2);
+        counters.add("pump", 3);
+        counters.add("raydium", 0);
+
+        assert_eq!(counters.take(), minute(&[("pump", 5)]));
+        assert!(counters.take().is_empty());
+    }
+}
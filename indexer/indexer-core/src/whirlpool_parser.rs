@@ -0,0 +1,498 @@
+// Orca Whirlpool (concentrated liquidity) swap parser.
+// Handles the Anchor `swap` and `swap_v2` instructions.
+
+use crate::bonding_parser::anchor_discriminator;
+use crate::models::BondingCurveTrade;
+use crate::price::PriceScale;
+use crate::spl_parser::{
+    account_indices_in_range, parse_guarded, swap_side, swap_vault_amounts, swap_vault_mints,
+    BlockRef,
+    InstructionRef, ParseCounters, TransactionRef,
+};
+use chrono::{TimeZone, Utc};
+
+// Orca Whirlpool program ID (mainnet).
+pub const WHIRLPOOL_PROGRAM_ID: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+
+/// Discriminator + amount + other_amount_threshold + sqrt_price_limit + two bools.
+const SWAP_DATA_LEN: usize = 8 + 8 + 8 + 16 + 1 + 1;
+
+/// Arguments shared by `swap` and `swap_v2` (v2 appends optional remaining-accounts info).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WhirlpoolSwapArgs {
+    /// Exact input if `amount_specified_is_input`, exact output otherwise.
+    pub amount: u64,
+    /// Minimum output for an exact-input swap, maximum input for an exact-output one.
+    pub other_amount_threshold: u64,
+    /// Q64.64 sqrt price the swap may not cross.
+    pub sqrt_price_limit: u128,
+    pub amount_specified_is_input: bool,
+    /// Token A is paid in (and token B out) when true.
+    pub a_to_b: bool,
+}
+
+/// Account positions of the two swap versions.
+struct SwapLayout {
+    authority: usize,
+    /// `swap_v2` names the mints; `swap` leaves them to the vaults' transfers.
+    mints: Option<(usize, usize)>,
+    vault_a: usize,
+    vault_b: usize,
+}
+
+/// swap accounts: [token_program, token_authority, whirlpool, token_owner_account_a,
+/// token_vault_a, token_owner_account_b, token_vault_b, tick_array_0..2, oracle]
+const SWAP_LAYOUT: SwapLayout = SwapLayout {
+    authority: 1,
+    mints: None,
+    vault_a: 4,
+    vault_b: 6,
+};
+
+/// swap_v2 accounts: [token_program_a, token_program_b, memo_program, token_authority,
+/// whirlpool, token_mint_a, token_mint_b, token_owner_account_a, token_vault_a,
+/// token_owner_account_b, token_vault_b, tick_array_0..2, oracle]
+const SWAP_V2_LAYOUT: SwapLayout = SwapLayout {
+    authority: 3,
+    mints: Some((5, 6)),
+    vault_a: 8,
+    vault_b: 10,
+};
+
+fn read_u64_le(bytes: &[u8]) -> Option<u64> {
+    let arr: [u8; 8] = bytes.get(..8)?.try_into().ok()?;
+    Some(u64::from_le_bytes(arr))
+}
+
+fn read_u128_le(bytes: &[u8]) -> Option<u128> {
+    let arr: [u8; 16] = bytes.get(..16)?.try_into().ok()?;
+    Some(u128::from_le_bytes(arr))
+}
+
+/// Decode the swap arguments following the 8-byte discriminator.
+fn parse_swap_args(data: &[u8]) -> Option<WhirlpoolSwapArgs> {
+    if data.len() < SWAP_DATA_LEN {
+        return None;
+    }
+    Some(WhirlpoolSwapArgs {
+        amount: read_u64_le(&data[8..])?,
+        other_amount_threshold: read_u64_le(&data[16..])?,
+        sqrt_price_limit: read_u128_le(&data[24..])?,
+        amount_specified_is_input: data[40] != 0,
+        a_to_b: data[41] != 0,
+    })
+}
+
+pub fn extract_whirlpool_trades_from_block(block: &BlockRef) -> Vec<BondingCurveTrade> {
+    extract_whirlpool_trades_with(block, None, &PriceScale::default())
+}
+
+/// Same as [`extract_whirlpool_trades_from_block`], pricing trades with `price_scale` and adding
+/// to `counters` a miss for every Whirlpool swap that could not be parsed and a bad index for
+/// every Whirlpool instruction with out-of-range accounts. Other Whirlpool instructions
+/// (liquidity, fees, tick arrays) are skipped without counting.
+pub fn extract_whirlpool_trades_with(
+    block: &BlockRef,
+    counters: Option<&ParseCounters>,
+    price_scale: &PriceScale,
+) -> Vec<BondingCurveTrade> {
+    let swap_disc = anchor_discriminator("swap");
+    let swap_v2_disc = anchor_discriminator("swap_v2");
+
+    let block_time = block
+        .block_time_unix
+        .and_then(|t| Utc.timestamp_opt(t, 0).single());
+
+    let mut trades = Vec::new();
+
+    for tx in &block.transactions {
+        for ix in &tx.instructions {
+            if ix.program_id != WHIRLPOOL_PROGRAM_ID {
+                continue;
+            }
+
+            if !account_indices_in_range("orca", tx, ix, counters) {
+                continue;
+            }
+
+            let layout = match ix.data.get(..8) {
+                Some(d) if d == swap_disc => &SWAP_LAYOUT,
+                Some(d) if d == swap_v2_disc => &SWAP_V2_LAYOUT,
+                _ => continue,
+            };
+
+            let parsed = parse_guarded("orca", tx, ix, counters, || {
+                parse_whirlpool_swap(block.slot, block_time, tx, ix, layout, price_scale)
+            });
+            match parsed {
+                Some(trade) => trades.push(trade),
+                None => {
+                    if let Some(c) = counters {
+                        c.miss();
+                    }
+                }
+            }
+        }
+    }
+
+    trades
+}
+
+/// Parse a Whirlpool `swap` / `swap_v2` instruction.
+///
+/// Data: [0..8] discriminator, [8..16] amount (u64 LE), [16..24] other_amount_threshold
+/// (u64 LE), [24..40] sqrt_price_limit (u128 LE), [40] amount_specified_is_input,
+/// [41] a_to_b.
+///
+/// One of the two amounts is only a slippage bound, so the executed amounts come from the
+/// swap's transfers into and out of the pool vaults. Swaps without both transfers, or whose
+/// mints can't be told apart into a quote and a traded token, give `None`.
+fn parse_whirlpool_swap(
+    slot: i64,
+    block_time: Option<chrono::DateTime<chrono::Utc>>,
+    tx: &TransactionRef,
+    ix: &InstructionRef,
+    layout: &SwapLayout,
+    price_scale: &PriceScale,
+) -> Option<BondingCurveTrade> {
+    let args = parse_swap_args(&ix.data)?;
+    let key = |pos: usize| {
+        let idx = *ix.accounts.get(pos)? as usize;
+        tx.message.account_keys.get(idx).map(String::as_str)
+    };
+
+    let trader = key(layout.authority)?.to_string();
+    let vaults: Vec<&str> = [layout.vault_a, layout.vault_b]
+        .into_iter()
+        .filter_map(key)
+        .collect();
+
+    // Mints paid in and out: named by swap_v2 and ordered by a_to_b, otherwise taken from the
+    // vaults' transfers.
+    let (input_mint, output_mint) = match layout.mints {
+        Some((a, b)) => {
+            let (mint_a, mint_b) = (key(a), key(b));
+            if args.a_to_b {
+                (mint_a, mint_b)
+            } else {
+                (mint_b, mint_a)
+            }
+        }
+        None => swap_vault_mints(tx, ix, &vaults),
+    };
+    let (amount_in, amount_out) = match swap_vault_amounts(tx, ix, &vaults) {
+        (Some(amount_in), Some(amount_out)) => (amount_in, amount_out),
+        _ => return None,
+    };
+    let (direction, mint_pubkey) = swap_side(input_mint, output_mint)?;
+    let mint_pubkey = mint_pubkey.to_string();
+    // SOL is paid in on a buy and out on a sell.
+    let (sol_amount, token_amount) = if direction == "sell" {
+        (amount_out, amount_in)
+    } else {
+        (amount_in, amount_out)
+    };
+
+    let price = price_scale.price_nanos(&mint_pubkey, sol_amount, token_amount);
+
+    Some(BondingCurveTrade {
+        signature: tx.signature.clone(),
+        slot,
+        block_time,
+        mint_pubkey,
+        trader,
+        side: direction.to_string(),
+        token_amount: token_amount as i64,
+        sol_amount: sol_amount as i64,
+        price_nanos_per_token: price,
+        tx_index: tx.index,
+        ix_index: ix.index,
+        inner_index: ix.inner_index,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spl_parser::{
+        InstructionRef, MessageRef, TransactionRef, SPL_TOKEN_PROGRAM_ID, TOP_LEVEL_INNER_INDEX,
+        WSOL_MINT,
+    };
+
+    fn create_swap_data(
+        ix_name: &str,
+        amount: u64,
+        threshold: u64,
+        exact_in: bool,
+        a_to_b: bool,
+    ) -> Vec<u8> {
+        let mut data = anchor_discriminator(ix_name).to_vec();
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&threshold.to_le_bytes());
+        data.extend_from_slice(&4_295_048_016u128.to_le_bytes());
+        data.push(exact_in as u8);
+        data.push(a_to_b as u8);
+        data
+    }
+
+    fn whirlpool_ix(accounts: Vec<u8>, data: Vec<u8>) -> InstructionRef {
+        InstructionRef {
+            program_id: WHIRLPOOL_PROGRAM_ID.to_string(),
+            accounts,
+            data,
+            index: 0,
+            inner_index: TOP_LEVEL_INNER_INDEX,
+        }
+    }
+
+    fn keys(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    /// Inner `TransferChecked` of the swap at top-level index 0. Accounts: [source, mint, dest,
+    /// owner].
+    fn transfer_checked(accounts: Vec<u8>, amount: u64, inner_index: i32) -> InstructionRef {
+        let mut data = vec![12];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.push(6);
+        InstructionRef {
+            program_id: SPL_TOKEN_PROGRAM_ID.to_string(),
+            accounts,
+            data,
+            index: 0,
+            inner_index,
+        }
+    }
+
+    #[test]
+    fn test_whirlpool_swap_args_parsing() {
+        let data = create_swap_data("swap", 1_000, 950, true, false);
+        let args = parse_swap_args(&data).unwrap();
+
+        assert_eq!(
+            args,
+            WhirlpoolSwapArgs {
+                amount: 1_000,
+                other_amount_threshold: 950,
+                sqrt_price_limit: 4_295_048_016,
+                amount_specified_is_input: true,
+                a_to_b: false,
+            }
+        );
+
+        assert_eq!(parse_swap_args(&data[..SWAP_DATA_LEN - 1]), None);
+    }
+
+    #[test]
+    fn test_whirlpool_swap_v2_buy_from_named_mints() {
+        let block = BlockRef {
+            slot: 300,
+            block_time_unix: Some(3000),
+            transactions: vec![TransactionRef {
+                signature: "orca_v2_sig".to_string(),
+                index: 0,
+                message: MessageRef {
+                    account_keys: keys(&[
+                        "token_program_a",
+                        "token_program_b",
+                        "memo",
+                        "trader",
+                        "whirlpool",
+                        WSOL_MINT,
+                        "token_mint",
+                        "trader_wsol",
+                        "vault_a",
+                        "trader_token",
+                        "vault_b",
+                    ]),
+                },
+                instructions: vec![
+                    // 2 SOL in for at least 500 tokens, SOL (A) -> token (B).
+                    whirlpool_ix(
+                        (0..11).collect(),
+                        create_swap_data("swap_v2", 2_000_000_000, 500_000_000, true, true),
+                    ),
+                    // The swap filled at 520 tokens, above the threshold.
+                    transfer_checked(vec![7, 5, 8, 3], 2_000_000_000, 0),
+                    transfer_checked(vec![10, 6, 9, 4], 520_000_000, 1),
+                ],
+            }],
+        };
+
+        let trades = extract_whirlpool_trades_from_block(&block);
+        assert_eq!(trades.len(), 1);
+        let t = &trades[0];
+        assert_eq!(t.side, "buy");
+        assert_eq!(t.mint_pubkey, "token_mint");
+        assert_eq!(t.trader, "trader");
+        assert_eq!(t.sol_amount, 2_000_000_000);
+        assert_eq!(t.token_amount, 520_000_000);
+    }
+
+    #[test]
+    fn test_whirlpool_swap_sell_detected_from_vault_paid_into() {
+        let block = BlockRef {
+            slot: 301,
+            block_time_unix: Some(3001),
+            transactions: vec![TransactionRef {
+                signature: "orca_sell_sig".to_string(),
+                index: 0,
+                message: MessageRef {
+                    account_keys: keys(&[
+                        "token_program",
+                        "trader",
+                        "whirlpool",
+                        "trader_wsol",
+                        "wsol_vault",
+                        "trader_token",
+                        "token_vault",
+                        WSOL_MINT,
+                        "token_mint",
+                    ]),
+                },
+                instructions: vec![
+                    // Exact output: 3 SOL out for at most 900 tokens in, token (B) -> SOL (A).
+                    whirlpool_ix(
+                        vec![0, 1, 2, 3, 4, 5, 6],
+                        create_swap_data("swap", 3_000_000_000, 900_000_000, false, false),
+                    ),
+                    // Only 850 tokens were needed, below the 900 bound.
+                    transfer_checked(vec![5, 8, 6, 1], 850_000_000, 0),
+                    transfer_checked(vec![4, 7, 3, 2], 3_000_000_000, 1),
+                ],
+            }],
+        };
+
+        let trades = extract_whirlpool_trades_from_block(&block);
+        assert_eq!(trades.len(), 1);
+        let t = &trades[0];
+        assert_eq!(t.side, "sell");
+        assert_eq!(t.mint_pubkey, "token_mint");
+        assert_eq!(t.sol_amount, 3_000_000_000);
+        assert_eq!(t.token_amount, 850_000_000);
+    }
+
+    #[test]
+    fn test_whirlpool_swap_without_vault_transfers_is_dropped() {
+        let block = BlockRef {
+            slot: 304,
+            block_time_unix: Some(3004),
+            transactions: vec![TransactionRef {
+                signature: "orca_no_transfers_sig".to_string(),
+                index: 0,
+                message: MessageRef {
+                    account_keys: keys(&[
+                        "token_program_a",
+                        "token_program_b",
+                        "memo",
+                        "trader",
+                        "whirlpool",
+                        WSOL_MINT,
+                        "token_mint",
+                        "trader_wsol",
+                        "vault_a",
+                        "trader_token",
+                        "vault_b",
+                    ]),
+                },
+                instructions: vec![
+                    whirlpool_ix(
+                        (0..11).collect(),
+                        create_swap_data("swap_v2", 2_000_000_000, 500_000_000, true, true),
+                    ),
+                    // Only the leg into the pool; no payout from the other vault.
+                    transfer_checked(vec![7, 5, 8, 3], 2_000_000_000, 0),
+                ],
+            }],
+        };
+
+        let counters = ParseCounters::default();
+        let trades = extract_whirlpool_trades_with(&block, Some(&counters), &PriceScale::default());
+        assert!(trades.is_empty());
+        assert_eq!(counters.misses(), 1);
+    }
+
+    #[test]
+    fn test_whirlpool_swap_without_known_mints_is_dropped() {
+        let block = BlockRef {
+            slot: 302,
+            block_time_unix: Some(3002),
+            transactions: vec![TransactionRef {
+                signature: "orca_unknown_sig".to_string(),
+                index: 0,
+                message: MessageRef {
+                    account_keys: keys(&[
+                        "token_program",
+                        "trader",
+                        "whirlpool",
+                        "owner_a",
+                        "vault_a",
+                        "owner_b",
+                        "vault_b",
+                    ]),
+                },
+                instructions: vec![
+                    whirlpool_ix(
+                        vec![0, 1, 2, 3, 4, 5, 6],
+                        create_swap_data("swap", 100_000_000, 400_000_000, true, true),
+                    ),
+                    // Plain transfers name no mints, so the quote side is unknown.
+                    InstructionRef {
+                        program_id: SPL_TOKEN_PROGRAM_ID.to_string(),
+                        accounts: vec![3, 4, 1],
+                        data: [&[3u8][..], &100_000_000u64.to_le_bytes()].concat(),
+                        index: 0,
+                        inner_index: 0,
+                    },
+                    InstructionRef {
+                        program_id: SPL_TOKEN_PROGRAM_ID.to_string(),
+                        accounts: vec![6, 5, 2],
+                        data: [&[3u8][..], &410_000_000u64.to_le_bytes()].concat(),
+                        index: 0,
+                        inner_index: 1,
+                    },
+                ],
+            }],
+        };
+
+        let trades = extract_whirlpool_trades_from_block(&block);
+        assert!(trades.is_empty());
+    }
+
+    #[test]
+    fn test_whirlpool_counts_truncated_swaps_and_skips_other_instructions() {
+        let mut truncated = create_swap_data("swap", 1, 1, true, true);
+        truncated.truncate(20);
+        let block = BlockRef {
+            slot: 303,
+            block_time_unix: Some(3003),
+            transactions: vec![TransactionRef {
+                signature: "orca_misc_sig".to_string(),
+                index: 0,
+                message: MessageRef {
+                    account_keys: keys(&["a", "b", "c"]),
+                },
+                instructions: vec![
+                    whirlpool_ix(vec![0, 1, 2], truncated),
+                    whirlpool_ix(vec![0, 1, 2], anchor_discriminator("increase_liquidity").to_vec()),
+                ],
+            }],
+        };
+
+        let counters = ParseCounters::default();
+        let trades = extract_whirlpool_trades_with(&block, Some(&counters), &PriceScale::default());
+        assert!(trades.is_empty());
+        assert_eq!(counters.misses(), 1);
+    }
+
+    #[test]
+    fn test_extract_whirlpool_trades_empty_block() {
+        let block = BlockRef {
+            slot: 100,
+            block_time_unix: Some(1000),
+            transactions: vec![],
+        };
+
+        assert!(extract_whirlpool_trades_from_block(&block).is_empty());
+    }
+}
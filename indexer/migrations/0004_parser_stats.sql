@@ -0,0 +1,10 @@
+-- Per-parser diagnostics written by the indexer and surfaced by the API's /metrics.
+-- parse_misses counts instructions that matched a parser's program id but could not be
+-- decoded (too short, truncated arguments, ...), which usually means the program's
+-- instruction layout changed.
+
+CREATE TABLE IF NOT EXISTS parser_stats (
+    parser TEXT PRIMARY KEY,
+    parse_misses BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,19 @@
+-- Inner (CPI) instructions share the `ix_index` of the top-level instruction that invoked them,
+-- so `(signature, ix_index)` is no longer unique per parsed row. `inner_index` is the position
+-- among those inner instructions, or -1 for the top-level instruction itself.
+
+ALTER TABLE token_transfers
+    ADD COLUMN IF NOT EXISTS inner_index INT NOT NULL DEFAULT -1;
+ALTER TABLE token_transfers
+    DROP CONSTRAINT IF EXISTS token_transfers_signature_ix_index_key;
+ALTER TABLE token_transfers
+    ADD CONSTRAINT token_transfers_signature_ix_inner_key
+    UNIQUE (signature, ix_index, inner_index);
+
+ALTER TABLE bonding_curve_trades
+    ADD COLUMN IF NOT EXISTS inner_index INT NOT NULL DEFAULT -1;
+ALTER TABLE bonding_curve_trades
+    DROP CONSTRAINT IF EXISTS bonding_curve_trades_signature_ix_index_key;
+ALTER TABLE bonding_curve_trades
+    ADD CONSTRAINT bonding_curve_trades_signature_ix_inner_key
+    UNIQUE (signature, ix_index, inner_index);
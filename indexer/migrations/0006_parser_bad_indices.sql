@@ -0,0 +1,5 @@
+-- bad_indices counts instructions whose account indices point past the transaction's
+-- account keys. Unlike parse_misses this means corrupt block data, not a layout change.
+
+ALTER TABLE parser_stats
+    ADD COLUMN IF NOT EXISTS bad_indices BIGINT NOT NULL DEFAULT 0;
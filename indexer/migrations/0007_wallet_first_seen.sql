@@ -0,0 +1,12 @@
+-- First slot each wallet traded in, across all mints. A trade is from a "new wallet" when it
+-- happened in that slot. Maintained by the writer on every trade insert; replays of older
+-- blocks move it back.
+
+CREATE TABLE IF NOT EXISTS wallet_first_seen (
+    wallet TEXT PRIMARY KEY,
+    first_seen_slot BIGINT NOT NULL
+);
+
+INSERT INTO wallet_first_seen (wallet, first_seen_slot)
+SELECT trader, MIN(slot) FROM bonding_curve_trades GROUP BY trader
+ON CONFLICT (wallet) DO NOTHING;
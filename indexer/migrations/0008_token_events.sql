@@ -0,0 +1,23 @@
+-- Non-transfer SPL Token instructions kept as safety signals: delegate approvals (a delegate
+-- can move the owner's tokens), revokes and token-account closes.
+
+CREATE TABLE IF NOT EXISTS token_events (
+    id BIGSERIAL PRIMARY KEY,
+    signature TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    block_time TIMESTAMPTZ,
+    mint_pubkey TEXT NOT NULL REFERENCES mints(mint_pubkey),
+    kind TEXT NOT NULL,
+    token_account TEXT NOT NULL,
+    authority TEXT NOT NULL,
+    counterparty TEXT,
+    amount BIGINT,
+    tx_index INT NOT NULL,
+    ix_index INT NOT NULL,
+    inner_index INT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (signature, ix_index, inner_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_token_events_mint_slot
+    ON token_events (mint_pubkey, slot);
@@ -0,0 +1,6 @@
+-- Transfers are parsed from both SPL Token and Token-2022 (same instruction layouts); record
+-- which program executed each one. Rows written before this were all SPL Token.
+
+ALTER TABLE token_transfers
+    ADD COLUMN IF NOT EXISTS program TEXT NOT NULL
+    DEFAULT 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA';
@@ -0,0 +1,5 @@
+-- The MintTo source ("system") and Burn destination ("burn") are placeholders, not wallets, and
+-- the writer no longer keeps balances for them. Their old rows only ever went negative (or
+-- grew with burned supply) and showed up as holders.
+
+DELETE FROM balances WHERE wallet IN ('system', 'burn');
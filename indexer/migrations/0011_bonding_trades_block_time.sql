@@ -0,0 +1,4 @@
+-- The trending list aggregates trades of every mint over a recent block_time window.
+
+CREATE INDEX IF NOT EXISTS idx_bonding_curve_trades_block_time
+    ON bonding_curve_trades (block_time);
@@ -0,0 +1,5 @@
+-- First slot a mint traded on a venue other than its pump.fun bonding curve (Raydium, Meteora,
+-- Orca). Such a mint has graduated (or never had a curve), so its bonding progress is 100 even
+-- when the curve's own trades predate the indexed history. Maintained by the writer.
+
+ALTER TABLE mints ADD COLUMN IF NOT EXISTS migrated_slot BIGINT;
@@ -0,0 +1,34 @@
+-- price_nanos_per_token used to be lamports per raw token unit (sol_amount / token_amount,
+-- truncated). It is now nano-SOL per whole token, scaled by the mint's decimals and rounded half
+-- up (see indexer-core/src/price.rs). Recompute every stored trade's price that way, assuming 6
+-- decimals (the default) while a mint's are unknown, and queue the candles to be rebuilt.
+
+UPDATE bonding_curve_trades t
+SET price_nanos_per_token = CASE
+        WHEN t.token_amount <= 0 THEN 0
+        ELSE LEAST(
+            round(
+                t.sol_amount::NUMERIC
+                    * power(10::NUMERIC, CASE WHEN m.decimals < 0 THEN 6 ELSE m.decimals END)
+                    / t.token_amount
+            ),
+            9223372036854775807
+        )::BIGINT
+    END
+FROM mints m
+WHERE m.mint_pubkey = t.mint_pubkey;
+
+-- The candles were built from the old prices and are dropped. Rebuilding them needs the writer's
+-- candles.open_mode, which a migration can't read, so every (mint, timeframe) that had candles
+-- is queued here and rebuilt from the rescaled trades on the next start (see
+-- db::rebuild_queued_candles).
+CREATE TABLE candle_rebuilds (
+    mint_pubkey TEXT NOT NULL,
+    timeframe_secs INT NOT NULL,
+    PRIMARY KEY (mint_pubkey, timeframe_secs)
+);
+
+INSERT INTO candle_rebuilds (mint_pubkey, timeframe_secs)
+SELECT DISTINCT mint_pubkey, timeframe_secs FROM candles;
+
+DELETE FROM candles;
@@ -0,0 +1,121 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertDirection {
+    /// Fire when the price rises to or above the target.
+    Above,
+    /// Fire when the price falls to or below the target.
+    Below,
+}
+
+/// A price alert that survives restarts. Prices are in the indexer's units
+/// (`price_nanos_per_token`, i.e. the close of the latest candle).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PriceAlert {
+    pub mint: String,
+    pub target_price: f64,
+    pub direction: AlertDirection,
+    /// Disarmed after firing; re-armed once the price crosses back over the target.
+    #[serde(default = "default_armed")]
+    pub armed: bool,
+}
+
+fn default_armed() -> bool {
+    true
+}
+
+impl PriceAlert {
+    pub fn new(mint: impl Into<String>, target_price: f64, direction: AlertDirection) -> Self {
+        Self {
+            mint: mint.into(),
+            target_price,
+            direction,
+            armed: true,
+        }
+    }
+
+    fn is_crossed(&self, price: f64) -> bool {
+        match self.direction {
+            AlertDirection::Above => price >= self.target_price,
+            AlertDirection::Below => price <= self.target_price,
+        }
+    }
+
+    /// Evaluate the alert against a fresh price. Returns `true` exactly once per crossing:
+    /// the alert then stays quiet until the price moves back to the other side of the target.
+    pub fn check(&mut self, price: f64) -> bool {
+        let crossed = self.is_crossed(price);
+        if self.armed && crossed {
+            self.armed = false;
+            return true;
+        }
+        if !self.armed && !crossed {
+            self.armed = true;
+        }
+        false
+    }
+}
+
+/// Load alerts from a JSON file. A missing file means no alerts yet.
+pub fn load_alerts(path: &Path) -> Result<Vec<PriceAlert>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = std::fs::read(path)
+        .with_context(|| format!("failed to read alerts file {}", path.display()))?;
+    serde_json::from_slice(&data)
+        .with_context(|| format!("invalid alerts file {}", path.display()))
+}
+
+pub fn save_alerts(path: &Path, alerts: &[PriceAlert]) -> Result<()> {
+    let data = serde_json::to_vec_pretty(alerts)?;
+    std::fs::write(path, data)
+        .with_context(|| format!("failed to write alerts file {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alerts_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("tx-alerts-{}.json", std::process::id()));
+        let mut fired = PriceAlert::new("mint_b", 50.0, AlertDirection::Below);
+        fired.armed = false;
+        let alerts = vec![PriceAlert::new("mint_a", 120.0, AlertDirection::Above), fired];
+
+        save_alerts(&path, &alerts).unwrap();
+        assert_eq!(load_alerts(&path).unwrap(), alerts);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(load_alerts(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_alert_fires_once_and_rearms_after_crossing_back() {
+        let mut alert = PriceAlert::new("mint", 100.0, AlertDirection::Above);
+
+        assert!(!alert.check(90.0));
+        assert!(alert.check(105.0));
+        // Still above the target: no repeat notification
+        assert!(!alert.check(110.0));
+        // Crossing back re-arms without firing
+        assert!(!alert.check(95.0));
+        assert!(alert.armed);
+        assert!(alert.check(100.0));
+    }
+
+    #[test]
+    fn test_below_alert_and_missing_armed_field_defaults_to_armed() {
+        let mut alert: PriceAlert =
+            serde_json::from_str(r#"{"mint":"m","target_price":10.0,"direction":"below"}"#)
+                .unwrap();
+
+        assert!(alert.armed);
+        assert!(!alert.check(11.0));
+        assert!(alert.check(9.5));
+    }
+}
@@ -1,9 +1,19 @@
+use crate::network::{
+    BondingTrade, HolderBalance, MarketUpdate, MintSummary, SafetyCounts, TraderStats,
+    WatchQuote,
+};
+use crate::orders::{LimitOrder, OrderSide};
+use crate::profiles::{default_profiles, TradeProfile};
+use crate::report::PositionRow;
+use crate::swap::{PendingSwap, SwapOrder, SwapStatus};
 use solana_sdk::pubkey::Pubkey;
-use std::time::{Duration, Instant};
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 pub struct TokenInfo {
     pub name: String,
     pub symbol: String,
+    /// In USD: price × supply × the SOL price; 0 until all three are known.
     pub market_cap: f64,
     pub fdv: f64,
     pub liquidity: f64,
@@ -26,6 +36,12 @@ pub struct TokenInfo {
     pub liquidity_delta_percent: f64,
     pub holders_delta_percent: Option<f64>,
     pub mint: String,
+    /// Delegate approvals / revokes / account closes seen for the mint; `None` until loaded.
+    pub safety_counts: Option<SafetyCounts>,
+    /// The mint's decimals, read from chain when the token is opened; sells need them.
+    pub decimals: Option<u8>,
+    /// Total supply in whole tokens, read from chain with the decimals.
+    pub supply: Option<f64>,
 }
 
 #[derive(Clone, Debug)]
@@ -46,8 +62,10 @@ pub struct Token {
 pub struct Holder {
     pub address: String,
     pub balance: f64, // Percentage
-    pub value: f64,   // Value in USD
+    pub amount: u64,  // Raw token units
     pub is_dev: bool,
+    /// USD value of the holding: its share of the market cap, once that is known.
+    pub value: Option<f64>,
 }
 
 pub struct Trade {
@@ -56,32 +74,127 @@ pub struct Trade {
     pub price: f64,
     pub volume: f64,
     pub maker: String,
+    /// The maker's first trade in any token (the indexer's `is_new_wallet`): fresh money.
+    pub is_new_wallet: bool,
 }
 
-#[derive(Clone, Copy)]
+impl Trade {
+    /// Transactions-tab row for an indexed trade. Trades without a block time (live ones from
+    /// the websocket) are stamped with the time they arrived.
+    pub fn from_bonding(trade: &BondingTrade) -> Self {
+        let time = trade
+            .block_time
+            .as_deref()
+            .and_then(|t| t.get(11..19))
+            .map_or_else(|| format_hms(unix_now()), str::to_string);
+        Trade {
+            time,
+            type_: if trade.side == "sell" { "Sell" } else { "Buy" }.to_string(),
+            price: trade.price_nanos_per_token as f64 / 1e9,
+            volume: trade.sol_amount as f64 / 1e9,
+            maker: short_address(&trade.trader),
+            is_new_wallet: trade.is_new_wallet,
+        }
+    }
+
+    /// Maker column text; new wallets are marked with 🆕.
+    pub fn maker_label(&self) -> String {
+        if self.is_new_wallet {
+            format!("🆕 {}", self.maker)
+        } else {
+            self.maker.clone()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Candle {
+    pub timestamp: i64, // bucket start, unix seconds
     pub open: f64,
     pub high: f64,
     pub low: f64,
     pub close: f64,
 }
 
+/// Candle and price under the mouse cursor in the chart area.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChartCursor {
+    pub candle_index: usize,
+    pub price: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Theme {
     Light,
     Dark,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+impl std::str::FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "light" => Ok(Theme::Light),
+            "dark" => Ok(Theme::Dark),
+            other => Err(format!(
+                "invalid theme '{other}' (expected one of: light, dark)"
+            )),
+        }
+    }
+}
+
+/// Swap amount the number keys edit: SOL to spend, or tokens to sell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SwapField {
+    Buy,
+    Sell,
+}
+
+/// Input being edited in the limit-order modal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OrderField {
+    Amount,
+    Trigger,
+}
+
+/// Input being edited in the position-size calculator.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SizerField {
+    Risk,
+    Stop,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum CurrentScreen {
     Home,
     TokenDetails,
+    /// Open token and a second one charted side by side.
+    Compare,
+    /// Table of watched tokens with their latest indexer price.
+    Watchlist,
+    /// Scrollable history of `add_log` messages.
+    Logs,
+}
+
+/// Second token in the compare view, plus indexer candles for both sides once fetched.
+pub struct CompareView {
+    pub mint: String,
+    pub symbol: String,
+    pub candles: Vec<Candle>,
+    /// Fetched candles for the open token; the live `App::candles` are charted until then.
+    pub primary_candles: Option<Vec<Candle>>,
 }
 
 pub struct App {
     pub should_quit: bool,
     pub current_screen: CurrentScreen,
     pub token_list: Vec<String>,
-    pub logs: Vec<String>,
+    /// Messages with when they were logged, oldest first, capped at MAX_LOG_LINES.
+    pub logs: Vec<(Instant, String)>,
+    // Lines up from the newest log on the Logs screen; 0 follows new messages
+    pub log_scroll: usize,
+    // Where Esc leaves the Logs screen to
+    pub logs_return_screen: CurrentScreen,
     pub wallet_balance: u64,
     pub selected_tab: usize,
     pub wallet_pubkey: Option<Pubkey>,
@@ -89,8 +202,15 @@ pub struct App {
     pub token_info: TokenInfo,
     pub recent_trades: Vec<Trade>,
     pub holders: Vec<Holder>,
-    pub bottom_tab_index: usize, // 0 = Trades, 1 = Holders, 2 = Orders (maybe later)
+    pub bottom_tab_index: usize, // 0 = Trades, 1 = Positions, 3 = Holders
+    // The loaded wallet's open positions, largest value first
+    pub positions: Vec<PositionRow>,
+    // When `positions` last arrived; `None` until the first fetch lands
+    pub positions_loaded_at: Option<Instant>,
     pub swap_amount: String,
+    // Tokens to sell back to SOL, in whole (UI) units
+    pub sell_amount: String,
+    pub swap_field: SwapField,
     // Layout State
     pub col_constraints: [u16; 3], // Left, Center, Right in %
     pub row_constraints: [u16; 2], // Chart, Trades in %
@@ -102,18 +222,79 @@ pub struct App {
     // Chart State
     pub chart_x_offset: f64,
     pub chart_y_offset: f64,
+    // Candles per chart window is CHART_WINDOW_CANDLES / chart_zoom
+    pub chart_zoom: f64,
+    // Candle size of the main chart, one of CHART_TIMEFRAMES
+    pub chart_timeframe_secs: u32,
+    // Fetched candles per (mint, timeframe) with when they arrived; only the open mint's are kept
+    pub chart_candle_cache: HashMap<(String, u32), (Instant, Vec<Candle>)>,
+    pub chart_cursor: Option<ChartCursor>,
     pub last_tick: Instant,
     pub show_search_modal: bool,
+    // `?` overlay listing every key; takes all keys but `?`, Esc and scrolling while shown
+    pub show_help: bool,
+    pub help_scroll: u16,
+    // Set while the search modal is picking the second token for the compare view
+    pub compare_picking: bool,
+    pub compare: Option<CompareView>,
     pub search_select_index: usize,
+    // Position-size calculator
+    pub show_sizer_modal: bool,
+    pub sizer_risk_sol: String,
+    pub sizer_stop_price: String,
+    pub sizer_field: SizerField,
+    // Limit orders, oldest first; the Orders tab's selection indexes into them
+    pub orders: Vec<LimitOrder>,
+    pub orders_selected: usize,
+    // Limit-order modal for the open token
+    pub show_order_modal: bool,
+    pub order_side: OrderSide,
+    pub order_amount: String,
+    pub order_trigger: String,
+    pub order_field: OrderField,
+    // Trades of at least this many SOL are highlighted and logged
+    pub large_trade_sol: f64,
+    // Swap settings bundles; never empty. `f` cycles the active one
+    pub trade_profiles: Vec<TradeProfile>,
+    pub active_profile_index: usize,
+    // Slippage sent with quotes: the active profile's until `l` picks a preset
+    pub slippage_bps: u64,
+    // Quoted swap shown in the confirmation modal; nothing is signed until Enter
+    pub pending_swap: Option<PendingSwap>,
+    // Latest swap progress reports, oldest first, capped at MAX_SWAP_STATUSES
+    pub swap_statuses: Vec<SwapStatus>,
     pub filtered_tokens: Vec<Token>,
     pub all_tokens: Vec<Token>,
     pub ticks_since_candle: usize,
-    // Home View Lists
+    // Home View Lists, narrowed to the navbar search by `filter_home_lists`
     pub new_tokens: Vec<Token>,
     pub bonding_tokens: Vec<Token>,
     pub migrated_tokens: Vec<Token>,
+    // Every Home card, whatever the search; the lists above are recomputed from these
+    pub unfiltered_new_tokens: Vec<Token>,
+    pub unfiltered_bonding_tokens: Vec<Token>,
+    pub unfiltered_migrated_tokens: Vec<Token>,
     pub home_selected_col: usize, // 0=New, 1=Bonding, 2=Migrated
     pub home_selected_row: usize,
+    // Typing on Home goes into the navbar search while set (`/` or a click on the search box)
+    pub home_search_focused: bool,
+    // Rendered logo art per mint; cards without an entry show `Token.image_asc`
+    pub logo_art: HashMap<String, Vec<String>>,
+    // Mint whose `token_info.bonding_curve` came from the indexer; the simulation leaves it alone
+    pub bonding_progress_mint: Option<String>,
+    // Mint whose trades and price come from the indexer; the simulation stops while it is open
+    pub live_trades_mint: Option<String>,
+    // Mint whose chart shows indexer candles; the simulation stops while it is open
+    pub live_candles_mint: Option<String>,
+    // Watched mints, in the order they were added; no duplicates
+    pub watchlist: Vec<String>,
+    pub watchlist_selected: usize,
+    // Latest indexer quote per watched mint; mints without candles have no entry
+    pub watch_quotes: HashMap<String, WatchQuote>,
+    // Seconds the indexer trails the chain; None until known
+    pub indexer_lag_secs: Option<u64>,
+    // USD price of one SOL from Jupiter; None until fetched
+    pub sol_usd: Option<f64>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -124,7 +305,7 @@ pub enum DragState {
 }
 
 impl App {
-    pub fn new(player_wallet: Option<Pubkey>, balance: u64) -> Self {
+    pub fn new(player_wallet: Option<Pubkey>, balance: u64, theme: Theme) -> Self {
         let make_token = |name: &str,
                           sym: &str,
                           p: f64,
@@ -322,7 +503,9 @@ impl App {
         Self {
             should_quit: false,
             token_list: Vec::new(),
-            logs: vec!["Welcome to Trading Terminal".to_string()],
+            logs: vec![(Instant::now(), "Welcome to Trading Terminal".to_string())],
+            log_scroll: 0,
+            logs_return_screen: CurrentScreen::Home,
             wallet_balance: balance,
             selected_tab: 0,
             wallet_pubkey: player_wallet,
@@ -341,8 +524,8 @@ impl App {
                 change_1h: None,
                 change_6h: 34.06,
                 change_24h: 31.39,
-                vol_24h: 2160.0,
-                net_vol_24h: 126.0,
+                vol_24h: 14.4,
+                net_vol_24h: 0.84,
                 sell_pressure: 53.0,
                 traders_24h: 2,
                 net_buyers: None,
@@ -351,6 +534,9 @@ impl App {
                 liquidity_delta_percent: -100.0,
                 holders_delta_percent: None,
                 mint: "RAN...mockHOLE".to_string(),
+                safety_counts: None,
+                decimals: None,
+                supply: None,
             },
             recent_trades: vec![
                 Trade {
@@ -359,6 +545,7 @@ impl App {
                     price: 0.0041709,
                     volume: 11.86,
                     maker: "HMs...AHF".to_string(),
+                    is_new_wallet: false,
                 },
                 Trade {
                     time: "23h".to_string(),
@@ -366,6 +553,7 @@ impl App {
                     price: 0.0041709,
                     volume: 11.86,
                     maker: "HMs...AHF".to_string(),
+                    is_new_wallet: false,
                 },
                 Trade {
                     time: "1d".to_string(),
@@ -373,42 +561,78 @@ impl App {
                     price: 0.0031463,
                     volume: 1.482,
                     maker: "GwZ...5db".to_string(),
+                    is_new_wallet: false,
                 },
             ],
             holders: vec![
                 Holder {
                     address: "8gm5...zMuk".to_string(),
                     balance: 5.59,
-                    value: 0.0,
+                    amount: 55_900_000_000_000,
                     is_dev: true,
+                    value: None,
                 },
                 Holder {
                     address: "Ha2...XFR".to_string(),
                     balance: 1.2,
-                    value: 0.0,
+                    amount: 12_000_000_000_000,
                     is_dev: false,
+                    value: None,
                 },
                 Holder {
                     address: "9EB...FyF".to_string(),
                     balance: 0.8,
-                    value: 0.0,
+                    amount: 8_000_000_000_000,
                     is_dev: false,
+                    value: None,
                 },
             ],
             bottom_tab_index: 0,
+            positions: Vec::new(),
+            positions_loaded_at: None,
             swap_amount: "0.00".to_string(),
+            sell_amount: String::new(),
+            swap_field: SwapField::Buy,
             col_constraints: [20, 60, 20],
             row_constraints: [60, 40],
             drag_state: None,
-            theme: Theme::Dark,
+            theme,
             candles: generate_fake_candles(),
             search_input: String::new(),
             chart_x_offset: 0.0,
+            chart_zoom: 1.0,
+            chart_timeframe_secs: CHART_TIMEFRAMES[0].0,
+            chart_candle_cache: HashMap::new(),
             chart_y_offset: 0.0,
+            chart_cursor: None,
             last_tick: Instant::now(),
             show_search_modal: false,
+            show_help: false,
+            help_scroll: 0,
+            compare_picking: false,
+            compare: None,
             search_select_index: 0,
+            show_sizer_modal: false,
+            sizer_risk_sol: String::new(),
+            sizer_stop_price: String::new(),
+            sizer_field: SizerField::Risk,
+            orders: Vec::new(),
+            orders_selected: 0,
+            show_order_modal: false,
+            order_side: OrderSide::Buy,
+            order_amount: String::new(),
+            order_trigger: String::new(),
+            order_field: OrderField::Amount,
+            large_trade_sol: DEFAULT_LARGE_TRADE_SOL,
+            slippage_bps: default_profiles()[0].slippage_bps,
+            pending_swap: None,
+            swap_statuses: Vec::new(),
+            trade_profiles: default_profiles(),
+            active_profile_index: 0,
             filtered_tokens: all_tokens.clone(),
+            unfiltered_new_tokens: new_tokens.clone(),
+            unfiltered_bonding_tokens: bonding_tokens.clone(),
+            unfiltered_migrated_tokens: migrated_tokens.clone(),
             new_tokens,
             bonding_tokens,
             migrated_tokens,
@@ -417,9 +641,197 @@ impl App {
             current_screen: CurrentScreen::Home,
             home_selected_col: 0,
             home_selected_row: 0,
+            home_search_focused: false,
+            logo_art: HashMap::new(),
+            bonding_progress_mint: None,
+            live_trades_mint: None,
+            live_candles_mint: None,
+            watchlist: Vec::new(),
+            watchlist_selected: 0,
+            watch_quotes: HashMap::new(),
+            indexer_lag_secs: None,
+            sol_usd: None,
         }
     }
 
+    /// Token position size for the calculator's risk and stop inputs at the current price.
+    pub fn sizer_position_size(&self) -> Option<f64> {
+        let risk_sol = self.sizer_risk_sol.parse::<f64>().ok()?;
+        let stop_price = self.sizer_stop_price.parse::<f64>().ok()?;
+        position_size(risk_sol, self.token_info.price, stop_price)
+    }
+
+    /// The calculator input currently being edited.
+    pub fn sizer_input_mut(&mut self) -> &mut String {
+        match self.sizer_field {
+            SizerField::Risk => &mut self.sizer_risk_sol,
+            SizerField::Stop => &mut self.sizer_stop_price,
+        }
+    }
+
+    /// Step the main chart's timeframe through `CHART_TIMEFRAMES`, showing cached candles
+    /// when there are fresh ones. Returns whether the new timeframe's candles need fetching.
+    pub fn step_chart_timeframe(&mut self, up: bool) -> bool {
+        let index = CHART_TIMEFRAMES
+            .iter()
+            .position(|&(secs, _)| secs == self.chart_timeframe_secs)
+            .unwrap_or(0);
+        let index = if up {
+            (index + 1).min(CHART_TIMEFRAMES.len() - 1)
+        } else {
+            index.saturating_sub(1)
+        };
+        self.chart_timeframe_secs = CHART_TIMEFRAMES[index].0;
+
+        let key = (self.token_info.mint.clone(), self.chart_timeframe_secs);
+        match self.chart_candle_cache.get(&key) {
+            Some((at, candles)) if at.elapsed() < CHART_CACHE_TTL => {
+                let candles = candles.clone();
+                self.show_chart_candles(candles);
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Cache fetched chart candles and show them if they are for the open token's current
+    /// timeframe. Other mints' cached candles are dropped.
+    pub fn apply_chart_candles(&mut self, mint: &str, timeframe_secs: u32, candles: Vec<Candle>) {
+        if self.token_info.mint != mint {
+            return;
+        }
+        self.chart_candle_cache.retain(|(cached, _), _| cached == mint);
+        self.chart_candle_cache.insert(
+            (mint.to_string(), timeframe_secs),
+            (Instant::now(), candles.clone()),
+        );
+        if timeframe_secs == self.chart_timeframe_secs {
+            self.show_chart_candles(candles);
+        }
+    }
+
+    /// Label of the main chart's timeframe, e.g. `5m`.
+    pub fn chart_timeframe_label(&self) -> &'static str {
+        CHART_TIMEFRAMES
+            .iter()
+            .find(|&&(secs, _)| secs == self.chart_timeframe_secs)
+            .map_or("?", |&(_, label)| label)
+    }
+
+    fn show_chart_candles(&mut self, candles: Vec<Candle>) {
+        self.candles = candles;
+        self.chart_x_offset = (self.candles.len() as f64 - 0.9 * self.chart_window()).max(0.0);
+        self.chart_y_offset = 0.0;
+        self.live_candles_mint = Some(self.token_info.mint.clone());
+    }
+
+    /// Whether the main chart shows the open token's indexed candles rather than simulated ones.
+    pub fn candles_live(&self) -> bool {
+        self.live_candles_mint.as_deref() == Some(self.token_info.mint.as_str())
+    }
+
+    /// Candles across the chart at the current zoom.
+    pub fn chart_window(&self) -> f64 {
+        CHART_WINDOW_CANDLES / self.chart_zoom
+    }
+
+    /// Zoom the chart by `factor` (above 1 zooms in), keeping candle position `anchor_x` at
+    /// the same place on screen. The zoom is clamped to `MIN_CHART_ZOOM..=MAX_CHART_ZOOM`.
+    pub fn zoom_chart(&mut self, factor: f64, anchor_x: f64) {
+        let old_window = self.chart_window();
+        self.chart_zoom = (self.chart_zoom * factor).clamp(MIN_CHART_ZOOM, MAX_CHART_ZOOM);
+        let along = (anchor_x - self.chart_x_offset) / old_window;
+        self.chart_x_offset = anchor_x - along * self.chart_window();
+    }
+
+    /// The limit-order input currently being edited.
+    pub fn order_input_mut(&mut self) -> &mut String {
+        match self.order_field {
+            OrderField::Amount => &mut self.order_amount,
+            OrderField::Trigger => &mut self.order_trigger,
+        }
+    }
+
+    /// Open the limit-order modal for the open token, with empty inputs.
+    pub fn open_order_modal(&mut self) {
+        self.order_amount.clear();
+        self.order_trigger.clear();
+        self.order_field = OrderField::Amount;
+        self.show_order_modal = true;
+    }
+
+    /// Turn the modal's inputs into an order for the open token, with the active trade
+    /// profile's settings, and add it to `orders`. Fails with a loggable reason, leaving the
+    /// modal open.
+    pub fn place_order(&mut self) -> Result<LimitOrder, String> {
+        let trigger_price = self
+            .order_trigger
+            .parse::<f64>()
+            .ok()
+            .filter(|&p| p > 0.0)
+            .ok_or_else(|| format!("invalid trigger price '{}'", self.order_trigger))?;
+        // Only a sell amount is in the token's units
+        let (decimals, amount_decimals) = match (self.order_side, self.token_info.decimals) {
+            (OrderSide::Buy, decimals) => (decimals.unwrap_or(0), 9),
+            (OrderSide::Sell, Some(decimals)) => (decimals, decimals),
+            (OrderSide::Sell, None) => {
+                return Err(format!("{} decimals not loaded yet", self.token_info.symbol))
+            }
+        };
+        let amount = parse_token_amount(&self.order_amount, amount_decimals)
+            .filter(|&raw| raw > 0)
+            .ok_or_else(|| format!("invalid order amount '{}'", self.order_amount))?;
+        let profile = self.active_profile();
+        let order = LimitOrder {
+            mint: self.token_info.mint.clone(),
+            symbol: self.token_info.symbol.clone(),
+            side: self.order_side,
+            amount,
+            decimals,
+            trigger_price,
+            slippage_bps: self.slippage_bps,
+            priority_fee_lamports: profile.priority_fee_lamports,
+            max_impact_pct: profile.max_impact_pct,
+        };
+        self.orders.push(order.clone());
+        self.show_order_modal = false;
+        Ok(order)
+    }
+
+    /// Remove the order selected in the Orders tab.
+    pub fn cancel_selected_order(&mut self) -> Option<LimitOrder> {
+        (self.orders_selected < self.orders.len()).then(|| self.take_order(self.orders_selected))
+    }
+
+    /// Remove `orders[index]`, keeping the Orders tab's selection in range.
+    pub fn take_order(&mut self, index: usize) -> LimitOrder {
+        let order = self.orders.remove(index);
+        self.orders_selected = self.orders_selected.min(self.orders.len().saturating_sub(1));
+        order
+    }
+
+    /// Index of the first order on `mint` that `price` triggers.
+    pub fn triggered_order(&self, mint: &str, price: f64) -> Option<usize> {
+        self.orders
+            .iter()
+            .position(|order| order.mint == mint && order.is_triggered(price))
+    }
+
+    /// The swap amount currently being edited.
+    pub fn swap_input_mut(&mut self) -> &mut String {
+        match self.swap_field {
+            SwapField::Buy => &mut self.swap_amount,
+            SwapField::Sell => &mut self.sell_amount,
+        }
+    }
+
+    pub fn toggle_swap_field(&mut self) {
+        self.swap_field = match self.swap_field {
+            SwapField::Buy => SwapField::Sell,
+            SwapField::Sell => SwapField::Buy,
+        };
+    }
+
     pub fn toggle_theme(&mut self) {
         self.theme = match self.theme {
             Theme::Light => Theme::Dark,
@@ -429,24 +841,188 @@ impl App {
 
     pub fn tick(&self) {}
 
-    pub fn update_search_results(&mut self) {
-        if self.search_input.is_empty() {
-            self.filtered_tokens = self.all_tokens.clone();
+    /// Add a trade to the feed, logging it if it clears `large_trade_sol`.
+    pub fn record_trade(&mut self, trade: Trade) {
+        if is_large_trade(trade.volume, self.large_trade_sol) {
+            self.add_log(format!(
+                "🐋 {} SOL {}",
+                trade.volume,
+                trade.type_.to_uppercase()
+            ));
+        }
+        self.recent_trades.insert(0, trade);
+        self.recent_trades.truncate(MAX_RECENT_TRADES);
+    }
+
+    /// Raise or lower the large-trade threshold, keeping it at least one step.
+    pub fn adjust_large_trade_sol(&mut self, delta: f64) {
+        self.large_trade_sol = (self.large_trade_sol + delta).max(LARGE_TRADE_SOL_STEP);
+        self.add_log(format!(
+            "Large trade threshold: {} SOL",
+            self.large_trade_sol
+        ));
+    }
+
+    pub fn active_profile(&self) -> &TradeProfile {
+        &self.trade_profiles[self.active_profile_index]
+    }
+
+    /// Drop the swap awaiting confirmation, freeing its in-flight slot.
+    pub fn cancel_pending_swap(&mut self) {
+        if self.pending_swap.take().is_some() {
+            self.push_swap_status(SwapStatus::Cancelled);
+        }
+    }
+
+    /// Whether the Positions tab is on screen, so its rows are worth refreshing.
+    pub fn positions_tab_open(&self) -> bool {
+        self.current_screen == CurrentScreen::TokenDetails
+            && self.bottom_tab_index == POSITIONS_TAB
+    }
+
+    /// Store fetched positions, largest value first; unpriced positions go last.
+    pub fn apply_positions(&mut self, mut positions: Vec<PositionRow>) {
+        positions.sort_by(|a, b| {
+            let value = |row: &PositionRow| row.value_sol.unwrap_or(f64::NEG_INFINITY);
+            value(b).total_cmp(&value(a))
+        });
+        self.positions = positions;
+        self.positions_loaded_at = Some(Instant::now());
+    }
+
+    /// Record a swap task's progress for the Swap panel, and in the log.
+    pub fn push_swap_status(&mut self, status: SwapStatus) {
+        self.add_log(status.to_string());
+        self.swap_statuses.push(status);
+        if self.swap_statuses.len() > MAX_SWAP_STATUSES {
+            self.swap_statuses.remove(0);
+        }
+    }
+
+    /// Decimals for displaying raw amounts of `mint`: SOL's, or the open token's once loaded.
+    pub fn display_decimals(&self, mint: &str) -> Option<u8> {
+        if mint == WSOL_MINT {
+            Some(9)
+        } else if mint == self.token_info.mint {
+            self.token_info.decimals
         } else {
-            let query = self.search_input.to_lowercase();
-            self.filtered_tokens = self
-                .all_tokens
-                .iter()
-                .filter(|t| {
-                    t.name.to_lowercase().contains(&query)
-                        || t.symbol.to_lowercase().contains(&query)
-                })
-                .cloned()
-                .collect();
+            None
+        }
+    }
+
+    /// Replace the trade profiles (never empty) and activate the first one.
+    pub fn set_trade_profiles(&mut self, profiles: Vec<TradeProfile>) {
+        self.trade_profiles = profiles;
+        self.active_profile_index = 0;
+        self.slippage_bps = self.active_profile().slippage_bps;
+    }
+
+    /// Switch to the next slippage preset above the current setting, wrapping to the lowest.
+    pub fn cycle_slippage(&mut self) {
+        self.slippage_bps = SLIPPAGE_PRESETS_BPS
+            .into_iter()
+            .find(|&bps| bps > self.slippage_bps)
+            .unwrap_or(SLIPPAGE_PRESETS_BPS[0]);
+        self.add_log(format!("Slippage: {} bps", self.slippage_bps));
+    }
+
+    /// Switch to the next trade profile, wrapping around. Its slippage replaces any preset
+    /// picked with `l`.
+    pub fn cycle_trade_profile(&mut self) {
+        self.active_profile_index = (self.active_profile_index + 1) % self.trade_profiles.len();
+        self.slippage_bps = self.active_profile().slippage_bps;
+        let profile = self.active_profile();
+        let message = format!(
+            "Trade profile: {} ({} bps slippage, {} lamports priority fee)",
+            profile.name, profile.slippage_bps, profile.priority_fee_lamports
+        );
+        self.add_log(message);
+    }
+
+    /// A SOL -> open token swap of the entered amount, using the current slippage and the
+    /// active profile's other settings.
+    pub fn swap_order(&self) -> SwapOrder {
+        let amount_sol = self.swap_amount.parse::<f64>().unwrap_or(0.0);
+        let profile = self.active_profile();
+        SwapOrder {
+            input_mint: WSOL_MINT.to_string(),
+            output_mint: self.token_info.mint.clone(),
+            amount: (amount_sol * 1_000_000_000.0) as u64,
+            slippage_bps: self.slippage_bps,
+            priority_fee_lamports: profile.priority_fee_lamports,
+            max_impact_pct: profile.max_impact_pct,
         }
+    }
+
+    /// An open token -> SOL swap of the entered token amount, with the same settings as
+    /// [`App::swap_order`]. Fails with a loggable reason while the mint's decimals are unknown or the
+    /// amount isn't a positive quantity the mint can represent.
+    pub fn sell_order(&self) -> Result<SwapOrder, String> {
+        let decimals = self
+            .token_info
+            .decimals
+            .ok_or_else(|| format!("{} decimals not loaded yet", self.token_info.symbol))?;
+        let amount = parse_token_amount(&self.sell_amount, decimals)
+            .filter(|&raw| raw > 0)
+            .ok_or_else(|| {
+                format!(
+                    "invalid sell amount '{}' ({} has {} decimals)",
+                    self.sell_amount, self.token_info.symbol, decimals
+                )
+            })?;
+        let profile = self.active_profile();
+        Ok(SwapOrder {
+            input_mint: self.token_info.mint.clone(),
+            output_mint: WSOL_MINT.to_string(),
+            amount,
+            slippage_bps: self.slippage_bps,
+            priority_fee_lamports: profile.priority_fee_lamports,
+            max_impact_pct: profile.max_impact_pct,
+        })
+    }
+
+    /// Recompute the search modal's results, and the Home columns, from `search_input`.
+    pub fn update_search_results(&mut self) {
+        let query = self.search_input.to_lowercase();
+        self.filtered_tokens = self
+            .all_tokens
+            .iter()
+            .filter(|t| token_matches(t, &query))
+            .cloned()
+            .collect();
         if self.search_select_index >= self.filtered_tokens.len() {
             self.search_select_index = 0;
         }
+        self.filter_home_lists();
+    }
+
+    /// Narrow the Home columns to cards whose name or symbol contains `search_input`, keeping
+    /// the selected row inside its column.
+    pub fn filter_home_lists(&mut self) {
+        let query = self.search_input.to_lowercase();
+        let filter = |tokens: &[Token]| -> Vec<Token> {
+            tokens
+                .iter()
+                .filter(|t| token_matches(t, &query))
+                .cloned()
+                .collect()
+        };
+        self.new_tokens = filter(&self.unfiltered_new_tokens);
+        self.bonding_tokens = filter(&self.unfiltered_bonding_tokens);
+        self.migrated_tokens = filter(&self.unfiltered_migrated_tokens);
+        self.home_selected_row = self
+            .home_selected_row
+            .min(self.home_column_len().saturating_sub(1));
+    }
+
+    /// Number of cards shown in the selected Home column.
+    pub fn home_column_len(&self) -> usize {
+        match self.home_selected_col {
+            0 => self.new_tokens.len(),
+            1 => self.bonding_tokens.len(),
+            2 => self.migrated_tokens.len(),
+            _ => 0,
+        }
     }
 
     pub fn select_current_token(&mut self) {
@@ -454,27 +1030,360 @@ impl App {
             self.token_info.name = token.name.clone();
             self.token_info.symbol = token.symbol.clone();
             self.token_info.price = token.price;
-            // Update other derived fields roughly
-            self.token_info.market_cap = token.price * 1_000_000_000.0;
             self.token_info.mint = token.mint.clone();
+            self.token_info.safety_counts = None;
+            self.token_info.decimals = None;
+            self.token_info.supply = None;
+            self.holders.clear();
+            self.refresh_market_cap();
+            self.recent_trades.clear();
             self.show_search_modal = false;
             self.search_input.clear();
             self.update_search_results();
         }
     }
 
+    /// Fill the sidebar's trader fields, ignoring stats for a token that is no longer open.
+    pub fn apply_trader_stats(&mut self, mint: &str, stats: &TraderStats) {
+        if self.token_info.mint != mint {
+            return;
+        }
+        self.token_info.traders_24h = stats.unique_traders;
+        self.token_info.net_buyers = Some(stats.net_buyers);
+        let (bought, sold) = (stats.buy_volume_sol as f64, stats.sell_volume_sol as f64);
+        self.token_info.vol_24h = (bought + sold) / 1_000_000_000.0;
+        self.token_info.net_vol_24h = (bought - sold) / 1_000_000_000.0;
+    }
+
+    /// Fill the Safety Check panel, ignoring counts for a token that is no longer open.
+    pub fn apply_safety_counts(&mut self, mint: &str, counts: SafetyCounts) {
+        if self.token_info.mint != mint {
+            return;
+        }
+        self.token_info.safety_counts = Some(counts);
+    }
+
+    /// Show the open token's holders, ignoring a list for a token that is no longer open.
+    pub fn apply_holders(&mut self, mint: &str, holders: Vec<Holder>) {
+        if self.token_info.mint != mint {
+            return;
+        }
+        self.holders = holders;
+        self.refresh_market_cap();
+    }
+
+    /// Record the open token's total supply (whole tokens), ignoring it for a token that is no
+    /// longer open.
+    pub fn apply_token_supply(&mut self, mint: &str, supply: f64) {
+        if self.token_info.mint != mint {
+            return;
+        }
+        self.token_info.supply = Some(supply);
+        self.refresh_market_cap();
+    }
+
+    /// Record the USD price of one SOL, which values the open token in dollars.
+    pub fn apply_sol_price(&mut self, sol_usd: f64) {
+        self.sol_usd = Some(sol_usd);
+        self.refresh_market_cap();
+    }
+
+    /// Recompute the open token's USD market cap, and each holder's share of it, from its SOL
+    /// price, its supply and the SOL price. Both stay unknown (0 and `None`) until all three are.
+    pub fn refresh_market_cap(&mut self) {
+        let market_cap = match (self.token_info.supply, self.sol_usd) {
+            (Some(supply), Some(sol_usd)) => Some(self.token_info.price * supply * sol_usd),
+            _ => None,
+        };
+        self.token_info.market_cap = market_cap.unwrap_or(0.0);
+        for holder in &mut self.holders {
+            holder.value = market_cap.map(|mc| holder.balance / 100.0 * mc);
+        }
+    }
+
+    /// SOL value of `holder`'s balance at the open token's price, once its decimals are known.
+    pub fn holder_value_sol(&self, holder: &Holder) -> Option<f64> {
+        let decimals = self.token_info.decimals?;
+        Some(holder.amount as f64 / 10f64.powi(decimals as i32) * self.token_info.price)
+    }
+
+    /// Record the open token's decimals, ignoring them for a token that is no longer open.
+    pub fn apply_token_decimals(&mut self, mint: &str, decimals: u8) {
+        if self.token_info.mint != mint {
+            return;
+        }
+        self.token_info.decimals = Some(decimals);
+    }
+
+    /// Fill the header from the indexer's view of a token opened from the watchlist, ignoring a
+    /// summary for a token that is no longer open.
+    pub fn apply_mint_summary(&mut self, mint: &str, summary: MintSummary) {
+        if self.token_info.mint != mint {
+            return;
+        }
+        if let Some(symbol) = summary.symbol {
+            self.token_info.name = symbol.clone();
+            self.token_info.symbol = symbol;
+        }
+        self.token_info.decimals = Some(summary.decimals);
+        self.token_info.holders = summary.holder_count;
+        if let Some(price) = summary.price {
+            self.token_info.price = price;
+            self.refresh_market_cap();
+        }
+    }
+
+    /// Show the indexer's bonding-curve progress, ignoring it for a token that is no longer open.
+    pub fn apply_bonding_progress(&mut self, mint: &str, progress_pct: f64) {
+        let progress_pct = progress_pct.clamp(0.0, 100.0);
+        for card in self.home_cards_mut(mint) {
+            card.bonding = progress_pct;
+        }
+        if self.token_info.mint != mint {
+            return;
+        }
+        self.token_info.bonding_curve = progress_pct;
+        self.bonding_progress_mint = Some(mint.to_string());
+    }
+
+    /// Reprice the Home cards for `update`'s mint, in whichever column holds it. Returns
+    /// whether any card matched.
+    pub fn apply_market_update(&mut self, update: &MarketUpdate) -> bool {
+        let mut matched = false;
+        for card in self.home_cards_mut(&update.mint) {
+            card.price = update.price;
+            matched = true;
+        }
+        if self.trades_live() && update.mint == self.token_info.mint {
+            self.token_info.price = update.price;
+            self.refresh_market_cap();
+            if let Some(trade) = &update.trade {
+                self.record_trade(Trade::from_bonding(trade));
+            }
+        }
+        matched
+    }
+
+    /// Whether the open token's Transactions tab shows indexed trades rather than simulated ones.
+    pub fn trades_live(&self) -> bool {
+        self.live_trades_mint.as_deref() == Some(self.token_info.mint.as_str())
+    }
+
+    /// Replace the simulated trades with the open token's indexed ones (newest first), ignoring
+    /// trades for a token that is no longer open. Live trades are prepended from then on.
+    pub fn apply_bonding_trades(&mut self, mint: &str, trades: &[BondingTrade]) {
+        if self.token_info.mint != mint {
+            return;
+        }
+        self.recent_trades = trades
+            .iter()
+            .take(MAX_RECENT_TRADES)
+            .map(Trade::from_bonding)
+            .collect();
+        if let Some(latest) = self.recent_trades.first() {
+            self.token_info.price = latest.price;
+            self.refresh_market_cap();
+        }
+        self.live_trades_mint = Some(mint.to_string());
+    }
+
+    /// Home cards for `mint`, both as shown and in the unfiltered lists.
+    fn home_cards_mut<'a>(&'a mut self, mint: &'a str) -> impl Iterator<Item = &'a mut Token> {
+        self.new_tokens
+            .iter_mut()
+            .chain(&mut self.bonding_tokens)
+            .chain(&mut self.migrated_tokens)
+            .chain(&mut self.unfiltered_new_tokens)
+            .chain(&mut self.unfiltered_bonding_tokens)
+            .chain(&mut self.unfiltered_migrated_tokens)
+            .filter(move |card| card.mint == mint)
+    }
+
+    /// Add `mint` to the watchlist. Returns `false` if it was already watched.
+    pub fn watch(&mut self, mint: &str) -> bool {
+        if mint.is_empty() || self.watchlist.iter().any(|m| m == mint) {
+            return false;
+        }
+        self.watchlist.push(mint.to_string());
+        true
+    }
+
+    /// Remove `mint` from the watchlist. Returns `false` if it wasn't watched.
+    pub fn unwatch(&mut self, mint: &str) -> bool {
+        let Some(pos) = self.watchlist.iter().position(|m| m == mint) else {
+            return false;
+        };
+        self.watchlist.remove(pos);
+        self.watch_quotes.remove(mint);
+        self.watchlist_selected = self
+            .watchlist_selected
+            .min(self.watchlist.len().saturating_sub(1));
+        true
+    }
+
+    /// Add the open token to the watchlist, or remove it if already watched.
+    pub fn toggle_watch_current(&mut self) {
+        let mint = self.token_info.mint.clone();
+        let symbol = self.token_info.symbol.clone();
+        if self.unwatch(&mint) {
+            self.add_log(format!("Removed {symbol} from watchlist"));
+        } else if self.watch(&mint) {
+            self.add_log(format!("Added {symbol} to watchlist"));
+        }
+    }
+
+    /// Card or search entry for `mint`, if the terminal knows the token.
+    pub fn known_token(&self, mint: &str) -> Option<&Token> {
+        self.all_tokens
+            .iter()
+            .chain(&self.unfiltered_new_tokens)
+            .chain(&self.unfiltered_bonding_tokens)
+            .chain(&self.unfiltered_migrated_tokens)
+            .find(|t| t.mint == mint)
+    }
+
+    /// Open the highlighted watchlist entry in the token details view. Unknown tokens are shown
+    /// under their shortened mint until the indexer's summary arrives. Returns the opened mint.
+    pub fn open_watched_token(&mut self) -> Option<String> {
+        let mint = self.watchlist.get(self.watchlist_selected)?.clone();
+        let (name, symbol) = match self.known_token(&mint) {
+            Some(token) => (token.name.clone(), token.symbol.clone()),
+            None => {
+                let short: String = mint.chars().take(6).collect();
+                (mint.clone(), short)
+            }
+        };
+        if let Some(quote) = self.watch_quotes.get(&mint) {
+            self.token_info.price = quote.price;
+            if let Some(change_pct) = quote.change_pct {
+                self.token_info.change_24h = change_pct;
+            }
+        }
+        self.token_info.name = name;
+        self.token_info.symbol = symbol;
+        self.token_info.mint = mint.clone();
+        self.token_info.safety_counts = None;
+        self.token_info.decimals = None;
+        self.token_info.supply = None;
+        self.holders.clear();
+        self.refresh_market_cap();
+        self.recent_trades.clear();
+        self.current_screen = CurrentScreen::TokenDetails;
+        Some(mint)
+    }
+
+    /// Mints of the home-screen cards that have no rendered logo yet, without duplicates.
+    pub fn card_mints_without_logo(&self) -> Vec<String> {
+        let mut mints: Vec<String> = Vec::new();
+        for token in self
+            .unfiltered_new_tokens
+            .iter()
+            .chain(&self.unfiltered_bonding_tokens)
+            .chain(&self.unfiltered_migrated_tokens)
+        {
+            if !self.logo_art.contains_key(&token.mint) && !mints.contains(&token.mint) {
+                mints.push(token.mint.clone());
+            }
+        }
+        mints
+    }
+
+    /// Open the compare view against the token highlighted in the search modal.
+    /// Until real candles arrive the second chart shows a simulated series at that token's
+    /// price. Returns the picked mint.
+    pub fn start_compare(&mut self) -> Option<String> {
+        let token = self.filtered_tokens.get(self.search_select_index)?.clone();
+        let scale = token.price / 0.004;
+        let candles = generate_fake_candles()
+            .into_iter()
+            .map(|c| Candle {
+                open: c.open * scale,
+                high: c.high * scale,
+                low: c.low * scale,
+                close: c.close * scale,
+                ..c
+            })
+            .collect();
+
+        self.compare = Some(CompareView {
+            mint: token.mint.clone(),
+            symbol: token.symbol.clone(),
+            candles,
+            primary_candles: None,
+        });
+        self.current_screen = CurrentScreen::Compare;
+        self.compare_picking = false;
+        self.show_search_modal = false;
+        self.search_input.clear();
+        self.update_search_results();
+        Some(token.mint)
+    }
+
+    /// Store fetched candles for whichever side of the compare view `mint` belongs to.
+    pub fn apply_compare_candles(&mut self, mint: &str, candles: Vec<Candle>) {
+        let primary_mint = self.token_info.mint.clone();
+        let Some(compare) = self.compare.as_mut() else {
+            return;
+        };
+        if candles.is_empty() {
+            return;
+        }
+        if compare.mint == mint {
+            compare.candles = candles;
+        } else if primary_mint == mint {
+            compare.primary_candles = Some(candles);
+        }
+    }
+
     pub fn quit(&mut self) {
         self.should_quit = true;
     }
 
     pub fn add_log(&mut self, message: String) {
-        self.logs.push(message);
-        if self.logs.len() > 100 {
+        self.logs.push((Instant::now(), message));
+        if self.logs.len() > MAX_LOG_LINES {
             self.logs.remove(0);
         }
+        // Keep a scrolled-back selection on the same message
+        if self.log_scroll > 0 {
+            self.log_scroll = (self.log_scroll + 1).min(self.logs.len() - 1);
+        }
+    }
+
+    /// Show the Logs screen at the newest message; Esc comes back to the current screen.
+    pub fn open_logs(&mut self) {
+        if self.current_screen != CurrentScreen::Logs {
+            self.logs_return_screen = self.current_screen;
+        }
+        self.current_screen = CurrentScreen::Logs;
+        self.log_scroll = 0;
+    }
+
+    pub fn close_logs(&mut self) {
+        self.current_screen = self.logs_return_screen;
+    }
+
+    /// Move the Logs screen selection towards older messages.
+    pub fn scroll_logs_up(&mut self) {
+        if self.log_scroll + 1 < self.logs.len() {
+            self.log_scroll += 1;
+        }
+    }
+
+    /// Move the Logs screen selection towards newer messages.
+    pub fn scroll_logs_down(&mut self) {
+        self.log_scroll = self.log_scroll.saturating_sub(1);
+    }
+
+    /// Index in `logs` of the line selected on the Logs screen.
+    pub fn selected_log(&self) -> Option<usize> {
+        self.logs.len().checked_sub(1 + self.log_scroll)
     }
 
     pub fn simulate_market_activity(&mut self) {
+        if self.trades_live() || self.candles_live() {
+            return;
+        }
         // Update much faster for smoother animation (e.g. 50ms)
         if self.last_tick.elapsed() < Duration::from_millis(50) {
             return;
@@ -492,9 +1401,12 @@ impl App {
 
         // Update token info
         self.token_info.price = new_price;
-        self.token_info.market_cap = new_price * 1_000_000_000.0 * 0.5; // Rough estimate
-        self.token_info.bonding_curve =
-            (self.token_info.bonding_curve + (if is_buy { 0.1 } else { -0.05 })).clamp(0.0, 100.0);
+        self.refresh_market_cap();
+        if self.bonding_progress_mint.as_deref() != Some(self.token_info.mint.as_str()) {
+            self.token_info.bonding_curve = (self.token_info.bonding_curve
+                + (if is_buy { 0.1 } else { -0.05 }))
+            .clamp(0.0, 100.0);
+        }
 
         // Add to trade history
         let volume = (rand::random::<f64>() * 10.0 + 0.1).round();
@@ -508,11 +1420,9 @@ impl App {
             price: new_price,
             volume,
             maker: "Simulated".to_string(),
+            is_new_wallet: is_buy && rand::random::<f64>() < 0.1,
         };
-        self.recent_trades.insert(0, trade);
-        if self.recent_trades.len() > 50 {
-            self.recent_trades.pop();
-        }
+        self.record_trade(trade);
 
         // Update Charts (Candles)
         // For simplicity, just update the last candle's close price
@@ -531,7 +1441,13 @@ impl App {
         self.ticks_since_candle += 1;
         if self.ticks_since_candle > 20 {
             let last_close = self.candles.last().map(|c| c.close).unwrap_or(new_price);
+            let timestamp = self
+                .candles
+                .last()
+                .map(|c| c.timestamp + CANDLE_SECS)
+                .unwrap_or_else(unix_now);
             let new_candle = Candle {
+                timestamp,
                 open: last_close,
                 high: last_close,
                 low: last_close,
@@ -541,23 +1457,182 @@ impl App {
             self.ticks_since_candle = 0;
 
             // Auto-scroll to keep latest candle in view
-            if self.candles.len() > 50 {
-                self.chart_x_offset = (self.candles.len() as f64 - 45.0).max(0.0);
+            let window = self.chart_window();
+            if self.candles.len() as f64 > window {
+                self.chart_x_offset = (self.candles.len() as f64 - 0.9 * window).max(0.0);
             }
         }
     }
 }
 
+/// Number of tokens to buy so that hitting `stop_price` loses `risk_sol`.
+/// Prices are in SOL per token. Returns `None` for non-positive or non-finite inputs and
+/// when the stop sits on the entry price (the size would be unbounded).
+pub fn position_size(risk_sol: f64, entry_price: f64, stop_price: f64) -> Option<f64> {
+    if !(risk_sol > 0.0 && entry_price > 0.0 && stop_price > 0.0) {
+        return None;
+    }
+    if !(risk_sol.is_finite() && entry_price.is_finite() && stop_price.is_finite()) {
+        return None;
+    }
+
+    let stop_distance = (entry_price - stop_price).abs();
+    if stop_distance <= f64::EPSILON * entry_price {
+        return None;
+    }
+
+    Some(risk_sol / stop_distance)
+}
+
+/// Raw units of a decimal token amount typed as `input` ("1.5" with 6 decimals is 1_500_000).
+/// Parsed digit by digit so there is no float rounding. `None` for malformed input, more
+/// fractional digits than `decimals`, or an overflow.
+pub fn parse_token_amount(input: &str, decimals: u8) -> Option<u64> {
+    let (whole, frac) = input.split_once('.').unwrap_or((input, ""));
+    if whole.is_empty() && frac.is_empty() || frac.len() > decimals as usize {
+        return None;
+    }
+    if !whole.chars().chain(frac.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    format!("{whole}{frac:0<width$}", width = decimals as usize)
+        .parse::<u64>()
+        .ok()
+}
+
+pub const DEFAULT_LARGE_TRADE_SOL: f64 = 10.0;
+/// Whether `token`'s name or symbol contains `query`, which must already be lowercase.
+fn token_matches(token: &Token, query: &str) -> bool {
+    token.name.to_lowercase().contains(query) || token.symbol.to_lowercase().contains(query)
+}
+
+/// How many messages `add_log` keeps.
+pub const MAX_LOG_LINES: usize = 100;
+/// How many trades the Transactions tab keeps.
+pub const MAX_RECENT_TRADES: usize = 50;
+/// How many swap statuses the Swap panel keeps.
+pub const MAX_SWAP_STATUSES: usize = 5;
+/// Main chart timeframes in seconds, with their labels, shortest first.
+pub const CHART_TIMEFRAMES: [(u32, &str); 4] =
+    [(60, "1m"), (300, "5m"), (3_600, "1h"), (86_400, "1d")];
+/// How long fetched chart candles are reused when switching back to their timeframe.
+pub const CHART_CACHE_TTL: Duration = Duration::from_secs(30);
+/// Candles across the chart at zoom 1.
+pub const CHART_WINDOW_CANDLES: f64 = 50.0;
+/// Zoom range of the mouse wheel: from 100 candles across down to 5.
+pub const MIN_CHART_ZOOM: f64 = 0.5;
+pub const MAX_CHART_ZOOM: f64 = 10.0;
+/// Zoom change per mouse-wheel notch.
+pub const CHART_ZOOM_STEP: f64 = 1.25;
+/// Share of the visible price range the Up/Down keys pan the chart by.
+pub const CHART_Y_PAN_STEP: f64 = 0.05;
+/// Index of the Positions tab in the Token Details bottom panel.
+pub const POSITIONS_TAB: usize = 1;
+/// Index of the Orders tab in the Token Details bottom panel.
+pub const ORDERS_TAB: usize = 2;
+
+/// Wrapped SOL, the input mint of every swap.
+pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+/// Slippage values the `l` key steps through, in basis points.
+pub const SLIPPAGE_PRESETS_BPS: [u64; 4] = [50, 100, 300, 500];
+/// Step used by the `+`/`-` keys when adjusting the large-trade threshold.
+pub const LARGE_TRADE_SOL_STEP: f64 = 1.0;
+
+/// Holders of the Holders tab: the top `limit` of `balances` (largest first), each as a
+/// share of the total held across all of `balances`.
+pub fn holders_from_balances(balances: &[HolderBalance], limit: usize) -> Vec<Holder> {
+    let total: f64 = balances.iter().map(|b| b.amount.max(0) as f64).sum();
+    balances
+        .iter()
+        .take(limit)
+        .map(|b| Holder {
+            address: short_address(&b.wallet),
+            balance: if total > 0.0 { b.amount as f64 / total * 100.0 } else { 0.0 },
+            amount: b.amount.max(0) as u64,
+            // The indexer does not record mint creators
+            is_dev: false,
+            value: None,
+        })
+        .collect()
+}
+
+/// `address` as its first and last four characters, e.g. `8gm5...zMuk`.
+pub fn short_address(address: &str) -> String {
+    let chars: Vec<char> = address.chars().collect();
+    if chars.len() <= 11 {
+        return address.to_string();
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{head}...{tail}")
+}
+
+/// Whether a trade of `volume_sol` counts as a "large print" for the given threshold.
+pub fn is_large_trade(volume_sol: f64, threshold_sol: f64) -> bool {
+    volume_sol.is_finite() && volume_sol >= threshold_sol
+}
+
+/// Longest swap amount the input box accepts.
+pub const MAX_AMOUNT_LEN: usize = 20;
+
+/// Apply one typed character to a decimal amount input.
+/// Accepts digits and a single '.', caps the length at [`MAX_AMOUNT_LEN`], and refuses
+/// extra leading zeros ("00", "01"); a leading '.' becomes "0.".
+/// Returns whether the character was accepted.
+pub fn apply_amount_keypress(input: &mut String, c: char) -> bool {
+    if input.len() >= MAX_AMOUNT_LEN {
+        return false;
+    }
+
+    match c {
+        '.' => {
+            if input.contains('.') {
+                return false;
+            }
+            if input.is_empty() {
+                input.push('0');
+            }
+            input.push('.');
+            true
+        }
+        '0'..='9' => {
+            if input == "0" {
+                return false;
+            }
+            input.push(c);
+            true
+        }
+        _ => false,
+    }
+}
+
+const CANDLE_SECS: i64 = 60;
+
+/// Format a unix timestamp as `HH:MM:SS` (UTC).
+pub fn format_hms(timestamp: i64) -> String {
+    let secs = timestamp.rem_euclid(86_400);
+    format!("{:02}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
 fn generate_fake_candles() -> Vec<Candle> {
     let mut candles = Vec::new();
     let mut price = 0.0040;
-    for _ in 0..50 {
+    let start = unix_now() - 50 * CANDLE_SECS;
+    for i in 0..50 {
         let change = (rand::random::<f64>() - 0.5) * 0.0002;
         let open = price;
         let close = price + change;
         let high = open.max(close) + (rand::random::<f64>() * 0.0001);
         let low = open.min(close) - (rand::random::<f64>() * 0.0001);
         candles.push(Candle {
+            timestamp: start + i * CANDLE_SECS,
             open,
             high,
             low,
@@ -567,3 +1642,712 @@ fn generate_fake_candles() -> Vec<Candle> {
     }
     candles
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_all(keys: &str) -> String {
+        let mut input = String::new();
+        for c in keys.chars() {
+            apply_amount_keypress(&mut input, c);
+        }
+        input
+    }
+
+    #[test]
+    fn test_amount_accepts_valid_decimals() {
+        assert_eq!(type_all("1.5"), "1.5");
+        assert_eq!(type_all("0.25"), "0.25");
+        assert_eq!(type_all("120"), "120");
+        assert_eq!(type_all(".5"), "0.5");
+    }
+
+    #[test]
+    fn test_amount_rejects_second_dot() {
+        let mut input = "1.5".to_string();
+        assert!(!apply_amount_keypress(&mut input, '.'));
+        assert_eq!(input, "1.5");
+        assert_eq!(type_all("1..2.3"), "1.23");
+    }
+
+    #[test]
+    fn test_amount_rejects_extra_leading_zeros() {
+        assert_eq!(type_all("00"), "0");
+        assert_eq!(type_all("05"), "0");
+        assert_eq!(type_all("0.05"), "0.05");
+    }
+
+    #[test]
+    fn test_amount_caps_length() {
+        let input = type_all(&"9".repeat(50));
+        assert_eq!(input.len(), MAX_AMOUNT_LEN);
+        assert!(input.parse::<f64>().unwrap().is_finite());
+    }
+
+    #[test]
+    fn test_position_size_formula() {
+        // Risk 1 SOL, entry 0.004, stop 0.003 -> 1 / 0.001 = 1000 tokens
+        let size = position_size(1.0, 0.004, 0.003).unwrap();
+        assert!((size - 1000.0).abs() < 1e-6);
+
+        // Stop above entry (short) uses the same distance
+        let size = position_size(0.5, 0.004, 0.005).unwrap();
+        assert!((size - 500.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_position_size_zero_stop_distance() {
+        assert_eq!(position_size(1.0, 0.004, 0.004), None);
+    }
+
+    #[test]
+    fn test_position_size_invalid_inputs() {
+        assert_eq!(position_size(0.0, 0.004, 0.003), None);
+        assert_eq!(position_size(-1.0, 0.004, 0.003), None);
+        assert_eq!(position_size(1.0, 0.0, 0.003), None);
+        assert_eq!(position_size(1.0, 0.004, 0.0), None);
+        assert_eq!(position_size(f64::INFINITY, 0.004, 0.003), None);
+        assert_eq!(position_size(f64::NAN, 0.004, 0.003), None);
+    }
+
+    #[test]
+    fn test_sizer_uses_token_price() {
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.token_info.price = 0.004;
+        app.sizer_risk_sol = "2".to_string();
+        app.sizer_stop_price = "0.003".to_string();
+        assert!((app.sizer_position_size().unwrap() - 2000.0).abs() < 1e-6);
+
+        app.sizer_stop_price = "0.".to_string();
+        assert_eq!(app.sizer_position_size(), None);
+    }
+
+    #[test]
+    fn test_theme_from_str() {
+        assert_eq!("light".parse::<Theme>(), Ok(Theme::Light));
+        assert_eq!("Dark".parse::<Theme>(), Ok(Theme::Dark));
+        assert_eq!(" DARK ".parse::<Theme>(), Ok(Theme::Dark));
+
+        let err = "solarized".parse::<Theme>().unwrap_err();
+        assert!(err.contains("light, dark"));
+    }
+
+    #[test]
+    fn test_amount_rejects_non_numeric() {
+        let mut input = "1".to_string();
+        assert!(!apply_amount_keypress(&mut input, 'e'));
+        assert!(!apply_amount_keypress(&mut input, '-'));
+        assert_eq!(input, "1");
+    }
+
+    #[test]
+    fn test_large_trade_classification() {
+        assert!(is_large_trade(10.0, 10.0));
+        assert!(is_large_trade(12.5, 10.0));
+        assert!(!is_large_trade(9.99, 10.0));
+        assert!(!is_large_trade(f64::NAN, 10.0));
+    }
+
+    #[test]
+    fn test_maker_label_marks_new_wallets() {
+        let mut trade = Trade {
+            time: "Just now".to_string(),
+            type_: "Buy".to_string(),
+            price: 0.004,
+            volume: 1.0,
+            maker: "HMs...AHF".to_string(),
+            is_new_wallet: false,
+        };
+        assert_eq!(trade.maker_label(), "HMs...AHF");
+
+        trade.is_new_wallet = true;
+        assert_eq!(trade.maker_label(), "🆕 HMs...AHF");
+    }
+
+    #[test]
+    fn test_record_trade_logs_only_large_prints() {
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.large_trade_sol = 2.0;
+        let logs_before = app.logs.len();
+        let trade = |volume: f64| Trade {
+            time: "Just now".to_string(),
+            type_: "Buy".to_string(),
+            price: 0.004,
+            volume,
+            maker: "maker".to_string(),
+            is_new_wallet: false,
+        };
+
+        app.record_trade(trade(1.5));
+        assert_eq!(app.logs.len(), logs_before);
+
+        app.record_trade(trade(2.5));
+        assert_eq!(app.logs.last().unwrap().1, "🐋 2.5 SOL BUY");
+        assert_eq!(app.recent_trades[0].volume, 2.5);
+
+        app.adjust_large_trade_sol(-10.0);
+        assert_eq!(app.large_trade_sol, LARGE_TRADE_SOL_STEP);
+    }
+
+    #[test]
+    fn test_trader_stats_only_apply_to_open_token() {
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.token_info.mint = "open_mint".to_string();
+        let stats = TraderStats {
+            unique_traders: 12,
+            net_buyers: -3,
+            buy_volume_sol: 1_500_000_000,
+            sell_volume_sol: 4_000_000_000,
+        };
+
+        app.apply_trader_stats("other_mint", &stats);
+        assert_eq!(app.token_info.net_buyers, None);
+
+        app.apply_trader_stats("open_mint", &stats);
+        assert_eq!(app.token_info.traders_24h, 12);
+        assert_eq!(app.token_info.net_buyers, Some(-3));
+        assert_eq!(app.token_info.vol_24h, 5.5);
+        assert_eq!(app.token_info.net_vol_24h, -2.5);
+    }
+
+    #[test]
+    fn test_swap_order_uses_active_trade_profile() {
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.token_info.mint = "open_mint".to_string();
+        app.swap_amount = "0.5".to_string();
+        app.trade_profiles = vec![
+            TradeProfile {
+                name: "safe".to_string(),
+                slippage_bps: 50,
+                priority_fee_lamports: 10_000,
+                max_impact_pct: Some(2.0),
+            },
+            TradeProfile {
+                name: "degen".to_string(),
+                slippage_bps: 1_500,
+                priority_fee_lamports: 2_000_000,
+                max_impact_pct: None,
+            },
+        ];
+
+        assert_eq!(app.swap_order().slippage_bps, 50);
+
+        app.cycle_trade_profile();
+        assert_eq!(app.active_profile().name, "degen");
+        assert!(app.logs.last().unwrap().1.starts_with("Trade profile: degen"));
+        assert_eq!(
+            app.swap_order(),
+            SwapOrder {
+                input_mint: WSOL_MINT.to_string(),
+                output_mint: "open_mint".to_string(),
+                amount: 500_000_000,
+                slippage_bps: 1_500,
+                priority_fee_lamports: 2_000_000,
+                max_impact_pct: None,
+            }
+        );
+
+        // Wraps back to the first profile
+        app.cycle_trade_profile();
+        assert_eq!(app.active_profile().name, "safe");
+    }
+
+    #[test]
+    fn test_safety_counts_only_apply_to_open_token() {
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.token_info.mint = "open_mint".to_string();
+        let counts = SafetyCounts {
+            approve: 2,
+            revoke: 1,
+            close_account: 0,
+        };
+
+        app.apply_safety_counts("other_mint", counts.clone());
+        assert_eq!(app.token_info.safety_counts, None);
+
+        app.apply_safety_counts("open_mint", counts.clone());
+        assert_eq!(app.token_info.safety_counts, Some(counts));
+    }
+
+    #[test]
+    fn test_watchlist_add_remove_without_duplicates() {
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.token_info.mint = "open_mint".to_string();
+
+        app.toggle_watch_current();
+        assert!(!app.watch("open_mint"));
+        assert!(app.watch("other_mint"));
+        assert_eq!(app.watchlist, vec!["open_mint", "other_mint"]);
+
+        app.watchlist_selected = 1;
+        app.watch_quotes.insert(
+            "other_mint".to_string(),
+            WatchQuote {
+                price: 0.002,
+                change_pct: None,
+            },
+        );
+        assert!(app.unwatch("other_mint"));
+        assert!(!app.unwatch("other_mint"));
+        assert!(app.watch_quotes.is_empty());
+        assert_eq!(app.watchlist_selected, 0);
+
+        app.toggle_watch_current();
+        assert!(app.watchlist.is_empty());
+        assert_eq!(app.logs.last().unwrap().1, "Removed RAN from watchlist");
+    }
+
+    #[test]
+    fn test_watched_token_opens_with_indexer_summary() {
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.watch("7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU");
+        app.watch_quotes.insert(
+            "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU".to_string(),
+            WatchQuote {
+                price: 0.003,
+                change_pct: Some(-12.5),
+            },
+        );
+
+        let mint = app.open_watched_token().unwrap();
+        assert_eq!(app.token_info.symbol, "7xKXtg");
+        assert_eq!(app.token_info.price, 0.003);
+        assert_eq!(app.token_info.change_24h, -12.5);
+
+        let summary = MintSummary {
+            symbol: Some("WIF".to_string()),
+            decimals: 6,
+            holder_count: 1_234,
+            price: Some(0.004),
+        };
+        app.apply_mint_summary("other_mint", summary.clone());
+        assert_eq!(app.token_info.symbol, "7xKXtg");
+
+        app.apply_mint_summary(&mint, summary);
+        assert_eq!(
+            (app.token_info.name.as_str(), app.token_info.symbol.as_str()),
+            ("WIF", "WIF")
+        );
+        assert_eq!(app.token_info.decimals, Some(6));
+        assert_eq!(app.token_info.holders, 1_234);
+        assert_eq!(app.token_info.price, 0.004);
+        // No market cap until the supply and the SOL price are known.
+        assert_eq!(app.token_info.market_cap, 0.0);
+    }
+
+    #[test]
+    fn test_market_cap_and_holder_values_in_usd() {
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.token_info.mint = "open".to_string();
+        app.token_info.price = 0.000_05;
+        app.apply_holders(
+            "open",
+            holders_from_balances(
+                &[
+                    HolderBalance { wallet: "a".to_string(), amount: 300 },
+                    HolderBalance { wallet: "b".to_string(), amount: 100 },
+                ],
+                10,
+            ),
+        );
+        assert!(app.holders.iter().all(|h| h.value.is_none()));
+
+        app.apply_token_supply("other", 1.0);
+        app.apply_token_supply("open", 1_000_000_000.0);
+        assert_eq!(app.token_info.market_cap, 0.0);
+
+        // 0.00005 SOL × 1B tokens × $150.
+        app.apply_sol_price(150.0);
+        assert_eq!(app.token_info.market_cap, 7_500_000.0);
+        assert_eq!(app.holders[0].value, Some(5_625_000.0));
+        assert_eq!(app.holders[1].value, Some(1_875_000.0));
+
+        // A new token starts over until its supply loads.
+        app.token_info.mint = "next".to_string();
+        app.token_info.supply = None;
+        app.refresh_market_cap();
+        assert_eq!(app.token_info.market_cap, 0.0);
+    }
+
+    #[test]
+    fn test_slippage_presets_cycle_and_reach_quotes() {
+        let mut app = App::new(None, 0, Theme::Dark);
+        assert_eq!(app.slippage_bps, 50);
+
+        app.cycle_slippage();
+        assert_eq!(app.swap_order().slippage_bps, 100);
+        assert_eq!(app.logs.last().unwrap().1, "Slippage: 100 bps");
+        app.cycle_slippage();
+        app.cycle_slippage();
+        assert_eq!(app.slippage_bps, 500);
+        app.cycle_slippage();
+        assert_eq!(app.slippage_bps, 50);
+
+        // A profile's own value (between presets) moves to the next preset up
+        app.cycle_trade_profile();
+        assert_eq!(app.slippage_bps, app.active_profile().slippage_bps);
+        app.slippage_bps = 200;
+        app.cycle_slippage();
+        assert_eq!(app.slippage_bps, 300);
+    }
+
+    #[test]
+    fn test_parse_token_amount_uses_mint_decimals() {
+        assert_eq!(parse_token_amount("1.5", 6), Some(1_500_000));
+        assert_eq!(parse_token_amount("1.5", 9), Some(1_500_000_000));
+        assert_eq!(parse_token_amount("250", 0), Some(250));
+        assert_eq!(parse_token_amount("0.", 6), Some(0));
+        assert_eq!(parse_token_amount(".000001", 6), Some(1));
+        // Finer than the mint can represent
+        assert_eq!(parse_token_amount("0.0000001", 6), None);
+        assert_eq!(parse_token_amount("", 6), None);
+        assert_eq!(parse_token_amount("1e3", 6), None);
+        assert_eq!(parse_token_amount("99999999999999999999", 6), None);
+    }
+
+    #[test]
+    fn test_sell_order_swaps_token_back_to_sol() {
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.token_info.mint = "open_mint".to_string();
+        app.swap_field = SwapField::Sell;
+        app.swap_input_mut().push_str("2.25");
+        assert!(app.sell_order().unwrap_err().contains("decimals not loaded"));
+
+        app.apply_token_decimals("other_mint", 9);
+        assert_eq!(app.token_info.decimals, None);
+        app.apply_token_decimals("open_mint", 6);
+        let order = app.sell_order().unwrap();
+        assert_eq!(order.input_mint, "open_mint");
+        assert_eq!(order.output_mint, WSOL_MINT);
+        assert_eq!(order.amount, 2_250_000);
+        assert_eq!(order.slippage_bps, app.active_profile().slippage_bps);
+        // The buy amount is untouched
+        assert_eq!(app.swap_amount, "0.00");
+
+        app.sell_amount = "0".to_string();
+        assert!(app.sell_order().is_err());
+    }
+
+    #[test]
+    fn test_place_trigger_and_cancel_orders() {
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.token_info.mint = "open_mint".to_string();
+        app.open_order_modal();
+        app.order_amount = "0.5".to_string();
+        app.order_field = OrderField::Trigger;
+        app.order_input_mut().push_str("0.002");
+        let buy = app.place_order().unwrap();
+        assert_eq!((buy.amount, buy.trigger_price), (500_000_000, 0.002));
+        assert_eq!(buy.slippage_bps, app.active_profile().slippage_bps);
+        assert!(!app.show_order_modal);
+
+        app.open_order_modal();
+        app.order_side = OrderSide::Sell;
+        app.order_amount = "3".to_string();
+        app.order_trigger = "0.004".to_string();
+        assert!(app.place_order().unwrap_err().contains("decimals not loaded"));
+        app.apply_token_decimals("open_mint", 6);
+        assert_eq!(app.place_order().unwrap().amount, 3_000_000);
+        app.order_trigger = "0".to_string();
+        assert!(app.place_order().is_err());
+
+        assert_eq!(app.triggered_order("open_mint", 0.003), None);
+        assert_eq!(app.triggered_order("open_mint", 0.0019), Some(0));
+        assert_eq!(app.triggered_order("open_mint", 0.005), Some(1));
+        assert_eq!(app.triggered_order("other_mint", 0.005), None);
+
+        app.orders_selected = 1;
+        assert_eq!(app.cancel_selected_order().unwrap().side, OrderSide::Sell);
+        assert_eq!((app.orders.len(), app.orders_selected), (1, 0));
+    }
+
+    #[test]
+    fn test_live_bonding_progress_is_not_simulated() {
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.token_info.mint = "open_mint".to_string();
+
+        app.apply_bonding_progress("other_mint", 40.0);
+        assert_eq!(app.bonding_progress_mint, None);
+
+        app.apply_bonding_progress("open_mint", 140.0);
+        assert_eq!(app.token_info.bonding_curve, 100.0);
+        app.apply_bonding_progress("open_mint", 37.5);
+        for _ in 0..20 {
+            app.last_tick = Instant::now() - Duration::from_secs(1);
+            app.simulate_market_activity();
+        }
+        assert_eq!(app.token_info.bonding_curve, 37.5);
+
+        // Another token goes back to the simulated gauge
+        app.token_info.mint = "next_mint".to_string();
+        app.last_tick = Instant::now() - Duration::from_secs(1);
+        app.simulate_market_activity();
+        assert_ne!(app.token_info.bonding_curve, 37.5);
+    }
+
+    #[test]
+    fn test_swap_status_history_is_capped() {
+        use crate::swap::SwapStage;
+
+        let mut app = App::new(None, 0, Theme::Dark);
+        for i in 0..MAX_SWAP_STATUSES + 2 {
+            app.push_swap_status(SwapStatus::Sent { sig: format!("sig{i}") });
+        }
+        app.push_swap_status(SwapStatus::Failed {
+            stage: SwapStage::Send,
+            msg: "blockhash not found".to_string(),
+        });
+        assert_eq!(app.swap_statuses.len(), MAX_SWAP_STATUSES);
+        assert_eq!(app.swap_statuses[0], SwapStatus::Sent { sig: "sig3".to_string() });
+        assert_eq!(app.logs.last().unwrap().1, "Send failed: blockhash not found");
+    }
+
+    #[test]
+    fn test_log_scroll_stays_within_history() {
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.current_screen = CurrentScreen::TokenDetails;
+        app.add_log("first".to_string());
+        app.add_log("second".to_string());
+
+        app.open_logs();
+        assert_eq!(app.current_screen, CurrentScreen::Logs);
+        assert_eq!(app.logs[app.selected_log().unwrap()].1, "second");
+
+        app.scroll_logs_up();
+        assert_eq!(app.logs[app.selected_log().unwrap()].1, "first");
+        for _ in 0..app.logs.len() + 5 {
+            app.scroll_logs_up();
+        }
+        assert_eq!(app.selected_log(), Some(0));
+        while app.logs[app.selected_log().unwrap()].1 != "first" {
+            app.scroll_logs_down();
+        }
+
+        // Scrolled back history keeps its place as new lines arrive
+        app.add_log("third".to_string());
+        assert_eq!(app.logs[app.selected_log().unwrap()].1, "first");
+
+        app.close_logs();
+        assert_eq!(app.current_screen, CurrentScreen::TokenDetails);
+    }
+
+    #[test]
+    fn test_navbar_search_filters_home_columns() {
+        let mut app = App::new(None, 0, Theme::Dark);
+        let total = app.new_tokens.len() + app.bonding_tokens.len() + app.migrated_tokens.len();
+        let target = app.bonding_tokens[0].clone();
+        app.home_selected_col = 1;
+        app.home_selected_row = app.bonding_tokens.len() - 1;
+
+        app.search_input = target.symbol.to_uppercase();
+        app.filter_home_lists();
+        assert!(app.bonding_tokens.iter().any(|t| t.mint == target.mint));
+        let shown = app
+            .new_tokens
+            .iter()
+            .chain(&app.bonding_tokens)
+            .chain(&app.migrated_tokens);
+        let query = target.symbol.to_lowercase();
+        for token in shown {
+            assert!(token_matches(token, &query));
+        }
+        assert!(app.home_selected_row < app.home_column_len());
+
+        app.search_input = "no token is called this".to_string();
+        app.filter_home_lists();
+        assert_eq!(app.home_column_len(), 0);
+        assert_eq!(app.home_selected_row, 0);
+
+        app.search_input.clear();
+        app.update_search_results();
+        let restored = app.new_tokens.len() + app.bonding_tokens.len() + app.migrated_tokens.len();
+        assert_eq!(restored, total);
+    }
+
+    #[test]
+    fn test_market_update_reprices_card_in_its_column() {
+        let mut app = App::new(None, 0, Theme::Dark);
+        let mint = app.bonding_tokens[0].mint.clone();
+        app.search_input = app.bonding_tokens[0].symbol.clone();
+        app.filter_home_lists();
+
+        let update = MarketUpdate {
+            mint: mint.clone(),
+            price: 0.0042,
+            bonding_trade: true,
+            trade: None,
+        };
+        assert!(app.apply_market_update(&update));
+        app.apply_bonding_progress(&mint, 150.0);
+        let card = app.bonding_tokens.iter().find(|t| t.mint == mint).unwrap();
+        assert_eq!((card.price, card.bonding), (0.0042, 100.0));
+
+        // The unfiltered copy keeps the update once the search is cleared
+        app.search_input.clear();
+        app.filter_home_lists();
+        let card = app.bonding_tokens.iter().find(|t| t.mint == mint).unwrap();
+        assert_eq!(card.price, 0.0042);
+
+        let unknown = MarketUpdate {
+            mint: "not_on_home".to_string(),
+            ..update
+        };
+        assert!(!app.apply_market_update(&unknown));
+    }
+
+    #[test]
+    fn test_positions_sorted_by_value_with_unpriced_last() {
+        let row = |mint: &str, value_sol: Option<f64>| PositionRow {
+            mint: mint.to_string(),
+            holdings: 1.0,
+            avg_cost_sol: None,
+            cost_basis_sol: None,
+            value_sol,
+        };
+        let mut app = App::new(None, 0, Theme::Dark);
+        assert!(app.positions_loaded_at.is_none());
+        app.apply_positions(vec![
+            row("small", Some(0.5)),
+            row("unpriced", None),
+            row("large", Some(3.0)),
+        ]);
+        let mints: Vec<&str> = app.positions.iter().map(|p| p.mint.as_str()).collect();
+        assert_eq!(mints, ["large", "small", "unpriced"]);
+        assert!(app.positions_loaded_at.is_some());
+
+        assert!(!app.positions_tab_open());
+        app.current_screen = CurrentScreen::TokenDetails;
+        app.bottom_tab_index = POSITIONS_TAB;
+        assert!(app.positions_tab_open());
+    }
+
+    #[test]
+    fn test_holders_are_shares_of_the_total_held() {
+        let balance = |wallet: &str, amount| HolderBalance {
+            wallet: wallet.to_string(),
+            amount,
+        };
+        let balances = [
+            balance("8gm5aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaazMuk", 600),
+            balance("bob", 300),
+            balance("carol", 100),
+        ];
+        let holders = holders_from_balances(&balances, 2);
+        assert_eq!(holders.len(), 2);
+        assert_eq!(holders[0].address, "8gm5...zMuk");
+        assert_eq!((holders[0].balance, holders[0].amount), (60.0, 600));
+        assert_eq!((holders[1].address.as_str(), holders[1].balance), ("bob", 30.0));
+
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.token_info.mint = "open".to_string();
+        app.apply_holders("stale", Vec::new());
+        assert_eq!(app.holders.len(), 3);
+        app.apply_holders("open", holders);
+        assert_eq!(app.holders.len(), 2);
+
+        app.token_info.price = 0.5;
+        assert_eq!(app.holder_value_sol(&app.holders[0]), None);
+        app.apply_token_decimals("open", 2);
+        assert_eq!(app.holder_value_sol(&app.holders[0]), Some(3.0));
+    }
+
+    #[test]
+    fn test_indexed_trades_replace_the_simulation() {
+        let bonding = |side: &str, block_time: Option<&str>| BondingTrade {
+            signature: "sig".to_string(),
+            block_time: block_time.map(str::to_string),
+            trader: "9EBxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxFyF".to_string(),
+            side: side.to_string(),
+            sol_amount: 2_500_000_000,
+            price_nanos_per_token: 30,
+            is_new_wallet: false,
+        };
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.token_info.mint = "open".to_string();
+        app.apply_bonding_trades("stale", &[bonding("buy", None)]);
+        assert!(!app.trades_live());
+
+        let history = [bonding("sell", Some("2024-05-01T12:34:56.789Z"))];
+        app.apply_bonding_trades("open", &history);
+        assert!(app.trades_live());
+        assert_eq!(app.recent_trades.len(), 1);
+        let trade = &app.recent_trades[0];
+        assert_eq!((trade.time.as_str(), trade.type_.as_str()), ("12:34:56", "Sell"));
+        assert_eq!((trade.volume, trade.maker.as_str()), (2.5, "9EBx...xFyF"));
+        assert_eq!(app.token_info.price, 0.00000003);
+
+        // The simulation no longer adds trades; websocket ones are prepended
+        app.last_tick = Instant::now() - Duration::from_secs(1);
+        app.simulate_market_activity();
+        assert_eq!(app.recent_trades.len(), 1);
+        let update = MarketUpdate {
+            mint: "open".to_string(),
+            price: 0.00000004,
+            bonding_trade: true,
+            trade: Some(bonding("buy", None)),
+        };
+        app.apply_market_update(&update);
+        assert_eq!(app.recent_trades.len(), 2);
+        assert_eq!(app.recent_trades[0].type_, "Buy");
+        assert_eq!(app.token_info.price, 0.00000004);
+    }
+
+    #[test]
+    fn test_chart_zoom_keeps_anchor_in_place_and_clamps() {
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.chart_x_offset = 10.0;
+        // Candle 30 sits 40% across the 50-candle window
+        app.zoom_chart(2.0, 30.0);
+        assert_eq!((app.chart_zoom, app.chart_window()), (2.0, 25.0));
+        assert_eq!(app.chart_x_offset, 20.0);
+
+        for _ in 0..20 {
+            app.zoom_chart(CHART_ZOOM_STEP, 30.0);
+        }
+        assert_eq!(app.chart_zoom, MAX_CHART_ZOOM);
+        for _ in 0..40 {
+            app.zoom_chart(1.0 / CHART_ZOOM_STEP, 30.0);
+        }
+        assert_eq!(app.chart_window(), 100.0);
+    }
+
+    #[test]
+    fn test_chart_timeframe_steps_and_reuses_cached_candles() {
+        let candle = |close: f64| Candle {
+            timestamp: 0,
+            open: close,
+            high: close,
+            low: close,
+            close,
+        };
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.token_info.mint = "open".to_string();
+        assert_eq!(app.chart_timeframe_label(), "1m");
+        app.step_chart_timeframe(false);
+        assert_eq!(app.chart_timeframe_secs, 60);
+
+        assert!(app.step_chart_timeframe(true));
+        assert_eq!((app.chart_timeframe_secs, app.chart_timeframe_label()), (300, "5m"));
+        app.apply_chart_candles("open", 300, vec![candle(2.0)]);
+        app.apply_chart_candles("stale", 300, vec![candle(9.0)]);
+        assert_eq!(app.candles, [candle(2.0)]);
+
+        // Indexed candles stop the simulation from drawing over them
+        assert!(app.candles_live());
+        app.last_tick = Instant::now() - Duration::from_secs(1);
+        app.simulate_market_activity();
+        assert_eq!(app.candles, [candle(2.0)]);
+
+        // 1m was never fetched; 5m comes back from the cache
+        assert!(app.step_chart_timeframe(false));
+        app.apply_chart_candles("open", 60, vec![candle(1.0)]);
+        assert!(!app.step_chart_timeframe(true));
+        assert_eq!(app.candles, [candle(2.0)]);
+
+        for _ in 0..5 {
+            app.step_chart_timeframe(true);
+        }
+        assert_eq!(app.chart_timeframe_label(), "1d");
+    }
+}
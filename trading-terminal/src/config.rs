@@ -1,3 +1,4 @@
+use crate::app::Theme;
 use clap::Parser;
 use dotenv::dotenv;
 
@@ -16,6 +17,52 @@ pub struct Args {
     /// Keypair file path
     #[arg(short, long, env = "KEYPAIR_PATH")]
     pub keypair_path: Option<String>,
+
+    /// Color theme at startup: light or dark (toggle at runtime with `t`)
+    #[arg(long, env = "TERMINAL_THEME", default_value = "dark")]
+    pub theme: Theme,
+
+    /// Base URL of the indexer API
+    #[arg(long, env = "INDEXER_URL", default_value = "http://127.0.0.1:8080")]
+    pub indexer_url: String,
+
+    /// JSON file of price alerts; alerts in it are re-armed and polled on startup
+    #[arg(long, env = "ALERTS_FILE")]
+    pub alerts_file: Option<String>,
+
+    /// Timeout in seconds for every RPC, indexer and Jupiter request
+    #[arg(long, env = "REQUEST_TIMEOUT_SECS", default_value_t = 10)]
+    pub request_timeout_secs: u64,
+
+    /// JSON file of trade profiles (slippage, priority fee, impact limit); `f` cycles them.
+    /// Built-in safe/fast/degen profiles are used when unset or missing
+    #[arg(long, env = "PROFILES_FILE")]
+    pub profiles_file: Option<String>,
+
+    /// Refuse to execute swaps whose quoted price impact exceeds this percentage
+    #[arg(long, env = "MAX_IMPACT_PCT")]
+    pub max_impact_pct: Option<f64>,
+
+    /// Maximum number of swaps in flight at once; further `s` presses are rejected
+    #[arg(long, env = "MAX_INFLIGHT_SWAPS", default_value_t = 1)]
+    pub max_inflight_swaps: usize,
+
+    /// Times a swap transaction is sent before giving up; each retry refreshes the blockhash
+    /// and doubles the priority fee
+    #[arg(long, env = "SEND_MAX_ATTEMPTS", default_value_t = 3)]
+    pub send_max_attempts: u32,
+
+    /// JSON file of watched mints; `w` adds or removes the open token, `W` shows the list
+    #[arg(long, env = "WATCHLIST_FILE", default_value = "watchlist.json")]
+    pub watchlist_file: String,
+
+    /// JSON file of limit orders; `o` places one on the open token, `x` cancels the selected one
+    #[arg(long, env = "ORDERS_FILE", default_value = "orders.json")]
+    pub orders_file: String,
+
+    /// Directory position reports (`e`) are written to
+    #[arg(long, env = "REPORT_DIR", default_value = ".")]
+    pub report_dir: String,
 }
 
 pub fn load_config() -> Args {
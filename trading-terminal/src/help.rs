@@ -0,0 +1,78 @@
+/// One row of the `?` help overlay.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyHelp {
+    /// Screen (or modal) the key works on; rows are grouped by it in table order.
+    pub screen: &'static str,
+    pub keys: &'static str,
+    pub action: &'static str,
+}
+
+const fn key(screen: &'static str, keys: &'static str, action: &'static str) -> KeyHelp {
+    KeyHelp {
+        screen,
+        keys,
+        action,
+    }
+}
+
+/// Every keybinding, grouped by screen. Add a row here when adding a key in `main.rs`.
+pub const KEY_HELP: &[KeyHelp] = &[
+    key("Anywhere", "q", "Quit"),
+    key("Anywhere", "?", "Show or hide this help"),
+    key("Anywhere", "L", "Log screen"),
+    key("Anywhere", "e", "Export a position report for the loaded wallet"),
+    key("Home", "Arrows", "Move between columns and tokens"),
+    key("Home", "Enter", "Open the selected token"),
+    key("Home", "/", "Search tokens by name or symbol; Esc clears"),
+    key("Home", "W", "Watchlist"),
+    key("Token Details", "0-9 .", "Type the amount being edited"),
+    key("Token Details", "a", "Switch between the buy (SOL) and sell (token) amount"),
+    key("Token Details", "s / S", "Buy with SOL / sell back to SOL"),
+    key("Token Details", "f", "Cycle the trade profile"),
+    key("Token Details", "l", "Cycle the slippage"),
+    key("Token Details", "o", "Place a limit order"),
+    key("Token Details", "p", "Position-size calculator"),
+    key("Token Details", "w", "Watch or unwatch the token"),
+    key("Token Details", "W", "Watchlist"),
+    key("Token Details", "c", "Compare with another token"),
+    key("Token Details", "/", "Search tokens"),
+    key("Token Details", "[ / ]", "Shorter / longer chart timeframe"),
+    key("Token Details", "Arrows", "Pan the chart"),
+    key("Token Details", "Wheel", "Zoom the chart"),
+    key("Token Details", "Tab", "Next bottom tab"),
+    key("Token Details", "r", "Refresh the Positions tab"),
+    key("Token Details", "Up / Down", "Select an order (Orders tab)"),
+    key("Token Details", "x", "Cancel the selected order (Orders tab)"),
+    key("Token Details", "+ / -", "Raise / lower the large-trade threshold"),
+    key("Token Details", "t", "Toggle the theme"),
+    key("Token Details", "Esc", "Back to Home"),
+    key("Swap confirmation", "Enter / Esc", "Send / cancel the quoted swap"),
+    key("Watchlist", "Up / Down", "Select a token"),
+    key("Watchlist", "Enter", "Open the selected token"),
+    key("Watchlist", "d", "Remove the selected token"),
+    key("Watchlist", "Esc", "Back to Home"),
+    key("Logs", "Up / Down", "Scroll"),
+    key("Logs", "Esc", "Back"),
+    key("Compare", "Left / Right", "Pan both charts"),
+    key("Compare", "Esc", "Back to Token Details"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_help_screens_are_contiguous_without_duplicate_keys() {
+        let mut seen_screens: Vec<&str> = Vec::new();
+        for (i, row) in KEY_HELP.iter().enumerate() {
+            if seen_screens.last() != Some(&row.screen) {
+                assert!(!seen_screens.contains(&row.screen), "{} split up", row.screen);
+                seen_screens.push(row.screen);
+            }
+            let duplicate = KEY_HELP[..i]
+                .iter()
+                .any(|other| other.screen == row.screen && other.keys == row.keys);
+            assert!(!duplicate, "{} listed twice on {}", row.keys, row.screen);
+        }
+    }
+}
@@ -1,5 +1,12 @@
+pub mod alerts;
 pub mod app;
 pub mod config;
+pub mod help;
+pub mod logo;
 pub mod network;
+pub mod orders;
+pub mod profiles;
+pub mod report;
 pub mod swap;
 pub mod ui;
+pub mod watchlist;
@@ -0,0 +1,149 @@
+use crate::network::{http_client, DEFAULT_REQUEST_TIMEOUT};
+use anyhow::{anyhow, Context, Result};
+use image::imageops::FilterType;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Size of the logo box on a token card, in terminal cells.
+pub const LOGO_COLS: u32 = 8;
+pub const LOGO_ROWS: u32 = 3;
+
+/// Characters from darkest to brightest.
+const LUMINANCE_RAMP: &[u8] = b" .:-=+*#%@";
+
+/// Pixels less opaque than this render as blank cells.
+const MIN_ALPHA: u8 = 128;
+
+/// Map a luminance value (0 = black, 255 = white) to a character of increasing ink density.
+pub fn luminance_to_char(luma: u8) -> char {
+    let idx = luma as usize * (LUMINANCE_RAMP.len() - 1) / u8::MAX as usize;
+    LUMINANCE_RAMP[idx] as char
+}
+
+/// Decode an image (PNG, JPEG or GIF) and downscale it to `cols` x `rows` characters.
+pub fn render_logo(bytes: &[u8], cols: u32, rows: u32) -> Result<Vec<String>> {
+    let img = image::load_from_memory(bytes).context("unsupported logo image")?;
+    let small = img
+        .resize_exact(cols, rows, FilterType::Triangle)
+        .to_luma_alpha8();
+
+    Ok(small
+        .rows()
+        .map(|row| {
+            row.map(|px| {
+                let [luma, alpha] = px.0;
+                if alpha < MIN_ALPHA {
+                    ' '
+                } else {
+                    luminance_to_char(luma)
+                }
+            })
+            .collect()
+        })
+        .collect())
+}
+
+/// Token list entry of the metadata endpoint; only the logo is used.
+#[derive(Deserialize)]
+struct TokenMetadata {
+    #[serde(rename = "logoURI")]
+    logo_uri: Option<String>,
+}
+
+/// Resolves token logos through Jupiter's token metadata endpoint.
+pub struct LogoClient {
+    client: reqwest::Client,
+    metadata_url: String,
+}
+
+impl LogoClient {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            client: http_client(DEFAULT_REQUEST_TIMEOUT)?,
+            metadata_url: "https://tokens.jup.ag/token".to_string(),
+        })
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Result<Self> {
+        self.client = http_client(timeout)?;
+        Ok(self)
+    }
+
+    /// Fetch the logo of `mint` and render it for a token card.
+    pub async fn fetch_logo_art(&self, mint: &str) -> Result<Vec<String>> {
+        let url = format!("{}/{}", self.metadata_url, mint);
+        let metadata: TokenMetadata = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let logo_uri = metadata
+            .logo_uri
+            .filter(|uri| !uri.is_empty())
+            .ok_or_else(|| anyhow!("no logo for {mint}"))?;
+
+        let bytes = self
+            .client
+            .get(&logo_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        render_logo(&bytes, LOGO_COLS, LOGO_ROWS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, ImageFormat, Rgba, RgbaImage};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_luminance_to_char_spans_the_ramp() {
+        assert_eq!(luminance_to_char(0), ' ');
+        assert_eq!(luminance_to_char(255), '@');
+        assert_eq!(luminance_to_char(128), '=');
+
+        // Brighter never maps to a lighter character
+        let ramp: Vec<usize> = (0..=255u8)
+            .map(|l| {
+                LUMINANCE_RAMP
+                    .iter()
+                    .position(|&c| c as char == luminance_to_char(l))
+                    .unwrap()
+            })
+            .collect();
+        assert!(ramp.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_render_logo_downscales_png() {
+        // Left half white, right half transparent
+        let img = RgbaImage::from_fn(32, 12, |x, _| {
+            if x < 16 {
+                Rgba([255, 255, 255, 255])
+            } else {
+                Rgba([0, 0, 0, 0])
+            }
+        });
+        let mut png = Vec::new();
+        DynamicImage::ImageRgba8(img)
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let art = render_logo(&png, 8, 3).unwrap();
+
+        assert_eq!(art.len(), 3);
+        for line in &art {
+            assert_eq!(line.chars().count(), 8);
+            // The filter may soften the middle column, not the edges
+            assert!(line.starts_with("@@@") && line.ends_with("   "), "{line:?}");
+        }
+        assert!(render_logo(b"not an image", 8, 3).is_err());
+    }
+}
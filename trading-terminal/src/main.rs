@@ -9,31 +9,563 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     Terminal,
 };
-use std::{io, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use tokio::sync::mpsc;
+use futures_util::{SinkExt, StreamExt};
+use tokio::sync::{mpsc, OwnedSemaphorePermit};
+use tokio_tungstenite::tungstenite::Message as WsMessage;
 
-use tx_terminal::app::{App, CurrentScreen, DragState};
-use tx_terminal::ui::ui;
+use tx_terminal::app::{
+    apply_amount_keypress, holders_from_balances, App, Candle, CurrentScreen, DragState, Holder,
+    OrderField, SizerField, CHART_Y_PAN_STEP, CHART_ZOOM_STEP, LARGE_TRADE_SOL_STEP,
+    MAX_RECENT_TRADES, ORDERS_TAB, POSITIONS_TAB, WSOL_MINT,
+};
+use tx_terminal::ui::{chart_bounds, chart_cursor_at, chart_point_at, is_too_small, ui};
 
-use base64::{engine::general_purpose, Engine as _};
 use solana_sdk::{
-    signer::{keypair::read_keypair_file, Signer},
-    transaction::VersionedTransaction,
+    pubkey::Pubkey,
+    signer::{
+        keypair::{read_keypair_file, Keypair},
+        Signer,
+    },
+};
+use tx_terminal::alerts::{load_alerts, save_alerts, AlertDirection};
+use tx_terminal::profiles::load_profiles;
+use tx_terminal::logo::LogoClient;
+use tx_terminal::network::{
+    indexer_ws_url, is_timeout, parse_market_event, BondingTrade, Confirmation, IndexerClient,
+    MarketUpdate, MintSummary, NetworkClient, SafetyCounts, SendRetry, TraderStats, WatchQuote,
+    HOLDERS_PAGE_SIZE, MARKET_FEED_SUBSCRIBE,
+};
+use tx_terminal::report::{
+    format_position_report, position_rows, write_position_report, PositionRow,
+};
+use tx_terminal::swap::{
+    failure_detail, raw_to_ui_amount, sign_and_send, JupiterClient, PendingSwap, QuoteResponse,
+    SwapGate, SwapOrder, SwapStage, SwapStatus, DEFAULT_PRICE_TTL,
 };
-use tx_terminal::network::{IndexerClient, NetworkClient};
-use tx_terminal::swap::JupiterClient;
+use tx_terminal::orders::{load_orders, save_orders, LimitOrder, OrderSide};
+use tx_terminal::watchlist::{load_watchlist, save_watchlist};
 
 enum AppEvent {
     Log(String),
     TokensFetched(Vec<String>),
+    TraderStatsFetched(String, TraderStats),
+    SafetyCountsFetched(String, SafetyCounts),
+    BondingProgressFetched(String, f64),
+    TokenSupplyFetched(String, u8, f64),
+    HoldersFetched(String, Vec<Holder>),
+    MintSummaryFetched(String, MintSummary),
+    BondingTradesFetched(String, Vec<BondingTrade>),
+    CompareCandlesFetched(String, Vec<Candle>),
+    ChartCandlesFetched(String, u32, Vec<Candle>),
+    LogoFetched(String, Vec<String>),
+    WatchQuotesFetched(HashMap<String, WatchQuote>),
+    IndexerLagFetched(Option<u64>),
+    SolPriceFetched(f64),
+    PortfolioFetched(Vec<PositionRow>),
+    SwapQuoted(Box<PendingSwap>),
+    SwapStatus(SwapStatus),
+    MarketUpdate(MarketUpdate),
+}
+
+/// Candles fetched for each side of the compare view.
+const COMPARE_CANDLE_LIMIT: u32 = 200;
+
+fn spawn_compare_candles_fetch(
+    indexer: &Arc<IndexerClient>,
+    tx: &mpsc::Sender<AppEvent>,
+    mint: String,
+) {
+    if mint.is_empty() {
+        return;
+    }
+    let indexer = indexer.clone();
+    let tx = tx.clone();
+    tokio::spawn(async move {
+        let event = match indexer.fetch_candles(&mint, 60, COMPARE_CANDLE_LIMIT).await {
+            Ok(candles) => AppEvent::CompareCandlesFetched(mint, candles),
+            Err(e) => AppEvent::Log(failure_message("Candles", &e)),
+        };
+        let _ = tx.send(event).await;
+    });
+}
+
+/// Candles fetched for the main chart per timeframe.
+const CHART_CANDLE_LIMIT: u32 = 200;
+
+fn spawn_chart_candles_fetch(
+    indexer: &Arc<IndexerClient>,
+    tx: &mpsc::Sender<AppEvent>,
+    mint: String,
+    timeframe_secs: u32,
+) {
+    if mint.is_empty() {
+        return;
+    }
+    let indexer = indexer.clone();
+    let tx = tx.clone();
+    tokio::spawn(async move {
+        let event = match indexer
+            .fetch_candles(&mint, timeframe_secs, CHART_CANDLE_LIMIT)
+            .await
+        {
+            Ok(candles) => AppEvent::ChartCandlesFetched(mint, timeframe_secs, candles),
+            Err(e) => AppEvent::Log(failure_message("Candles", &e)),
+        };
+        let _ = tx.send(event).await;
+    });
+}
+
+/// Resolve and render the logos of the home-screen cards. Failures are dropped silently: the
+/// card keeps its emoji placeholder.
+fn spawn_logo_fetches(logos: &Arc<LogoClient>, tx: &mpsc::Sender<AppEvent>, mints: Vec<String>) {
+    for mint in mints {
+        let logos = logos.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            if let Ok(art) = logos.fetch_logo_art(&mint).await {
+                let _ = tx.send(AppEvent::LogoFetched(mint, art)).await;
+            }
+        });
+    }
+}
+
+/// The watchlist's change column covers `WATCH_CANDLE_LIMIT` candles of this size (24h).
+const WATCH_TIMEFRAME_SECS: u32 = 3_600;
+const WATCH_CANDLE_LIMIT: u32 = 24;
+/// How often watchlist quotes are refreshed while the watchlist is on screen.
+const WATCH_REFRESH: Duration = Duration::from_secs(10);
+
+/// Load prices for every watched mint in batch requests.
+fn spawn_watch_quotes_fetch(
+    indexer: &Arc<IndexerClient>,
+    tx: &mpsc::Sender<AppEvent>,
+    mints: Vec<String>,
+) {
+    if mints.is_empty() {
+        return;
+    }
+    let indexer = indexer.clone();
+    let tx = tx.clone();
+    tokio::spawn(async move {
+        let event = match indexer
+            .fetch_watch_quotes(&mints, WATCH_TIMEFRAME_SECS, WATCH_CANDLE_LIMIT)
+            .await
+        {
+            Ok(quotes) => AppEvent::WatchQuotesFetched(quotes),
+            Err(e) => AppEvent::Log(failure_message("Watchlist prices", &e)),
+        };
+        let _ = tx.send(event).await;
+    });
+}
+
+/// Load the indexer's symbol, decimals, holder count and price for a token opened from the
+/// watchlist, which may not be on the Home screen.
+fn spawn_mint_summary_fetch(
+    indexer: &Arc<IndexerClient>,
+    tx: &mpsc::Sender<AppEvent>,
+    mint: String,
+) {
+    let indexer = indexer.clone();
+    let tx = tx.clone();
+    tokio::spawn(async move {
+        let event = match indexer.fetch_mint_summary(&mint).await {
+            Ok(Some(summary)) => AppEvent::MintSummaryFetched(mint, summary),
+            Ok(None) => AppEvent::Log(format!("Indexer has no data for {mint}")),
+            Err(e) => AppEvent::Log(failure_message("Token summary", &e)),
+        };
+        let _ = tx.send(event).await;
+    });
+}
+
+/// Window for the sidebar's "Traders" and "Net Buyers" figures.
+const TRADER_STATS_WINDOW_SECS: u64 = 86_400;
+
+/// Load trader stats for the token that was just opened.
+fn spawn_trader_stats_fetch(
+    indexer: &Arc<IndexerClient>,
+    tx: &mpsc::Sender<AppEvent>,
+    mint: String,
+) {
+    if mint.is_empty() {
+        return;
+    }
+    let indexer = indexer.clone();
+    let tx = tx.clone();
+    tokio::spawn(async move {
+        let event = match indexer
+            .fetch_trader_stats(&mint, TRADER_STATS_WINDOW_SECS)
+            .await
+        {
+            Ok(stats) => AppEvent::TraderStatsFetched(mint, stats),
+            Err(e) => AppEvent::Log(failure_message("Trader stats", &e)),
+        };
+        let _ = tx.send(event).await;
+    });
+}
+
+/// Load the approve/revoke/close counts for the token that was just opened.
+fn spawn_safety_counts_fetch(
+    indexer: &Arc<IndexerClient>,
+    tx: &mpsc::Sender<AppEvent>,
+    mint: String,
+) {
+    if mint.is_empty() {
+        return;
+    }
+    let indexer = indexer.clone();
+    let tx = tx.clone();
+    tokio::spawn(async move {
+        let event = match indexer.fetch_safety_counts(&mint).await {
+            Ok(counts) => AppEvent::SafetyCountsFetched(mint, counts),
+            Err(e) => AppEvent::Log(failure_message("Safety events", &e)),
+        };
+        let _ = tx.send(event).await;
+    });
+}
+
+/// Read the decimals and supply of the token that was just opened, so sell amounts can be
+/// converted and its market cap computed.
+fn spawn_token_supply_fetch(
+    network: &NetworkClient,
+    tx: &mpsc::Sender<AppEvent>,
+    mint: String,
+) {
+    let Ok(pubkey) = mint.parse::<Pubkey>() else {
+        return;
+    };
+    let network = network.clone();
+    let tx = tx.clone();
+    tokio::spawn(async move {
+        let event = match network.get_mint_supply(&pubkey).await {
+            Ok((decimals, supply)) => AppEvent::TokenSupplyFetched(mint, decimals, supply),
+            Err(e) => AppEvent::Log(failure_message("Token supply", &e)),
+        };
+        let _ = tx.send(event).await;
+    });
+}
+
+/// Load the real bonding-curve progress for the token that was just opened.
+fn spawn_bonding_progress_fetch(
+    indexer: &Arc<IndexerClient>,
+    tx: &mpsc::Sender<AppEvent>,
+    mint: String,
+) {
+    if mint.is_empty() {
+        return;
+    }
+    let indexer = indexer.clone();
+    let tx = tx.clone();
+    tokio::spawn(async move {
+        let event = match indexer.fetch_bonding_progress(&mint).await {
+            Ok(pct) => AppEvent::BondingProgressFetched(mint, pct),
+            Err(e) => AppEvent::Log(failure_message("Bonding progress", &e)),
+        };
+        let _ = tx.send(event).await;
+    });
+}
+
+/// Load the latest trades of the token that was just opened; live ones follow over the
+/// market feed.
+fn spawn_bonding_trades_fetch(
+    indexer: &Arc<IndexerClient>,
+    tx: &mpsc::Sender<AppEvent>,
+    mint: String,
+) {
+    let indexer = indexer.clone();
+    let tx = tx.clone();
+    tokio::spawn(async move {
+        let event = match indexer.fetch_bonding_trades(&mint, MAX_RECENT_TRADES).await {
+            Ok(trades) => AppEvent::BondingTradesFetched(mint, trades),
+            Err(e) => AppEvent::Log(failure_message("Trades", &e)),
+        };
+        let _ = tx.send(event).await;
+    });
+}
+
+/// Pages of `HOLDERS_PAGE_SIZE` holders loaded for the Holders tab's percentages.
+const MAX_HOLDER_PAGES: usize = 10;
+/// Largest holders listed in the Holders tab.
+const MAX_HOLDERS_SHOWN: usize = 100;
+
+/// Load the holders of the token that was just opened.
+fn spawn_holders_fetch(indexer: &Arc<IndexerClient>, tx: &mpsc::Sender<AppEvent>, mint: String) {
+    let indexer = indexer.clone();
+    let tx = tx.clone();
+    tokio::spawn(async move {
+        let event = match indexer
+            .fetch_holders(&mint, HOLDERS_PAGE_SIZE, MAX_HOLDER_PAGES)
+            .await
+        {
+            Ok(balances) => {
+                AppEvent::HoldersFetched(mint, holders_from_balances(&balances, MAX_HOLDERS_SHOWN))
+            }
+            Err(e) => AppEvent::Log(failure_message("Holders", &e)),
+        };
+        let _ = tx.send(event).await;
+    });
+}
+
+/// How often the Positions tab is refreshed while it is on screen.
+const POSITIONS_REFRESH: Duration = Duration::from_secs(15);
+
+/// Load `wallet`'s open positions for the Positions tab.
+fn spawn_positions_fetch(
+    indexer: &Arc<IndexerClient>,
+    tx: &mpsc::Sender<AppEvent>,
+    wallet: String,
+) {
+    let indexer = indexer.clone();
+    let tx = tx.clone();
+    tokio::spawn(async move {
+        let fetched = tokio::try_join!(
+            indexer.fetch_portfolio(&wallet),
+            indexer.fetch_wallet_pnl(&wallet)
+        );
+        let event = match fetched {
+            Ok((portfolio, pnl)) => AppEvent::PortfolioFetched(position_rows(&portfolio, &pnl)),
+            Err(e) => AppEvent::Log(failure_message("Positions", &e)),
+        };
+        let _ = tx.send(event).await;
+    });
+}
+
+/// Fetch `wallet`'s portfolio and per-mint PnL and write a Markdown position report to `dir`.
+fn spawn_position_report(
+    indexer: &Arc<IndexerClient>,
+    tx: &mpsc::Sender<AppEvent>,
+    wallet: String,
+    dir: PathBuf,
+) {
+    let indexer = indexer.clone();
+    let tx = tx.clone();
+    tokio::spawn(async move {
+        let fetched = tokio::try_join!(
+            indexer.fetch_portfolio(&wallet),
+            indexer.fetch_wallet_pnl(&wallet)
+        );
+        let message = match fetched {
+            Ok((portfolio, _)) if position_rows(&portfolio, &[]).is_empty() => {
+                "Position report skipped: no open positions.".to_string()
+            }
+            Ok((portfolio, pnl)) => {
+                let generated_at = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs());
+                let report = format_position_report(&wallet, generated_at, &portfolio, &pnl);
+                match write_position_report(&dir, generated_at, &report) {
+                    Ok(path) => format!("Position report written to {}", path.display()),
+                    Err(e) => failure_message("Position report", &e),
+                }
+            }
+            Err(e) => failure_message("Position report", &e),
+        };
+        let _ = tx.send(AppEvent::Log(message)).await;
+    });
+}
+
+/// How long a sent swap is polled for before it's reported as unconfirmed. A blockhash lasts
+/// about a minute, so a dropped transaction is usually reported as expired well before this.
+const SWAP_CONFIRM_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Quote `order` on Jupiter, reporting progress as [`SwapStatus`]es. `None` when quoting
+/// failed or the quote is blocked by a price-impact limit.
+async fn quote_order(
+    order: &SwapOrder,
+    jupiter: &JupiterClient,
+    tx: &mpsc::Sender<AppEvent>,
+) -> Option<QuoteResponse> {
+    let status = |status: SwapStatus| {
+        let tx = tx.clone();
+        async move {
+            let _ = tx.send(AppEvent::SwapStatus(status)).await;
+        }
+    };
+    let failed = |stage: SwapStage, msg: String| status(SwapStatus::Failed { stage, msg });
+
+    status(SwapStatus::Quoting).await;
+    let quote = match jupiter.get_quote(order).await {
+        Ok(quote) => quote,
+        Err(e) => {
+            failed(SwapStage::Quote, failure_detail(&e)).await;
+            return None;
+        }
+    };
+    let out_amount = if order.output_mint == WSOL_MINT {
+        let sol = |raw: &str| raw_to_ui_amount(raw, 9).unwrap_or(0.0);
+        format!(
+            "{} SOL (min {} SOL)",
+            sol(&quote.out_amount),
+            sol(&quote.other_amount_threshold)
+        )
+    } else {
+        quote.out_amount.clone()
+    };
+    status(SwapStatus::Quoted { out_amount }).await;
+    let _ = tx
+        .send(AppEvent::Log(format!("Price impact: {}%", quote.price_impact_pct)))
+        .await;
+    if quote.blocked {
+        let msg = format!("price impact {}% exceeds limit", quote.price_impact_pct);
+        failed(SwapStage::Quote, msg).await;
+        return None;
+    }
+    Some(quote)
+}
+
+/// Quote `order` on Jupiter and hand it to the confirmation modal. Blocked quotes stop here;
+/// `permit` then frees on return.
+async fn quote_swap(
+    order: SwapOrder,
+    permit: OwnedSemaphorePermit,
+    jupiter: Arc<JupiterClient>,
+    tx: mpsc::Sender<AppEvent>,
+) {
+    let Some(quote) = quote_order(&order, &jupiter, &tx).await else {
+        return;
+    };
+    let pending = PendingSwap {
+        order,
+        quote,
+        permit,
+    };
+    let _ = tx.send(AppEvent::SwapQuoted(Box::new(pending))).await;
+}
+
+/// Quote and send a triggered limit order straight away: placing the order stood in for the
+/// confirmation modal.
+async fn execute_limit_order(
+    order: LimitOrder,
+    permit: OwnedSemaphorePermit,
+    jupiter: Arc<JupiterClient>,
+    network: NetworkClient,
+    keypair: Arc<Keypair>,
+    tx: mpsc::Sender<AppEvent>,
+) {
+    let order = order.swap_order();
+    let Some(quote) = quote_order(&order, &jupiter, &tx).await else {
+        return;
+    };
+    let pending = PendingSwap {
+        order,
+        quote,
+        permit,
+    };
+    send_swap(pending, jupiter, network, keypair, tx).await;
+}
+
+/// Build, sign and send a confirmed swap from `keypair`, then wait for it to land, reporting
+/// each step as a [`SwapStatus`]. The swap's in-flight slot is held until the transaction is
+/// sent or the swap fails, not while it confirms.
+async fn send_swap(
+    pending: PendingSwap,
+    jupiter: Arc<JupiterClient>,
+    network: NetworkClient,
+    keypair: Arc<Keypair>,
+    tx: mpsc::Sender<AppEvent>,
+) {
+    let PendingSwap {
+        order,
+        quote,
+        permit,
+    } = pending;
+    let status = |status: SwapStatus| {
+        let tx = tx.clone();
+        async move {
+            let _ = tx.send(AppEvent::SwapStatus(status)).await;
+        }
+    };
+    let failed = |stage: SwapStage, msg: String| status(SwapStatus::Failed { stage, msg });
+
+    let user_pubkey = keypair.pubkey().to_string();
+    let swap_base64 = match jupiter
+        .get_swap_transaction(&user_pubkey, quote, order.priority_fee_lamports)
+        .await
+    {
+        Ok(swap_base64) => swap_base64,
+        Err(e) => return failed(SwapStage::Build, failure_detail(&e)).await,
+    };
+    status(SwapStatus::Signing).await;
+    let log_retry = |retry: SendRetry<'_>| {
+        let _ = tx.try_send(AppEvent::Log(retry.to_string()));
+    };
+    let sent = sign_and_send(
+        &swap_base64,
+        keypair.as_ref(),
+        |blockhash| {
+            let network = &network;
+            async move { network.is_blockhash_valid(&blockhash).await }
+        },
+        || network.get_latest_blockhash(),
+        |message| network.send_with_fee_bump(message, keypair.as_ref(), log_retry),
+    )
+    .await;
+    let (sig, blockhash) = match sent {
+        Ok(sent) => sent,
+        Err(failure) => return status(failure.into()).await,
+    };
+    drop(permit);
+    let sig_text = sig.to_string();
+    status(SwapStatus::Sent { sig: sig_text.clone() }).await;
+
+    let landed = network
+        .confirm_signature(&sig, &blockhash, SWAP_CONFIRM_TIMEOUT)
+        .await;
+    let final_status = match landed {
+        Confirmation::Confirmed { slot } => SwapStatus::Confirmed { sig: sig_text, slot },
+        Confirmation::Expired => SwapStatus::Expired { sig: sig_text },
+        Confirmation::Failed { slot, error } => SwapStatus::Failed {
+            stage: SwapStage::Execute,
+            msg: format!("{error} (slot {slot})"),
+        },
+        Confirmation::TimedOut => SwapStatus::Failed {
+            stage: SwapStage::Confirm,
+            msg: format!(
+                "not seen after {}s, it may still land: {sig_text}",
+                SWAP_CONFIRM_TIMEOUT.as_secs()
+            ),
+        },
+    };
+    status(final_status).await;
+}
+
+/// Log line for a failed network call, calling out timeouts separately from other errors.
+fn failure_message(what: &str, err: &anyhow::Error) -> String {
+    if is_timeout(err) {
+        format!("{what} timed out: {err}")
+    } else {
+        format!("{what} failed: {err}")
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let config = tx_terminal::config::load_config();
-    let network_client = NetworkClient::new(&config.rpc_url);
-    let indexer_client = IndexerClient::new();
+    let request_timeout = Duration::from_secs(config.request_timeout_secs.max(1));
+    let network_client = NetworkClient::new(&config.rpc_url)
+        .with_timeout(request_timeout)
+        .with_send_attempts(config.send_max_attempts);
+    // Shared HTTP clients: reqwest pools connections per client, so build them once.
+    let indexer_client = Arc::new(
+        IndexerClient::new()?
+            .with_base_url(&config.indexer_url)
+            .with_timeout(request_timeout)?,
+    );
+    let jupiter_client = Arc::new(
+        JupiterClient::new()?
+            .with_max_impact_pct(config.max_impact_pct)
+            .with_timeout(request_timeout)?,
+    );
+    let logo_client = Arc::new(LogoClient::new()?.with_timeout(request_timeout)?);
+
+    let swap_gate = SwapGate::new(config.max_inflight_swaps);
 
     // Channel for async events
     let (tx, mut rx) = mpsc::channel(100);
@@ -59,7 +591,7 @@ async fn main() -> Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     // Create app
-    let mut app = App::new(wallet_pubkey, balance);
+    let mut app = App::new(wallet_pubkey, balance, config.theme);
 
     if let Some(pk) = wallet_pubkey {
         app.add_log(format!("Wallet loaded: {}", pk));
@@ -67,10 +599,38 @@ async fn main() -> Result<()> {
         app.add_log("No wallet loaded. Use --keypair-path to connect.".to_string());
     }
 
+    if let Some(path) = &config.profiles_file {
+        match load_profiles(Path::new(path)) {
+            Ok(profiles) => app.set_trade_profiles(profiles),
+            Err(err) => app.add_log(format!("Using default trade profiles: {err:#}")),
+        }
+    }
+
+    let orders_path = PathBuf::from(&config.orders_file);
+    match load_orders(&orders_path) {
+        Ok(orders) => app.orders = orders,
+        Err(err) => app.add_log(format!("Limit orders not loaded: {err:#}")),
+    }
+    if !app.orders.is_empty() {
+        let idle = if wallet_keypair.is_none() {
+            "; they stay idle until a wallet is loaded"
+        } else {
+            ""
+        };
+        app.add_log(format!("{} limit orders loaded{idle}.", app.orders.len()));
+    }
+
+    let watchlist_path = PathBuf::from(&config.watchlist_file);
+    match load_watchlist(&watchlist_path) {
+        Ok(watchlist) => app.watchlist = watchlist,
+        Err(err) => app.add_log(format!("Watchlist not loaded: {err:#}")),
+    }
+
     // Fetch initial token list
     let tx_tokens = tx.clone();
+    let indexer = indexer_client.clone();
     tokio::spawn(async move {
-        if let Ok(tokens) = indexer_client.fetch_tokens().await {
+        if let Ok(tokens) = indexer.fetch_tokens().await {
             let _ = tx_tokens.send(AppEvent::TokensFetched(tokens)).await;
         } else {
             let _ = tx_tokens
@@ -79,6 +639,20 @@ async fn main() -> Result<()> {
         }
     });
 
+    spawn_logo_fetches(&logo_client, &tx, app.card_mints_without_logo());
+
+    tokio::spawn(run_market_feed(indexer_ws_url(&config.indexer_url), tx.clone()));
+    tokio::spawn(run_indexer_lag_poll(indexer_client.clone(), tx.clone()));
+    tokio::spawn(run_sol_price_poll(jupiter_client.clone(), tx.clone()));
+
+    if let Some(path) = &config.alerts_file {
+        tokio::spawn(run_price_alerts(
+            PathBuf::from(path),
+            indexer_client.clone(),
+            tx.clone(),
+        ));
+    }
+
     // Run app
     let res = run_app(
         &mut terminal,
@@ -87,6 +661,12 @@ async fn main() -> Result<()> {
         &mut rx,
         network_client,
         wallet_keypair,
+        indexer_client,
+        jupiter_client,
+        swap_gate,
+        PathBuf::from(&config.report_dir),
+        watchlist_path,
+        orders_path,
     )
     .await;
 
@@ -106,16 +686,181 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Wait before the first reconnect of the market feed; doubles per failure up to the max.
+const MARKET_FEED_RETRY_INITIAL: Duration = Duration::from_secs(1);
+const MARKET_FEED_RETRY_MAX: Duration = Duration::from_secs(30);
+/// Bonding trades refetch a card's progress at most this often per mint.
+const CARD_BONDING_REFRESH: Duration = Duration::from_secs(5);
+
+/// Stream bonding trades and candle updates from the indexer websocket at `url` into the Home
+/// columns. Reconnects with exponential backoff whenever connecting fails or the socket drops.
+async fn run_market_feed(url: String, tx: mpsc::Sender<AppEvent>) {
+    let mut delay = MARKET_FEED_RETRY_INITIAL;
+    loop {
+        let message = match tokio_tungstenite::connect_async(url.as_str()).await {
+            Ok((socket, _)) => {
+                delay = MARKET_FEED_RETRY_INITIAL;
+                let _ = tx.send(AppEvent::Log("Market feed connected".to_string())).await;
+                let reason = stream_market_updates(socket, &tx).await;
+                format!("Market feed dropped: {reason}; reconnecting in {delay:?}")
+            }
+            Err(e) => format!("Market feed connect failed: {e}; retrying in {delay:?}"),
+        };
+        if tx.send(AppEvent::Log(message)).await.is_err() {
+            return;
+        }
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MARKET_FEED_RETRY_MAX);
+    }
+}
+
+/// Subscribe on `socket` and forward price updates until it closes. Returns why it stopped.
+async fn stream_market_updates(
+    mut socket: tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >,
+    tx: &mpsc::Sender<AppEvent>,
+) -> String {
+    if let Err(e) = socket
+        .send(WsMessage::Text(MARKET_FEED_SUBSCRIBE.to_string()))
+        .await
+    {
+        return e.to_string();
+    }
+    while let Some(msg) = socket.next().await {
+        match msg {
+            Ok(WsMessage::Text(text)) => {
+                let Some(update) = parse_market_event(&text) else {
+                    continue;
+                };
+                if tx.send(AppEvent::MarketUpdate(update)).await.is_err() {
+                    return "terminal closed".to_string();
+                }
+            }
+            Ok(WsMessage::Close(_)) => return "closed by indexer".to_string(),
+            Ok(_) => {}
+            Err(e) => return e.to_string(),
+        }
+    }
+    "stream ended".to_string()
+}
+
+/// How often the navbar's indexer lag is refreshed.
+const INDEXER_LAG_REFRESH: Duration = Duration::from_secs(15);
+
+/// Keep the navbar's "indexer N s behind" indicator current. An unreachable indexer clears it
+/// rather than logging every poll.
+async fn run_indexer_lag_poll(indexer: Arc<IndexerClient>, tx: mpsc::Sender<AppEvent>) {
+    let mut interval = tokio::time::interval(INDEXER_LAG_REFRESH);
+    loop {
+        interval.tick().await;
+        let lag = indexer.fetch_indexer_lag().await.unwrap_or(None);
+        if tx.send(AppEvent::IndexerLagFetched(lag)).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Keep the SOL/USD price behind market caps and holder values current. Failed fetches keep
+/// the last price rather than logging every poll.
+async fn run_sol_price_poll(jupiter: Arc<JupiterClient>, tx: mpsc::Sender<AppEvent>) {
+    let mut interval = tokio::time::interval(DEFAULT_PRICE_TTL);
+    loop {
+        interval.tick().await;
+        let Ok(price) = jupiter.get_price(WSOL_MINT).await else {
+            continue;
+        };
+        if tx.send(AppEvent::SolPriceFetched(price)).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Poll the indexer for every armed alert's mint, whether or not the token is open, and log
+/// an alert when its target is crossed. Fired/re-armed state is written back to `path`.
+async fn run_price_alerts(path: PathBuf, indexer: Arc<IndexerClient>, tx: mpsc::Sender<AppEvent>) {
+    let mut alerts = match load_alerts(&path) {
+        Ok(alerts) => alerts,
+        Err(err) => {
+            let _ = tx.send(AppEvent::Log(format!("Price alerts disabled: {err}"))).await;
+            return;
+        }
+    };
+    if alerts.is_empty() {
+        return;
+    }
+    let _ = tx
+        .send(AppEvent::Log(format!("Loaded {} price alerts.", alerts.len())))
+        .await;
+
+    let mut interval = tokio::time::interval(Duration::from_secs(15));
+    loop {
+        interval.tick().await;
+        let mut changed = false;
+        for alert in &mut alerts {
+            let Ok(Some(price)) = indexer.fetch_latest_price(&alert.mint).await else {
+                continue;
+            };
+            let was_armed = alert.armed;
+            if alert.check(price) {
+                let side = match alert.direction {
+                    AlertDirection::Above => "above",
+                    AlertDirection::Below => "below",
+                };
+                let _ = tx
+                    .send(AppEvent::Log(format!(
+                        "ALERT: {} is {} {} (last {})",
+                        alert.mint, side, alert.target_price, price
+                    )))
+                    .await;
+            }
+            changed |= alert.armed != was_armed;
+        }
+        if changed {
+            if let Err(err) = save_alerts(&path, &alerts) {
+                let _ = tx.send(AppEvent::Log(format!("{err}"))).await;
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
     tx: mpsc::Sender<AppEvent>,
     rx: &mut mpsc::Receiver<AppEvent>,
     network_client: NetworkClient,
-    wallet_keypair: Option<Arc<solana_sdk::signer::keypair::Keypair>>,
+    wallet_keypair: Option<Arc<Keypair>>,
+    indexer_client: Arc<IndexerClient>,
+    jupiter_client: Arc<JupiterClient>,
+    swap_gate: SwapGate,
+    report_dir: PathBuf,
+    watchlist_path: PathBuf,
+    orders_path: PathBuf,
 ) -> Result<()> {
+    // When watchlist quotes were last requested; `None` forces a fetch on the next frame.
+    let mut watch_fetched_at: Option<Instant> = None;
+    // Same for the Positions tab
+    let mut positions_fetched_at: Option<Instant> = None;
+    // When each Home card's bonding progress was last refetched after a live trade
+    let mut card_bonding_fetched_at: HashMap<String, Instant> = HashMap::new();
     loop {
         app.simulate_market_activity();
+        if app.current_screen == CurrentScreen::Watchlist
+            && watch_fetched_at.is_none_or(|at| at.elapsed() >= WATCH_REFRESH)
+        {
+            spawn_watch_quotes_fetch(&indexer_client, &tx, app.watchlist.clone());
+            watch_fetched_at = Some(Instant::now());
+        }
+        if let Some(wallet) = app.wallet_pubkey {
+            if app.positions_tab_open()
+                && positions_fetched_at.is_none_or(|at| at.elapsed() >= POSITIONS_REFRESH)
+            {
+                spawn_positions_fetch(&indexer_client, &tx, wallet.to_string());
+                positions_fetched_at = Some(Instant::now());
+            }
+        }
         terminal.draw(|f| ui(f, app))?;
 
         // Check for async events
@@ -129,19 +874,186 @@ async fn run_app<B: ratatui::backend::Backend>(
                         app.token_list.len()
                     ));
                 }
+                AppEvent::TraderStatsFetched(mint, stats) => {
+                    app.apply_trader_stats(&mint, &stats);
+                }
+                AppEvent::SafetyCountsFetched(mint, counts) => {
+                    app.apply_safety_counts(&mint, counts);
+                }
+                AppEvent::BondingProgressFetched(mint, pct) => {
+                    app.apply_bonding_progress(&mint, pct);
+                }
+                AppEvent::TokenSupplyFetched(mint, decimals, supply) => {
+                    app.apply_token_decimals(&mint, decimals);
+                    app.apply_token_supply(&mint, supply);
+                }
+                AppEvent::HoldersFetched(mint, holders) => app.apply_holders(&mint, holders),
+                AppEvent::MintSummaryFetched(mint, summary) => {
+                    app.apply_mint_summary(&mint, summary);
+                }
+                AppEvent::BondingTradesFetched(mint, trades) => {
+                    app.apply_bonding_trades(&mint, &trades);
+                }
+                AppEvent::ChartCandlesFetched(mint, timeframe_secs, candles) => {
+                    app.apply_chart_candles(&mint, timeframe_secs, candles);
+                }
+                AppEvent::CompareCandlesFetched(mint, candles) => {
+                    app.apply_compare_candles(&mint, candles);
+                }
+                AppEvent::LogoFetched(mint, art) => {
+                    app.logo_art.insert(mint, art);
+                }
+                AppEvent::WatchQuotesFetched(quotes) => {
+                    app.watch_quotes.extend(quotes);
+                }
+                AppEvent::IndexerLagFetched(lag) => app.indexer_lag_secs = lag,
+                AppEvent::SolPriceFetched(price) => app.apply_sol_price(price),
+                AppEvent::PortfolioFetched(positions) => app.apply_positions(positions),
+                AppEvent::SwapStatus(status) => {
+                    // A landed swap changes the wallet's holdings
+                    if matches!(status, SwapStatus::Confirmed { .. }) {
+                        positions_fetched_at = None;
+                    }
+                    app.push_swap_status(status);
+                }
+                AppEvent::MarketUpdate(update) => {
+                    // Orders fire one at a time; one left waiting on the swap gate is retried
+                    // on the mint's next update
+                    if let (Some(index), Some(kp)) =
+                        (app.triggered_order(&update.mint, update.price), &wallet_keypair)
+                    {
+                        if let Some(permit) = swap_gate.try_start() {
+                            let order = app.take_order(index);
+                            if let Err(err) = save_orders(&orders_path, &app.orders) {
+                                app.add_log(format!("{err:#}"));
+                            }
+                            app.add_log(format!(
+                                "Limit {} triggered: {} at {:.10} SOL (trigger {:.10})",
+                                order.side,
+                                order.symbol,
+                                update.price,
+                                order.trigger_price
+                            ));
+                            tokio::spawn(execute_limit_order(
+                                order,
+                                permit,
+                                jupiter_client.clone(),
+                                network_client.clone(),
+                                kp.clone(),
+                                tx.clone(),
+                            ));
+                        }
+                    }
+                    let on_card = app.apply_market_update(&update);
+                    let due = card_bonding_fetched_at
+                        .get(&update.mint)
+                        .is_none_or(|at| at.elapsed() >= CARD_BONDING_REFRESH);
+                    if on_card && update.bonding_trade && due {
+                        card_bonding_fetched_at.insert(update.mint.clone(), Instant::now());
+                        spawn_bonding_progress_fetch(&indexer_client, &tx, update.mint);
+                    }
+                }
+                AppEvent::SwapQuoted(pending) => {
+                    app.add_log("Quote ready: Enter to send, Esc to cancel".to_string());
+                    app.pending_swap = Some(*pending);
+                }
             }
         }
 
         if crossterm::event::poll(Duration::from_millis(10))? {
             match crossterm::event::read()? {
                 Event::Key(key) => {
+                    // The help overlay takes every key until it is closed
+                    if app.show_help {
+                        match key.code {
+                            KeyCode::Char('?') | KeyCode::Esc => app.show_help = false,
+                            KeyCode::Up => app.help_scroll = app.help_scroll.saturating_sub(1),
+                            KeyCode::Down => app.help_scroll = app.help_scroll.saturating_add(1),
+                            _ => {}
+                        }
+                        continue;
+                    }
+                    // `?` is typed as text into the search boxes
+                    if key.code == KeyCode::Char('?')
+                        && !app.home_search_focused
+                        && !app.show_search_modal
+                    {
+                        app.show_help = true;
+                        app.help_scroll = 0;
+                        continue;
+                    }
+
                     // Global Keys
-                    if key.code == KeyCode::Char('q') {
+                    if key.code == KeyCode::Char('q') && !app.home_search_focused {
                         app.quit();
                         return Ok(());
                     }
 
+                    // The confirmation modal takes every key until the swap is sent or dropped
+                    if app.pending_swap.is_some() {
+                        match key.code {
+                            KeyCode::Enter => {
+                                if let (Some(pending), Some(kp)) =
+                                    (app.pending_swap.take(), &wallet_keypair)
+                                {
+                                    tokio::spawn(send_swap(
+                                        pending,
+                                        jupiter_client.clone(),
+                                        network_client.clone(),
+                                        kp.clone(),
+                                        tx.clone(),
+                                    ));
+                                }
+                            }
+                            KeyCode::Esc => app.cancel_pending_swap(),
+                            _ => {}
+                        }
+                        continue;
+                    }
+
+                    // Export position report, unless a modal is taking text input
+                    if key.code == KeyCode::Char('e')
+                        && !app.show_search_modal
+                        && !app.show_sizer_modal
+                        && !app.show_order_modal
+                        && !app.home_search_focused
+                    {
+                        match app.wallet_pubkey {
+                            Some(wallet) => spawn_position_report(
+                                &indexer_client,
+                                &tx,
+                                wallet.to_string(),
+                                report_dir.clone(),
+                            ),
+                            None => app.add_log(
+                                "Cannot export report: No wallet loaded.".to_string(),
+                            ),
+                        }
+                        continue;
+                    }
+
                     match app.current_screen {
+                        // Navbar search: typing narrows the Home columns until Enter/Down
+                        // hands the keys back to the columns or Esc clears it
+                        CurrentScreen::Home if app.home_search_focused => match key.code {
+                            KeyCode::Esc => {
+                                app.home_search_focused = false;
+                                app.search_input.clear();
+                                app.update_search_results();
+                            }
+                            KeyCode::Enter | KeyCode::Down | KeyCode::Tab => {
+                                app.home_search_focused = false;
+                            }
+                            KeyCode::Backspace => {
+                                app.search_input.pop();
+                                app.update_search_results();
+                            }
+                            KeyCode::Char(c) => {
+                                app.search_input.push(c);
+                                app.update_search_results();
+                            }
+                            _ => {}
+                        },
                         CurrentScreen::Home => {
                             match key.code {
                                 KeyCode::Right => {
@@ -156,15 +1068,28 @@ async fn run_app<B: ratatui::backend::Backend>(
                                         app.home_selected_row = 0;
                                     }
                                 }
-                                KeyCode::Down => {
-                                    // simplistic check, ideally check vec len
+                                KeyCode::Down
+                                    if app.home_selected_row + 1 < app.home_column_len() =>
+                                {
                                     app.home_selected_row += 1;
                                 }
+                                KeyCode::Char('/') => {
+                                    app.home_search_focused = true;
+                                }
+                                KeyCode::Esc if !app.search_input.is_empty() => {
+                                    app.search_input.clear();
+                                    app.update_search_results();
+                                }
                                 KeyCode::Up => {
                                     if app.home_selected_row > 0 {
                                         app.home_selected_row -= 1;
                                     }
                                 }
+                                KeyCode::Char('W') => {
+                                    app.current_screen = CurrentScreen::Watchlist;
+                                    watch_fetched_at = None;
+                                }
+                                KeyCode::Char('L') => app.open_logs(),
                                 KeyCode::Enter => {
                                     // Select token logic
                                     let token = match app.home_selected_col {
@@ -178,20 +1103,173 @@ async fn run_app<B: ratatui::backend::Backend>(
                                         app.token_info.name = t.name.clone();
                                         app.token_info.symbol = t.symbol.clone();
                                         app.token_info.price = t.price;
+                                        app.token_info.mint = t.mint.clone();
+                                        app.token_info.safety_counts = None;
+                                        app.token_info.decimals = None;
+                                        app.token_info.supply = None;
+                                        app.holders.clear();
+                                        app.refresh_market_cap();
+                                        app.recent_trades.clear();
                                         app.current_screen = CurrentScreen::TokenDetails;
+                                        spawn_trader_stats_fetch(
+                                            &indexer_client,
+                                            &tx,
+                                            app.token_info.mint.clone(),
+                                        );
+                                        spawn_safety_counts_fetch(
+                                            &indexer_client,
+                                            &tx,
+                                            app.token_info.mint.clone(),
+                                        );
+                                        spawn_bonding_progress_fetch(
+                                            &indexer_client,
+                                            &tx,
+                                            app.token_info.mint.clone(),
+                                        );
+                                        spawn_token_supply_fetch(
+                                            &network_client,
+                                            &tx,
+                                            app.token_info.mint.clone(),
+                                        );
+                                        spawn_holders_fetch(
+                                            &indexer_client,
+                                            &tx,
+                                            app.token_info.mint.clone(),
+                                        );
+                                        spawn_bonding_trades_fetch(
+                                            &indexer_client,
+                                            &tx,
+                                            app.token_info.mint.clone(),
+                                        );
                                     }
                                 }
                                 _ => {}
                             }
                         }
+                        CurrentScreen::Watchlist => match key.code {
+                            KeyCode::Esc => {
+                                app.current_screen = CurrentScreen::Home;
+                            }
+                            KeyCode::Down
+                                if app.watchlist_selected
+                                    < app.watchlist.len().saturating_sub(1) =>
+                            {
+                                app.watchlist_selected += 1;
+                            }
+                            KeyCode::Up if app.watchlist_selected > 0 => {
+                                app.watchlist_selected -= 1;
+                            }
+                            KeyCode::Enter => {
+                                if let Some(mint) = app.open_watched_token() {
+                                    spawn_mint_summary_fetch(&indexer_client, &tx, mint.clone());
+                                    spawn_trader_stats_fetch(&indexer_client, &tx, mint.clone());
+                                    spawn_safety_counts_fetch(&indexer_client, &tx, mint.clone());
+                                    spawn_bonding_progress_fetch(
+                                        &indexer_client,
+                                        &tx,
+                                        mint.clone(),
+                                    );
+                                    spawn_token_supply_fetch(
+                                        &network_client,
+                                        &tx,
+                                        mint.clone(),
+                                    );
+                                    spawn_holders_fetch(&indexer_client, &tx, mint.clone());
+                                    spawn_bonding_trades_fetch(&indexer_client, &tx, mint);
+                                }
+                            }
+                            KeyCode::Char('d') | KeyCode::Delete => {
+                                let selected = app.watchlist.get(app.watchlist_selected).cloned();
+                                if let Some(mint) = selected {
+                                    app.unwatch(&mint);
+                                    if let Err(err) =
+                                        save_watchlist(&watchlist_path, &app.watchlist)
+                                    {
+                                        app.add_log(format!("{err:#}"));
+                                    }
+                                }
+                            }
+                            KeyCode::Char('L') => app.open_logs(),
+                            KeyCode::Char('t') => {
+                                app.toggle_theme();
+                            }
+                            _ => {}
+                        },
+                        CurrentScreen::Logs => match key.code {
+                            KeyCode::Esc => app.close_logs(),
+                            KeyCode::Up => app.scroll_logs_up(),
+                            KeyCode::Down => app.scroll_logs_down(),
+                            KeyCode::Char('t') => {
+                                app.toggle_theme();
+                            }
+                            _ => {}
+                        },
+                        CurrentScreen::Compare => match key.code {
+                            KeyCode::Esc => {
+                                app.compare = None;
+                                app.current_screen = CurrentScreen::TokenDetails;
+                            }
+                            KeyCode::Right => {
+                                app.chart_x_offset += 1.0;
+                            }
+                            KeyCode::Left => {
+                                app.chart_x_offset -= 1.0;
+                            }
+                            KeyCode::Char('L') => app.open_logs(),
+                            KeyCode::Char('t') => {
+                                app.toggle_theme();
+                            }
+                            _ => {}
+                        },
                         CurrentScreen::TokenDetails => {
                             if app.show_search_modal {
                                 match key.code {
                                     KeyCode::Esc => {
                                         app.show_search_modal = false;
+                                        app.compare_picking = false;
+                                    }
+                                    KeyCode::Enter if app.compare_picking => {
+                                        if let Some(mint) = app.start_compare() {
+                                            spawn_compare_candles_fetch(&indexer_client, &tx, mint);
+                                            spawn_compare_candles_fetch(
+                                                &indexer_client,
+                                                &tx,
+                                                app.token_info.mint.clone(),
+                                            );
+                                        }
                                     }
                                     KeyCode::Enter => {
                                         app.select_current_token();
+                                        spawn_trader_stats_fetch(
+                                            &indexer_client,
+                                            &tx,
+                                            app.token_info.mint.clone(),
+                                        );
+                                        spawn_safety_counts_fetch(
+                                            &indexer_client,
+                                            &tx,
+                                            app.token_info.mint.clone(),
+                                        );
+                                        spawn_bonding_progress_fetch(
+                                            &indexer_client,
+                                            &tx,
+                                            app.token_info.mint.clone(),
+                                        );
+                                        spawn_token_supply_fetch(
+                                            &network_client,
+                                            &tx,
+                                            app.token_info.mint.clone(),
+                                        );
+                                        spawn_holders_fetch(
+                                            &indexer_client,
+                                            &tx,
+                                            app.token_info.mint.clone(),
+                                        );
+                                        spawn_bonding_trades_fetch(
+                                            &indexer_client,
+                                            &tx,
+                                            app.token_info.mint.clone(),
+                                        );
                                         app.show_search_modal = false;
                                         app.search_input.clear();
                                         app.update_search_results(); // Reset results
@@ -218,173 +1296,141 @@ async fn run_app<B: ratatui::backend::Backend>(
                                     }
                                     _ => {}
                                 }
+                            } else if app.show_sizer_modal {
+                                match key.code {
+                                    KeyCode::Esc => {
+                                        app.show_sizer_modal = false;
+                                    }
+                                    KeyCode::Tab | KeyCode::Up | KeyCode::Down => {
+                                        app.sizer_field = match app.sizer_field {
+                                            SizerField::Risk => SizerField::Stop,
+                                            SizerField::Stop => SizerField::Risk,
+                                        };
+                                    }
+                                    KeyCode::Backspace => {
+                                        app.sizer_input_mut().pop();
+                                    }
+                                    KeyCode::Char(c) => {
+                                        apply_amount_keypress(app.sizer_input_mut(), c);
+                                    }
+                                    _ => {}
+                                }
+                            } else if app.show_order_modal {
+                                match key.code {
+                                    KeyCode::Esc => {
+                                        app.show_order_modal = false;
+                                    }
+                                    KeyCode::Tab | KeyCode::Up | KeyCode::Down => {
+                                        app.order_field = match app.order_field {
+                                            OrderField::Amount => OrderField::Trigger,
+                                            OrderField::Trigger => OrderField::Amount,
+                                        };
+                                    }
+                                    KeyCode::Char('b') => app.order_side = OrderSide::Buy,
+                                    KeyCode::Char('s') => app.order_side = OrderSide::Sell,
+                                    KeyCode::Enter => match app.place_order() {
+                                        Ok(order) => {
+                                            let unit = match order.side {
+                                                OrderSide::Buy => "SOL",
+                                                OrderSide::Sell => order.symbol.as_str(),
+                                            };
+                                            app.add_log(format!(
+                                                "Limit {} placed: {} {} of {} at {} SOL",
+                                                order.side,
+                                                order.ui_amount(),
+                                                unit,
+                                                order.symbol,
+                                                order.trigger_price
+                                            ));
+                                            if let Err(err) = save_orders(&orders_path, &app.orders)
+                                            {
+                                                app.add_log(format!("{err:#}"));
+                                            }
+                                        }
+                                        Err(reason) => {
+                                            app.add_log(format!("Cannot place order: {reason}"));
+                                        }
+                                    },
+                                    KeyCode::Backspace => {
+                                        app.order_input_mut().pop();
+                                    }
+                                    KeyCode::Char(c) => {
+                                        apply_amount_keypress(app.order_input_mut(), c);
+                                    }
+                                    _ => {}
+                                }
                             } else {
                                 match key.code {
                                     KeyCode::Esc => {
                                         app.current_screen = CurrentScreen::Home;
                                     }
                                     KeyCode::Backspace => {
-                                        app.swap_amount.pop();
+                                        app.swap_input_mut().pop();
                                     }
                                     KeyCode::Char(c) if c.is_digit(10) || c == '.' => {
-                                        app.swap_amount.push(c);
+                                        apply_amount_keypress(app.swap_input_mut(), c);
                                     }
                                     KeyCode::Char('s') => {
-                                        if let Some(kp) = &wallet_keypair {
-                                            app.add_log(format!(
-                                                "Initiating swap: {} SOL -> {}",
-                                                app.swap_amount, app.token_info.symbol
-                                            ));
-
-                                            // Capture data before spawn
-                                            let input_mint =
-                                                "So11111111111111111111111111111111111111112"
-                                                    .to_string();
-                                            let output_mint = app.token_info.mint.clone();
-                                            let amount_sol =
-                                                app.swap_amount.parse::<f64>().unwrap_or(0.0);
-                                            let amount = (amount_sol * 1_000_000_000.0) as u64;
-
-                                            let tx_swap = tx.clone();
-                                            let nc = network_client.clone();
-                                            let kp_arc = kp.clone();
-
-                                            tokio::spawn(async move {
-                                                let jupiter = JupiterClient::new();
-                                                // SOL -> Selected Token
-                                                // input_mint, output_mint, amount already captured
-
-                                                let quote_res = jupiter
-                                                    .get_quote(
-                                                        &input_mint,
-                                                        &output_mint,
-                                                        amount,
-                                                        50,
-                                                    )
-                                                    .await;
-
-                                                match quote_res {
-                                                    Ok(quote) => {
-                                                        let _ = tx_swap
-                                                            .send(AppEvent::Log(format!(
-                                                                "Quote: Out {}",
-                                                                quote.out_amount
-                                                            )))
-                                                            .await;
-
-                                                        // Get Swap Transaction
-                                                        let user_pubkey =
-                                                            kp_arc.pubkey().to_string();
-                                                        match jupiter
-                                                            .get_swap_transaction(
-                                                                &user_pubkey,
-                                                                quote,
-                                                            )
-                                                            .await
-                                                        {
-                                                            Ok(swap_base64) => {
-                                                                // Descerealize
-                                                                if let Ok(swap_bytes) =
-                                                                    general_purpose::STANDARD
-                                                                        .decode(swap_base64)
-                                                                {
-                                                                    if let Ok(versioned_tx) =
-                                                                        bincode::deserialize::<
-                                                                            VersionedTransaction,
-                                                                        >(
-                                                                            &swap_bytes
-                                                                        )
-                                                                    {
-                                                                        // Sign
-                                                                        // VersionedTransaction signing is different, usually needs latest blockhash?
-                                                                        // Jupiter provides blockhash in the tx.
-                                                                        // We just need to sign.
-                                                                        let signed_tx =
-                                                                            VersionedTransaction::try_new(
-                                                                                versioned_tx.message,
-                                                                                &[kp_arc.as_ref()],
-                                                                            );
-
-                                                                        match signed_tx {
-                                                                            Ok(tx_signed) => {
-                                                                                // Send
-                                                                                match nc
-                                                                                    .rpc_client
-                                                                                    .send_transaction(
-                                                                                        &tx_signed,
-                                                                                    )
-                                                                                    .await
-                                                                                {
-                                                                                    Ok(sig) => {
-                                                                                        let _ = tx_swap
-                                                                                            .send(
-                                                                                                AppEvent::Log(
-                                                                                                    format!(
-                                                                                                        "Swap sent: {}",
-                                                                                                        sig
-                                                                                                    ),
-                                                                                                ),
-                                                                                            )
-                                                                                            .await;
-                                                                                    }
-                                                                                    Err(e) => {
-                                                                                        let _ = tx_swap.send(AppEvent::Log(format!("Send failed: {}", e))).await;
-                                                                                    }
-                                                                                }
-                                                                            }
-                                                                            Err(e) => {
-                                                                                let _ = tx_swap
-                                                                                    .send(AppEvent::Log(
-                                                                                        format!(
-                                                                                            "Signing failed: {}",
-                                                                                            e
-                                                                                        ),
-                                                                                    ))
-                                                                                    .await;
-                                                                            }
-                                                                        }
-                                                                    } else {
-                                                                        let _ = tx_swap
-                                                                            .send(AppEvent::Log(
-                                                                                "Failed to deserialize tx"
-                                                                                    .to_string(),
-                                                                            ))
-                                                                            .await;
-                                                                    }
-                                                                } else {
-                                                                    let _ = tx_swap
-                                                                        .send(AppEvent::Log(
-                                                                            "Failed to decode base64"
-                                                                                .to_string(),
-                                                                        ))
-                                                                        .await;
-                                                                }
-                                                            }
-                                                            Err(e) => {
-                                                                let _ = tx_swap
-                                                                    .send(AppEvent::Log(format!(
-                                                                        "Swap API failed: {}",
-                                                                        e
-                                                                    )))
-                                                                    .await;
-                                                            }
-                                                        }
-                                                    }
-                                                    Err(e) => {
-                                                        let _ = tx_swap
-                                                            .send(AppEvent::Log(format!(
-                                                                "Quote failed: {}",
-                                                                e
-                                                            )))
-                                                            .await;
-                                                    }
-                                                }
-                                            });
-                                        } else {
+                                        if wallet_keypair.is_none() {
                                             app.add_log(
                                                 "Cannot swap: No wallet loaded.".to_string(),
                                             );
+                                            continue;
                                         }
+                                        let Some(permit) = swap_gate.try_start() else {
+                                            app.add_log(
+                                                "Swap ignored: previous swap still in flight"
+                                                    .to_string(),
+                                            );
+                                            continue;
+                                        };
+                                        app.add_log(format!(
+                                            "Initiating swap: {} SOL -> {}",
+                                            app.swap_amount, app.token_info.symbol
+                                        ));
+                                        // SOL -> Selected Token, with the trade profile
+                                        // active at key press
+                                        tokio::spawn(quote_swap(
+                                            app.swap_order(),
+                                            permit,
+                                            jupiter_client.clone(),
+                                            tx.clone(),
+                                        ));
+                                    }
+                                    KeyCode::Char('S') => {
+                                        if wallet_keypair.is_none() {
+                                            app.add_log(
+                                                "Cannot sell: No wallet loaded.".to_string(),
+                                            );
+                                            continue;
+                                        }
+                                        let order = match app.sell_order() {
+                                            Ok(order) => order,
+                                            Err(reason) => {
+                                                app.add_log(format!("Cannot sell: {reason}"));
+                                                continue;
+                                            }
+                                        };
+                                        let Some(permit) = swap_gate.try_start() else {
+                                            app.add_log(
+                                                "Swap ignored: previous swap still in flight"
+                                                    .to_string(),
+                                            );
+                                            continue;
+                                        };
+                                        app.add_log(format!(
+                                            "Initiating sell: {} {} -> SOL",
+                                            app.sell_amount, app.token_info.symbol
+                                        ));
+                                        tokio::spawn(quote_swap(
+                                            order,
+                                            permit,
+                                            jupiter_client.clone(),
+                                            tx.clone(),
+                                        ));
+                                    }
+                                    KeyCode::Char('a') => {
+                                        app.toggle_swap_field();
                                     }
                                     KeyCode::Char('/') => {
                                         app.show_search_modal = true;
@@ -392,6 +1438,89 @@ async fn run_app<B: ratatui::backend::Backend>(
                                     KeyCode::Char('t') => {
                                         app.toggle_theme();
                                     }
+                                    KeyCode::Char('p') => {
+                                        app.show_sizer_modal = true;
+                                    }
+                                    KeyCode::Char('o') => {
+                                        if wallet_keypair.is_none() {
+                                            app.add_log(
+                                                "Cannot place order: No wallet loaded."
+                                                    .to_string(),
+                                            );
+                                            continue;
+                                        }
+                                        app.open_order_modal();
+                                    }
+                                    KeyCode::Char('x') if app.bottom_tab_index == ORDERS_TAB => {
+                                        if let Some(order) = app.cancel_selected_order() {
+                                            app.add_log(format!(
+                                                "Limit {} on {} cancelled",
+                                                order.side, order.symbol
+                                            ));
+                                            if let Err(err) = save_orders(&orders_path, &app.orders)
+                                            {
+                                                app.add_log(format!("{err:#}"));
+                                            }
+                                        }
+                                    }
+                                    KeyCode::Up if app.bottom_tab_index == ORDERS_TAB => {
+                                        app.orders_selected = app.orders_selected.saturating_sub(1);
+                                    }
+                                    KeyCode::Down if app.bottom_tab_index == ORDERS_TAB => {
+                                        let last = app.orders.len().saturating_sub(1);
+                                        app.orders_selected = (app.orders_selected + 1).min(last);
+                                    }
+                                    KeyCode::Char('f') => {
+                                        app.cycle_trade_profile();
+                                    }
+                                    KeyCode::Char('l') => {
+                                        app.cycle_slippage();
+                                    }
+                                    KeyCode::Char('w') => {
+                                        app.toggle_watch_current();
+                                        if let Err(err) =
+                                            save_watchlist(&watchlist_path, &app.watchlist)
+                                        {
+                                            app.add_log(format!("{err:#}"));
+                                        }
+                                    }
+                                    KeyCode::Char('W') => {
+                                        app.current_screen = CurrentScreen::Watchlist;
+                                        watch_fetched_at = None;
+                                    }
+                                    KeyCode::Char('L') => app.open_logs(),
+                                    KeyCode::Char('r') => {
+                                        if app.wallet_pubkey.is_none() {
+                                            app.add_log(
+                                                "Cannot load positions: No wallet loaded."
+                                                    .to_string(),
+                                            );
+                                            continue;
+                                        }
+                                        app.bottom_tab_index = POSITIONS_TAB;
+                                        positions_fetched_at = None;
+                                    }
+                                    KeyCode::Char('[') | KeyCode::Char(']') => {
+                                        let up = key.code == KeyCode::Char(']');
+                                        if app.step_chart_timeframe(up) {
+                                            spawn_chart_candles_fetch(
+                                                &indexer_client,
+                                                &tx,
+                                                app.token_info.mint.clone(),
+                                                app.chart_timeframe_secs,
+                                            );
+                                        }
+                                    }
+                                    KeyCode::Char('c') => {
+                                        app.compare_picking = true;
+                                        app.show_search_modal = true;
+                                    }
+                                    KeyCode::Char('+') | KeyCode::Char('=') => {
+                                        app.adjust_large_trade_sol(LARGE_TRADE_SOL_STEP);
+                                    }
+                                    KeyCode::Char('-') => {
+                                        app.adjust_large_trade_sol(-LARGE_TRADE_SOL_STEP);
+                                    }
                                     // Chart Navigation
                                     KeyCode::Right => {
                                         app.chart_x_offset += 1.0;
@@ -400,10 +1529,12 @@ async fn run_app<B: ratatui::backend::Backend>(
                                         app.chart_x_offset -= 1.0;
                                     }
                                     KeyCode::Up => {
-                                        app.chart_y_offset += 0.0001;
+                                        let (_, [low, high]) = chart_bounds(app);
+                                        app.chart_y_offset += (high - low) * CHART_Y_PAN_STEP;
                                     }
                                     KeyCode::Down => {
-                                        app.chart_y_offset -= 0.0001;
+                                        let (_, [low, high]) = chart_bounds(app);
+                                        app.chart_y_offset -= (high - low) * CHART_Y_PAN_STEP;
                                     }
                                     KeyCode::Tab => {
                                         app.bottom_tab_index = (app.bottom_tab_index + 1) % 6;
@@ -417,6 +1548,12 @@ async fn run_app<B: ratatui::backend::Backend>(
                 Event::Mouse(mouse) => {
                     let size = terminal.size()?;
                     let size = Rect::new(0, 0, size.width, size.height);
+                    // Only the resize hint is on screen; there is nothing to click or drag.
+                    if is_too_small(size) {
+                        app.drag_state = None;
+                        app.chart_cursor = None;
+                        continue;
+                    }
 
                     // Calculate Layout Rects (matching ui.rs)
                     let vertical_layout = Layout::default()
@@ -464,9 +1601,16 @@ async fn run_app<B: ratatui::backend::Backend>(
                                 && y >= navbar_chunks[1].top()
                                 && y < navbar_chunks[1].bottom();
 
-                            if is_search_click {
+                            if is_search_click && app.current_screen == CurrentScreen::Home {
+                                app.home_search_focused = true;
+                            } else if is_search_click {
                                 app.show_search_modal = true;
-                            } else if app.show_search_modal {
+                            } else if app.show_search_modal
+                                || app.show_help
+                                || app.show_sizer_modal
+                                || app.show_order_modal
+                                || app.pending_swap.is_some()
+                            {
                                 // If modal is open, ignore clicks on underlying UI
                             } else {
                                 // Check Vertical Separators
@@ -491,14 +1635,14 @@ async fn run_app<B: ratatui::backend::Backend>(
                                             // Check for Tab Clicks in Bottom Panel
                                             let bottom_panel_top = center_chunks[1].top();
                                             if y >= bottom_panel_top && y < bottom_panel_top + 3 {
-                                                // Tab click logic
-                                                let panel_width = main_content_chunks[1].width;
-                                                if panel_width > 0 {
-                                                    let tab_width = panel_width / 6;
-                                                    let rel_x = x.saturating_sub(
-                                                        main_content_chunks[1].left(),
-                                                    );
-                                                    let clicked_tab = (rel_x / tab_width) as usize;
+                                                // Tab click logic; a panel narrower than
+                                                // six columns has zero-width tabs
+                                                let tab_width = main_content_chunks[1].width / 6;
+                                                let rel_x =
+                                                    x.saturating_sub(main_content_chunks[1].left());
+                                                let clicked_tab = rel_x.checked_div(tab_width);
+                                                if let Some(clicked_tab) = clicked_tab {
+                                                    let clicked_tab = clicked_tab as usize;
                                                     if clicked_tab < 6 {
                                                         app.bottom_tab_index = clicked_tab;
                                                     }
@@ -556,6 +1700,36 @@ async fn run_app<B: ratatui::backend::Backend>(
                         MouseEventKind::Up(_) => {
                             app.drag_state = None;
                         }
+                        MouseEventKind::ScrollUp | MouseEventKind::ScrollDown
+                            if app.current_screen == CurrentScreen::TokenDetails
+                                && !app.show_search_modal
+                                && !app.show_sizer_modal
+                                && !app.show_order_modal
+                                && app.pending_swap.is_none() =>
+                        {
+                            let point =
+                                chart_point_at(app, center_chunks[0], mouse.column, mouse.row);
+                            if let Some((x, _)) = point {
+                                let factor = if mouse.kind == MouseEventKind::ScrollUp {
+                                    CHART_ZOOM_STEP
+                                } else {
+                                    1.0 / CHART_ZOOM_STEP
+                                };
+                                app.zoom_chart(factor, x);
+                            }
+                        }
+                        MouseEventKind::Moved => {
+                            let over_chart = app.current_screen == CurrentScreen::TokenDetails
+                                && !app.show_search_modal
+                                && !app.show_sizer_modal
+                                && !app.show_order_modal
+                                && app.pending_swap.is_none();
+                            app.chart_cursor = if over_chart {
+                                chart_cursor_at(app, center_chunks[0], mouse.column, mouse.row)
+                            } else {
+                                None
+                            };
+                        }
                         _ => {}
                     }
                 }
@@ -1,11 +1,465 @@
+use crate::app::Candle;
 use anyhow::Result;
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::pubkey::Pubkey;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    compute_budget,
+    hash::Hash,
+    message::VersionedMessage,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    transaction::VersionedTransaction,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// Applied to every RPC and HTTP call unless configured otherwise.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An RPC call that did not complete within the client's timeout.
+#[derive(Debug)]
+pub struct RequestTimeout {
+    pub operation: &'static str,
+    pub after: Duration,
+}
+
+impl fmt::Display for RequestTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} timed out after {:.1}s",
+            self.operation,
+            self.after.as_secs_f64()
+        )
+    }
+}
+
+impl std::error::Error for RequestTimeout {}
+
+/// Whether `err` is an RPC [`RequestTimeout`] or a reqwest timeout.
+pub fn is_timeout(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause.is::<RequestTimeout>()
+            || cause
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(|e| e.is_timeout())
+    })
+}
+
+/// reqwest client with a total per-request timeout.
+pub fn http_client(timeout: Duration) -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder().timeout(timeout).build()?)
+}
+
+/// Response of the indexer's `/token/:mint/trader_stats`.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct TraderStats {
+    pub unique_traders: u64,
+    pub net_buyers: i64,
+    /// Lamports spent on buys and received from sells over the window.
+    #[serde(default)]
+    pub buy_volume_sol: u64,
+    #[serde(default)]
+    pub sell_volume_sol: u64,
+}
+
+/// Per-kind totals from the indexer's `/token/:mint/safety_events`.
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize)]
+#[serde(default)]
+pub struct SafetyCounts {
+    pub approve: u64,
+    pub revoke: u64,
+    pub close_account: u64,
+}
+
+/// One row of the indexer's `/wallet/:owner/portfolio`; `amount` is in raw token units.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct WalletBalance {
+    pub mint_pubkey: String,
+    pub amount: i64,
+}
+
+/// One row of the indexer's `/token/:mint/holders`; `amount` is in raw token units.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct HolderBalance {
+    pub wallet: String,
+    pub amount: i64,
+}
+
+/// One row of the indexer's `/search`.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct IndexedMint {
+    pub mint_pubkey: String,
+    pub symbol: Option<String>,
+    pub decimals: i32,
+}
+
+/// What the indexer knows about a mint that isn't on the home screen: its `/search` row, how
+/// many wallets hold it and the close of its latest candle in SOL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MintSummary {
+    pub symbol: Option<String>,
+    pub decimals: u8,
+    pub holder_count: u64,
+    pub price: Option<f64>,
+}
+
+/// Rows requested per `/token/:mint/holders` page, the most the indexer returns at once.
+pub const HOLDERS_PAGE_SIZE: usize = 1_000;
+
+/// One row of the indexer's `/wallet/:owner/pnl`: the wallet's buy/sell totals in a mint (raw
+/// token units and lamports) and the mint's latest trade price in nano-SOL per whole token.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct WalletMintPnl {
+    pub mint_pubkey: String,
+    pub decimals: i32,
+    pub tokens_bought: i64,
+    pub sol_spent: i64,
+    pub tokens_sold: i64,
+    pub sol_received: i64,
+    pub last_price_nanos_per_token: Option<i64>,
+}
+
+/// Latest price of a watched mint, from the indexer's `/candles/batch`. Prices are in SOL.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WatchQuote {
+    pub price: f64,
+    /// Percent change from the open of the oldest candle fetched to the latest close; `None`
+    /// when that open is zero.
+    pub change_pct: Option<f64>,
+}
+
+/// Most mints the indexer's `/candles/batch` accepts in one request.
+pub const MAX_BATCH_MINTS: usize = 100;
+
+/// The fields of a `/candles/batch` candle the watchlist needs (nano-SOL per token).
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+pub struct BatchCandle {
+    pub open: i64,
+    pub close: i64,
+}
+
+/// Quote from one mint's batch candles, newest first as the indexer returns them. `None` when
+/// the mint has no candles.
+pub fn watch_quote(candles: &[BatchCandle]) -> Option<WatchQuote> {
+    let latest = candles.first()?;
+    let oldest = candles.last()?;
+    let change_pct = (oldest.open != 0)
+        .then(|| (latest.close - oldest.open) as f64 / oldest.open as f64 * 100.0);
+    Some(WatchQuote {
+        price: latest.close as f64 / 1e9,
+        change_pct,
+    })
+}
+
+/// Subscription sent on connecting to the indexer's `/ws`. In-progress candle updates are
+/// coalesced server-side so a busy mint can't flood the UI.
+pub const MARKET_FEED_SUBSCRIBE: &str =
+    r#"{"type":"subscribe","topics":["bonding","candles"],"coalesce_ms":500}"#;
+
+/// Live price of a mint from the indexer websocket, in SOL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketUpdate {
+    pub mint: String,
+    pub price: f64,
+    /// A bonding-curve trade, which also moves the mint's bonding progress.
+    pub bonding_trade: bool,
+    /// The trade itself, when the event carried all of its fields.
+    pub trade: Option<BondingTrade>,
+}
+
+/// A bonding-curve trade from `/token/:mint/bonding_trades` or the websocket "bonding" topic.
+/// `sol_amount` is in lamports and the price in nano-SOL per whole token. Websocket events
+/// carry no block time.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct BondingTrade {
+    pub signature: String,
+    #[serde(default)]
+    pub block_time: Option<String>,
+    pub trader: String,
+    pub side: String,
+    pub sol_amount: i64,
+    pub price_nanos_per_token: i64,
+    #[serde(default)]
+    pub is_new_wallet: bool,
+}
+
+/// `ws://` URL of the indexer's websocket for its HTTP `base_url`.
+pub fn indexer_ws_url(base_url: &str) -> String {
+    let base = base_url.trim_end_matches('/');
+    let base = if let Some(rest) = base.strip_prefix("https://") {
+        format!("wss://{rest}")
+    } else if let Some(rest) = base.strip_prefix("http://") {
+        format!("ws://{rest}")
+    } else {
+        base.to_string()
+    };
+    format!("{base}/ws")
+}
+
+/// Parse one `{"topic","mint_pubkey","payload"}` websocket message. Bonding trades and
+/// in-progress candles carry a price; candle closes, other topics and replies such as
+/// `{"type":"subscribed"}` give `None`.
+pub fn parse_market_event(text: &str) -> Option<MarketUpdate> {
+    let event: serde_json::Value = serde_json::from_str(text).ok()?;
+    let mint = event["mint_pubkey"].as_str()?.to_string();
+    let payload = &event["payload"];
+    let (nanos, bonding_trade) = match event["topic"].as_str()? {
+        "bonding" => (payload["price_nanos_per_token"].as_i64()?, true),
+        "candles" if payload["closed"].as_bool() != Some(true) => {
+            (payload["close"].as_i64()?, false)
+        }
+        _ => return None,
+    };
+    let trade = if bonding_trade {
+        serde_json::from_value(payload.clone()).ok()
+    } else {
+        None
+    };
+    Some(MarketUpdate {
+        mint,
+        price: nanos as f64 / 1e9,
+        bonding_trade,
+        trade,
+    })
+}
+
+/// Decode the indexer's `/token/:mint/candles.bin` payload (layout documented in
+/// `indexer-core/src/candle_codec.rs`) into chart candles, oldest first, priced in SOL.
+pub fn decode_candles_bin(data: &[u8]) -> Result<Vec<Candle>> {
+    const HEADER_LEN: usize = 16;
+    const RECORD_LEN: usize = 60;
+
+    if data.len() < HEADER_LEN || &data[0..4] != b"CNDL" {
+        anyhow::bail!("not a candle buffer");
+    }
+    let version = u16::from_le_bytes([data[4], data[5]]);
+    let record_len = u16::from_le_bytes([data[6], data[7]]) as usize;
+    if version != 1 || record_len != RECORD_LEN {
+        anyhow::bail!("unsupported candle encoding (version {version}, record {record_len})");
+    }
+    let count = u32::from_le_bytes(data[12..16].try_into()?) as usize;
+    let body = &data[HEADER_LEN..];
+    if body.len() != count * RECORD_LEN {
+        anyhow::bail!("truncated candle buffer");
+    }
+
+    let i64_at = |rec: &[u8], off: usize| i64::from_le_bytes(rec[off..off + 8].try_into().unwrap());
+    let sol = |nanos: i64| nanos as f64 / 1e9;
+    let mut candles: Vec<Candle> = body
+        .chunks_exact(RECORD_LEN)
+        .map(|rec| Candle {
+            timestamp: i64_at(rec, 0),
+            open: sol(i64_at(rec, 8)),
+            high: sol(i64_at(rec, 16)),
+            low: sol(i64_at(rec, 24)),
+            close: sol(i64_at(rec, 32)),
+        })
+        .collect();
+    // The indexer returns newest first; charts draw left to right.
+    candles.reverse();
+    Ok(candles)
+}
+
+/// Sends per swap by default: the first try plus two retries (see [`send_with_retry`]).
+pub const DEFAULT_SEND_ATTEMPTS: u32 = 3;
+/// Pause before resending a failed transaction.
+const SEND_RETRY_DELAY: Duration = Duration::from_millis(500);
+/// Compute-unit price (micro-lamports) a retry bumps a zero price up to.
+const MIN_RETRY_CU_PRICE: u64 = 10_000;
+/// `ComputeBudgetInstruction::SetComputeUnitPrice` tag; the price follows as a u64 LE.
+const SET_COMPUTE_UNIT_PRICE_TAG: u8 = 3;
+
+/// Raise the price of `message`'s `SetComputeUnitPrice` instruction for a retry: doubled, and at
+/// least [`MIN_RETRY_CU_PRICE`]. Returns the new price, or `None` if the message sets no price
+/// (adding an instruction would shift account indexes in a v0 message).
+pub fn bump_compute_unit_price(message: &mut VersionedMessage) -> Option<u64> {
+    let budget_program = compute_budget::id();
+    let keys = message.static_account_keys().to_vec();
+    let instructions = match message {
+        VersionedMessage::Legacy(m) => &mut m.instructions,
+        VersionedMessage::V0(m) => &mut m.instructions,
+    };
+    let ix = instructions.iter_mut().find(|ix| {
+        keys.get(ix.program_id_index as usize) == Some(&budget_program)
+            && ix.data.len() == 9
+            && ix.data[0] == SET_COMPUTE_UNIT_PRICE_TAG
+    })?;
+    let price = u64::from_le_bytes(ix.data[1..9].try_into().unwrap());
+    let bumped = price.saturating_mul(2).max(MIN_RETRY_CU_PRICE);
+    ix.data[1..9].copy_from_slice(&bumped.to_le_bytes());
+    Some(bumped)
+}
+
+/// A failed send that is about to be retried, for logging.
+#[derive(Debug)]
+pub struct SendRetry<'a> {
+    /// 1-based number of the attempt that failed.
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub error: &'a anyhow::Error,
+    pub next: NextSend,
+}
+
+/// What the attempt after a failed send sends.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NextSend {
+    /// The same signed transaction: the failed one may still land, so it is not replaced.
+    Rebroadcast,
+    /// A new transaction with a fresh blockhash, at this compute-unit price if the fee could be
+    /// bumped. Only once the failed one can no longer land.
+    Resigned { cu_price: Option<u64> },
+}
+
+impl fmt::Display for SendRetry<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "send attempt {}/{} failed: {}; ",
+            self.attempt, self.max_attempts, self.error
+        )?;
+        match self.next {
+            NextSend::Rebroadcast => write!(f, "rebroadcasting the same transaction"),
+            NextSend::Resigned {
+                cu_price: Some(price),
+            } => write!(f, "re-signing at {price} micro-lamports/CU"),
+            NextSend::Resigned { cu_price: None } => write!(f, "re-signing with the same fee"),
+        }
+    }
+}
+
+/// The RPC calls [`send_with_retry`] makes.
+trait SendRpc {
+    async fn send(&self, tx: &VersionedTransaction) -> Result<Signature>;
+    async fn latest_blockhash(&self) -> Result<Hash>;
+    async fn blockhash_valid(&self, blockhash: &Hash) -> Result<bool>;
+    /// Whether `sig` has landed, successfully or not.
+    async fn landed(&self, sig: &Signature) -> Result<bool>;
+}
+
+/// Sign `message` and send it up to `max_attempts` times, telling `on_retry` about every
+/// failed attempt that is retried. A failed send may still have reached a leader (a client-side
+/// timeout, say), and a second signature could then spend the funds twice. So the same signed
+/// transaction is rebroadcast until its blockhash has expired with the signature unseen; only
+/// then is it re-signed with a fresh blockhash and a bumped compute-unit price. Returns the
+/// signature and blockhash of the transaction that was accepted, or that landed anyway.
+async fn send_with_retry(
+    rpc: &impl SendRpc,
+    mut message: VersionedMessage,
+    signer: &Keypair,
+    max_attempts: u32,
+    delay: Duration,
+    mut on_retry: impl FnMut(SendRetry<'_>),
+) -> Result<(Signature, Hash)> {
+    let max_attempts = max_attempts.max(1);
+    let mut tx = VersionedTransaction::try_new(message.clone(), &[signer])?;
+    let mut attempt = 1;
+    loop {
+        let error = match rpc.send(&tx).await {
+            Ok(sig) => return Ok((sig, *tx.message.recent_blockhash())),
+            Err(e) if attempt >= max_attempts => return Err(e),
+            Err(e) => e,
+        };
+        tokio::time::sleep(delay).await;
+
+        let (sig, blockhash) = (tx.signatures[0], *tx.message.recent_blockhash());
+        // Checked before the status: a transaction unseen after its blockhash expired is gone.
+        let expired = matches!(rpc.blockhash_valid(&blockhash).await, Ok(false));
+        let next = match rpc.landed(&sig).await {
+            // Landed despite the error; confirmation takes it from here.
+            Ok(true) => return Ok((sig, blockhash)),
+            Ok(false) if expired => {
+                let cu_price = bump_compute_unit_price(&mut message);
+                if let Ok(hash) = rpc.latest_blockhash().await {
+                    message.set_recent_blockhash(hash);
+                }
+                tx = VersionedTransaction::try_new(message.clone(), &[signer])?;
+                NextSend::Resigned { cu_price }
+            }
+            _ => NextSend::Rebroadcast,
+        };
+        on_retry(SendRetry {
+            attempt,
+            max_attempts,
+            error: &error,
+            next,
+        });
+        attempt += 1;
+    }
+}
+
+/// How often [`NetworkClient::confirm_signature`] asks for the signature's status.
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Where a sent transaction ended up, from [`NetworkClient::confirm_signature`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Confirmation {
+    /// Landed in `slot` and reached `confirmed` commitment.
+    Confirmed { slot: u64 },
+    /// Landed in `slot`, but the transaction failed on chain.
+    Failed { slot: u64, error: String },
+    /// Never landed and its blockhash has expired, so it never will: safe to retry.
+    Expired,
+    /// Still unseen after the timeout while its blockhash was valid; it may land yet.
+    TimedOut,
+}
+
+/// A signature's status as the RPC reports it, reduced to what confirmation needs.
+#[derive(Debug, Clone, PartialEq)]
+struct LandedStatus {
+    slot: u64,
+    error: Option<String>,
+    confirmed: bool,
+}
+
+/// Poll `status` every `interval` until the transaction is confirmed or failed, its blockhash
+/// has expired (per `blockhash_valid`) without it landing, or `timeout` passes. RPC errors
+/// while polling are ignored; the next poll tries again.
+async fn poll_confirmation<St, StFut, V, VFut>(
+    timeout: Duration,
+    interval: Duration,
+    mut status: St,
+    mut blockhash_valid: V,
+) -> Confirmation
+where
+    St: FnMut() -> StFut,
+    StFut: Future<Output = Result<Option<LandedStatus>>>,
+    V: FnMut() -> VFut,
+    VFut: Future<Output = Result<bool>>,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        // Checked before the status: a transaction unseen after its blockhash expired is gone.
+        let expired = matches!(blockhash_valid().await, Ok(false));
+        match status().await {
+            Ok(Some(LandedStatus {
+                slot,
+                error: Some(error),
+                ..
+            })) => return Confirmation::Failed { slot, error },
+            Ok(Some(LandedStatus {
+                slot,
+                confirmed: true,
+                ..
+            })) => return Confirmation::Confirmed { slot },
+            Ok(None) if expired => return Confirmation::Expired,
+            _ => {}
+        }
+        if tokio::time::Instant::now() + interval > deadline {
+            return Confirmation::TimedOut;
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
 
 #[derive(Clone)]
 pub struct NetworkClient {
     pub rpc_client: Arc<RpcClient>,
+    timeout: Duration,
+    send_attempts: u32,
 }
 
 impl NetworkClient {
@@ -13,29 +467,412 @@ impl NetworkClient {
         let rpc_client = RpcClient::new(rpc_url.to_string());
         Self {
             rpc_client: Arc::new(rpc_client),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            send_attempts: DEFAULT_SEND_ATTEMPTS,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// How many times [`NetworkClient::send_with_fee_bump`] sends before giving up (min 1).
+    pub fn with_send_attempts(mut self, attempts: u32) -> Self {
+        self.send_attempts = attempts.max(1);
+        self
+    }
+
+    async fn timed<T, E>(
+        &self,
+        operation: &'static str,
+        call: impl Future<Output = std::result::Result<T, E>>,
+    ) -> Result<T>
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        match tokio::time::timeout(self.timeout, call).await {
+            Ok(result) => Ok(result?),
+            Err(_) => Err(RequestTimeout {
+                operation,
+                after: self.timeout,
+            }
+            .into()),
         }
     }
 
     pub async fn get_block_height(&self) -> Result<u64> {
-        let height = self.rpc_client.get_block_height().await?;
-        Ok(height)
+        self.timed("getBlockHeight", self.rpc_client.get_block_height())
+            .await
     }
 
     pub async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64> {
-        let balance = self.rpc_client.get_balance(pubkey).await?;
-        Ok(balance)
+        self.timed("getBalance", self.rpc_client.get_balance(pubkey))
+            .await
+    }
+
+    /// Decimals and total supply (in whole tokens) of the SPL mint `mint`.
+    pub async fn get_mint_supply(&self, mint: &Pubkey) -> Result<(u8, f64)> {
+        let supply = self
+            .timed("getTokenSupply", self.rpc_client.get_token_supply(mint))
+            .await?;
+        let raw: f64 = supply.amount.parse()?;
+        Ok((supply.decimals, raw / 10f64.powi(i32::from(supply.decimals))))
+    }
+
+    pub async fn send_transaction(&self, tx: &VersionedTransaction) -> Result<Signature> {
+        self.timed("sendTransaction", self.rpc_client.send_transaction(tx))
+            .await
+    }
+
+    pub async fn get_latest_blockhash(&self) -> Result<Hash> {
+        self.timed("getLatestBlockhash", self.rpc_client.get_latest_blockhash())
+            .await
+    }
+
+    /// Whether `blockhash` can still be used for a new transaction.
+    pub async fn is_blockhash_valid(&self, blockhash: &Hash) -> Result<bool> {
+        self.timed(
+            "isBlockhashValid",
+            self.rpc_client
+                .is_blockhash_valid(blockhash, CommitmentConfig::processed()),
+        )
+        .await
+    }
+
+    /// Sign `message` with `signer` and send it, retrying failed sends per [`send_with_retry`]:
+    /// the same transaction while it may still land, then re-signed with a fresh blockhash and a
+    /// doubled priority fee (see [`bump_compute_unit_price`]). `on_retry` hears about every
+    /// failed attempt that is retried; the last error is returned. On success, returns the
+    /// signature and the blockhash it was sent with, for [`NetworkClient::confirm_signature`].
+    pub async fn send_with_fee_bump(
+        &self,
+        message: VersionedMessage,
+        signer: &Keypair,
+        on_retry: impl FnMut(SendRetry<'_>),
+    ) -> Result<(Signature, Hash)> {
+        send_with_retry(self, message, signer, self.send_attempts, SEND_RETRY_DELAY, on_retry)
+            .await
+    }
+
+    async fn signature_status(&self, sig: &Signature) -> Result<Option<LandedStatus>> {
+        let response = self
+            .timed(
+                "getSignatureStatuses",
+                self.rpc_client.get_signature_statuses(&[*sig]),
+            )
+            .await?;
+        Ok(response.value.into_iter().next().flatten().map(|status| LandedStatus {
+            slot: status.slot,
+            confirmed: status.satisfies_commitment(CommitmentConfig::confirmed()),
+            error: status.err.map(|e| e.to_string()),
+        }))
+    }
+
+    /// Wait up to `timeout` for `sig` to land at `confirmed` commitment. `blockhash` is the one
+    /// the transaction was signed with; once it expires an unseen transaction is reported as
+    /// [`Confirmation::Expired`] rather than waiting out the timeout.
+    pub async fn confirm_signature(
+        &self,
+        sig: &Signature,
+        blockhash: &Hash,
+        timeout: Duration,
+    ) -> Confirmation {
+        let status = || self.signature_status(sig);
+        let blockhash_valid = || self.is_blockhash_valid(blockhash);
+        poll_confirmation(timeout, CONFIRM_POLL_INTERVAL, status, blockhash_valid).await
+    }
+}
+
+impl SendRpc for NetworkClient {
+    async fn send(&self, tx: &VersionedTransaction) -> Result<Signature> {
+        self.send_transaction(tx).await
+    }
+
+    async fn latest_blockhash(&self) -> Result<Hash> {
+        self.get_latest_blockhash().await
+    }
+
+    async fn blockhash_valid(&self, blockhash: &Hash) -> Result<bool> {
+        self.is_blockhash_valid(blockhash).await
+    }
+
+    async fn landed(&self, sig: &Signature) -> Result<bool> {
+        Ok(self.signature_status(sig).await?.is_some())
     }
 }
 
 pub struct IndexerClient {
     pub client: reqwest::Client,
+    pub base_url: String,
 }
 
 impl IndexerClient {
-    pub fn new() -> Self {
-        Self {
-            client: reqwest::Client::new(),
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            client: http_client(DEFAULT_REQUEST_TIMEOUT)?,
+            base_url: "http://127.0.0.1:8080".to_string(),
+        })
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Result<Self> {
+        self.client = http_client(timeout)?;
+        Ok(self)
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub async fn fetch_trader_stats(&self, mint: &str, window_secs: u64) -> Result<TraderStats> {
+        let url = format!(
+            "{}/token/{}/trader_stats?window_secs={}",
+            self.base_url, mint, window_secs
+        );
+        let stats = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(stats)
+    }
+
+    pub async fn fetch_safety_counts(&self, mint: &str) -> Result<SafetyCounts> {
+        let url = format!("{}/token/{}/safety_events?limit=1", self.base_url, mint);
+        let body: serde_json::Value = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(serde_json::from_value(body["counts"].clone())?)
+    }
+
+    pub async fn fetch_portfolio(&self, wallet: &str) -> Result<Vec<WalletBalance>> {
+        let url = format!("{}/wallet/{}/portfolio", self.base_url, wallet);
+        let portfolio = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(portfolio)
+    }
+
+    pub async fn fetch_wallet_pnl(&self, wallet: &str) -> Result<Vec<WalletMintPnl>> {
+        let url = format!("{}/wallet/{}/pnl", self.base_url, wallet);
+        let pnl = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(pnl)
+    }
+
+    /// The latest `limit` bonding-curve trades of `mint`, newest first.
+    pub async fn fetch_bonding_trades(
+        &self,
+        mint: &str,
+        limit: usize,
+    ) -> Result<Vec<BondingTrade>> {
+        let url = format!("{}/token/{}/bonding_trades?limit={}", self.base_url, mint, limit);
+        let trades = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(trades)
+    }
+
+    /// Holders of `mint` with a non-zero balance, largest first, paging through the indexer
+    /// `page_size` rows at a time. Stops after `max_pages`, so the smallest balances of a very
+    /// widely held mint are left out.
+    pub async fn fetch_holders(
+        &self,
+        mint: &str,
+        page_size: usize,
+        max_pages: usize,
+    ) -> Result<Vec<HolderBalance>> {
+        let mut holders = Vec::new();
+        for page in 0..max_pages {
+            let url = format!(
+                "{}/token/{}/holders?limit={}&offset={}",
+                self.base_url,
+                mint,
+                page_size,
+                page * page_size
+            );
+            let rows: Vec<HolderBalance> = self
+                .client
+                .get(&url)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            // Rows come largest first, so a short page or an empty balance ends the list.
+            let before = holders.len();
+            holders.extend(rows.into_iter().take_while(|row| row.amount > 0));
+            if holders.len() - before < page_size {
+                break;
+            }
         }
+        Ok(holders)
+    }
+
+    /// Symbol, decimals, holder count and latest price of `mint`, or `None` if the indexer has
+    /// never seen it.
+    pub async fn fetch_mint_summary(&self, mint: &str) -> Result<Option<MintSummary>> {
+        // `/search` matches mint prefixes, so pick the exact row out of the results.
+        let url = format!("{}/search?q={}&limit=5", self.base_url, mint);
+        let rows: Vec<IndexedMint> = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let Some(row) = rows.into_iter().find(|row| row.mint_pubkey == mint) else {
+            return Ok(None);
+        };
+
+        let url = format!("{}/token/{}/holder_count", self.base_url, mint);
+        let body: serde_json::Value = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let holder_count = body["holder_count"]
+            .as_u64()
+            .ok_or_else(|| anyhow::anyhow!("holder_count response has no holder_count"))?;
+
+        Ok(Some(MintSummary {
+            symbol: row.symbol.filter(|symbol| !symbol.is_empty()),
+            decimals: u8::try_from(row.decimals)?,
+            holder_count,
+            price: self.fetch_latest_price(mint).await?,
+        }))
+    }
+
+    /// Seconds the indexer trails the chain, from its `/metrics.json`. `None` until it has
+    /// stored a trade with a block time.
+    pub async fn fetch_indexer_lag(&self) -> Result<Option<u64>> {
+        let url = format!("{}/metrics.json", self.base_url);
+        let body: serde_json::Value = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(body["lag_secs"].as_u64())
+    }
+
+    /// Bonding-curve progress of `mint` in percent (0-100).
+    pub async fn fetch_bonding_progress(&self, mint: &str) -> Result<f64> {
+        let url = format!("{}/token/{}/bonding_progress", self.base_url, mint);
+        let body: serde_json::Value = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        body["progress_pct"]
+            .as_f64()
+            .ok_or_else(|| anyhow::anyhow!("bonding_progress response has no progress_pct"))
+    }
+
+    pub async fn fetch_candles(
+        &self,
+        mint: &str,
+        timeframe_secs: u32,
+        limit: u32,
+    ) -> Result<Vec<Candle>> {
+        let url = format!(
+            "{}/token/{}/candles.bin?timeframe_secs={}&limit={}",
+            self.base_url, mint, timeframe_secs, limit
+        );
+        let bytes = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        decode_candles_bin(&bytes)
+    }
+
+    /// Close of the mint's latest 1m candle, or `None` if the indexer has no candles for it.
+    pub async fn fetch_latest_price(&self, mint: &str) -> Result<Option<f64>> {
+        let url = format!("{}/token/{}/candles?limit=1", self.base_url, mint);
+        let candles: Vec<serde_json::Value> = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(candles
+            .first()
+            .and_then(|c| c.get("close"))
+            .and_then(|v| v.as_f64()))
+    }
+
+    /// Quotes for `mints` from `/candles/batch`, [`MAX_BATCH_MINTS`] mints per request, over
+    /// the last `limit` candles of `timeframe_secs`. Mints without candles are left out.
+    pub async fn fetch_watch_quotes(
+        &self,
+        mints: &[String],
+        timeframe_secs: u32,
+        limit: u32,
+    ) -> Result<HashMap<String, WatchQuote>> {
+        let url = format!("{}/candles/batch", self.base_url);
+        let mut quotes = HashMap::new();
+        for chunk in mints.chunks(MAX_BATCH_MINTS) {
+            let body = serde_json::json!({
+                "mints": chunk,
+                "timeframe_secs": timeframe_secs,
+                "limit": limit,
+            });
+            let by_mint: HashMap<String, Vec<BatchCandle>> = self
+                .client
+                .post(&url)
+                .json(&body)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            quotes.extend(
+                by_mint
+                    .into_iter()
+                    .filter_map(|(mint, candles)| Some((mint, watch_quote(&candles)?))),
+            );
+        }
+        Ok(quotes)
     }
 
     pub async fn fetch_tokens(&self) -> Result<Vec<String>> {
@@ -47,3 +884,507 @@ impl IndexerClient {
         ])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_parse_market_event_topics() {
+        let trade = r#"{"topic":"bonding","mint_pubkey":"mint_a",
+            "payload":{"side":"buy","sol_amount":5,"price_nanos_per_token":2500}}"#;
+        assert_eq!(
+            parse_market_event(trade),
+            Some(MarketUpdate {
+                mint: "mint_a".to_string(),
+                price: 0.0000025,
+                bonding_trade: true,
+                trade: None,
+            })
+        );
+
+        let full = r#"{"topic":"bonding","mint_pubkey":"mint_a","payload":{"signature":"sig",
+            "slot":7,"trader":"wallet","side":"sell","token_amount":10,"sol_amount":5,
+            "price_nanos_per_token":2500,"is_new_wallet":true}}"#;
+        let trade = parse_market_event(full).unwrap().trade.unwrap();
+        assert_eq!((trade.side.as_str(), trade.sol_amount), ("sell", 5));
+        assert!(trade.is_new_wallet && trade.block_time.is_none());
+
+        let candle = r#"{"topic":"candles","mint_pubkey":"mint_b",
+            "payload":{"close":1000000000,"closed":false}}"#;
+        let update = parse_market_event(candle).unwrap();
+        assert_eq!((update.price, update.bonding_trade), (1.0, false));
+
+        let close = r#"{"topic":"candles","mint_pubkey":"mint_b","payload":{"closed":true}}"#;
+        assert_eq!(parse_market_event(close), None);
+        assert_eq!(parse_market_event(r#"{"type":"subscribed"}"#), None);
+        assert_eq!(
+            parse_market_event(r#"{"topic":"transfers","mint_pubkey":"m","payload":{}}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn test_indexer_ws_url() {
+        assert_eq!(indexer_ws_url("http://localhost:3000"), "ws://localhost:3000/ws");
+        assert_eq!(indexer_ws_url("https://indexer.example/"), "wss://indexer.example/ws");
+    }
+
+    #[test]
+    fn test_decode_candles_bin_oldest_first_in_sol() {
+        let mut data = b"CNDL".to_vec();
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&60u16.to_le_bytes());
+        data.extend_from_slice(&60i32.to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        for (ts, price) in [(120i64, 2_000_000i64), (60, 1_000_000)] {
+            data.extend_from_slice(&ts.to_le_bytes());
+            for _ in 0..4 {
+                data.extend_from_slice(&price.to_le_bytes());
+            }
+            data.extend_from_slice(&0i64.to_le_bytes());
+            data.extend_from_slice(&0i64.to_le_bytes());
+            data.extend_from_slice(&1i32.to_le_bytes());
+        }
+
+        let candles = decode_candles_bin(&data).unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[0].timestamp, 60);
+        assert_eq!(candles[0].close, 0.001);
+        assert_eq!(candles[1].close, 0.002);
+        assert!(decode_candles_bin(&data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_watch_quote_from_newest_first_candles() {
+        let candle = |open: i64, close: i64| BatchCandle { open, close };
+        let newest_first = [candle(1_100_000, 1_500_000), candle(1_000_000, 1_100_000)];
+        let quote = watch_quote(&newest_first).unwrap();
+        assert_eq!(quote.price, 0.0015);
+        assert_eq!(quote.change_pct, Some(50.0));
+
+        assert_eq!(watch_quote(&[candle(0, 7)]).unwrap().change_pct, None);
+        assert_eq!(watch_quote(&[]), None);
+    }
+
+    /// A transfer paying `cu_price` micro-lamports per compute unit, as a signer-ready message.
+    fn priced_message(payer: &Keypair, cu_price: Option<u64>) -> VersionedMessage {
+        use solana_sdk::{
+            compute_budget::ComputeBudgetInstruction, message::Message, signer::Signer,
+            system_instruction,
+        };
+
+        let mut ixs = vec![system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1)];
+        if let Some(price) = cu_price {
+            ixs.insert(0, ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        VersionedMessage::Legacy(Message::new(&ixs, Some(&payer.pubkey())))
+    }
+
+    fn cu_price(message: &VersionedMessage) -> Option<u64> {
+        message
+            .instructions()
+            .iter()
+            .find(|ix| {
+                message.static_account_keys()[ix.program_id_index as usize] == compute_budget::id()
+            })
+            .map(|ix| u64::from_le_bytes(ix.data[1..9].try_into().unwrap()))
+    }
+
+    #[test]
+    fn test_bump_compute_unit_price() {
+        let payer = Keypair::new();
+
+        let mut message = priced_message(&payer, Some(50_000));
+        assert_eq!(bump_compute_unit_price(&mut message), Some(100_000));
+        assert_eq!(cu_price(&message), Some(100_000));
+
+        let mut message = priced_message(&payer, Some(0));
+        assert_eq!(bump_compute_unit_price(&mut message), Some(MIN_RETRY_CU_PRICE));
+
+        let mut message = priced_message(&payer, None);
+        assert_eq!(bump_compute_unit_price(&mut message), None);
+    }
+
+    /// Scripted [`SendRpc`]: sends fail while `failures` lasts, then succeed.
+    struct ScriptedRpc {
+        sent: std::sync::Mutex<Vec<VersionedTransaction>>,
+        failures: usize,
+        fresh: Hash,
+        blockhash_valid: bool,
+        landed: bool,
+    }
+
+    impl ScriptedRpc {
+        fn new(failures: usize, blockhash_valid: bool, landed: bool) -> Self {
+            Self {
+                sent: Default::default(),
+                failures,
+                fresh: Hash::new_unique(),
+                blockhash_valid,
+                landed,
+            }
+        }
+    }
+
+    impl SendRpc for ScriptedRpc {
+        async fn send(&self, tx: &VersionedTransaction) -> Result<Signature> {
+            let mut sent = self.sent.lock().unwrap();
+            sent.push(tx.clone());
+            if sent.len() <= self.failures {
+                anyhow::bail!("send timed out");
+            }
+            Ok(tx.signatures[0])
+        }
+
+        async fn latest_blockhash(&self) -> Result<Hash> {
+            Ok(self.fresh)
+        }
+
+        async fn blockhash_valid(&self, _: &Hash) -> Result<bool> {
+            Ok(self.blockhash_valid)
+        }
+
+        async fn landed(&self, _: &Signature) -> Result<bool> {
+            Ok(self.landed)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_rebroadcasts_same_transaction_while_it_may_land() {
+        let payer = Keypair::new();
+        let rpc = ScriptedRpc::new(2, true, false);
+        let mut retries = Vec::new();
+
+        let message = priced_message(&payer, Some(1_000));
+        let sent = send_with_retry(&rpc, message, &payer, 3, Duration::ZERO, |retry| {
+            retries.push(retry.to_string())
+        })
+        .await
+        .unwrap();
+
+        let txs = rpc.sent.into_inner().unwrap();
+        assert_eq!(txs.len(), 3);
+        assert!(txs.iter().all(|tx| *tx == txs[0]), "re-signed while the first could land");
+        assert_eq!(sent, (txs[0].signatures[0], *txs[0].message.recent_blockhash()));
+        assert_eq!(
+            retries[0],
+            "send attempt 1/3 failed: send timed out; rebroadcasting the same transaction"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_resigns_with_bumped_fee_once_blockhash_expired_unlanded() {
+        let payer = Keypair::new();
+        let rpc = ScriptedRpc::new(2, false, false);
+        let mut retries = Vec::new();
+
+        let message = priced_message(&payer, Some(1_000));
+        let (sig, blockhash) = send_with_retry(&rpc, message, &payer, 3, Duration::ZERO, |retry| {
+            retries.push(retry.to_string())
+        })
+        .await
+        .unwrap();
+
+        let sent = rpc.sent.into_inner().unwrap();
+        assert_eq!(sent.len(), 3);
+        assert_eq!(sig, sent[2].signatures[0]);
+        assert_eq!(blockhash, rpc.fresh);
+        let prices: Vec<_> = sent.iter().map(|tx| cu_price(&tx.message)).collect();
+        assert_eq!(prices, [Some(1_000), Some(10_000), Some(20_000)]);
+        assert_ne!(*sent[0].message.recent_blockhash(), rpc.fresh);
+        assert_eq!(*sent[1].message.recent_blockhash(), rpc.fresh);
+        assert!(sent.iter().all(|tx| tx.verify_with_results().iter().all(|ok| *ok)));
+        assert_eq!(
+            retries,
+            [
+                "send attempt 1/3 failed: send timed out; re-signing at 10000 micro-lamports/CU",
+                "send attempt 2/3 failed: send timed out; re-signing at 20000 micro-lamports/CU",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_stops_once_failed_transaction_landed() {
+        let payer = Keypair::new();
+        let rpc = ScriptedRpc::new(1, false, true);
+
+        let message = priced_message(&payer, Some(1_000));
+        let (sig, _) = send_with_retry(&rpc, message, &payer, 3, Duration::ZERO, |_| {})
+            .await
+            .unwrap();
+
+        let sent = rpc.sent.into_inner().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sig, sent[0].signatures[0]);
+    }
+
+    #[tokio::test]
+    async fn test_send_gives_up_after_max_attempts() {
+        let payer = Keypair::new();
+        let rpc = ScriptedRpc::new(usize::MAX, true, false);
+        let message = priced_message(&payer, None);
+        let err = send_with_retry(&rpc, message, &payer, 2, Duration::ZERO, |_| {})
+            .await
+            .unwrap_err();
+
+        assert_eq!(rpc.sent.into_inner().unwrap().len(), 2);
+        assert_eq!(err.to_string(), "send timed out");
+    }
+
+    /// Runs `poll_confirmation` against canned status replies, one per poll (the last repeats).
+    async fn confirm_with(statuses: Vec<Option<LandedStatus>>, valid: Vec<bool>) -> Confirmation {
+        use std::sync::Mutex;
+
+        fn next<T: Clone>(items: &Mutex<Vec<T>>) -> T {
+            let mut items = items.lock().unwrap();
+            if items.len() > 1 {
+                items.remove(0)
+            } else {
+                items[0].clone()
+            }
+        }
+        let (statuses, valid) = (Mutex::new(statuses), Mutex::new(valid));
+        poll_confirmation(
+            Duration::from_millis(50),
+            Duration::from_millis(5),
+            || std::future::ready(Ok(next(&statuses))),
+            || std::future::ready(Ok(next(&valid))),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_confirmation_outcomes() {
+        let landed = |confirmed: bool, error: Option<&str>| LandedStatus {
+            slot: 321,
+            error: error.map(str::to_string),
+            confirmed,
+        };
+
+        // Unseen, then processed, then confirmed.
+        let statuses = vec![None, Some(landed(false, None)), Some(landed(true, None))];
+        assert_eq!(
+            confirm_with(statuses, vec![true]).await,
+            Confirmation::Confirmed { slot: 321 }
+        );
+
+        let failed = Some(landed(false, Some("custom program error: 0x1771")));
+        assert_eq!(
+            confirm_with(vec![failed], vec![true]).await,
+            Confirmation::Failed {
+                slot: 321,
+                error: "custom program error: 0x1771".to_string()
+            }
+        );
+
+        assert_eq!(confirm_with(vec![None], vec![true, false]).await, Confirmation::Expired);
+        assert_eq!(confirm_with(vec![None], vec![true]).await, Confirmation::TimedOut);
+        // Landing in the last valid slot is not an expiry.
+        let statuses = vec![Some(landed(true, None))];
+        assert_eq!(
+            confirm_with(statuses, vec![false]).await,
+            Confirmation::Confirmed { slot: 321 }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_holders_pages_until_short_page() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Five holders, the last with an emptied account, served two per page.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let pages = [
+                r#"[{"wallet":"a","amount":500},{"wallet":"b","amount":300}]"#,
+                r#"[{"wallet":"c","amount":150},{"wallet":"d","amount":50}]"#,
+                r#"[{"wallet":"e","amount":0}]"#,
+            ];
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let page = ["offset=0 ", "offset=2 ", "offset=4 "]
+                    .iter()
+                    .position(|offset| request.contains(offset))
+                    .map_or("[]", |i| pages[i]);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\n\
+                     content-length: {}\r\nconnection: close\r\n\r\n{page}",
+                    page.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        let client = IndexerClient::new().unwrap().with_base_url(&format!("http://{addr}"));
+
+        let holders = client.fetch_holders("mint", 2, 10).await.unwrap();
+        let wallets: Vec<&str> = holders.iter().map(|h| h.wallet.as_str()).collect();
+        assert_eq!(wallets, ["a", "b", "c", "d"]);
+
+        let capped = client.fetch_holders("mint", 2, 1).await.unwrap();
+        assert_eq!(capped.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_mint_summary_picks_the_exact_mint() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let routes = [
+                (
+                    "GET /search?q=abc&",
+                    r#"[{"mint_pubkey":"abcd","symbol":"LONG","decimals":9,"first_seen_slot":1},
+                        {"mint_pubkey":"abc","symbol":"ABC","decimals":6,"first_seen_slot":2}]"#,
+                ),
+                ("GET /search?q=zzz&", "[]"),
+                ("GET /token/abc/holder_count ", r#"{"holder_count":42}"#),
+                ("GET /token/abc/candles?", r#"[{"close":0.0025}]"#),
+            ];
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let body = routes
+                    .iter()
+                    .find(|(route, _)| request.starts_with(route))
+                    .map_or("[]", |(_, body)| body);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\n\
+                     content-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        let client = IndexerClient::new().unwrap().with_base_url(&format!("http://{addr}"));
+
+        let summary = client.fetch_mint_summary("abc").await.unwrap();
+        assert_eq!(
+            summary,
+            Some(MintSummary {
+                symbol: Some("ABC".to_string()),
+                decimals: 6,
+                holder_count: 42,
+                price: Some(0.0025),
+            })
+        );
+        assert_eq!(client.fetch_mint_summary("zzz").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_indexer_lag_reads_metrics_json() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for body in [r#"{"lag_secs":12,"total_mints":3}"#, r#"{"lag_secs":null}"#] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 1024];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                assert!(String::from_utf8_lossy(&buf[..n]).starts_with("GET /metrics.json "));
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\n\
+                     content-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        let client = IndexerClient::new().unwrap().with_base_url(&format!("http://{addr}"));
+
+        assert_eq!(client.fetch_indexer_lag().await.unwrap(), Some(12));
+        assert_eq!(client.fetch_indexer_lag().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_watch_quotes_are_fetched_in_batches() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                // Read until the whole JSON body is in; it ends the request.
+                let body = loop {
+                    let n = stream.read(&mut buf).await.unwrap_or(0);
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request).into_owned();
+                    if let Some(body) = text.split_once("\r\n\r\n").map(|(_, b)| b.to_string()) {
+                        if let Ok(body) = serde_json::from_str::<serde_json::Value>(&body) {
+                            break body;
+                        }
+                    }
+                    assert!(n > 0, "request ended early");
+                };
+                let mints = body["mints"].as_array().unwrap();
+                let (status, reply) = if mints.len() > MAX_BATCH_MINTS {
+                    ("400 Bad Request", serde_json::json!({}))
+                } else {
+                    let candle =
+                        serde_json::json!([{ "open": 1_000_000_000, "close": 2_000_000_000 }]);
+                    let by_mint: serde_json::Map<_, _> = mints
+                        .iter()
+                        .map(|mint| (mint.as_str().unwrap().to_string(), candle.clone()))
+                        .collect();
+                    ("200 OK", serde_json::Value::Object(by_mint))
+                };
+                let reply = reply.to_string();
+                let response = format!(
+                    "HTTP/1.1 {status}\r\ncontent-type: application/json\r\n\
+                     content-length: {}\r\nconnection: close\r\n\r\n{reply}",
+                    reply.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        let client = IndexerClient::new().unwrap().with_base_url(&format!("http://{addr}"));
+
+        let mints: Vec<String> = (0..MAX_BATCH_MINTS + 50).map(|i| format!("mint{i}")).collect();
+        let quotes = client.fetch_watch_quotes(&mints, 3_600, 24).await.unwrap();
+        assert_eq!(quotes.len(), mints.len());
+        assert_eq!(quotes["mint149"].price, 2.0);
+    }
+
+    #[tokio::test]
+    async fn test_slow_indexer_times_out() {
+        // Accepts connections but never answers.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                held.push(stream);
+            }
+        });
+
+        let client = IndexerClient::new()
+            .unwrap()
+            .with_base_url(&format!("http://{addr}"))
+            .with_timeout(Duration::from_millis(200))
+            .unwrap();
+        let err = client.fetch_latest_price("mint").await.unwrap_err();
+
+        assert!(is_timeout(&err), "expected a timeout, got {err:?}");
+    }
+
+    #[tokio::test]
+    async fn test_rpc_timeout_error_is_distinct() {
+        let client = NetworkClient::new("http://127.0.0.1:1").with_timeout(Duration::from_millis(1));
+        let err = client
+            .timed("getSlow", std::future::pending::<std::result::Result<(), std::io::Error>>())
+            .await
+            .unwrap_err();
+
+        assert!(is_timeout(&err));
+        assert_eq!(err.to_string(), "getSlow timed out after 0.0s");
+        assert!(!is_timeout(&anyhow::anyhow!("connection refused")));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::app::WSOL_MINT;
+use crate::swap::SwapOrder;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderSide {
+    /// Spend SOL on the token once its price falls to or below the trigger.
+    Buy,
+    /// Sell the token for SOL once its price rises to or above the trigger.
+    Sell,
+}
+
+impl fmt::Display for OrderSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderSide::Buy => write!(f, "buy"),
+            OrderSide::Sell => write!(f, "sell"),
+        }
+    }
+}
+
+/// A limit order that survives restarts. It only fires while the terminal is running, at
+/// whatever Jupiter quotes when the indexer's live price crosses `trigger_price`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LimitOrder {
+    pub mint: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    /// Raw units of what is spent: lamports for a buy, token units for a sell.
+    pub amount: u64,
+    /// The mint's decimals, for showing a sell amount in whole tokens.
+    pub decimals: u8,
+    /// SOL per whole token, like the indexer's live prices.
+    pub trigger_price: f64,
+    /// Swap settings of the trade profile that was active when the order was placed.
+    pub slippage_bps: u64,
+    pub priority_fee_lamports: u64,
+    #[serde(default)]
+    pub max_impact_pct: Option<f64>,
+}
+
+impl LimitOrder {
+    pub fn is_triggered(&self, price: f64) -> bool {
+        match self.side {
+            OrderSide::Buy => price <= self.trigger_price,
+            OrderSide::Sell => price >= self.trigger_price,
+        }
+    }
+
+    /// The amount spent in whole units: SOL for a buy, tokens for a sell.
+    pub fn ui_amount(&self) -> f64 {
+        let decimals = match self.side {
+            OrderSide::Buy => 9,
+            OrderSide::Sell => self.decimals,
+        };
+        self.amount as f64 / 10f64.powi(decimals as i32)
+    }
+
+    /// The swap this order executes when it fires.
+    pub fn swap_order(&self) -> SwapOrder {
+        let (input_mint, output_mint) = match self.side {
+            OrderSide::Buy => (WSOL_MINT.to_string(), self.mint.clone()),
+            OrderSide::Sell => (self.mint.clone(), WSOL_MINT.to_string()),
+        };
+        SwapOrder {
+            input_mint,
+            output_mint,
+            amount: self.amount,
+            slippage_bps: self.slippage_bps,
+            priority_fee_lamports: self.priority_fee_lamports,
+            max_impact_pct: self.max_impact_pct,
+        }
+    }
+}
+
+/// Load open orders from a JSON file. A missing file means no orders yet.
+pub fn load_orders(path: &Path) -> Result<Vec<LimitOrder>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = std::fs::read(path)
+        .with_context(|| format!("failed to read orders file {}", path.display()))?;
+    serde_json::from_slice(&data)
+        .with_context(|| format!("invalid orders file {}", path.display()))
+}
+
+pub fn save_orders(path: &Path, orders: &[LimitOrder]) -> Result<()> {
+    let data = serde_json::to_vec_pretty(orders)?;
+    std::fs::write(path, data)
+        .with_context(|| format!("failed to write orders file {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(side: OrderSide) -> LimitOrder {
+        LimitOrder {
+            mint: "mint".to_string(),
+            symbol: "TKN".to_string(),
+            side,
+            amount: 1_500_000,
+            decimals: 6,
+            trigger_price: 0.002,
+            slippage_bps: 100,
+            priority_fee_lamports: 10_000,
+            max_impact_pct: None,
+        }
+    }
+
+    #[test]
+    fn test_orders_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("tx-orders-{}.json", std::process::id()));
+        assert!(load_orders(&path).unwrap().is_empty());
+
+        let orders = vec![order(OrderSide::Buy), order(OrderSide::Sell)];
+        save_orders(&path, &orders).unwrap();
+        assert_eq!(load_orders(&path).unwrap(), orders);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_buy_fires_at_or_below_and_sell_at_or_above() {
+        let buy = order(OrderSide::Buy);
+        assert!(!buy.is_triggered(0.0021));
+        assert!(buy.is_triggered(0.002));
+        assert!(buy.is_triggered(0.0015));
+
+        let sell = order(OrderSide::Sell);
+        assert!(!sell.is_triggered(0.0019));
+        assert!(sell.is_triggered(0.002));
+        assert_eq!(sell.ui_amount(), 1.5);
+        assert_eq!(buy.ui_amount(), 0.0015);
+
+        let swap = sell.swap_order();
+        assert_eq!((swap.input_mint.as_str(), swap.output_mint.as_str()), ("mint", WSOL_MINT));
+        assert_eq!(buy.swap_order().input_mint, WSOL_MINT);
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Swap settings applied together, so switching between careful and fast trading is one key
+/// press instead of re-entering each value.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TradeProfile {
+    pub name: String,
+    pub slippage_bps: u64,
+    /// Sent to Jupiter as `prioritizationFeeLamports`.
+    pub priority_fee_lamports: u64,
+    /// Block quotes above this price impact (percent). `--max-impact-pct` still applies on top,
+    /// so the stricter of the two wins.
+    #[serde(default)]
+    pub max_impact_pct: Option<f64>,
+}
+
+impl TradeProfile {
+    fn new(name: &str, slippage_bps: u64, priority_fee_lamports: u64, max_impact_pct: Option<f64>) -> Self {
+        Self {
+            name: name.to_string(),
+            slippage_bps,
+            priority_fee_lamports,
+            max_impact_pct,
+        }
+    }
+}
+
+/// Profiles used when no profiles file is given: "safe" is the startup profile.
+pub fn default_profiles() -> Vec<TradeProfile> {
+    vec![
+        TradeProfile::new("safe", 50, 10_000, Some(2.0)),
+        TradeProfile::new("fast", 300, 500_000, Some(10.0)),
+        TradeProfile::new("degen", 1_500, 2_000_000, None),
+    ]
+}
+
+/// Load profiles from a JSON array. A missing file means the defaults; an empty list is an error
+/// since there would be nothing to trade with.
+pub fn load_profiles(path: &Path) -> Result<Vec<TradeProfile>> {
+    if !path.exists() {
+        return Ok(default_profiles());
+    }
+    let data = std::fs::read(path)
+        .with_context(|| format!("failed to read profiles file {}", path.display()))?;
+    let profiles: Vec<TradeProfile> = serde_json::from_slice(&data)
+        .with_context(|| format!("invalid profiles file {}", path.display()))?;
+    if profiles.is_empty() {
+        bail!("profiles file {} has no profiles", path.display());
+    }
+    Ok(profiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_profiles_from_file_or_defaults() {
+        let path = std::env::temp_dir().join(format!("tx-profiles-{}.json", std::process::id()));
+        assert_eq!(load_profiles(&path).unwrap(), default_profiles());
+
+        std::fs::write(
+            &path,
+            r#"[{"name":"sniper","slippage_bps":2500,"priority_fee_lamports":5000000}]"#,
+        )
+        .unwrap();
+        let profiles = load_profiles(&path).unwrap();
+        assert_eq!(profiles, vec![TradeProfile::new("sniper", 2_500, 5_000_000, None)]);
+
+        std::fs::write(&path, "[]").unwrap();
+        assert!(load_profiles(&path).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,220 @@
+use crate::network::{WalletBalance, WalletMintPnl};
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Decimals assumed for a held mint the indexer has no trades for (pump.fun mints use 6).
+const DEFAULT_TOKEN_DECIMALS: i32 = 6;
+
+const LAMPORTS_PER_SOL: f64 = 1e9;
+
+/// One held mint of the position report. Cost figures use the average buy price; they are
+/// `None` when the wallet has no indexed buys of the mint (e.g. it received the tokens by
+/// transfer), and value is `None` when the mint never traded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionRow {
+    pub mint: String,
+    /// Whole tokens held.
+    pub holdings: f64,
+    pub avg_cost_sol: Option<f64>,
+    pub cost_basis_sol: Option<f64>,
+    pub value_sol: Option<f64>,
+}
+
+impl PositionRow {
+    pub fn unrealized_pnl_sol(&self) -> Option<f64> {
+        Some(self.value_sol? - self.cost_basis_sol?)
+    }
+}
+
+/// Join the wallet's holdings with its per-mint trade totals. Mints the wallet traded but no
+/// longer holds are left out.
+pub fn position_rows(portfolio: &[WalletBalance], pnl: &[WalletMintPnl]) -> Vec<PositionRow> {
+    portfolio
+        .iter()
+        .filter(|balance| balance.amount > 0)
+        .map(|balance| {
+            let trades = pnl.iter().find(|p| p.mint_pubkey == balance.mint_pubkey);
+            // The indexer reports -1 for a mint whose decimals it has not seen yet.
+            let decimals = trades
+                .map(|p| p.decimals)
+                .filter(|&d| d >= 0)
+                .unwrap_or(DEFAULT_TOKEN_DECIMALS);
+            let unit = 10f64.powi(decimals);
+            let holdings = balance.amount as f64 / unit;
+
+            let avg_cost_sol = trades
+                .filter(|p| p.tokens_bought > 0)
+                .map(|p| (p.sol_spent as f64 / LAMPORTS_PER_SOL) / (p.tokens_bought as f64 / unit));
+            let price_sol = trades
+                .and_then(|p| p.last_price_nanos_per_token)
+                .map(|nanos| nanos as f64 / LAMPORTS_PER_SOL);
+
+            PositionRow {
+                mint: balance.mint_pubkey.clone(),
+                holdings,
+                avg_cost_sol,
+                cost_basis_sol: avg_cost_sol.map(|cost| cost * holdings),
+                value_sol: price_sol.map(|price| price * holdings),
+            }
+        })
+        .collect()
+}
+
+/// A SOL figure to 4 decimals, or `n/a` when unknown.
+pub fn sol_cell(value: Option<f64>) -> String {
+    value.map_or_else(|| "n/a".to_string(), |v| format!("{v:.4}"))
+}
+
+/// A signed SOL PnL figure to 4 decimals, or `n/a` when unknown.
+pub fn pnl_cell(value: Option<f64>) -> String {
+    value.map_or_else(|| "n/a".to_string(), |v| format!("{v:+.4}"))
+}
+
+/// Render the position report as Markdown. `generated_at` is a unix timestamp.
+pub fn format_position_report(
+    wallet: &str,
+    generated_at: u64,
+    portfolio: &[WalletBalance],
+    pnl: &[WalletMintPnl],
+) -> String {
+    let rows = position_rows(portfolio, pnl);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# Position report\n");
+    let _ = writeln!(out, "Wallet: `{wallet}`  ");
+    let _ = writeln!(out, "Generated: {generated_at} (unix)\n");
+
+    if rows.is_empty() {
+        let _ = writeln!(out, "No open positions.");
+        return out;
+    }
+
+    let _ = writeln!(
+        out,
+        "| Mint | Holdings | Avg cost (SOL) | Cost basis (SOL) | Value (SOL) | Unrealized PnL (SOL) |"
+    );
+    let _ = writeln!(out, "|---|---:|---:|---:|---:|---:|");
+    for row in &rows {
+        let _ = writeln!(
+            out,
+            "| {} | {:.6} | {} | {} | {} | {} |",
+            row.mint,
+            row.holdings,
+            sol_cell(row.avg_cost_sol),
+            sol_cell(row.cost_basis_sol),
+            sol_cell(row.value_sol),
+            pnl_cell(row.unrealized_pnl_sol()),
+        );
+    }
+
+    // Totals only cover rows where both sides of the PnL are known.
+    let priced: Vec<&PositionRow> = rows
+        .iter()
+        .filter(|row| row.unrealized_pnl_sol().is_some())
+        .collect();
+    let cost: f64 = priced.iter().filter_map(|row| row.cost_basis_sol).sum();
+    let value: f64 = priced.iter().filter_map(|row| row.value_sol).sum();
+    let _ = writeln!(
+        out,
+        "| **Total** | | | {cost:.4} | {value:.4} | {:+.4} |",
+        value - cost
+    );
+
+    if priced.len() < rows.len() {
+        let _ = writeln!(
+            out,
+            "\nn/a: no indexed buys (cost) or trades (value) for the mint; excluded from the total."
+        );
+    }
+    out
+}
+
+/// Write the report to `position-report-<generated_at>.md` in `dir` and return its path.
+pub fn write_position_report(dir: &Path, generated_at: u64, report: &str) -> Result<PathBuf> {
+    let path = dir.join(format!("position-report-{generated_at}.md"));
+    std::fs::write(&path, report)
+        .with_context(|| format!("failed to write report {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance(mint: &str, amount: i64) -> WalletBalance {
+        WalletBalance {
+            mint_pubkey: mint.to_string(),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_format_position_report() {
+        let portfolio = vec![
+            // 1.5 tokens
+            balance("mintA", 1_500_000),
+            // Received by transfer, never traded
+            balance("mintB", 2_000_000),
+            balance("empty", 0),
+        ];
+        let pnl = vec![
+            WalletMintPnl {
+                mint_pubkey: "mintA".to_string(),
+                decimals: 6,
+                // 2 tokens for 0.01 SOL: 0.005 SOL each
+                tokens_bought: 2_000_000,
+                sol_spent: 10_000_000,
+                tokens_sold: 500_000,
+                sol_received: 4_000_000,
+                last_price_nanos_per_token: Some(9_000_000),
+            },
+            // Fully sold, so not a position
+            WalletMintPnl {
+                mint_pubkey: "mintC".to_string(),
+                decimals: 6,
+                tokens_bought: 1_000_000,
+                sol_spent: 1_000_000,
+                tokens_sold: 1_000_000,
+                sol_received: 2_000_000,
+                last_price_nanos_per_token: Some(2_000_000),
+            },
+        ];
+
+        let report = format_position_report("Wallet111", 1_700_000_000, &portfolio, &pnl);
+        let lines: Vec<&str> = report.lines().collect();
+
+        assert_eq!(lines[0], "# Position report");
+        assert!(report.contains("Wallet: `Wallet111`"));
+        assert!(report.contains("Generated: 1700000000 (unix)"));
+        assert!(lines.contains(&"| mintA | 1.500000 | 0.0050 | 0.0075 | 0.0135 | +0.0060 |"));
+        assert!(lines.contains(&"| mintB | 2.000000 | n/a | n/a | n/a | n/a |"));
+        assert!(lines.contains(&"| **Total** | | | 0.0075 | 0.0135 | +0.0060 |"));
+        assert!(report.contains("excluded from the total"));
+        assert!(!report.contains("mintC"));
+        assert!(!report.contains("| empty |"));
+    }
+
+    #[test]
+    fn test_unknown_decimals_fall_back_to_default() {
+        let pnl = WalletMintPnl {
+            mint_pubkey: "mintA".to_string(),
+            decimals: -1,
+            tokens_bought: 0,
+            sol_spent: 0,
+            tokens_sold: 0,
+            sol_received: 0,
+            last_price_nanos_per_token: None,
+        };
+
+        let rows = position_rows(&[balance("mintA", 1_500_000)], &[pnl]);
+        assert_eq!(rows[0].holdings, 1.5);
+    }
+
+    #[test]
+    fn test_format_position_report_without_positions() {
+        let report = format_position_report("Wallet111", 0, &[balance("gone", 0)], &[]);
+        assert!(report.ends_with("No open positions.\n"));
+        assert!(!report.contains("| Mint |"));
+    }
+}
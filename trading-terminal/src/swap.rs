@@ -1,5 +1,20 @@
-use anyhow::Result;
+use crate::network::{http_client, is_timeout, DEFAULT_REQUEST_TIMEOUT};
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine as _};
 use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    hash::Hash,
+    message::VersionedMessage,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::VersionedTransaction,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -16,6 +31,43 @@ pub struct QuoteResponse {
     pub route_plan: Vec<RoutePlan>,
     pub context_slot: Option<u64>,
     pub time_taken: Option<f64>,
+    /// Set locally when `price_impact_pct` exceeds the client's `max_impact_pct`.
+    /// A blocked quote must not be executed. Never sent back to Jupiter.
+    #[serde(default, skip_serializing)]
+    pub blocked: bool,
+}
+
+impl QuoteResponse {
+    /// Price impact as a number, or `None` if Jupiter sent something unparseable.
+    pub fn price_impact(&self) -> Option<f64> {
+        self.price_impact_pct
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+    }
+
+    /// AMM labels of the route's legs in order, e.g. "Raydium -> Meteora DLMM".
+    pub fn route_labels(&self) -> String {
+        let labels: Vec<&str> = self
+            .route_plan
+            .iter()
+            .map(|leg| leg.swap_info.label.as_str())
+            .collect();
+        labels.join(" -> ")
+    }
+
+    /// Mark the quote as blocked if its impact is above `max_impact_pct`.
+    /// An impact that can't be parsed is treated as too high.
+    pub fn apply_impact_guard(&mut self, max_impact_pct: Option<f64>) {
+        let Some(max) = max_impact_pct else {
+            return;
+        };
+        self.blocked = match self.price_impact() {
+            Some(impact) => impact.abs() > max,
+            None => true,
+        };
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -56,35 +108,348 @@ pub struct SwapResponse {
 pub struct SwapRequest {
     pub user_public_key: String,
     pub quote_response: QuoteResponse,
+    pub prioritization_fee_lamports: u64,
+}
+
+/// What a swap task executes, captured from the UI (amount, token, active trade profile) when
+/// the swap starts so later key presses don't affect it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwapOrder {
+    pub input_mint: String,
+    pub output_mint: String,
+    /// In the input mint's raw units.
+    pub amount: u64,
+    pub slippage_bps: u64,
+    pub priority_fee_lamports: u64,
+    /// The profile's impact limit, applied together with the client's `max_impact_pct`.
+    pub max_impact_pct: Option<f64>,
+}
+
+/// A raw token amount as Jupiter sends it (e.g. `outAmount`) in whole units of a mint with
+/// `decimals`, for display. `None` if the string isn't an integer.
+pub fn raw_to_ui_amount(raw: &str, decimals: u8) -> Option<f64> {
+    let raw = raw.trim().parse::<u64>().ok()?;
+    Some(raw as f64 / 10f64.powi(decimals as i32))
+}
+
+/// A quoted swap waiting for the user to confirm it. Holds the [`SwapGate`] slot, so no other
+/// swap starts until it is sent or dropped.
+pub struct PendingSwap {
+    pub order: SwapOrder,
+    pub quote: QuoteResponse,
+    pub permit: OwnedSemaphorePermit,
+}
+
+/// Step of a swap that can fail, so a rejected quote reads differently from an RPC send error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapStage {
+    Quote,
+    Build,
+    Sign,
+    Send,
+    /// Waiting for a sent transaction to confirm.
+    Confirm,
+    /// The transaction landed but failed on chain.
+    Execute,
+}
+
+impl fmt::Display for SwapStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SwapStage::Quote => "Quote",
+            SwapStage::Build => "Swap API",
+            SwapStage::Sign => "Signing",
+            SwapStage::Send => "Send",
+            SwapStage::Confirm => "Confirmation",
+            SwapStage::Execute => "Transaction",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Progress of a swap task, reported back to the UI as it goes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SwapStatus {
+    Quoting,
+    /// `out_amount` is already formatted for display, e.g. "0.42 SOL".
+    Quoted { out_amount: String },
+    Signing,
+    /// Accepted by the RPC node; confirmation pending.
+    Sent { sig: String },
+    Confirmed { sig: String, slot: u64 },
+    /// Never landed and its blockhash expired, so it can be retried safely.
+    Expired { sig: String },
+    /// Dropped from the confirmation modal before anything was signed.
+    Cancelled,
+    Failed { stage: SwapStage, msg: String },
+}
+
+impl fmt::Display for SwapStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SwapStatus::Quoting => write!(f, "Quoting..."),
+            SwapStatus::Quoted { out_amount } => write!(f, "Quoted: ~{out_amount} out"),
+            SwapStatus::Signing => write!(f, "Signing..."),
+            SwapStatus::Sent { sig } => write!(f, "Swap sent: {sig}"),
+            SwapStatus::Confirmed { sig, slot } => {
+                write!(f, "Swap confirmed in slot {slot}: {sig}")
+            }
+            SwapStatus::Expired { sig } => {
+                write!(f, "Swap expired without landing, safe to retry: {sig}")
+            }
+            SwapStatus::Cancelled => write!(f, "Swap cancelled"),
+            SwapStatus::Failed { stage, msg } => write!(f, "{stage} failed: {msg}"),
+        }
+    }
+}
+
+/// A swap step that failed, reported as [`SwapStatus::Failed`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwapFailure {
+    pub stage: SwapStage,
+    pub msg: String,
 }
 
+impl SwapFailure {
+    fn new(stage: SwapStage, msg: impl Into<String>) -> Self {
+        Self {
+            stage,
+            msg: msg.into(),
+        }
+    }
+}
+
+impl From<SwapFailure> for SwapStatus {
+    fn from(failure: SwapFailure) -> Self {
+        SwapStatus::Failed {
+            stage: failure.stage,
+            msg: failure.msg,
+        }
+    }
+}
+
+/// `err` for a [`SwapStatus::Failed`], calling out timeouts.
+pub fn failure_detail(err: &anyhow::Error) -> String {
+    if is_timeout(err) {
+        format!("timed out: {err}")
+    } else {
+        err.to_string()
+    }
+}
+
+/// Sign Jupiter's base64 swap transaction with `keypair` and hand its message to `send`, which
+/// signs and sends it and returns the signature and the blockhash it was sent with.
+///
+/// `keypair` must be one of the message's required signers, and the only one: Jupiter builds
+/// the transaction for the wallet alone. If `blockhash_valid` reports the message's blockhash
+/// as expired, it is replaced with `latest_blockhash` before signing; that only touches the
+/// header field, so the address lookup tables of a v0 message are kept as they are. A failed
+/// validity check keeps Jupiter's blockhash, which is normally seconds old.
+pub async fn sign_and_send<V, VFut, L, LFut, S, SFut>(
+    swap_base64: &str,
+    keypair: &Keypair,
+    blockhash_valid: V,
+    latest_blockhash: L,
+    send: S,
+) -> std::result::Result<(Signature, Hash), SwapFailure>
+where
+    V: FnOnce(Hash) -> VFut,
+    VFut: Future<Output = Result<bool>>,
+    L: FnOnce() -> LFut,
+    LFut: Future<Output = Result<Hash>>,
+    S: FnOnce(VersionedMessage) -> SFut,
+    SFut: Future<Output = Result<(Signature, Hash)>>,
+{
+    let Ok(swap_bytes) = general_purpose::STANDARD.decode(swap_base64) else {
+        return Err(SwapFailure::new(SwapStage::Build, "invalid base64 transaction"));
+    };
+    let Ok(versioned_tx) = bincode::deserialize::<VersionedTransaction>(&swap_bytes) else {
+        return Err(SwapFailure::new(SwapStage::Build, "undecodable transaction"));
+    };
+    let mut message = versioned_tx.message;
+
+    let required = message.header().num_required_signatures as usize;
+    let keys = message.static_account_keys();
+    let signers = &keys[..required.min(keys.len())];
+    let wallet = keypair.pubkey();
+    if !signers.contains(&wallet) {
+        let msg = format!("wallet {wallet} is not a required signer of the swap transaction");
+        return Err(SwapFailure::new(SwapStage::Sign, msg));
+    }
+    if signers.len() > 1 {
+        let msg = format!("transaction needs {required} signatures but only the wallet can sign");
+        return Err(SwapFailure::new(SwapStage::Sign, msg));
+    }
+
+    if let Ok(false) = blockhash_valid(*message.recent_blockhash()).await {
+        match latest_blockhash().await {
+            Ok(hash) => message.set_recent_blockhash(hash),
+            Err(e) => {
+                let msg = format!("blockhash expired, refresh failed: {}", failure_detail(&e));
+                return Err(SwapFailure::new(SwapStage::Sign, msg));
+            }
+        }
+    }
+    // Surface signing errors as such rather than as a failed send.
+    if let Err(e) = VersionedTransaction::try_new(message.clone(), &[keypair]) {
+        return Err(SwapFailure::new(SwapStage::Sign, e.to_string()));
+    }
+
+    send(message)
+        .await
+        .map_err(|e| SwapFailure::new(SwapStage::Send, failure_detail(&e)))
+}
+
+/// The stricter of two optional price-impact limits.
+fn stricter_limit(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// A request Jupiter answered with a non-2xx status, read from its
+/// `{"error": "...", "errorCode": "..."}` body.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JupiterError {
+    /// No route between the two mints for this amount.
+    NoRoute,
+    /// One of the mints can't be traded on Jupiter.
+    NotTradable,
+    RateLimited,
+    /// Anything else, with Jupiter's message (or the raw body if it wasn't the usual shape).
+    Api { status: u16, message: String },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct JupiterErrorBody {
+    error: String,
+    #[serde(default)]
+    error_code: Option<String>,
+}
+
+impl JupiterError {
+    fn from_response(status: reqwest::StatusCode, body: &str) -> Self {
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return JupiterError::RateLimited;
+        }
+        let Ok(parsed) = serde_json::from_str::<JupiterErrorBody>(body) else {
+            let message = match body.trim() {
+                "" => status.canonical_reason().unwrap_or("no response body").to_string(),
+                text => text.chars().take(200).collect(),
+            };
+            return JupiterError::Api { status: status.as_u16(), message };
+        };
+        match parsed.error_code.as_deref() {
+            Some("COULD_NOT_FIND_ANY_ROUTE" | "NO_ROUTES_FOUND") => JupiterError::NoRoute,
+            Some("TOKEN_NOT_TRADABLE") => JupiterError::NotTradable,
+            _ => JupiterError::Api {
+                status: status.as_u16(),
+                message: parsed.error,
+            },
+        }
+    }
+}
+
+impl fmt::Display for JupiterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JupiterError::NoRoute => write!(f, "no route found for this pair and amount"),
+            JupiterError::NotTradable => write!(f, "token is not tradable on Jupiter"),
+            JupiterError::RateLimited => write!(f, "rate limited by Jupiter, try again shortly"),
+            JupiterError::Api { status, message } => write!(f, "Jupiter error {status}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for JupiterError {}
+
+/// Pass 2xx responses through; turn anything else into a [`JupiterError`].
+async fn check_jupiter_status(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(JupiterError::from_response(status, &body).into())
+}
+
+/// Response of Jupiter's price API: `{"data": {"<mint>": {"price": "123.4", ...}}}`, with `null`
+/// for mints it can't price.
+#[derive(Deserialize, Debug)]
+struct PriceResponse {
+    data: HashMap<String, Option<PriceEntry>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PriceEntry {
+    price: String,
+}
+
+/// How long a price from [`JupiterClient::get_price`] is reused before asking again, so callers
+/// can ask every frame.
+pub const DEFAULT_PRICE_TTL: Duration = Duration::from_secs(10);
+
 pub struct JupiterClient {
     client: reqwest::Client,
     base_url: String,
+    price_url: String,
+    max_impact_pct: Option<f64>,
+    price_ttl: Duration,
+    /// mint -> (fetched at, USD price)
+    prices: Mutex<HashMap<String, (Instant, f64)>>,
 }
 
 impl JupiterClient {
-    pub fn new() -> Self {
-        Self {
-            client: reqwest::Client::new(),
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            client: http_client(DEFAULT_REQUEST_TIMEOUT)?,
             base_url: "https://quote-api.jup.ag/v6".to_string(),
-        }
+            price_url: "https://api.jup.ag/price/v2".to_string(),
+            max_impact_pct: None,
+            price_ttl: DEFAULT_PRICE_TTL,
+            prices: Mutex::default(),
+        })
     }
 
-    pub async fn get_quote(
-        &self,
-        input_mint: &str,
-        output_mint: &str,
-        amount: u64,
-        slippage_bps: u64,
-    ) -> Result<QuoteResponse> {
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_price_url(mut self, price_url: &str) -> Self {
+        self.price_url = price_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_price_ttl(mut self, ttl: Duration) -> Self {
+        self.price_ttl = ttl;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Result<Self> {
+        self.client = http_client(timeout)?;
+        Ok(self)
+    }
+
+    /// Flag quotes above this price impact (in percent) as `blocked`.
+    pub fn with_max_impact_pct(mut self, max_impact_pct: Option<f64>) -> Self {
+        self.max_impact_pct = max_impact_pct;
+        self
+    }
+
+    /// Quote `order` with its slippage. The quote is blocked if its impact exceeds the order's
+    /// or the client's limit, whichever is stricter.
+    pub async fn get_quote(&self, order: &SwapOrder) -> Result<QuoteResponse> {
         let url = format!(
             "{}/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}",
-            self.base_url, input_mint, output_mint, amount, slippage_bps
+            self.base_url, order.input_mint, order.output_mint, order.amount, order.slippage_bps
         );
 
-        let response = self.client.get(&url).send().await?;
-        let quote = response.json::<QuoteResponse>().await?;
+        let response = check_jupiter_status(self.client.get(&url).send().await?).await?;
+        let mut quote = response.json::<QuoteResponse>().await?;
+        quote.apply_impact_guard(stricter_limit(self.max_impact_pct, order.max_impact_pct));
         Ok(quote)
     }
 
@@ -92,15 +457,411 @@ impl JupiterClient {
         &self,
         user_public_key: &str,
         quote: QuoteResponse,
+        priority_fee_lamports: u64,
     ) -> Result<String> {
         let url = format!("{}/swap", self.base_url);
         let request = SwapRequest {
             user_public_key: user_public_key.to_string(),
             quote_response: quote,
+            prioritization_fee_lamports: priority_fee_lamports,
         };
 
         let response = self.client.post(&url).json(&request).send().await?;
+        let response = check_jupiter_status(response).await?;
         let swap_response = response.json::<SwapResponse>().await?;
         Ok(swap_response.swap_transaction)
     }
+
+    /// USD price of one whole token of `mint` (e.g. [`crate::app::WSOL_MINT`] for SOL), served
+    /// from cache when fetched within the price TTL.
+    pub async fn get_price(&self, mint: &str) -> Result<f64> {
+        if let Some(&(at, price)) = self.prices.lock().unwrap().get(mint) {
+            if at.elapsed() < self.price_ttl {
+                return Ok(price);
+            }
+        }
+
+        let url = format!("{}?ids={mint}", self.price_url);
+        let response = check_jupiter_status(self.client.get(&url).send().await?).await?;
+        let body = response.json::<PriceResponse>().await?;
+        let price = body
+            .data
+            .get(mint)
+            .and_then(Option::as_ref)
+            .and_then(|entry| entry.price.trim().parse::<f64>().ok())
+            .filter(|p| p.is_finite() && *p > 0.0)
+            .ok_or_else(|| anyhow!("no Jupiter price for {mint}"))?;
+
+        self.prices
+            .lock()
+            .unwrap()
+            .insert(mint.to_string(), (Instant::now(), price));
+        Ok(price)
+    }
+}
+
+/// Limits how many swaps may be in flight at once. Swaps all sign and send from the same
+/// wallet, so firing several concurrently can collide on blockhashes and waste fees.
+#[derive(Clone)]
+pub struct SwapGate {
+    permits: Arc<Semaphore>,
+}
+
+impl SwapGate {
+    pub fn new(max_in_flight: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(max_in_flight.max(1))),
+        }
+    }
+
+    /// Claim a slot for a new swap, or `None` if the limit is reached. The slot is freed when
+    /// the returned permit is dropped, so move it into the swap task.
+    pub fn try_start(&self) -> Option<OwnedSemaphorePermit> {
+        self.permits.clone().try_acquire_owned().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    fn quote_with_impact(price_impact_pct: &str) -> QuoteResponse {
+        serde_json::from_value(serde_json::json!({
+            "inputMint": "So11111111111111111111111111111111111111112",
+            "inAmount": "1000000000",
+            "outputMint": "mint",
+            "outAmount": "123456",
+            "otherAmountThreshold": "122838",
+            "swapMode": "ExactIn",
+            "slippageBps": 50,
+            "platformFee": null,
+            "priceImpactPct": price_impact_pct,
+            "routePlan": [],
+            "contextSlot": 1,
+            "timeTaken": 0.01
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_high_impact_quote_is_blocked() {
+        let mut quote = quote_with_impact("12");
+        quote.apply_impact_guard(Some(5.0));
+        assert!(quote.blocked);
+    }
+
+    #[test]
+    fn test_low_impact_quote_is_not_blocked() {
+        let mut quote = quote_with_impact("1");
+        quote.apply_impact_guard(Some(5.0));
+        assert!(!quote.blocked);
+    }
+
+    #[test]
+    fn test_no_threshold_never_blocks() {
+        let mut quote = quote_with_impact("99");
+        quote.apply_impact_guard(None);
+        assert!(!quote.blocked);
+    }
+
+    #[test]
+    fn test_unparseable_impact_is_blocked() {
+        let mut quote = quote_with_impact("n/a");
+        assert_eq!(quote.price_impact(), None);
+        quote.apply_impact_guard(Some(5.0));
+        assert!(quote.blocked);
+    }
+
+    #[test]
+    fn test_blocked_flag_not_sent_to_jupiter() {
+        let mut quote = quote_with_impact("12");
+        quote.apply_impact_guard(Some(5.0));
+        let json = serde_json::to_value(&quote).unwrap();
+        assert!(json.get("blocked").is_none());
+    }
+
+    #[test]
+    fn test_stricter_impact_limit_wins() {
+        assert_eq!(stricter_limit(Some(5.0), Some(2.0)), Some(2.0));
+        assert_eq!(stricter_limit(None, Some(2.0)), Some(2.0));
+        assert_eq!(stricter_limit(Some(5.0), None), Some(5.0));
+        assert_eq!(stricter_limit(None, None), None);
+    }
+
+    #[test]
+    fn test_route_labels_follow_route_plan() {
+        let mut quote = quote_with_impact("1");
+        assert_eq!(quote.route_labels(), "");
+
+        let leg = |label: &str| RoutePlan {
+            swap_info: SwapInfo {
+                amm_key: "amm".to_string(),
+                label: label.to_string(),
+                input_mint: "in".to_string(),
+                output_mint: "out".to_string(),
+                in_amount: "1".to_string(),
+                out_amount: "1".to_string(),
+                fee_amount: "0".to_string(),
+                fee_mint: "in".to_string(),
+            },
+            percent: 100,
+        };
+        quote.route_plan = vec![leg("Raydium"), leg("Meteora DLMM")];
+        assert_eq!(quote.route_labels(), "Raydium -> Meteora DLMM");
+    }
+
+    #[test]
+    fn test_failed_status_names_its_stage() {
+        let status = SwapStatus::Failed {
+            stage: SwapStage::Send,
+            msg: "blockhash not found".to_string(),
+        };
+        assert_eq!(status.to_string(), "Send failed: blockhash not found");
+        let status = SwapStatus::Failed {
+            stage: SwapStage::Quote,
+            msg: "price impact 12% exceeds limit".to_string(),
+        };
+        assert_eq!(status.to_string(), "Quote failed: price impact 12% exceeds limit");
+    }
+
+    #[test]
+    fn test_confirmation_statuses_show_outcome() {
+        let sig = "5sig".to_string();
+        let status = SwapStatus::Confirmed { sig: sig.clone(), slot: 280_000_123 };
+        assert_eq!(status.to_string(), "Swap confirmed in slot 280000123: 5sig");
+        let status = SwapStatus::Expired { sig };
+        assert_eq!(status.to_string(), "Swap expired without landing, safe to retry: 5sig");
+        let status = SwapStatus::Failed {
+            stage: SwapStage::Execute,
+            msg: "custom program error: 0x1771 (slot 7)".to_string(),
+        };
+        assert_eq!(status.to_string(), "Transaction failed: custom program error: 0x1771 (slot 7)");
+    }
+
+    #[test]
+    fn test_raw_to_ui_amount() {
+        assert_eq!(raw_to_ui_amount("1500000000", 9), Some(1.5));
+        assert_eq!(raw_to_ui_amount("2500000", 6), Some(2.5));
+        assert_eq!(raw_to_ui_amount("1.5", 9), None);
+    }
+
+    #[test]
+    fn test_swap_request_carries_priority_fee() {
+        let request = SwapRequest {
+            user_public_key: "wallet".to_string(),
+            quote_response: quote_with_impact("1"),
+            prioritization_fee_lamports: 250_000,
+        };
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["prioritizationFeeLamports"], 250_000);
+    }
+
+    /// Answers every request with `status` and the JSON `body`, counting the requests.
+    async fn json_server(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf).await;
+                let response = format!(
+                    "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\
+                     connection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (format!("http://{addr}"), hits)
+    }
+
+    #[tokio::test]
+    async fn test_get_price_is_cached() {
+        use std::sync::atomic::Ordering;
+
+        let (url, hits) = json_server("200 OK", concat!(
+            r#"{"data":{"So11111111111111111111111111111111111111112":"#,
+            r#"{"type":"derivedPrice","price":"142.5"},"unknown":null},"timeTaken":0.001}"#,
+        ))
+        .await;
+        let sol = crate::app::WSOL_MINT;
+
+        let client = JupiterClient::new().unwrap().with_price_url(&url);
+        assert_eq!(client.get_price(sol).await.unwrap(), 142.5);
+        assert_eq!(client.get_price(sol).await.unwrap(), 142.5);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        // Unpriced mints are an error, not 0.
+        assert!(client.get_price("unknown").await.is_err());
+
+        let client = JupiterClient::new()
+            .unwrap()
+            .with_price_url(&url)
+            .with_price_ttl(Duration::ZERO);
+        client.get_price(sol).await.unwrap();
+        client.get_price(sol).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_jupiter_error_bodies_are_mapped() {
+        use reqwest::StatusCode;
+
+        let no_route =
+            r#"{"error":"Could not find any route","errorCode":"COULD_NOT_FIND_ANY_ROUTE"}"#;
+        let err = JupiterError::from_response(StatusCode::BAD_REQUEST, no_route);
+        assert_eq!(err, JupiterError::NoRoute);
+        assert_eq!(err.to_string(), "no route found for this pair and amount");
+
+        let other = r#"{"error":"Amount is too small","errorCode":"SOMETHING_NEW"}"#;
+        let err = JupiterError::from_response(StatusCode::BAD_REQUEST, other);
+        assert_eq!(err.to_string(), "Jupiter error 400: Amount is too small");
+
+        let err = JupiterError::from_response(StatusCode::BAD_GATEWAY, "");
+        assert_eq!(err.to_string(), "Jupiter error 502: Bad Gateway");
+        let err = JupiterError::from_response(StatusCode::TOO_MANY_REQUESTS, "slow down");
+        assert_eq!(err, JupiterError::RateLimited);
+    }
+
+    #[tokio::test]
+    async fn test_get_quote_surfaces_jupiter_error() {
+        let (url, _) = json_server(
+            "400 Bad Request",
+            r#"{"error":"The token is not tradable","errorCode":"TOKEN_NOT_TRADABLE"}"#,
+        )
+        .await;
+        let client = JupiterClient::new().unwrap().with_base_url(&url);
+        let order = SwapOrder {
+            input_mint: crate::app::WSOL_MINT.to_string(),
+            output_mint: "mint".to_string(),
+            amount: 1_000,
+            slippage_bps: 50,
+            priority_fee_lamports: 0,
+            max_impact_pct: None,
+        };
+
+        let err = client.get_quote(&order).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<JupiterError>(), Some(&JupiterError::NotTradable));
+        assert_eq!(err.to_string(), "token is not tradable on Jupiter");
+    }
+
+    #[tokio::test]
+    async fn test_swap_gate_limits_in_flight_swaps() {
+        let gate = SwapGate::new(1);
+
+        let permit = gate.try_start().expect("first swap should start");
+        assert!(gate.try_start().is_none());
+
+        // A task that fails still releases its slot when the permit drops.
+        let task = tokio::spawn(async move {
+            let _permit = permit;
+            Err::<(), _>(anyhow::anyhow!("send failed"))
+        });
+        assert!(task.await.unwrap().is_err());
+        assert!(gate.try_start().is_some());
+
+        let gate = SwapGate::new(2);
+        let _a = gate.try_start().unwrap();
+        let _b = gate.try_start().unwrap();
+        assert!(gate.try_start().is_none());
+    }
+
+    /// Jupiter-style unsigned v0 swap transaction paid by `payer`, base64-encoded.
+    fn unsigned_swap_base64(payer: &Pubkey, blockhash: Hash) -> String {
+        use solana_sdk::{message::v0, system_instruction};
+
+        let ix = system_instruction::transfer(payer, &Pubkey::new_unique(), 1);
+        let message = v0::Message::try_compile(payer, &[ix], &[], blockhash).unwrap();
+        let tx = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(message),
+        };
+        general_purpose::STANDARD.encode(bincode::serialize(&tx).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_send_refreshes_a_stale_blockhash() {
+        use std::sync::Mutex;
+
+        let wallet = Keypair::new();
+        let (stale, fresh) = (Hash::new_unique(), Hash::new_unique());
+        let sent: Mutex<Vec<VersionedMessage>> = Mutex::default();
+        let send = |message: VersionedMessage| {
+            let tx = VersionedTransaction::try_new(message.clone(), &[&wallet]);
+            sent.lock().unwrap().push(message);
+            async move { Ok((tx?.signatures[0], fresh)) }
+        };
+
+        let swap = unsigned_swap_base64(&wallet.pubkey(), stale);
+        let (_, blockhash) = sign_and_send(
+            &swap,
+            &wallet,
+            |hash| async move { Ok(hash != stale) },
+            || async move { Ok(fresh) },
+            send,
+        )
+        .await
+        .unwrap();
+        assert_eq!(blockhash, fresh);
+        assert_eq!(*sent.lock().unwrap()[0].recent_blockhash(), fresh);
+
+        // A valid blockhash, or one whose check failed, is sent as Jupiter built it.
+        let swap = unsigned_swap_base64(&wallet.pubkey(), fresh);
+        sign_and_send(
+            &swap,
+            &wallet,
+            |_| async move { Err(anyhow!("rpc down")) },
+            || async move { panic!("blockhash was not stale") },
+            send,
+        )
+        .await
+        .unwrap();
+        assert_eq!(*sent.lock().unwrap()[1].recent_blockhash(), fresh);
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_send_rejects_bad_transactions() {
+        let wallet = Keypair::new();
+        let attempt = |swap: String| {
+            let wallet = &wallet;
+            async move {
+                sign_and_send(
+                    &swap,
+                    wallet,
+                    |_| async move { Ok(false) },
+                    || async move { Err(anyhow!("rpc down")) },
+                    |_| async move { panic!("nothing should be sent") },
+                )
+                .await
+                .unwrap_err()
+            }
+        };
+
+        let other = Pubkey::new_unique();
+        let failure = attempt(unsigned_swap_base64(&other, Hash::new_unique())).await;
+        assert_eq!(failure.stage, SwapStage::Sign);
+        assert_eq!(
+            failure.msg,
+            format!("wallet {} is not a required signer of the swap transaction", wallet.pubkey())
+        );
+
+        let failure = attempt(unsigned_swap_base64(&wallet.pubkey(), Hash::new_unique())).await;
+        assert_eq!(
+            SwapStatus::from(failure).to_string(),
+            "Signing failed: blockhash expired, refresh failed: rpc down"
+        );
+
+        let failure = attempt("not base64!".to_string()).await;
+        assert_eq!(failure, SwapFailure::new(SwapStage::Build, "invalid base64 transaction"));
+    }
 }
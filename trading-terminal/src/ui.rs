@@ -1,15 +1,35 @@
-use crate::app::{App, CurrentScreen, Theme};
+use crate::app::{
+    format_hms, is_large_trade, App, Candle, ChartCursor, CurrentScreen, OrderField, SizerField,
+    SwapField,
+    Theme, Trade, MAX_LOG_LINES, MAX_SWAP_STATUSES, ORDERS_TAB, POSITIONS_TAB, WSOL_MINT,
+};
+use crate::orders::OrderSide;
+use crate::help::KEY_HELP;
+use crate::logo::LOGO_COLS;
+use crate::report::{pnl_cell, sol_cell};
+use crate::swap::{raw_to_ui_amount, SwapStatus};
+use std::time::{SystemTime, UNIX_EPOCH};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    text::{Line as TextLine, Span},
+    text::{Line as TextLine, Span, Text},
     widgets::{
-        Block, BorderType, Borders, Clear, Gauge, List, ListItem, Paragraph, Row, Table, Tabs,
+        Block, BorderType, Borders, Cell, Clear, Gauge, List, ListItem, ListState, Paragraph, Row,
+        Table, Tabs, Wrap,
         canvas::{Canvas, Line, Rectangle},
     },
 };
 
+/// Smallest terminal the layout is drawn in; below it only a resize hint is shown.
+pub const MIN_TERMINAL_WIDTH: u16 = 60;
+pub const MIN_TERMINAL_HEIGHT: u16 = 15;
+
+/// Whether `area` is below [`MIN_TERMINAL_WIDTH`] x [`MIN_TERMINAL_HEIGHT`].
+pub fn is_too_small(area: Rect) -> bool {
+    area.width < MIN_TERMINAL_WIDTH || area.height < MIN_TERMINAL_HEIGHT
+}
+
 pub fn ui(f: &mut Frame, app: &App) {
     let (bg_color, fg_color, border_color) = match app.theme {
         Theme::Light => (Color::White, Color::Black, Color::Black),
@@ -20,6 +40,19 @@ pub fn ui(f: &mut Frame, app: &App) {
     let size = f.area();
     f.render_widget(Block::default().style(base_style), size);
 
+    if is_too_small(size) {
+        let message = format!(
+            "Terminal too small (min {MIN_TERMINAL_WIDTH}x{MIN_TERMINAL_HEIGHT})"
+        );
+        f.render_widget(
+            Paragraph::new(message)
+                .style(Style::default().fg(Color::Yellow))
+                .wrap(Wrap { trim: true }),
+            size,
+        );
+        return;
+    }
+
     // Vertical Split: Navbar (Top) vs Main Content (Bottom)
     let vertical_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -38,11 +71,32 @@ pub fn ui(f: &mut Frame, app: &App) {
         CurrentScreen::TokenDetails => {
             render_token_details(f, app, content_area, border_color, fg_color)
         }
+        CurrentScreen::Compare => render_compare(f, app, content_area, border_color, fg_color),
+        CurrentScreen::Watchlist => {
+            render_watchlist(f, app, content_area, border_color, fg_color)
+        }
+        CurrentScreen::Logs => render_logs(f, app, content_area, border_color, fg_color),
     }
 
     if app.show_search_modal {
         render_search_modal(f, app, size, border_color, fg_color);
     }
+
+    if app.show_sizer_modal {
+        render_sizer_modal(f, app, size, border_color, fg_color);
+    }
+
+    if app.show_order_modal {
+        render_order_modal(f, app, size, border_color, fg_color);
+    }
+
+    if app.pending_swap.is_some() {
+        render_confirm_modal(f, app, size, border_color, fg_color);
+    }
+
+    if app.show_help {
+        render_help_modal(f, app, size, border_color, fg_color);
+    }
 }
 
 fn render_home(f: &mut Frame, app: &App, area: Rect, border: Color, text: Color) {
@@ -136,13 +190,15 @@ fn render_token_column(
             height: card_height,
         };
 
-        render_token_card(f, token, is_selected, card_area, border, text);
+        let logo = app.logo_art.get(&token.mint);
+        render_token_card(f, token, logo, is_selected, card_area, border, text);
     }
 }
 
 fn render_token_card(
     f: &mut Frame,
     token: &crate::app::Token,
+    logo: Option<&Vec<String>>,
     is_selected: bool,
     area: Rect,
     border: Color,
@@ -172,7 +228,7 @@ fn render_token_card(
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Length(8), // Image box width
+            Constraint::Length(LOGO_COLS as u16), // Image box width
             Constraint::Min(0),    // Content
         ])
         .split(inner);
@@ -185,7 +241,17 @@ fn render_token_card(
         _ => Color::Magenta,
     };
 
-    let image_placeholder = Paragraph::new(token.image_asc.clone())
+    // Fetched logo art when available, the emoji placeholder otherwise
+    let image_text = match logo {
+        Some(lines) => Text::from(
+            lines
+                .iter()
+                .map(|l| TextLine::from(l.as_str()))
+                .collect::<Vec<_>>(),
+        ),
+        None => Text::from(token.image_asc.clone()),
+    };
+    let image_placeholder = Paragraph::new(image_text)
         .block(Block::default().borders(Borders::NONE))
         .style(
             Style::default()
@@ -275,12 +341,40 @@ fn render_token_details(
         ])
         .split(main_layout[1]);
 
-    render_chart_area(f, app, center_layout[0], border_color, fg_color);
+    render_chart_area(
+        f,
+        app,
+        center_layout[0],
+        &app.candles,
+        ChartScale::Price,
+        &format!(
+            "Chart - {}/SOL · {}",
+            app.token_info.symbol,
+            app.chart_timeframe_label()
+        ),
+        border_color,
+        fg_color,
+    );
     render_bottom_panel(f, app, center_layout[1], border_color, fg_color);
 
     render_right_sidebar(f, app, main_layout[2], border_color, fg_color);
 }
 
+/// Indexer lag up to this many seconds is shown as live (green); up to the second threshold as
+/// lagging (yellow); beyond it as stale (red).
+const INDEXER_LAG_LIVE_SECS: u64 = 10;
+const INDEXER_LAG_STALE_SECS: u64 = 60;
+
+fn indexer_lag_color(lag_secs: u64) -> Color {
+    if lag_secs <= INDEXER_LAG_LIVE_SECS {
+        Color::Green
+    } else if lag_secs <= INDEXER_LAG_STALE_SECS {
+        Color::Yellow
+    } else {
+        Color::Red
+    }
+}
+
 fn render_navbar(f: &mut Frame, app: &App, area: Rect, border: Color, text: Color) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -291,18 +385,23 @@ fn render_navbar(f: &mut Frame, app: &App, area: Rect, border: Color, text: Colo
         ])
         .split(area);
 
-    // 1. Title
+    // 1. Title, with how far behind the chain live data is
+    let mut title_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(border));
+    if let Some(lag) = app.indexer_lag_secs {
+        title_block = title_block.title(Span::styled(
+            format!(" indexer {lag}s behind "),
+            Style::default().fg(indexer_lag_color(lag)),
+        ));
+    }
     let title = Paragraph::new(Span::styled(
         "Trading Terminal",
         Style::default()
             .fg(text)
             .add_modifier(Modifier::BOLD | Modifier::ITALIC),
     ))
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(border)),
-    );
+    .block(title_block);
     f.render_widget(title, chunks[0]);
 
     // 2. Search Bar
@@ -321,8 +420,16 @@ fn render_navbar(f: &mut Frame, app: &App, area: Rect, border: Color, text: Colo
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
-                .title("Search")
-                .border_style(Style::default().fg(border)),
+                .title(if app.home_search_focused {
+                    "Filter (Enter done, Esc clear)"
+                } else {
+                    "Search [/]"
+                })
+                .border_style(Style::default().fg(if app.home_search_focused {
+                    Color::Yellow
+                } else {
+                    border
+                })),
         );
     f.render_widget(search, chunks[1]);
 
@@ -371,7 +478,11 @@ fn render_left_sidebar(f: &mut Frame, app: &App, area: Rect, border: Color, text
         TextLine::from(vec![
             Span::raw("MC"),
             Span::styled(
-                format!(" ${:.2}K", app.token_info.market_cap),
+                if app.token_info.market_cap > 0.0 {
+                    format!(" ${:.2}K", app.token_info.market_cap / 1000.0)
+                } else {
+                    " -".to_string()
+                },
                 Style::default().fg(text).add_modifier(Modifier::BOLD),
             ),
         ]),
@@ -439,7 +550,7 @@ fn render_left_sidebar(f: &mut Frame, app: &App, area: Rect, border: Color, text
         .gauge_style(Style::default().fg(Color::Yellow))
         .percent(app.token_info.bonding_curve as u16)
         .label(Span::styled(
-            format!("{}% (Graduates at $61k)", app.token_info.bonding_curve),
+            format!("{:.1}% (Graduates at $61k)", app.token_info.bonding_curve),
             Style::default().fg(text).add_modifier(Modifier::BOLD),
         ));
     f.render_widget(bonding_curve_gauge, chunks[1]);
@@ -459,8 +570,8 @@ fn render_left_sidebar(f: &mut Frame, app: &App, area: Rect, border: Color, text
     };
     let change_6h = format!("{:.2}%", app.token_info.change_6h);
     let change_24h = format!("{:.2}%", app.token_info.change_24h);
-    let vol_24h = format!("${:.2}K", app.token_info.vol_24h / 1000.0);
-    let net_vol_24h = format!("${}", app.token_info.net_vol_24h);
+    let vol_24h = format!("{:.2} SOL", app.token_info.vol_24h);
+    let net_vol_24h = format!("{:+.2} SOL", app.token_info.net_vol_24h);
     let sell_pressure = format!("{}%", app.token_info.sell_pressure);
     let traders_24h = format!("{}", app.token_info.traders_24h);
     let net_buyers = if let Some(v) = app.token_info.net_buyers {
@@ -533,19 +644,172 @@ fn render_left_sidebar(f: &mut Frame, app: &App, area: Rect, border: Color, text
     );
 }
 
-fn render_chart_area(f: &mut Frame, app: &App, area: Rect, border: Color, _text: Color) {
+/// Visible x (candle index) and y (price) ranges of the chart canvas. The y range fits the
+/// visible candles, shifted by the user's pan offset.
+pub fn chart_bounds(app: &App) -> ([f64; 2], [f64; 2]) {
+    let x_bounds = [app.chart_x_offset, app.chart_x_offset + app.chart_window()];
+    let [low, high] = fit_y_bounds(&app.candles, x_bounds);
+    (x_bounds, [low + app.chart_y_offset, high + app.chart_y_offset])
+}
+
+/// Inverse of the canvas mapping: the candle position (fractional) and price under a
+/// terminal cell. `chart_area` is the full chart widget rect (including its border).
+/// Returns `None` when the cell is outside the plot.
+pub fn chart_point_at(app: &App, chart_area: Rect, column: u16, row: u16) -> Option<(f64, f64)> {
+    let inner = Block::default().borders(Borders::ALL).inner(chart_area);
+    if inner.width < 2 || inner.height < 2 {
+        return None;
+    }
+    if column < inner.left()
+        || column >= inner.right()
+        || row < inner.top()
+        || row >= inner.bottom()
+    {
+        return None;
+    }
+
+    let ([x_min, x_max], [y_min, y_max]) = chart_bounds(app);
+    let fx = (column - inner.left()) as f64 / (inner.width - 1) as f64;
+    let fy = (row - inner.top()) as f64 / (inner.height - 1) as f64;
+
+    let x = x_min + fx * (x_max - x_min);
+    let price = y_max - fy * (y_max - y_min);
+    Some((x, price))
+}
+
+/// The candle and price under a terminal cell, or `None` when the cell is outside the plot
+/// or not over a candle.
+pub fn chart_cursor_at(app: &App, chart_area: Rect, column: u16, row: u16) -> Option<ChartCursor> {
+    let (x, price) = chart_point_at(app, chart_area, column, row)?;
+    let candle_index = x.round();
+    if candle_index < 0.0 || candle_index >= app.candles.len() as f64 {
+        return None;
+    }
+
+    Some(ChartCursor {
+        candle_index: candle_index as usize,
+        price,
+    })
+}
+
+/// How a chart maps candle prices to the y axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChartScale {
+    /// Raw prices, with the user's pan/zoom offsets.
+    Price,
+    /// Percent change from the first visible candle's open, so series with different price
+    /// levels can share a scale.
+    PercentChange,
+}
+
+/// Rescale candles to percent change from the open of `candles[window_start]`.
+/// Returns the input unchanged when there is no usable base price.
+pub fn normalize_candles(candles: &[Candle], window_start: usize) -> Vec<Candle> {
+    let Some(base) = candles
+        .get(window_start)
+        .or(candles.first())
+        .map(|c| c.open)
+        .filter(|b| *b > 0.0)
+    else {
+        return candles.to_vec();
+    };
+    let pct = |v: f64| (v / base - 1.0) * 100.0;
+    candles
+        .iter()
+        .map(|c| Candle {
+            timestamp: c.timestamp,
+            open: pct(c.open),
+            high: pct(c.high),
+            low: pct(c.low),
+            close: pct(c.close),
+        })
+        .collect()
+}
+
+/// Half-height of the y range, as a fraction of the price, when every visible candle sits at
+/// one price (e.g. a brand-new token with a single trade).
+const FLAT_RANGE_PAD: f64 = 0.05;
+
+/// y range covering the candles in `x_bounds`, padded by 5% on each side. A flat series gets
+/// ±[`FLAT_RANGE_PAD`] around its price (±1 around zero) so it still draws as a line.
+fn fit_y_bounds(candles: &[Candle], x_bounds: [f64; 2]) -> [f64; 2] {
+    let start = x_bounds[0].max(0.0) as usize;
+    let end = (x_bounds[1].max(0.0) as usize + 1).min(candles.len());
+    let visible = candles.get(start..end).unwrap_or(&[]);
+    let low = visible.iter().map(|c| c.low).fold(f64::INFINITY, f64::min);
+    let high = visible.iter().map(|c| c.high).fold(f64::NEG_INFINITY, f64::max);
+    if !low.is_finite() || !high.is_finite() {
+        return [-1.0, 1.0];
+    }
+    let mid = (low + high) / 2.0;
+    if high - low <= mid.abs() * 1e-9 {
+        let pad = if mid == 0.0 { 1.0 } else { mid.abs() * FLAT_RANGE_PAD };
+        return [mid - pad, mid + pad];
+    }
+    let pad = (high - low) * 0.05;
+    [low - pad, high + pad]
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render_chart_area(
+    f: &mut Frame,
+    app: &App,
+    area: Rect,
+    candles: &[Candle],
+    scale: ChartScale,
+    title: &str,
+    border: Color,
+    text: Color,
+) {
+    let normalized;
+    let (candles, x_bounds, y_bounds) = match scale {
+        ChartScale::Price => {
+            let (x_bounds, y_bounds) = chart_bounds(app);
+            (candles, x_bounds, y_bounds)
+        }
+        ChartScale::PercentChange => {
+            let (x_bounds, _) = chart_bounds(app);
+            normalized = normalize_candles(candles, x_bounds[0].max(0.0) as usize);
+            let y_bounds = fit_y_bounds(&normalized, x_bounds);
+            (normalized.as_slice(), x_bounds, y_bounds)
+        }
+    };
+    // The hover cursor is computed against the main price chart only.
+    let cursor = app
+        .chart_cursor
+        .filter(|c| scale == ChartScale::Price && c.candle_index < candles.len());
+
     let canvas = Canvas::default()
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
                 .border_style(Style::default().fg(border))
-                .title("Chart - RAN/SOL"),
+                .title(title.to_string()),
         )
-        .x_bounds([app.chart_x_offset, app.chart_x_offset + 50.0])
-        .y_bounds([0.0035 + app.chart_y_offset, 0.0045 + app.chart_y_offset])
+        .x_bounds(x_bounds)
+        .y_bounds(y_bounds)
         .paint(|ctx| {
-            for (i, candle) in app.candles.iter().enumerate() {
+            if let Some(c) = cursor {
+                // Crosshair
+                ctx.draw(&Line {
+                    x1: c.candle_index as f64,
+                    y1: y_bounds[0],
+                    x2: c.candle_index as f64,
+                    y2: y_bounds[1],
+                    color: Color::DarkGray,
+                });
+                ctx.draw(&Line {
+                    x1: x_bounds[0],
+                    y1: c.price,
+                    x2: x_bounds[1],
+                    y2: c.price,
+                    color: Color::DarkGray,
+                });
+                ctx.layer();
+            }
+
+            for (i, candle) in candles.iter().enumerate() {
                 let color = if candle.close >= candle.open {
                     Color::Green
                 } else {
@@ -591,6 +855,168 @@ fn render_chart_area(f: &mut Frame, app: &App, area: Rect, border: Color, _text:
             }
         });
     f.render_widget(canvas, area);
+
+    // Price/time readout for the hovered candle
+    if let Some(c) = cursor {
+        let candle = &candles[c.candle_index];
+        let label = format!(
+            " {} O {:.7} H {:.7} L {:.7} C {:.7} | {:.7} ",
+            format_hms(candle.timestamp),
+            candle.open,
+            candle.high,
+            candle.low,
+            candle.close,
+            c.price
+        );
+        let inner = Block::default().borders(Borders::ALL).inner(area);
+        let label_area = Rect {
+            x: inner.x,
+            y: inner.y,
+            width: (label.chars().count() as u16).min(inner.width),
+            height: inner.height.min(1),
+        };
+        f.render_widget(Clear, label_area);
+        f.render_widget(
+            Paragraph::new(label).style(Style::default().fg(text).bg(Color::DarkGray)),
+            label_area,
+        );
+    }
+}
+
+/// Open token (left) and the picked comparison token (right), both as percent change.
+fn render_compare(f: &mut Frame, app: &App, area: Rect, border: Color, text: Color) {
+    let Some(compare) = app.compare.as_ref() else {
+        return;
+    };
+    let halves = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+
+    let primary = compare.primary_candles.as_deref().unwrap_or(&app.candles);
+    render_chart_area(
+        f,
+        app,
+        halves[0],
+        primary,
+        ChartScale::PercentChange,
+        &format!("{} - % change", app.token_info.symbol),
+        border,
+        text,
+    );
+    render_chart_area(
+        f,
+        app,
+        halves[1],
+        &compare.candles,
+        ChartScale::PercentChange,
+        &format!("{} - % change", compare.symbol),
+        border,
+        text,
+    );
+}
+
+/// Watched tokens with the indexer's latest price and change; Enter opens the highlighted one.
+fn render_watchlist(f: &mut Frame, app: &App, area: Rect, border: Color, text: Color) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(border))
+        .title("Watchlist (Enter open, d remove, Esc back)");
+
+    if app.watchlist.is_empty() {
+        let placeholder = Paragraph::new("No watched tokens yet. Press w on a token to watch it.")
+            .style(Style::default().fg(Color::Gray))
+            .block(block);
+        f.render_widget(placeholder, area);
+        return;
+    }
+
+    let rows: Vec<Row> = app
+        .watchlist
+        .iter()
+        .enumerate()
+        .map(|(i, mint)| {
+            let symbol = app
+                .known_token(mint)
+                .map_or_else(|| "?".to_string(), |t| t.symbol.clone());
+            let quote = app.watch_quotes.get(mint);
+            let price = quote.map_or_else(|| "-".to_string(), |q| format!("{:.9}", q.price));
+            let (change, change_color) = match quote.and_then(|q| q.change_pct) {
+                Some(pct) if pct >= 0.0 => (format!("+{pct:.2}%"), Color::Green),
+                Some(pct) => (format!("{pct:.2}%"), Color::Red),
+                None => ("-".to_string(), text),
+            };
+            let style = if i == app.watchlist_selected {
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(text)
+            };
+            Row::new(vec![
+                Cell::from(symbol),
+                Cell::from(mint.clone()),
+                Cell::from(price),
+                Cell::from(change).style(Style::default().fg(change_color)),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(15),
+            Constraint::Percentage(50),
+            Constraint::Percentage(20),
+            Constraint::Percentage(15),
+        ],
+    )
+    .header(
+        Row::new(vec!["Token", "Mint", "Price (SOL)", "24h"])
+            .style(Style::default().fg(Color::Yellow))
+            .bottom_margin(1),
+    )
+    .block(block);
+
+    f.render_widget(table, area);
+}
+
+fn render_logs(f: &mut Frame, app: &App, area: Rect, border: Color, text: Color) {
+    // Entries only carry an Instant, so place them on the wall clock relative to now
+    let now = SystemTime::now();
+    let items: Vec<ListItem> = app
+        .logs
+        .iter()
+        .map(|(at, message)| {
+            let logged_at = now
+                .checked_sub(at.elapsed())
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs() as i64);
+            ListItem::new(TextLine::from(vec![
+                Span::styled(
+                    format!("{} ", format_hms(logged_at)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(message.clone(), Style::default().fg(text)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border))
+                .title(format!(
+                    "Logs ({}/{MAX_LOG_LINES}, Up/Down scroll, Esc back)",
+                    app.logs.len()
+                )),
+        )
+        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD));
+    let mut state = ListState::default().with_selected(app.selected_log());
+    f.render_stateful_widget(list, area, &mut state);
 }
 
 fn render_bottom_panel(f: &mut Frame, app: &App, area: Rect, border: Color, text: Color) {
@@ -639,6 +1065,8 @@ fn render_bottom_panel(f: &mut Frame, app: &App, area: Rect, border: Color, text
     // 2. Content
     match app.bottom_tab_index {
         0 => render_transactions(f, app, chunks[1], border, text),
+        POSITIONS_TAB => render_positions(f, app, chunks[1], text),
+        ORDERS_TAB => render_orders(f, app, chunks[1], text),
         3 => render_holders_list(f, app, chunks[1], border, text),
         _ => {
             let p = Paragraph::new("Coming soon...").block(Block::default().borders(Borders::NONE));
@@ -647,6 +1075,16 @@ fn render_bottom_panel(f: &mut Frame, app: &App, area: Rect, border: Color, text
     }
 }
 
+/// Maker column of the Transactions tab; new wallets stand out in cyan.
+fn maker_cell(t: &Trade) -> Cell<'static> {
+    let cell = Cell::from(t.maker_label());
+    if t.is_new_wallet {
+        cell.style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+    } else {
+        cell
+    }
+}
+
 fn render_transactions(f: &mut Frame, app: &App, area: Rect, _border: Color, _text: Color) {
     let rows: Vec<Row> = app
         .recent_trades
@@ -657,14 +1095,24 @@ fn render_transactions(f: &mut Frame, app: &App, area: Rect, _border: Color, _te
             } else {
                 Color::Red
             };
+            let large = is_large_trade(t.volume, app.large_trade_sol);
+            let type_label = if large {
+                format!("🐋 {}", t.type_)
+            } else {
+                t.type_.clone()
+            };
+            let mut style = Style::default().fg(color);
+            if large {
+                style = style.add_modifier(Modifier::BOLD | Modifier::SLOW_BLINK);
+            }
             Row::new(vec![
-                t.time.clone(),
-                t.type_.clone(),
-                format!("{:.7}", t.price),
-                format!("{:.2}", t.volume),
-                t.maker.clone(),
+                Cell::from(t.time.clone()),
+                Cell::from(type_label),
+                Cell::from(format!("{:.7}", t.price)),
+                Cell::from(format!("{:.2}", t.volume)),
+                maker_cell(t),
             ])
-            .style(Style::default().fg(color))
+            .style(style)
         })
         .collect();
 
@@ -688,6 +1136,113 @@ fn render_transactions(f: &mut Frame, app: &App, area: Rect, _border: Color, _te
     f.render_widget(table, area);
 }
 
+/// The loaded wallet's holdings of every mint, valued at each mint's last indexed price.
+fn render_positions(f: &mut Frame, app: &App, area: Rect, text: Color) {
+    let notice = if app.wallet_pubkey.is_none() {
+        Some("No wallet loaded. Start with --keypair-path to see your positions.")
+    } else if app.positions_loaded_at.is_none() {
+        Some("Loading positions...")
+    } else if app.positions.is_empty() {
+        Some("No open positions.")
+    } else {
+        None
+    };
+    if let Some(notice) = notice {
+        let p = Paragraph::new(notice).style(Style::default().fg(Color::DarkGray));
+        f.render_widget(p, area);
+        return;
+    }
+
+    let rows: Vec<Row> = app
+        .positions
+        .iter()
+        .map(|p| {
+            let pnl = p.unrealized_pnl_sol();
+            let color = match pnl {
+                Some(pnl) if pnl > 0.0 => Color::Green,
+                Some(pnl) if pnl < 0.0 => Color::Red,
+                _ => text,
+            };
+            Row::new(vec![
+                Cell::from(p.mint.clone()),
+                Cell::from(format!("{:.2}", p.holdings)),
+                Cell::from(sol_cell(p.value_sol)),
+                Cell::from(pnl_cell(pnl)).style(Style::default().fg(color)),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(40),
+            Constraint::Percentage(20),
+            Constraint::Percentage(20),
+            Constraint::Percentage(20),
+        ],
+    )
+    .header(
+        Row::new(vec!["Mint", "Amount", "Value (SOL)", "Unrealized P/L"])
+            .style(Style::default().fg(Color::Yellow))
+            .bottom_margin(1),
+    )
+    .block(Block::default().borders(Borders::NONE));
+
+    f.render_widget(table, area);
+}
+
+/// Open limit orders on every mint; the selected one is what `x` cancels.
+fn render_orders(f: &mut Frame, app: &App, area: Rect, text: Color) {
+    if app.orders.is_empty() {
+        let p = Paragraph::new("No limit orders. Press O to place one on this token.")
+            .style(Style::default().fg(Color::DarkGray));
+        f.render_widget(p, area);
+        return;
+    }
+
+    let rows: Vec<Row> = app
+        .orders
+        .iter()
+        .enumerate()
+        .map(|(i, order)| {
+            let (color, unit) = match order.side {
+                OrderSide::Buy => (Color::Green, "SOL"),
+                OrderSide::Sell => (Color::Red, order.symbol.as_str()),
+            };
+            let mut style = Style::default().fg(text);
+            if i == app.orders_selected {
+                style = style.bg(Color::DarkGray).add_modifier(Modifier::BOLD);
+            }
+            Row::new(vec![
+                Cell::from(order.side.to_string().to_uppercase())
+                    .style(Style::default().fg(color)),
+                Cell::from(order.symbol.clone()),
+                Cell::from(format!("{} {}", order.ui_amount(), unit)),
+                Cell::from(format!("{:.10}", order.trigger_price)),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(15),
+            Constraint::Percentage(25),
+            Constraint::Percentage(30),
+            Constraint::Percentage(30),
+        ],
+    )
+    .header(
+        Row::new(vec!["Side", "Token", "Amount", "Trigger (SOL)"])
+            .style(Style::default().fg(Color::Yellow))
+            .bottom_margin(1),
+    )
+    .block(Block::default().borders(Borders::NONE));
+
+    f.render_widget(table, area);
+}
+
 fn render_holders_list(f: &mut Frame, app: &App, area: Rect, _border: Color, text: Color) {
     let rows: Vec<Row> = app
         .holders
@@ -699,7 +1254,8 @@ fn render_holders_list(f: &mut Frame, app: &App, area: Rect, _border: Color, tex
                 format!("{}", i + 1),
                 h.address.clone(),
                 format!("{:.2}%", h.balance),
-                format!("${:.2}", h.value),
+                sol_cell(app.holder_value_sol(h)),
+                h.value.map_or_else(|| "-".to_string(), |usd| format!("${usd:.2}")),
             ])
             .style(Style::default().fg(color))
         })
@@ -709,13 +1265,14 @@ fn render_holders_list(f: &mut Frame, app: &App, area: Rect, _border: Color, tex
         rows,
         [
             Constraint::Percentage(10),
-            Constraint::Percentage(40),
-            Constraint::Percentage(25),
-            Constraint::Percentage(25),
+            Constraint::Percentage(30),
+            Constraint::Percentage(20),
+            Constraint::Percentage(20),
+            Constraint::Percentage(20),
         ],
     )
     .header(
-        Row::new(vec!["Rank", "Address", "% Held", "Value"])
+        Row::new(vec!["Rank", "Address", "% Held", "Value (SOL)", "Value ($)"])
             .style(Style::default().fg(Color::Yellow))
             .bottom_margin(1),
     )
@@ -727,27 +1284,61 @@ fn render_holders_list(f: &mut Frame, app: &App, area: Rect, _border: Color, tex
 fn render_right_sidebar(f: &mut Frame, app: &App, area: Rect, border: Color, _text: Color) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(12), Constraint::Min(10)])
+        .constraints([
+            Constraint::Length(12),
+            Constraint::Length(MAX_SWAP_STATUSES as u16 + 2),
+            Constraint::Min(10),
+        ])
         .split(area);
 
+    let profile = app.active_profile();
+    // The amount the number keys edit is marked with '>'
+    let amount_line = |field: SwapField, label: &str, amount: String| {
+        let marker = if app.swap_field == field { "> " } else { "  " };
+        TextLine::from(vec![
+            Span::styled(marker, Style::default().fg(Color::Yellow)),
+            Span::raw(label.to_string()),
+            Span::styled(amount, Style::default().add_modifier(Modifier::BOLD)),
+        ])
+    };
+    let sell_amount = if app.sell_amount.is_empty() {
+        "0"
+    } else {
+        app.sell_amount.as_str()
+    };
     let swap_text = vec![
         TextLine::from(vec![
-            Span::raw("Sell"),
-            Span::styled(" [SOL] -> USDC", Style::default().fg(Color::Blue)),
+            Span::raw(app.token_info.symbol.clone()),
+            Span::styled(" <-> SOL", Style::default().fg(Color::Blue)),
+            Span::raw(" [a] switch amount"),
         ]),
-        TextLine::from(""),
+        amount_line(SwapField::Buy, "Buy:  ", format!("{} SOL", app.swap_amount)),
+        amount_line(
+            SwapField::Sell,
+            "Sell: ",
+            format!("{} {}", sell_amount, app.token_info.symbol),
+        ),
         TextLine::from(vec![
-            Span::raw("Amount: "),
+            Span::raw("Profile: "),
             Span::styled(
-                format!("{} SOL", app.swap_amount),
-                Style::default().add_modifier(Modifier::BOLD),
+                profile.name.clone(),
+                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
             ),
+            Span::raw(" [f]"),
+        ]),
+        TextLine::from(format!(
+            "Slip {} bps [l] | Fee {} | Impact {}",
+            app.slippage_bps,
+            profile.priority_fee_lamports,
+            profile
+                .max_impact_pct
+                .map_or_else(|| "no limit".to_string(), |pct| format!("<{pct}%")),
+        )),
+        TextLine::from(vec![
+            Span::styled("[s] BUY", Style::default().bg(Color::Green).fg(Color::Black)),
+            Span::raw(" "),
+            Span::styled("[S] SELL", Style::default().bg(Color::Red).fg(Color::Black)),
         ]),
-        TextLine::from(""),
-        TextLine::from(vec![Span::styled(
-            "[ENTER TO SWAP]",
-            Style::default().bg(Color::Green).fg(Color::Black),
-        )]),
     ];
     let swap_panel = Paragraph::new(swap_text).block(
         Block::default()
@@ -757,14 +1348,32 @@ fn render_right_sidebar(f: &mut Frame, app: &App, area: Rect, border: Color, _te
             .title("Swap"),
     );
     f.render_widget(swap_panel, chunks[0]);
+    render_swap_status(f, app, chunks[1], border);
 
-    let profile_text = vec![
+    let mut profile_text = vec![
         TextLine::from("Safety Check:"),
         TextLine::from(vec![Span::styled(
             "Mint Auth: No",
             Style::default().fg(Color::Green),
         )]),
     ];
+    if let Some(counts) = &app.token_info.safety_counts {
+        let flagged = |n: u64| {
+            if n > 0 {
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Green)
+            }
+        };
+        profile_text.push(TextLine::from(vec![Span::styled(
+            format!("Delegate approvals: {}", counts.approve),
+            flagged(counts.approve),
+        )]));
+        profile_text.push(TextLine::from(vec![Span::styled(
+            format!("Account closes: {}", counts.close_account),
+            flagged(counts.close_account),
+        )]));
+    }
     let profile = Paragraph::new(profile_text).block(
         Block::default()
             .borders(Borders::ALL)
@@ -772,7 +1381,416 @@ fn render_right_sidebar(f: &mut Frame, app: &App, area: Rect, border: Color, _te
             .border_style(Style::default().fg(border))
             .title("Info"),
     );
-    f.render_widget(profile, chunks[1]);
+    f.render_widget(profile, chunks[2]);
+}
+
+/// Recent swap statuses, newest first; the current one is bold and older ones dimmed.
+fn render_swap_status(f: &mut Frame, app: &App, area: Rect, border: Color) {
+    let lines: Vec<TextLine> = if app.swap_statuses.is_empty() {
+        vec![TextLine::from(Span::styled(
+            "No swaps yet",
+            Style::default().fg(Color::DarkGray),
+        ))]
+    } else {
+        app.swap_statuses
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, status)| {
+                let color = match status {
+                    SwapStatus::Quoting | SwapStatus::Signing | SwapStatus::Sent { .. } => {
+                        Color::Yellow
+                    }
+                    SwapStatus::Quoted { .. } => Color::Cyan,
+                    SwapStatus::Confirmed { .. } => Color::Green,
+                    SwapStatus::Expired { .. } => Color::Magenta,
+                    SwapStatus::Cancelled => Color::DarkGray,
+                    SwapStatus::Failed { .. } => Color::Red,
+                };
+                let style = if i == 0 {
+                    Style::default().fg(color).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(color).add_modifier(Modifier::DIM)
+                };
+                TextLine::from(Span::styled(status.to_string(), style))
+            })
+            .collect()
+    };
+    let panel = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(border))
+            .title("Swap Status"),
+    );
+    f.render_widget(panel, area);
+}
+
+fn render_sizer_modal(f: &mut Frame, app: &App, area: Rect, border: Color, text: Color) {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(30),
+            Constraint::Length(13),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    let center_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(30),
+            Constraint::Percentage(40),
+            Constraint::Percentage(30),
+        ])
+        .split(popup_layout[1]);
+
+    let chunk = center_layout[1];
+    f.render_widget(Clear, chunk);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .border_style(Style::default().fg(border))
+        .title("Position Size");
+    f.render_widget(block.clone(), chunk);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Entry price
+            Constraint::Length(3), // Risk input
+            Constraint::Length(3), // Stop input
+            Constraint::Min(0),    // Result
+        ])
+        .split(block.inner(chunk));
+
+    f.render_widget(
+        Paragraph::new(format!(
+            " Entry: {:.8} SOL ({})",
+            app.token_info.price, app.token_info.symbol
+        ))
+        .style(Style::default().fg(text)),
+        chunks[0],
+    );
+
+    let input = |title: &'static str, value: &str, active: bool| {
+        let border_color = if active { Color::Yellow } else { border };
+        Paragraph::new(value.to_string())
+            .style(Style::default().fg(text))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(border_color))
+                    .title(title),
+            )
+    };
+    f.render_widget(
+        input(
+            "Risk (SOL)",
+            &app.sizer_risk_sol,
+            app.sizer_field == SizerField::Risk,
+        ),
+        chunks[1],
+    );
+    f.render_widget(
+        input(
+            "Stop Price (SOL)",
+            &app.sizer_stop_price,
+            app.sizer_field == SizerField::Stop,
+        ),
+        chunks[2],
+    );
+
+    let mut lines = match app.sizer_position_size() {
+        Some(size) => vec![
+            TextLine::from(Span::styled(
+                format!(" Size: {:.2} {}", size, app.token_info.symbol),
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            TextLine::from(format!(
+                " Notional: {:.4} SOL",
+                size * app.token_info.price
+            )),
+        ],
+        None => vec![TextLine::from(Span::styled(
+            " Enter a risk and a stop different from entry",
+            Style::default().fg(Color::DarkGray),
+        ))],
+    };
+    lines.push(TextLine::from(Span::styled(
+        " [Tab] switch field  [Esc] close",
+        Style::default().fg(Color::DarkGray),
+    )));
+    f.render_widget(Paragraph::new(lines).style(Style::default().fg(text)), chunks[3]);
+}
+
+fn render_order_modal(f: &mut Frame, app: &App, area: Rect, border: Color, text: Color) {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(25),
+            Constraint::Length(16),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    let center_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(25),
+            Constraint::Percentage(50),
+            Constraint::Percentage(25),
+        ])
+        .split(popup_layout[1]);
+
+    let chunk = center_layout[1];
+    f.render_widget(Clear, chunk);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .border_style(Style::default().fg(border))
+        .title("Limit Order");
+    f.render_widget(block.clone(), chunk);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2), // Price and side
+            Constraint::Length(3), // Amount input
+            Constraint::Length(3), // Trigger input
+            Constraint::Min(0),    // Caveats
+        ])
+        .split(block.inner(chunk));
+
+    let side_span = |side: OrderSide, label: &'static str, color: Color| {
+        let style = if app.order_side == side {
+            Style::default().fg(color).add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        Span::styled(label, style)
+    };
+    let header = vec![
+        TextLine::from(format!(
+            " Price: {:.10} SOL ({})",
+            app.token_info.price, app.token_info.symbol
+        )),
+        TextLine::from(vec![
+            Span::raw(" "),
+            side_span(OrderSide::Buy, " Buy at or below ", Color::Green),
+            Span::raw(" "),
+            side_span(OrderSide::Sell, " Sell at or above ", Color::Red),
+        ]),
+    ];
+    f.render_widget(Paragraph::new(header).style(Style::default().fg(text)), chunks[0]);
+
+    let input = |title: String, value: &str, active: bool| {
+        let border_color = if active { Color::Yellow } else { border };
+        Paragraph::new(value.to_string())
+            .style(Style::default().fg(text))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(border_color))
+                    .title(title),
+            )
+    };
+    let amount_title = match app.order_side {
+        OrderSide::Buy => "Spend (SOL)".to_string(),
+        OrderSide::Sell => format!("Sell ({})", app.token_info.symbol),
+    };
+    f.render_widget(
+        input(amount_title, &app.order_amount, app.order_field == OrderField::Amount),
+        chunks[1],
+    );
+    f.render_widget(
+        input(
+            "Trigger Price (SOL)".to_string(),
+            &app.order_trigger,
+            app.order_field == OrderField::Trigger,
+        ),
+        chunks[2],
+    );
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let lines = vec![
+        TextLine::from(Span::styled(
+            " Best effort: orders only fire while this terminal is open and",
+            Style::default().fg(Color::Yellow),
+        )),
+        TextLine::from(Span::styled(
+            " receiving live prices, and swap at the market price when triggered.",
+            Style::default().fg(Color::Yellow),
+        )),
+        TextLine::from(Span::styled(
+            " Fills can be worse than the trigger, up to the profile's slippage.",
+            dim,
+        )),
+        TextLine::from(Span::styled(
+            " [B/S] side  [Tab] switch field  [Enter] place  [Esc] close",
+            dim,
+        )),
+    ];
+    f.render_widget(Paragraph::new(lines), chunks[3]);
+}
+
+fn render_confirm_modal(f: &mut Frame, app: &App, area: Rect, border: Color, text: Color) {
+    let Some(pending) = &app.pending_swap else {
+        return;
+    };
+    let quote = &pending.quote;
+
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(30),
+            Constraint::Length(10),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    let center_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(25),
+            Constraint::Percentage(50),
+            Constraint::Percentage(25),
+        ])
+        .split(popup_layout[1]);
+
+    let chunk = center_layout[1];
+    f.render_widget(Clear, chunk);
+
+    let is_buy = pending.order.input_mint == WSOL_MINT;
+    let (title, title_color) = if is_buy {
+        ("Confirm BUY", Color::Green)
+    } else {
+        ("Confirm SELL", Color::Red)
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .border_style(Style::default().fg(border))
+        .title(Span::styled(
+            title,
+            Style::default().fg(title_color).add_modifier(Modifier::BOLD),
+        ));
+
+    // Raw Jupiter amounts in whole units when the mint's decimals are known
+    let symbol = |mint: &str| {
+        if mint == WSOL_MINT {
+            "SOL".to_string()
+        } else if mint == app.token_info.mint {
+            app.token_info.symbol.clone()
+        } else {
+            mint.chars().take(8).collect()
+        }
+    };
+    let amount = |raw: &str, mint: &str| {
+        match app
+            .display_decimals(mint)
+            .and_then(|decimals| raw_to_ui_amount(raw, decimals))
+        {
+            Some(ui_amount) => format!("{} {}", ui_amount, symbol(mint)),
+            None => format!("{} (raw) {}", raw, symbol(mint)),
+        }
+    };
+    let route = quote.route_labels();
+
+    let lines = vec![
+        TextLine::from(format!(" In:     {}", amount(&quote.in_amount, &quote.input_mint))),
+        TextLine::from(format!(" Out:    ~{}", amount(&quote.out_amount, &quote.output_mint))),
+        TextLine::from(format!(
+            " Min:    {}",
+            amount(&quote.other_amount_threshold, &quote.output_mint)
+        )),
+        TextLine::from(format!(
+            " Impact: {}%   Slippage: {} bps",
+            quote.price_impact_pct, quote.slippage_bps
+        )),
+        TextLine::from(format!(
+            " Route:  {}",
+            if route.is_empty() { "-" } else { route.as_str() }
+        )),
+        TextLine::from(""),
+        TextLine::from(Span::styled(
+            " [Enter] confirm  [Esc] cancel",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    f.render_widget(
+        Paragraph::new(lines)
+            .style(Style::default().fg(text))
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        chunk,
+    );
+}
+
+fn render_help_modal(f: &mut Frame, app: &App, area: Rect, border: Color, text: Color) {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(10),
+            Constraint::Percentage(80),
+            Constraint::Percentage(10),
+        ])
+        .split(area);
+
+    let center_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(20),
+            Constraint::Percentage(60),
+            Constraint::Percentage(20),
+        ])
+        .split(popup_layout[1]);
+
+    let chunk = center_layout[1];
+    f.render_widget(Clear, chunk);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .border_style(Style::default().fg(border))
+        .title("Keys - [?/Esc] close  [Up/Down] scroll");
+
+    let keys_width = KEY_HELP.iter().map(|row| row.keys.len()).max().unwrap_or(0);
+    let mut lines: Vec<TextLine> = Vec::new();
+    let mut screen = "";
+    for row in KEY_HELP {
+        if row.screen != screen {
+            if !screen.is_empty() {
+                lines.push(TextLine::from(""));
+            }
+            screen = row.screen;
+            lines.push(TextLine::from(Span::styled(
+                screen,
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        }
+        lines.push(TextLine::from(vec![
+            Span::styled(
+                format!("  {:<keys_width$}  ", row.keys),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::raw(row.action),
+        ]));
+    }
+
+    let help = Paragraph::new(lines)
+        .style(Style::default().fg(text))
+        .block(block)
+        .scroll((app.help_scroll, 0));
+    f.render_widget(help, chunk);
 }
 
 fn render_search_modal(f: &mut Frame, app: &App, area: Rect, border: Color, text: Color) {
@@ -862,3 +1880,327 @@ fn render_search_modal(f: &mut Frame, app: &App, area: Rect, border: Color, text
     // For simplicity of rendering "selected" background on the item itself, the manual map above works well.
     f.render_widget(list, chunks[1]);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app_at_origin() -> App {
+        let mut app = App::new(None, 0, Theme::Dark);
+        app.chart_x_offset = 0.0;
+        app.chart_y_offset = 0.0;
+        app
+    }
+
+    // 52x12 widget -> 50x10 plot area starting at (1, 1).
+    const CHART: Rect = Rect {
+        x: 0,
+        y: 0,
+        width: 52,
+        height: 12,
+    };
+
+    #[test]
+    fn test_indexer_lag_color_thresholds() {
+        assert_eq!(indexer_lag_color(0), Color::Green);
+        assert_eq!(indexer_lag_color(INDEXER_LAG_LIVE_SECS), Color::Green);
+        assert_eq!(indexer_lag_color(INDEXER_LAG_LIVE_SECS + 1), Color::Yellow);
+        assert_eq!(indexer_lag_color(INDEXER_LAG_STALE_SECS), Color::Yellow);
+        assert_eq!(indexer_lag_color(INDEXER_LAG_STALE_SECS + 1), Color::Red);
+    }
+
+    #[test]
+    fn test_chart_cursor_maps_columns_to_candles() {
+        let app = app_at_origin();
+
+        let first = chart_cursor_at(&app, CHART, 1, 1).unwrap();
+        assert_eq!(first.candle_index, 0);
+
+        // x = 10 * 50 / 49 ~= 10.2 -> candle 10
+        let tenth = chart_cursor_at(&app, CHART, 11, 5).unwrap();
+        assert_eq!(tenth.candle_index, 10);
+    }
+
+    #[test]
+    fn test_chart_cursor_maps_rows_to_price() {
+        let app = app_at_origin();
+        let ([_, _], [y_min, y_max]) = chart_bounds(&app);
+
+        let top = chart_cursor_at(&app, CHART, 1, 1).unwrap();
+        assert!((top.price - y_max).abs() < 1e-12);
+
+        let bottom = chart_cursor_at(&app, CHART, 1, 10).unwrap();
+        assert!((bottom.price - y_min).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_chart_cursor_follows_pan_offset() {
+        let mut app = app_at_origin();
+        app.chart_x_offset = 5.0;
+
+        let cursor = chart_cursor_at(&app, CHART, 1, 1).unwrap();
+        assert_eq!(cursor.candle_index, 5);
+    }
+
+    #[test]
+    fn test_chart_cursor_outside_plot_is_none() {
+        let app = app_at_origin();
+
+        // Border cells
+        assert_eq!(chart_cursor_at(&app, CHART, 0, 5), None);
+        assert_eq!(chart_cursor_at(&app, CHART, 10, 11), None);
+        // Outside the widget entirely
+        assert_eq!(chart_cursor_at(&app, CHART, 60, 5), None);
+        // Past the last candle (x = 50, only 50 candles)
+        assert_eq!(chart_cursor_at(&app, CHART, 50, 5), None);
+    }
+
+    #[test]
+    fn test_format_hms() {
+        assert_eq!(format_hms(0), "00:00:00");
+        assert_eq!(format_hms(86_400 + 3_723), "01:02:03");
+    }
+
+    fn series(base: f64, step: f64) -> Vec<Candle> {
+        (0..50)
+            .map(|i| {
+                let open = base + step * i as f64;
+                Candle {
+                    timestamp: i * 60,
+                    open,
+                    high: open + step,
+                    low: open,
+                    close: open + step,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_normalize_candles_to_percent_change() {
+        let candles = series(2.0, 0.5);
+
+        let pct = normalize_candles(&candles, 0);
+        assert_eq!(pct[0].open, 0.0);
+        assert_eq!(pct[0].close, 25.0);
+        assert_eq!(pct[2].open, 50.0);
+
+        // Window start moves the base
+        let pct = normalize_candles(&candles, 2);
+        assert_eq!(pct[2].open, 0.0);
+    }
+
+    #[test]
+    fn test_fit_y_bounds_expands_flat_series() {
+        let flat = |price: f64| Candle {
+            timestamp: 0,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+        };
+
+        let [low, high] = fit_y_bounds(&[flat(0.004), flat(0.004)], [0.0, 50.0]);
+        assert!((low - 0.0038).abs() < 1e-12, "{low}");
+        assert!((high - 0.0042).abs() < 1e-12, "{high}");
+
+        // Percent-change charts sit at exactly 0 before the price moves
+        assert_eq!(fit_y_bounds(&[flat(0.0)], [0.0, 50.0]), [-1.0, 1.0]);
+
+        // A real range keeps the 5% padding
+        let [low, high] = fit_y_bounds(&[flat(1.0), flat(2.0)], [0.0, 50.0]);
+        assert!((low - 0.95).abs() < 1e-12 && (high - 2.05).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_chart_bounds_fit_indexed_prices() {
+        let candle = |low: f64, high: f64| Candle {
+            timestamp: 0,
+            open: low,
+            high,
+            low,
+            close: high,
+        };
+        // Prices far below the simulated series' 0.004
+        let mut app = app_at_origin();
+        app.candles = vec![candle(2e-8, 3e-8), candle(3e-8, 4e-8)];
+
+        let (_, [low, high]) = chart_bounds(&app);
+        assert!((low - 1.9e-8).abs() < 1e-15, "{low}");
+        assert!((high - 4.1e-8).abs() < 1e-15, "{high}");
+
+        // Panning shifts the fitted range
+        app.chart_y_offset = 1e-8;
+        let (_, [low, high]) = chart_bounds(&app);
+        assert!((low - 2.9e-8).abs() < 1e-15 && (high - 5.1e-8).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_watchlist_screen_shows_placeholder_then_rows() {
+        use crate::network::WatchQuote;
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let screen_text = |app: &App| {
+            let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+            terminal.draw(|f| ui(f, app)).unwrap();
+            let text: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+            text
+        };
+
+        let mut app = app_at_origin();
+        app.current_screen = CurrentScreen::Watchlist;
+        assert!(screen_text(&app).contains("No watched tokens yet"));
+
+        app.watch("So11111111111111111111111111111111111111112");
+        app.watch("unknown_mint");
+        app.watch_quotes.insert(
+            "unknown_mint".to_string(),
+            WatchQuote {
+                price: 0.0015,
+                change_pct: Some(-12.5),
+            },
+        );
+        let text = screen_text(&app);
+        assert!(text.contains("SOL"));
+        assert!(text.contains("0.001500000"));
+        assert!(text.contains("-12.50%"));
+    }
+
+    #[test]
+    fn test_tiny_terminal_shows_size_hint_instead_of_layout() {
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut app = app_at_origin();
+        for screen in [CurrentScreen::Home, CurrentScreen::TokenDetails] {
+            app.current_screen = screen;
+            app.show_search_modal = true;
+            let mut terminal = Terminal::new(TestBackend::new(5, 5)).unwrap();
+            terminal.draw(|f| ui(f, &app)).unwrap();
+
+            let text: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+            assert!(text.starts_with("Termi"), "{text:?}");
+        }
+
+        // Zero-size areas must not panic either
+        let mut terminal = Terminal::new(TestBackend::new(0, 0)).unwrap();
+        terminal.draw(|f| ui(f, &app)).unwrap();
+        // The full layout starts at exactly the minimum size
+        app.show_search_modal = false;
+        let mut terminal =
+            Terminal::new(TestBackend::new(MIN_TERMINAL_WIDTH, MIN_TERMINAL_HEIGHT)).unwrap();
+        terminal.draw(|f| ui(f, &app)).unwrap();
+
+        assert!(is_too_small(Rect::new(0, 0, 200, 10)));
+        assert!(!is_too_small(Rect::new(0, 0, MIN_TERMINAL_WIDTH, MIN_TERMINAL_HEIGHT)));
+    }
+
+    #[test]
+    fn test_compare_view_draws_both_panels() {
+        use crate::app::CompareView;
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut app = app_at_origin();
+        app.token_info.symbol = "LEFT".to_string();
+        app.candles = series(0.004, 0.0001);
+        app.compare = Some(CompareView {
+            mint: "right_mint".to_string(),
+            symbol: "RIGHT".to_string(),
+            // Very different price level: only visible on a shared scale once normalized
+            candles: series(250.0, 3.0),
+            primary_candles: None,
+        });
+        app.current_screen = CurrentScreen::Compare;
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|f| ui(f, &app)).unwrap();
+        let buffer = terminal.backend().buffer();
+
+        let text: String = buffer.content().iter().map(|c| c.symbol()).collect();
+        assert!(text.contains("LEFT - % change"));
+        assert!(text.contains("RIGHT - % change"));
+
+        // Each half has candle cells drawn inside its plot area (below navbar, inside borders).
+        let drawn_in = |x_range: std::ops::Range<u16>| {
+            x_range.into_iter().any(|x| {
+                (4..29).any(|y| {
+                    let cell = &buffer[(x, y)];
+                    cell.fg == Color::Green && cell.symbol() != " "
+                })
+            })
+        };
+        assert!(drawn_in(1..59), "left chart is empty");
+        assert!(drawn_in(61..119), "right chart is empty");
+    }
+
+    #[test]
+    fn test_confirm_modal_shows_quote_details() {
+        use crate::swap::{PendingSwap, SwapGate};
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut app = app_at_origin();
+        app.token_info.mint = "token_mint".to_string();
+        app.token_info.symbol = "BONK".to_string();
+        app.token_info.decimals = Some(5);
+        let quote = serde_json::from_value(serde_json::json!({
+            "inputMint": WSOL_MINT,
+            "inAmount": "1500000000",
+            "outputMint": "token_mint",
+            "outAmount": "250000000",
+            "otherAmountThreshold": "248750000",
+            "swapMode": "ExactIn",
+            "slippageBps": 50,
+            "platformFee": null,
+            "priceImpactPct": "0.42",
+            "routePlan": [{
+                "swapInfo": {
+                    "ammKey": "amm",
+                    "label": "Raydium",
+                    "inputMint": WSOL_MINT,
+                    "outputMint": "token_mint",
+                    "inAmount": "1500000000",
+                    "outAmount": "250000000",
+                    "feeAmount": "0",
+                    "feeMint": WSOL_MINT
+                },
+                "percent": 100
+            }],
+            "contextSlot": 1,
+            "timeTaken": 0.01
+        }))
+        .unwrap();
+        app.pending_swap = Some(PendingSwap {
+            order: app.swap_order(),
+            quote,
+            permit: SwapGate::new(1).try_start().unwrap(),
+        });
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|f| ui(f, &app)).unwrap();
+        let text: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|c| c.symbol())
+            .collect();
+        assert!(text.contains("Confirm BUY"));
+        assert!(text.contains("1.5 SOL"));
+        assert!(text.contains("~2500 BONK"));
+        assert!(text.contains("2487.5 BONK"));
+        assert!(text.contains("0.42%"));
+        assert!(text.contains("Raydium"));
+        assert!(text.contains("[Enter] confirm"));
+    }
+}
@@ -0,0 +1,47 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Load watched mints from a JSON array. A missing file means an empty watchlist; duplicates
+/// left by hand edits are dropped, keeping the first occurrence.
+pub fn load_watchlist(path: &Path) -> Result<Vec<String>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = std::fs::read(path)
+        .with_context(|| format!("failed to read watchlist file {}", path.display()))?;
+    let mints: Vec<String> = serde_json::from_slice(&data)
+        .with_context(|| format!("invalid watchlist file {}", path.display()))?;
+    let mut watchlist: Vec<String> = Vec::with_capacity(mints.len());
+    for mint in mints {
+        if !watchlist.contains(&mint) {
+            watchlist.push(mint);
+        }
+    }
+    Ok(watchlist)
+}
+
+pub fn save_watchlist(path: &Path, mints: &[String]) -> Result<()> {
+    let data = serde_json::to_vec_pretty(mints)?;
+    std::fs::write(path, data)
+        .with_context(|| format!("failed to write watchlist file {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watchlist_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("tx-watchlist-{}.json", std::process::id()));
+        assert!(load_watchlist(&path).unwrap().is_empty());
+
+        let mints = vec!["mint_a".to_string(), "mint_b".to_string()];
+        save_watchlist(&path, &mints).unwrap();
+        assert_eq!(load_watchlist(&path).unwrap(), mints);
+
+        std::fs::write(&path, r#"["mint_b","mint_a","mint_b"]"#).unwrap();
+        assert_eq!(load_watchlist(&path).unwrap(), vec!["mint_b", "mint_a"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}